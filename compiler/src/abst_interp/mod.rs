@@ -5,13 +5,15 @@ use std::sync::Arc;
 
 use domino::moment::MomentApi;
 use jssat_ir::collections::StrictZip;
-use jssat_ir::id::{Counter, LiftedCtx, RegisterId, Tag, UnionId, UniqueListId, UniqueRecordId, IdCompat};
-use jssat_ir::isa::BlockJump;
-use jssat_ir::value_snapshot::{ValueSnapshotArena, SnapshotValue, SnapshotList};
+use jssat_ir::id::{Counter, LiftedCtx, RegisterId, Tag, UnionId, UniqueListId, UniqueRecordId, UniqueTupleId, IdCompat};
+use jssat_ir::isa::{BlockJump, NumericValue, SwitchValue};
+use jssat_ir::value_snapshot::{ValueSnapshotArena, SnapshotValue, SnapshotList, SnapshotTuple};
+use ordered_float::OrderedFloat;
 use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::lifted::{FunctionId, LiftedProgram};
-use crate::types::{List, Record, Type, TypeCtx, TypeCtxMut, TypeDuplication, TypeCtxImmut};
+use crate::frontend::ir::{FFIValueType, Returns};
+use crate::types::{List, Record, Type, TypeCtx, TypeCtxMut, TypeDuplication, TypeCtxImmut, Union};
 
 /// Collects statistics during abstract interpretation.
 pub trait AbsIntCollector<T: Tag> {
@@ -126,7 +128,7 @@ impl<'code> AbsIntCollector<LiftedCtx> for MomentCollector<'code, LiftedCtx> {
     }
 
     fn fn_start(&mut self, function: FunctionId, kind: EvaluationStateKind) {
-        self.moment.enter(function);
+        self.moment.enter(function, None);
         self.function.push(function);
         self.pos.push(None);
     }
@@ -164,10 +166,11 @@ impl<'ctx, T: Tag> SnapshotVisitor<Type<'ctx, T>, RegisterId<LiftedCtx>> {
             Type::Nothing => todo!(),
             Type::Bytes => todo!(),
             Type::Number => todo!(),
+            Type::BigNumber => SnapshotValue::BigNumber,
             Type::Boolean => todo!(),
             Type::Atom(x) => SnapshotValue::Atom(x),
             Type::Int(x) => SnapshotValue::Number(x),
-            Type::Float(x) => todo!(),
+            Type::Float(x) => SnapshotValue::Float(x),
             Type::Bool(x) => SnapshotValue::Boolean(x),
             Type::FnPtr(x) => SnapshotValue::FnPtr(x),
             Type::Byts(x) => SnapshotValue::Bytes(x.to_vec()),
@@ -191,6 +194,26 @@ impl<'ctx, T: Tag> SnapshotVisitor<Type<'ctx, T>, RegisterId<LiftedCtx>> {
                 self.arena.lists.insert(id, SnapshotList(snapshot_types));
                 SnapshotValue::List(id)
             }
+            Type::Tuple(x) => {
+                if let Some(seen) = self.seen.get(&typ) {
+                    if let Some(reg) = primary {
+                        self.arena.registers.insert(reg, seen.clone());
+                    }
+
+                    return seen.clone();
+                }
+
+                let id = x.unique_id().value();
+
+                self.seen.insert(typ, SnapshotValue::Tuple(id));
+
+                let snapshot_types = x.iter()
+                    .map(|typ| self.visit(*typ))
+                    .collect::<Vec<_>>();
+
+                self.arena.tuples.insert(id, SnapshotTuple(snapshot_types));
+                SnapshotValue::Tuple(id)
+            }
             Type::Record(x) => {
                 if let Some(seen) = self.seen.get(&typ) {
                     if let Some(reg) = primary {
@@ -229,6 +252,8 @@ pub struct AbsIntEngine<'program, C> {
     program: &'program LiftedProgram,
     record_id: Counter<UniqueRecordId<LiftedCtx>>,
     list_id: Counter<UniqueListId<LiftedCtx>>,
+    tuple_id: Counter<UniqueTupleId<LiftedCtx>>,
+    union_id: Counter<UnionId<LiftedCtx>>,
     recursion_detector: RecursionDetector,
     function_cache: FunctionCache,
     pub collector: C,
@@ -260,6 +285,16 @@ pub enum AbsIntError {
     TypeError,
     #[error("Invalid program: key of record does not exist")]
     RegKeyDNE,
+    #[error("Invalid program: cannot switch on a value of this type")]
+    NotSwitchable,
+    #[error("Invalid program: only some paths return a value")]
+    ReturnMismatch,
+    #[error("Invalid program: string slice is out of range")]
+    StringInvalidRange,
+    #[error("Invalid program: string index is out of range")]
+    StringInvalidIndex,
+    #[error("Invalid program: tuple index is out of range")]
+    TupleInvalidIndex,
 }
 
 impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
@@ -268,6 +303,8 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
             program,
             record_id: Default::default(),
             list_id: Default::default(),
+            tuple_id: Default::default(),
+            union_id: Default::default(),
             recursion_detector: Default::default(),
             function_cache: Default::default(),
             collector,
@@ -412,7 +449,7 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
                                 list.get(i).unwrap()
                             }
                             Type::Union(_) => todo!("need to recursively re-apply the list set operator for every union variant"),
-                            Type::Float(_) => todo!("debating whether or not to implement this"),
+                            // lists are only ever indexed by integers, the same as the interpreter
                             _ => panic!("not well formed jssat ir"),
                         };
 
@@ -457,7 +494,7 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
                                 }
                             }
                             Type::Union(_) => todo!("need to recursively re-apply the list set operator for every union variant"),
-                            Type::Float(_) => todo!("debating whether or not to implement this"),
+                            // lists are only ever indexed by integers, the same as the interpreter
                             _ => panic!("not well formed jssat ir"),
                         };
                         
@@ -485,13 +522,132 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
                         let list = list.unwrap_list().borrow();
                         insert!(state, i.result, Type::Int(list.len() as i64));
                     }
+                    NewTuple(i) => {
+                        let elements = i.elements.iter()
+                            .map(|r| state.rget(*r))
+                            .collect::<Result<Vec<_>, _>>()?;
+
+                        let unique_id = self.tuple_id.next();
+                        insert!(state, i.result, state.make_type_tuple(unique_id, &elements));
+                    },
+                    TupleGet(i) => {
+                        let tuple = state.rget(i.tuple)?.unwrap_tuple();
+
+                        let element = *tuple.get(i.index).ok_or(AbsIntError::TupleInvalidIndex)?;
+
+                        insert!(state, i.result, element);
+                    },
+                    TupleLen(i) => {
+                        let tuple = state.rget(i.tuple)?.unwrap_tuple();
+                        insert!(state, i.result, Type::Int(tuple.len() as i64));
+                    },
+                    StringConcat(i) => {
+                        let lhs = state.rget(i.lhs)?;
+                        let rhs = state.rget(i.rhs)?;
+
+                        use Type::*;
+                        let res_typ = match (lhs, rhs) {
+                            (Byts(a), Byts(b)) => {
+                                let mut combined = a.to_vec();
+                                combined.extend_from_slice(&b);
+                                state.make_type_byts(&combined)
+                            },
+                            (Bytes | Byts(_), Bytes | Byts(_)) => Bytes,
+                            _ => panic!("invalid program"),
+                        };
+
+                        insert!(state, i.result, res_typ);
+                    },
+                    StringLen(i) => {
+                        use Type::*;
+                        let res_typ = match state.rget(i.string)? {
+                            Byts(x) => Int(jssat_ir::isa::string_len(&x) as i64),
+                            Bytes => Number,
+                            _ => panic!("invalid program"),
+                        };
+
+                        insert!(state, i.result, res_typ);
+                    },
+                    StringSlice(i) => {
+                        let string = state.rget(i.string)?;
+                        let start = state.rget(i.start)?;
+                        let end = state.rget(i.end)?;
+
+                        use Type::*;
+                        let res_typ = match (string, start, end) {
+                            (Byts(x), Int(start), Int(end)) => {
+                                let slice = jssat_ir::isa::string_slice(&x, start, end)
+                                    .ok_or(AbsIntError::StringInvalidRange)?;
+                                state.make_type_byts(slice)
+                            },
+                            (Bytes | Byts(_), Number | Int(_), Number | Int(_)) => Bytes,
+                            _ => panic!("invalid program"),
+                        };
+
+                        insert!(state, i.result, res_typ);
+                    },
+                    StringCodeUnitAt(i) => {
+                        let string = state.rget(i.string)?;
+                        let index = state.rget(i.index)?;
+
+                        use Type::*;
+                        let res_typ = match (string, index) {
+                            (Byts(x), Int(index)) => {
+                                let code_unit = jssat_ir::isa::string_code_unit_at(&x, index)
+                                    .ok_or(AbsIntError::StringInvalidIndex)?;
+                                Int(code_unit as i64)
+                            },
+                            (Bytes | Byts(_), Number | Int(_)) => Number,
+                            _ => panic!("invalid program"),
+                        };
+
+                        insert!(state, i.result, res_typ);
+                    },
+                    NumberToString(i) => {
+                        use jssat_ir::isa::{encode_utf16, number_to_string, NumericValue};
+                        use Type::*;
+                        let res_typ = match state.rget(i.number)? {
+                            Int(x) => state.make_type_byts(&encode_utf16(&number_to_string(NumericValue::Integer(x)))),
+                            Float(x) => state.make_type_byts(&encode_utf16(&number_to_string(NumericValue::Float(x.0)))),
+                            Number => Bytes,
+                            _ => panic!("invalid program"),
+                        };
+
+                        insert!(state, i.result, res_typ);
+                    },
+                    StringToNumber(i) => {
+                        use jssat_ir::isa::{decode_utf16, string_to_number, NumericValue};
+                        use Type::*;
+                        let res_typ = match state.rget(i.string)? {
+                            Byts(x) => match decode_utf16(&x).map(|x| string_to_number(&x)) {
+                                Some(NumericValue::Integer(x)) => Int(x),
+                                Some(NumericValue::Float(x)) => Float(OrderedFloat(x)),
+                                None => Float(OrderedFloat(f64::NAN)),
+                            },
+                            Bytes => Number,
+                            _ => panic!("invalid program"),
+                        };
+
+                        insert!(state, i.result, res_typ);
+                    },
                     GetFnPtr(i) => {
                         insert!(state, i.result, Type::FnPtr(i.item));
                     }
                     CallStatic(i) => {
                         self.call_fn(&mut state, i.result, i.calling, &i.args)?;
                     }
-                    CallExtern(_) => todo!(),
+                    CallExtern(i) => {
+                        let ext_fn = self.program.external_functions.get(&i.calling).unwrap();
+
+                        match (i.result, &ext_fn.return_type) {
+                            (Some(result), Returns::Value(typ)) => {
+                                let typ = self.ffi_type(&mut state, typ);
+                                insert!(state, result, typ);
+                            }
+                            (Some(_), Returns::Void) => Err(AbsIntError::TypeError)?,
+                            (None, _) => {}
+                        }
+                    }
                     CallVirt(i) => {
                         let fnptr = state.get(&i.calling).unwrap();
                         let fnptr = fnptr.unwrap_fnptr();
@@ -508,6 +664,12 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
                     MakeInteger(i) => {
                         insert!(state, i.result, Type::Int(i.item));
                     }
+                    MakeFloat(i) => {
+                        insert!(state, i.result, Type::Float(i.item));
+                    }
+                    MakeBigInt(i) => {
+                        insert!(state, i.result, Type::BigNumber);
+                    }
                     MakeBoolean(i) => {
                         insert!(state, i.result, Type::Bool(i.item));
                     },
@@ -520,10 +682,18 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
                         let res_typ = match i.op {
                             Add => {
                                 match (lhs, rhs) {
-                                    (Int(a), Int(b)) => Int(a + b),
-                                    (Int(_), Number) |
-                                    (Number, Int(_)) |
+                                    (Int(a), Int(b)) => match i.op.fold_integers(a, b) {
+                                        Some(NumericValue::Integer(x)) => Int(x),
+                                        Some(NumericValue::Float(x)) => Float(OrderedFloat(x)),
+                                        None => Number,
+                                    },
+                                    (Float(a), Float(b)) => Float(a + b),
+                                    (Int(a), Float(b)) |
+                                    (Float(b), Int(a)) => Float(OrderedFloat(a as f64) + b),
+                                    (Int(_) | Float(_), Number) |
+                                    (Number, Int(_) | Float(_)) |
                                     (Number, Number) => Number,
+                                    (BigNumber, BigNumber) => BigNumber,
                                     _ => todo!("2op add: {:?}, {:?}", lhs, rhs),
                                 }
                             },
@@ -546,7 +716,16 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
                                     (a, b) => panic!("2op or: {:?} vs {:?}", a, b),
                                 }
                             },
-                            Equals => {
+                            Equals | SameValue => {
+                                // Number::equal and Number::sameValue only differ for NaN and -0
+                                let num_eq = |a: f64, b: f64| match i.op {
+                                    SameValue => {
+                                        (a.is_nan() && b.is_nan())
+                                            || (a == b && a.is_sign_negative() == b.is_sign_negative())
+                                    }
+                                    _ => a == b,
+                                };
+
                                 match (lhs, rhs) {
                                     (Atom(a), Atom(b)) => Bool(a == b),
                                     (Atom(_), Record(_)) => Bool(false),
@@ -554,6 +733,7 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
                                     (Record(a), Record(b)) => {
                                         Bool(a == b)
                                     }
+                                    (Tuple(a), Tuple(b)) => Bool(a.unique_id() == b.unique_id()),
                                     (Byts(a), Byts(b)) => Bool(a == b),
                                     (Bytes, Byts(_)) |
                                     (Byts(_), Bytes) |
@@ -563,18 +743,47 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
                                     (Boolean, Bool(_)) |
                                     (Boolean, Boolean) => Boolean,
                                     (Int(a), Int(b)) => Bool(a == b),
-                                    (Int(_), Number) |
-                                    (Number, Int(_)) |
+                                    (Float(a), Float(b)) => Bool(num_eq(a.0, b.0)),
+                                    (Int(a), Float(b)) |
+                                    (Float(b), Int(a)) => Bool(num_eq(a as f64, b.0)),
+                                    (Int(_) | Float(_), Number) |
+                                    (Number, Int(_) | Float(_)) |
                                     (Number, Number) => Boolean,
+                                    (BigNumber, BigNumber) => Boolean,
                                     (a, b) => panic!("2op eq: {:?} vs {:?}", a, b)
                                 }
                             },
+                            Subtract | Multiply | Divide | Remainder | Exponent | BitwiseAnd
+                            | BitwiseOr | BitwiseXor | ShiftLeft | ShiftRight | UnsignedShiftRight => {
+                                let numeric = |x| match x {
+                                    NumericValue::Integer(x) => Int(x),
+                                    NumericValue::Float(x) => Float(OrderedFloat(x)),
+                                };
+
+                                let folded = match (lhs, rhs) {
+                                    (Int(a), Int(b)) => i.op.fold_integers(a, b).map(numeric),
+                                    (Int(a), Float(b)) => i.op.fold_floats(a as f64, b.0).map(numeric),
+                                    (Float(a), Int(b)) => i.op.fold_floats(a.0, b as f64).map(numeric),
+                                    (Float(a), Float(b)) => i.op.fold_floats(a.0, b.0).map(numeric),
+                                    (Int(_) | Float(_) | Number, Number) |
+                                    (Number, Int(_) | Float(_)) => None,
+                                    // the values of BigInts are not tracked
+                                    (BigNumber, BigNumber) => Some(BigNumber),
+                                    (a, b) => panic!("2op {}: {:?} vs {:?}", i.op, a, b),
+                                };
+
+                                folded.unwrap_or(Number)
+                            },
                             LessThan => {
                                 match (lhs, rhs) {
                                     (Number, Number) => Boolean,
-                                    (Int(_), Number) => Boolean,
-                                    (Number, Int(_)) => Boolean,
+                                    (Int(_) | Float(_), Number) => Boolean,
+                                    (Number, Int(_) | Float(_)) => Boolean,
                                     (Int(a), Int(b)) => Bool(a < b),
+                                    (Float(a), Float(b)) => Bool(a.0 < b.0),
+                                    (Int(a), Float(b)) => Bool((a as f64) < b.0),
+                                    (Float(a), Int(b)) => Bool(a.0 < b as f64),
+                                    (BigNumber, BigNumber) => Boolean,
                                     _ => panic!("invalid program"),
                                 }
                             },
@@ -585,17 +794,41 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
                     Negate(i) => {
                         let operand = state.rget(i.operand)?;
 
+                        use Type::*;
+                        let res_typ = match operand {
+                            Number => Number,
+                            // `-0` and `-i64::MIN` aren't integers
+                            Int(x) => match x.checked_neg() {
+                                Some(0) | None => Float(OrderedFloat(-(x as f64))),
+                                Some(x) => Int(x),
+                            },
+                            Float(x) => Float(-x),
+                            BigNumber => BigNumber,
+                            _ => panic!("invalid program"),
+                        };
+
+                        insert!(state, i.result, res_typ);
+                    },
+                    Not(i) => {
+                        let operand = state.rget(i.operand)?;
+
                         use Type::*;
                         let res_typ = match operand {
                             Boolean => Boolean,
                             Bool(x) => Bool(!x),
-                            Number => Number,
-                            Int(x) => Int(-x),
                             _ => panic!("invalid program"),
                         };
 
                         insert!(state, i.result, res_typ);
                     },
+                    NumberToBigInt(i) => {
+                        match state.rget(i.operand)? {
+                            Type::Number | Type::Int(_) | Type::Float(_) => {},
+                            _ => panic!("invalid program"),
+                        };
+
+                        insert!(state, i.result, Type::BigNumber);
+                    },
                     Generalize(_) => todo!(),
                     Assert(i) => {
                         let cond = state.get(&i.condition).unwrap();
@@ -618,11 +851,12 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
                             Register(r) => {
                                 let typ =  state.rget(r)?;
                                 match typ {
-                                    // TODO: handle BigNumber and Runtime
+                                    // TODO: handle Runtime
                                     Type::Any => todo!(),
                                     Type::Nothing => unimplemented!(),
                                     Type::Bytes => ValueType::Bytes,
                                     Type::Number => ValueType::Number,
+                                    Type::BigNumber => ValueType::BigNumber,
                                     Type::Boolean => ValueType::Boolean,
                                     Type::Atom(_) => ValueType::Atom,
                                     Type::Int(_) => ValueType::Number,
@@ -631,6 +865,7 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
                                     Type::FnPtr(_) => ValueType::FnPtr,
                                     Type::Byts(_) => ValueType::Bytes,
                                     Type::List(_) => ValueType::List,
+                                    Type::Tuple(_) => ValueType::Tuple,
                                     Type::Record(_) => ValueType::Record,
                                     Type::Union(_) => todo!(),
                                 }
@@ -641,12 +876,10 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
                         let reg_typ = state.rget(i.value)?;
                         let is_eq = match (reg_typ, v) {
                             (Type::Nothing, _) => panic!("huh"),
-                            (Type::Number, ValueType::BigNumber) => todo!("idk"),
-                            (Type::Int(_), ValueType::BigNumber) => todo!("idk"),
-                            (Type::Float(_), ValueType::BigNumber) => todo!("idk"),
                             (Type::Any, _) => None,
                             (Type::Bytes, ValueType::Bytes) |
                             (Type::Number, ValueType::Number) |
+                            (Type::BigNumber, ValueType::BigNumber) |
                             (Type::Boolean, ValueType::Boolean) |
                             (Type::Atom(_), ValueType::Atom) |
                             (Type::Int(_), ValueType::Number) |
@@ -655,6 +888,7 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
                             (Type::FnPtr(_), ValueType::FnPtr) |
                             (Type::Byts(_), ValueType::Bytes) |
                             (Type::List(_), ValueType::List) |
+                            (Type::Tuple(_), ValueType::Tuple) |
                             (Type::Record(_), ValueType::Record) => Some(true),
                             (Type::Union(_), _) => todo!(),
                             _ => Some(false),
@@ -702,24 +936,28 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
                     JumpIf(i) => {
                         let cond = state.rget(i.condition)?;
 
-                    use Type::*;
-                        match cond {
-                            Boolean => todo!(),
-                            Bool(branch) => {
-                                let branch = match branch {
-                                    true => &i.if_so,
-                                    false => &i.other,
-                                };
+                        use Type::*;
+                        let paths = match cond {
+                            Boolean => vec![&i.if_so, &i.other],
+                            Bool(true) => vec![&i.if_so],
+                            Bool(false) => vec![&i.other],
+                            _ => Err(AbsIntError::TypeError)?,
+                        };
 
-                                let result = self.do_blk_jmp(&mut state, branch)?;
-                                result
-                            }
-                            _ => panic!("invalid program"),
-                        }
+                        self.do_blk_jmps(&mut state, paths)?
+                    }
+                    Switch(i) => {
+                        let value = state.rget(i.value)?;
+
+                        let mut paths = Vec::new();
+                        switch_paths(i, value, &mut paths)?;
+
+                        self.do_blk_jmps(&mut state, paths)?
                     }
                     Return(i) => match i.0 {
                         Some(ret_reg) => {
                             let typ = state.rget(ret_reg)?;
+                            self.collector.record(ret_reg, typ);
                             TypeCtx::new_initial(None, typ)
                         }
                         None => TypeCtx::new(),
@@ -746,6 +984,35 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
         Ok(result)
     }
 
+    /// The type of a value returned by an external function, which is only
+    /// known through the type it is declared to return.
+    fn ffi_type<'ctx>(
+        &mut self,
+        state: &mut TypeCtxMut<'_, 'ctx, LiftedCtx, RegisterId<LiftedCtx>>,
+        typ: &FFIValueType,
+    ) -> Type<'ctx, LiftedCtx> {
+        match typ {
+            FFIValueType::Any | FFIValueType::Runtime => Type::Any,
+            FFIValueType::String | FFIValueType::Bytes => Type::Bytes,
+            // opaque pointers are carried around as integers
+            FFIValueType::I64 | FFIValueType::F64 | FFIValueType::Opaque => Type::Number,
+            FFIValueType::Bool => Type::Boolean,
+            FFIValueType::Record => {
+                let record = Record::new(self.record_id.next());
+                state.make_type_record(record)
+            }
+            // nullable values are either an empty tuple, or a tuple of the value
+            FFIValueType::Nullable(typ) => {
+                let value = self.ffi_type(state, typ);
+
+                let mut union = Union::new(self.union_id.next());
+                union.push(state.make_type_tuple(self.tuple_id.next(), &[]));
+                union.push(state.make_type_tuple(self.tuple_id.next(), &[value]));
+                state.make_type_union(union)
+            }
+        }
+    }
+
     fn do_blk_jmp(
         &mut self,
         state: &mut TypeCtxMut<LiftedCtx, RegisterId<LiftedCtx>>,
@@ -763,6 +1030,68 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
         Ok(ret_typ)
     }
 
+    /// Takes every path that may be taken, joining the values they return into
+    /// a union. Paths are taken one after another, so the records that one
+    /// path changes are seen by the paths after it.
+    ///
+    /// Unions returned by a path are flattened into the joined union, and
+    /// types already in it are not added twice.
+    fn do_blk_jmps(
+        &mut self,
+        state: &mut TypeCtxMut<LiftedCtx, RegisterId<LiftedCtx>>,
+        jmps: Vec<&BlockJump<FunctionId, LiftedCtx>>,
+    ) -> Result<TypeCtx<LiftedCtx, Option<RegisterId<LiftedCtx>>>, AbsIntError> {
+        if let [jmp] = jmps[..] {
+            return self.do_blk_jmp(state, jmp);
+        }
+
+        let mut returned = Vec::new();
+        let mut returns_void = false;
+
+        for jmp in jmps {
+            let result = self.call_fn(state, None, jmp.0, &jmp.1)?;
+
+            let ret_typ = result.ctx.borrow(|result| {
+                let mut dup = TypeDuplication::new(state);
+                result.get(&None).map(|t| dup.duplicate_type(t))
+            });
+
+            let typ = match ret_typ {
+                Some(typ) => typ,
+                None => {
+                    returns_void = true;
+                    continue;
+                }
+            };
+
+            let variants = match typ {
+                Type::Union(union) => union.borrow().to_vec(),
+                typ => vec![typ],
+            };
+
+            for typ in variants {
+                if !returned.contains(&typ) {
+                    returned.push(typ);
+                }
+            }
+        }
+
+        match (returned.len(), returns_void) {
+            (0, _) => Ok(TypeCtx::new()),
+            (_, true) => Err(AbsIntError::ReturnMismatch),
+            (1, false) => Ok(TypeCtx::new_initial(None, returned[0])),
+            (_, false) => {
+                let mut union = Union::new(self.union_id.next());
+                for typ in returned {
+                    union.push(typ);
+                }
+
+                let typ = state.make_type_union(union);
+                Ok(TypeCtx::new_initial(None, typ))
+            }
+        }
+    }
+
     fn call_fn(
         &mut self,
         state: &mut TypeCtxMut<LiftedCtx, RegisterId<LiftedCtx>>,
@@ -850,6 +1179,55 @@ impl<'p, C: AbsIntCollector<LiftedCtx>> AbsIntEngine<'p, C> {
     }
 }
 
+/// Collects the paths of a switch that a value of the type may take. Values of
+/// an exact type take exactly one path, while values of a more general type may
+/// take any case of the same kind, or the default path. A union may take the
+/// paths of any of its variants.
+fn switch_paths<'s>(
+    switch: &'s jssat_ir::isa::Switch<FunctionId, LiftedCtx>,
+    typ: Type<LiftedCtx>,
+    paths: &mut Vec<&'s BlockJump<FunctionId, LiftedCtx>>,
+) -> Result<(), AbsIntError> {
+    let mut take = |path: &'s BlockJump<FunctionId, LiftedCtx>| {
+        if !paths.iter().any(|p| std::ptr::eq(*p, path)) {
+            paths.push(path);
+        }
+    };
+
+    let exact = match typ {
+        Type::Atom(atom) => Some(SwitchValue::Atom(atom)),
+        Type::Int(integer) => Some(SwitchValue::Integer(integer)),
+        Type::Bool(boolean) => Some(SwitchValue::Boolean(boolean)),
+        _ => None,
+    };
+
+    if let Some(value) = exact {
+        take(switch.take_path(value));
+        return Ok(());
+    }
+
+    let admits: fn(&SwitchValue) -> bool = match typ {
+        Type::Number => |case| matches!(case, SwitchValue::Integer(_)),
+        Type::Boolean => |case| matches!(case, SwitchValue::Boolean(_)),
+        Type::Union(union) => {
+            for variant in union.borrow().iter() {
+                switch_paths(switch, *variant, paths)?;
+            }
+            return Ok(());
+        }
+        _ => return Err(AbsIntError::NotSwitchable),
+    };
+
+    for (case, path) in switch.cases.iter() {
+        if admits(case) {
+            take(path);
+        }
+    }
+    take(&switch.default);
+
+    Ok(())
+}
+
 fn duplicate_registers(current_state: &mut TypeCtx, args: &TypeCtx) {
     current_state.borrow_mut(|mut current_state| {
         args.borrow(|args| {
@@ -901,8 +1279,16 @@ impl EvalResult {
         matches!(self, Self::Never)
     }
 
+    /// The type of the value returned, which is empty if the function never
+    /// returns or returns nothing.
     pub fn return_type(&self) -> TypeCtx<crate::id::LiftedCtx, ()> {
-        todo!()
+        match self {
+            Self::Never => TypeCtx::new(),
+            Self::Present(result) => result.ctx.borrow(|ctx| match ctx.get(&None) {
+                Some(typ) => TypeCtx::new_initial((), typ),
+                None => TypeCtx::new(),
+            }),
+        }
     }
 }
 
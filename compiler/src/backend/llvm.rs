@@ -2,6 +2,7 @@ use rustc_hash::FxHashMap;
 use std::hash::Hash;
 
 use super::BuildArtifact;
+use crate::frontend::ir::{self, FFIValueType, Returns};
 
 type BlockId = crate::id::BlockId<crate::id::LlvmCtx>;
 type FunctionId = crate::id::FunctionId<crate::id::LlvmCtx>;
@@ -19,12 +20,12 @@ use inkwell::{
     passes::{PassManager, PassManagerBuilder},
     targets::{CodeModel, FileType, RelocMode, Target, TargetMachine},
     types::{BasicType, BasicTypeEnum, IntType, StructType},
-    values::{BasicValue, BasicValueEnum, FunctionValue, GlobalValue, IntValue},
-    AddressSpace, IntPredicate, OptimizationLevel,
+    values::{BasicValue, BasicValueEnum, FloatValue, FunctionValue, GlobalValue, IntValue},
+    AddressSpace, FloatPredicate, IntPredicate, OptimizationLevel,
 };
 
 #[cfg(feature = "link-llvm")]
-use crate::UnwrapNone;
+use crate::{isa::BinaryOperator, UnwrapNone};
 
 #[cfg(not(feature = "link-llvm"))]
 use std::marker::PhantomData;
@@ -137,6 +138,19 @@ pub enum Instruction {
         dividend: RegisterId,
         divisor: RegisterId,
     },
+    /// # [`Instruction::BinOp`]
+    ///
+    /// Performs a binary operation on two integers of the same size. Booleans
+    /// are represented as `i1`s, so logical operators are bitwise operators.
+    BinOp(crate::isa::BinOp<crate::id::LlvmCtx>),
+    /// # [`Instruction::Negate`]
+    ///
+    /// Negates a signed integer.
+    Negate(crate::isa::Negate<crate::id::LlvmCtx>),
+    /// # [`Instruction::Not`]
+    ///
+    /// Inverts a boolean.
+    Not(crate::isa::Not<crate::id::LlvmCtx>),
     /// # [`Instruction::Unreachable`]
     ///
     /// Indicates that it is impossible for the current path of execution to
@@ -162,6 +176,16 @@ pub enum Instruction {
         true_path: BlockId,
         false_path: BlockId,
     },
+    /// # [`Instruction::Switch`]
+    ///
+    /// Jumps to the block of the first case equal to the integer in the
+    /// register, otherwise jumps to the default block. Atoms and booleans are
+    /// switched on as the integers they are represented by.
+    Switch {
+        value: RegisterId,
+        cases: Vec<(u64, BlockId)>,
+        default: BlockId,
+    },
     /// # [`Instruction::Phi`]
     ///
     /// Will choose a value for the given register based on the value of
@@ -196,6 +220,7 @@ pub type ReturnType = crate::frontend::ir::Returns<ValueType>;
 pub enum ValueType {
     WordSizeBitType,
     BitType(u16),
+    Float64,
     Opaque(OpaqueStructId),
     Defined(StructId),
     Pointer(Box<ValueType>),
@@ -212,6 +237,60 @@ impl ValueType {
     pub fn into_ptr(self) -> ValueType {
         ValueType::Pointer(box self)
     }
+
+    /// The type that values of the FFI type are passed to and returned from
+    /// functions in `jssatrt` as.
+    pub fn from_ffi(typ: &FFIValueType, structs: &RuntimeStructs) -> ValueType {
+        match typ {
+            FFIValueType::Any => ValueType::Opaque(structs.any).into_ptr(),
+            FFIValueType::Runtime => ValueType::Opaque(structs.runtime).into_ptr(),
+            FFIValueType::String => ValueType::Opaque(structs.string).into_ptr(),
+            FFIValueType::Bytes => ValueType::Opaque(structs.bytes).into_ptr(),
+            FFIValueType::Record => ValueType::Opaque(structs.record).into_ptr(),
+            FFIValueType::Opaque => ValueType::WordSizeBitType,
+            FFIValueType::I64 => ValueType::BitType(64),
+            FFIValueType::F64 => ValueType::Float64,
+            FFIValueType::Bool => ValueType::BitType(1),
+            // pointers are null if the value is null, and other values are put
+            // behind a pointer so that they can be null
+            FFIValueType::Nullable(typ) => match ValueType::from_ffi(typ, structs) {
+                pointer @ ValueType::Pointer(_) => pointer,
+                value => value.into_ptr(),
+            },
+        }
+    }
+}
+
+/// The opaque structs of `jssatrt` that external functions are given pointers
+/// to.
+#[derive(Debug, Clone, Copy)]
+pub struct RuntimeStructs {
+    pub runtime: OpaqueStructId,
+    pub any: OpaqueStructId,
+    pub string: OpaqueStructId,
+    pub bytes: OpaqueStructId,
+    pub record: OpaqueStructId,
+}
+
+impl ExternalFunction {
+    /// Declares an external function of the IR as the function in `jssatrt`
+    /// that implements it.
+    pub fn from_ffi(external_function: &ir::ExternalFunction, structs: &RuntimeStructs) -> Self {
+        let parameters = (external_function.parameters.iter())
+            .map(|typ| ValueType::from_ffi(typ, structs))
+            .collect();
+
+        let return_type = match &external_function.return_type {
+            Returns::Value(typ) => ReturnType::Value(ValueType::from_ffi(typ, structs)),
+            Returns::Void => ReturnType::Void,
+        };
+
+        ExternalFunction {
+            name: external_function.name.clone(),
+            return_type,
+            parameters,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -746,6 +825,26 @@ impl<'c> BackendCompiler<'c, '_> {
                         self.builder
                             .build_conditional_branch(condition, then_block, else_block);
                     }
+                    Instruction::Switch {
+                        value,
+                        cases,
+                        default,
+                    } => {
+                        let value = register_values.get(&value).unwrap();
+                        debug_assert!(value.is_int_value());
+                        let value = value.into_int_value();
+
+                        let cases = (cases.into_iter())
+                            .map(|(case, path)| {
+                                let case = value.get_type().const_int(case, false);
+                                (case, *block_map.get(&path).unwrap())
+                            })
+                            .collect::<Vec<_>>();
+
+                        let default = *block_map.get(&default).unwrap();
+
+                        self.builder.build_switch(value, default, &cases);
+                    }
                     Instruction::Phi(result, implications) => {
                         let mut register_types = (implications.iter())
                             .map(|(_, r)| *register_values.get(r).unwrap())
@@ -826,20 +925,41 @@ impl<'c> BackendCompiler<'c, '_> {
                         let value = self.builder.build_load(field, "");
                         register_values.insert(result, value).expect_free();
                     }
-                    Instruction::OpLessThan(inst) => {
-                        let lhs = register_values.get(&inst.lhs).unwrap();
-                        let rhs = register_values.get(&inst.rhs).unwrap();
+                    Instruction::BinOp(inst) => {
+                        let lhs = *register_values.get(&inst.lhs).unwrap();
+                        let rhs = *register_values.get(&inst.rhs).unwrap();
 
-                        debug_assert!(lhs.is_int_value());
-                        let lhs = lhs.into_int_value();
-                        debug_assert!(rhs.is_int_value());
-                        let rhs = rhs.into_int_value();
+                        let result = match (lhs, rhs) {
+                            (BasicValueEnum::IntValue(lhs), BasicValueEnum::IntValue(rhs)) => {
+                                self.llvm_int_binop(inst.op, lhs, rhs)
+                            }
+                            (BasicValueEnum::FloatValue(lhs), BasicValueEnum::FloatValue(rhs)) => {
+                                self.llvm_float_binop(inst.op, lhs, rhs)
+                            }
+                            _ => unreachable!("operands of binary operators have the same type"),
+                        };
 
-                        let result =
-                            self.builder
-                                .build_int_compare(IntPredicate::SLT, lhs, rhs, "");
+                        register_values.insert(inst.result, result).expect_free();
+                    }
+                    Instruction::Negate(inst) => {
+                        let operand = register_values.get(&inst.operand).unwrap();
+                        debug_assert!(operand.is_float_value());
+                        let operand = operand.into_float_value();
+
+                        let result = self.builder.build_float_neg(operand, "");
+                        register_values
+                            .insert(inst.result, result.as_basic_value_enum())
+                            .expect_free();
+                    }
+                    Instruction::Not(inst) => {
+                        let operand = register_values.get(&inst.operand).unwrap();
+                        debug_assert!(operand.is_int_value());
+                        let operand = operand.into_int_value();
 
-                        register_values.insert(inst.result, result.as_basic_value_enum());
+                        let result = self.builder.build_not(operand, "");
+                        register_values
+                            .insert(inst.result, result.as_basic_value_enum())
+                            .expect_free();
                     }
                 }
             }
@@ -861,6 +981,100 @@ impl<'c> BackendCompiler<'c, '_> {
         }
     }
 
+    /// Performs a binary operator on integers the way the interpreter does.
+    /// Arithmetic on integers may produce floats, so the skeleton only lets
+    /// comparisons and bitwise operators through.
+    fn llvm_int_binop(
+        &self,
+        op: BinaryOperator,
+        lhs: IntValue<'c>,
+        rhs: IntValue<'c>,
+    ) -> BasicValueEnum<'c> {
+        use BinaryOperator::*;
+
+        let result = match op {
+            And => self.builder.build_and(lhs, rhs, ""),
+            Or => self.builder.build_or(lhs, rhs, ""),
+            Equals | SameValue => self
+                .builder
+                .build_int_compare(IntPredicate::EQ, lhs, rhs, ""),
+            LessThan => self
+                .builder
+                .build_int_compare(IntPredicate::SLT, lhs, rhs, ""),
+            BitwiseAnd | BitwiseOr | BitwiseXor | ShiftLeft | ShiftRight | UnsignedShiftRight => {
+                self.llvm_int32_binop(op, lhs, rhs)
+            }
+            Add | Subtract | Multiply | Divide | Remainder | Exponent => {
+                unreachable!("arithmetic on integers is not lowered")
+            }
+        };
+
+        result.as_basic_value_enum()
+    }
+
+    /// Performs a bitwise operator on the 32 bit integers that the operands
+    /// wrap to, like JavaScript does. The amount to shift by is taken modulo
+    /// 32, and the result of `>>>` is unsigned.
+    fn llvm_int32_binop(
+        &self,
+        op: BinaryOperator,
+        lhs: IntValue<'c>,
+        rhs: IntValue<'c>,
+    ) -> IntValue<'c> {
+        use BinaryOperator::*;
+
+        let i32_type = self.context.i32_type();
+        let lhs32 = self.builder.build_int_truncate(lhs, i32_type, "");
+        let rhs32 = self.builder.build_int_truncate(rhs, i32_type, "");
+        let shift = self
+            .builder
+            .build_and(rhs32, i32_type.const_int(31, false), "");
+
+        let result = match op {
+            BitwiseAnd => self.builder.build_and(lhs32, rhs32, ""),
+            BitwiseOr => self.builder.build_or(lhs32, rhs32, ""),
+            BitwiseXor => self.builder.build_xor(lhs32, rhs32, ""),
+            ShiftLeft => self.builder.build_left_shift(lhs32, shift, ""),
+            ShiftRight => self.builder.build_right_shift(lhs32, shift, true, ""),
+            UnsignedShiftRight => self.builder.build_right_shift(lhs32, shift, false, ""),
+            _ => unreachable!("only bitwise operators are performed on 32 bit integers"),
+        };
+
+        match op {
+            UnsignedShiftRight => self.builder.build_int_z_extend(result, lhs.get_type(), ""),
+            _ => self.builder.build_int_s_extend(result, lhs.get_type(), ""),
+        }
+    }
+
+    /// Performs a binary operator on floats the way the interpreter does. The
+    /// skeleton only lets through operators that LLVM has an equivalent of.
+    fn llvm_float_binop(
+        &self,
+        op: BinaryOperator,
+        lhs: FloatValue<'c>,
+        rhs: FloatValue<'c>,
+    ) -> BasicValueEnum<'c> {
+        use BinaryOperator::*;
+
+        match op {
+            Add => self.builder.build_float_add(lhs, rhs, "").into(),
+            Subtract => self.builder.build_float_sub(lhs, rhs, "").into(),
+            Multiply => self.builder.build_float_mul(lhs, rhs, "").into(),
+            Divide => self.builder.build_float_div(lhs, rhs, "").into(),
+            // `frem` takes the sign of the dividend, like `%` in JavaScript
+            Remainder => self.builder.build_float_rem(lhs, rhs, "").into(),
+            Equals => self
+                .builder
+                .build_float_compare(FloatPredicate::OEQ, lhs, rhs, "")
+                .into(),
+            LessThan => self
+                .builder
+                .build_float_compare(FloatPredicate::OLT, lhs, rhs, "")
+                .into(),
+            _ => unreachable!("the operator is not lowered for floats"),
+        }
+    }
+
     fn llvm_typeify_value(
         &self,
         value_type: ValueType,
@@ -873,6 +1087,7 @@ impl<'c> BackendCompiler<'c, '_> {
                 .ptr_type(AddressSpace::Generic)
                 .as_basic_type_enum(),
             ValueType::WordSizeBitType => self.word_size.as_basic_type_enum(),
+            ValueType::Float64 => self.context.f64_type().as_basic_type_enum(),
             ValueType::Opaque(id) => opaque_struct_resolver.resolve(&id).as_basic_type_enum(),
             ValueType::BitType(bits) => self
                 .context
@@ -1,14 +1,15 @@
 use crate::codegen::Program;
 
 pub mod llvm;
+pub mod skeleton;
 
 pub struct BuildArtifact {
     pub llvm_ir: String,
     pub obj: Vec<u8>,
 }
 
-pub fn compile(_program: Program) -> BuildArtifact {
-    todo!()
-    // let backend_ir = skeleton::translate(program);
-    // llvm::compile(backend_ir)
+pub fn compile(program: Program) -> Result<BuildArtifact, skeleton::SkeletonError> {
+    let names = skeleton::Names::new(&program);
+    let backend_ir = skeleton::translate(&program, &names)?;
+    Ok(llvm::compile(backend_ir))
 }
@@ -0,0 +1,944 @@
+//! Translates a lowered [`Program`] into the [`BackendIR`] that the LLVM
+//! backend compiles.
+//!
+//! Every function is given the runtime as an implicit first parameter. The
+//! `main` function made for the program creates the runtime with `jssatrt`,
+//! calls the entrypoint with it, and drops it afterwards. External functions
+//! are called as the functions of `jssatrt` that implement them, typed by
+//! [`ExternalFunction::from_ffi`], and are given the implicit runtime wherever
+//! they declare a [`FFIValueType::Runtime`] parameter.
+
+use rustc_hash::FxHashMap;
+use thiserror::Error;
+
+use super::llvm::{
+    BackendIR, Callable, Constant, ExternalFunction, Function, Instruction, LLVMLinkage,
+    NumberValue, OpaqueStruct, Parameter, ReturnType, RuntimeStructs, ValueType,
+};
+use crate::codegen::{self, EndInstruction, Program};
+use crate::frontend::ir::{FFIValueType, Returns};
+use crate::id::{AssemblerCtx, Counter, IdCompat, LlvmCtx, LowerCtx};
+use crate::isa::{self, BinaryOperator, SwitchValue};
+
+type BlockId = crate::id::BlockId<LlvmCtx>;
+type FunctionId = crate::id::FunctionId<LlvmCtx>;
+type RegisterId = crate::id::RegisterId<LlvmCtx>;
+type ExternalFunctionId = crate::id::ExternalFunctionId<LlvmCtx>;
+type OpaqueStructId = crate::id::OpaqueStructId<LlvmCtx>;
+
+type LowerFnId = crate::id::FunctionId<LowerCtx>;
+type LowerBlkId = crate::id::BlockId<LowerCtx>;
+type LowerRegId = crate::id::RegisterId<LowerCtx>;
+type LowerBlock = codegen::Block<LowerCtx, LowerBlkId>;
+type ConstantId = crate::id::ConstantId<AssemblerCtx>;
+
+/// Why a program could not be translated into the [`BackendIR`].
+#[derive(Clone, Debug, Error)]
+pub enum SkeletonError {
+    /// The backend does not know how to compile the instruction.
+    #[error("the backend does not support `{0}`")]
+    Unsupported(&'static str),
+    /// Nothing gives the register a value of a type the backend can compile.
+    #[error("the type of register %{0} is not known")]
+    UnknownType(LowerRegId),
+    /// The register is given a value of a different type than where it's used.
+    #[error("register %{register} is a `{found:?}`, but a `{expected:?}` is needed")]
+    TypeMismatch {
+        register: LowerRegId,
+        expected: ValueType,
+        found: ValueType,
+    },
+    /// Two paths of a function return values of different types.
+    #[error("function {0} returns values of different types")]
+    ReturnMismatch(LowerFnId),
+    /// A block jumps to another more than once with different arguments.
+    #[error("block {0} jumps to block {1} with different arguments")]
+    ConflictingJumps(LowerBlkId, LowerBlkId),
+}
+
+pub type SkeletonResult<T> = Result<T, SkeletonError>;
+
+/// The names of the functions and constants of a program, which the
+/// [`BackendIR`] borrows.
+pub struct Names {
+    functions: FxHashMap<LowerFnId, String>,
+    constants: FxHashMap<ConstantId, String>,
+}
+
+impl Names {
+    pub fn new(program: &Program) -> Self {
+        let functions = (program.functions.keys())
+            .map(|id| (*id, format!("jssat_fn{}", id)))
+            .collect();
+
+        let constants = (program.constants.keys())
+            .map(|id| (*id, format!("jssat_constant{}", id)))
+            .collect();
+
+        Names {
+            functions,
+            constants,
+        }
+    }
+}
+
+/// Translates a program into the [`BackendIR`], inferring the type of every
+/// register from the values given to it.
+pub fn translate<'name>(
+    program: &Program,
+    names: &'name Names,
+) -> SkeletonResult<BackendIR<'name>> {
+    let opaque_struct_ids = Counter::<OpaqueStructId>::new();
+    let structs = RuntimeStructs {
+        runtime: opaque_struct_ids.next(),
+        any: opaque_struct_ids.next(),
+        string: opaque_struct_ids.next(),
+        bytes: opaque_struct_ids.next(),
+        record: opaque_struct_ids.next(),
+    };
+
+    let opaque_structs = [
+        (structs.runtime, "Runtime"),
+        (structs.any, "Any"),
+        (structs.string, "String"),
+        (structs.bytes, "Bytes"),
+        (structs.record, "Record"),
+    ]
+    .into_iter()
+    .map(|(id, name)| (id, OpaqueStruct { name }))
+    .collect();
+
+    let constants = (program.constants.iter())
+        .map(|(id, constant)| {
+            let name = names.constants[id].as_str();
+            let payload = constant.payload.clone();
+            (id.convert(), Constant { name, payload })
+        })
+        .collect();
+
+    let mut external_functions = (program.external_functions.iter())
+        .map(|(id, ext_fn)| (id.convert(), ExternalFunction::from_ffi(ext_fn, &structs)))
+        .collect::<FxHashMap<ExternalFunctionId, _>>();
+
+    // the functions of `jssatrt` that the translation calls on its own
+    let ext_fn_ids = Counter::<ExternalFunctionId>::new_with_value(
+        (external_functions.keys().map(|id| id.value() + 1))
+            .max()
+            .unwrap_or(0),
+    );
+    let runtime = ValueType::Opaque(structs.runtime).into_ptr();
+    let bytes = ValueType::Opaque(structs.bytes).into_ptr();
+    let helpers = Helpers {
+        runtime_new: ext_fn_ids.next(),
+        runtime_drop: ext_fn_ids.next(),
+        bytes_new: ext_fn_ids.next(),
+    };
+
+    for (id, name, parameters, return_type) in [
+        (
+            helpers.runtime_new,
+            "jssatrt_runtime_new",
+            vec![],
+            ReturnType::Value(runtime.clone()),
+        ),
+        (
+            helpers.runtime_drop,
+            "jssatrt_runtime_drop",
+            vec![runtime.clone()],
+            ReturnType::Void,
+        ),
+        (
+            helpers.bytes_new,
+            "jssatrt_bytes_new",
+            vec![
+                runtime.clone(),
+                ValueType::BitType(8).into_ptr(),
+                ValueType::WordSizeBitType,
+            ],
+            ReturnType::Value(bytes.clone()),
+        ),
+    ] {
+        let name = name.to_owned();
+        let ext_fn = ExternalFunction {
+            name,
+            return_type,
+            parameters,
+        };
+        external_functions.insert(id, ext_fn);
+    }
+
+    let mut skeleton = Skeleton {
+        program,
+        structs,
+        helpers,
+        types: FxHashMap::default(),
+        returns: FxHashMap::default(),
+    };
+    skeleton.infer()?;
+
+    let mut functions = FxHashMap::<FunctionId, _>::default();
+    for (id, function) in program.functions.iter() {
+        let name = names.functions[id].as_str();
+        functions.insert(id.convert(), skeleton.function(*id, function, name)?);
+    }
+
+    let main_id = FunctionId::new_with_value(
+        (functions.keys().map(|id| id.value() + 1))
+            .max()
+            .unwrap_or(0),
+    );
+    functions.insert(main_id, skeleton.main());
+
+    Ok(BackendIR {
+        constants,
+        opaque_structs,
+        structs: FxHashMap::default(),
+        external_functions,
+        functions,
+    })
+}
+
+/// The functions of `jssatrt` that the translation calls on its own.
+#[derive(Clone, Copy)]
+struct Helpers {
+    runtime_new: ExternalFunctionId,
+    runtime_drop: ExternalFunctionId,
+    bytes_new: ExternalFunctionId,
+}
+
+struct Skeleton<'p> {
+    program: &'p Program,
+    structs: RuntimeStructs,
+    helpers: Helpers,
+    types: FxHashMap<LowerFnId, FxHashMap<LowerRegId, ValueType>>,
+    returns: FxHashMap<LowerFnId, ReturnType>,
+}
+
+impl Skeleton<'_> {
+    /// Infers the types of registers until no more can be inferred. Types flow
+    /// from instructions into their results, from jumps into the parameters of
+    /// blocks, and from calls into the parameters of functions and back.
+    fn infer(&mut self) -> SkeletonResult<()> {
+        let program = self.program;
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for (id, function) in program.functions.iter() {
+                for block in function.blocks.values() {
+                    for inst in block.instructions.iter() {
+                        changed |= self.infer_inst(*id, inst)?;
+                    }
+
+                    changed |= self.infer_end(*id, function, block)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn infer_inst(
+        &mut self,
+        function: LowerFnId,
+        inst: &codegen::Instruction<LowerCtx>,
+    ) -> SkeletonResult<bool> {
+        use codegen::Instruction::*;
+
+        let (result, typ) = match inst {
+            MakeInteger(i) => (i.result, ValueType::BitType(64)),
+            MakeBoolean(i) => (i.result, ValueType::BitType(1)),
+            MakeAtom(i) => (i.result, ValueType::BitType(16)),
+            MakeBytes(i) => (i.result, ValueType::Opaque(self.structs.bytes).into_ptr()),
+            BinOp(i) => match (self.type_of(function, i.lhs), i.op) {
+                (None, _) => return Ok(false),
+                (Some(_), BinaryOperator::Equals)
+                | (Some(_), BinaryOperator::SameValue)
+                | (Some(_), BinaryOperator::LessThan) => (i.result, ValueType::BitType(1)),
+                (Some(typ), _) => (i.result, typ),
+            },
+            Negate(isa::Negate { result, operand }) | Not(isa::Not { result, operand }) => {
+                match self.type_of(function, *operand) {
+                    Some(typ) => (*result, typ),
+                    None => return Ok(false),
+                }
+            }
+            CallStatic(i) => {
+                let callee = &self.program.functions[&i.calling];
+                let parameters = &callee.blocks[&callee.entry].parameters;
+
+                let mut changed = false;
+                for (parameter, arg) in parameters.iter().zip(i.args.iter()) {
+                    if let Some(typ) = self.type_of(function, *arg) {
+                        changed |= self.assign(i.calling, *parameter, typ)?;
+                    }
+                }
+
+                match (i.result, self.returns.get(&i.calling)) {
+                    (Some(result), Some(ReturnType::Value(typ))) => {
+                        let typ = typ.clone();
+                        return Ok(self.assign(function, result, typ)? || changed);
+                    }
+                    _ => return Ok(changed),
+                }
+            }
+            CallExtern(i) => {
+                let ext_fn = &self.program.external_functions[&i.calling];
+                match (i.result, &ext_fn.return_type) {
+                    (Some(result), Returns::Value(typ)) => {
+                        (result, ValueType::from_ffi(typ, &self.structs))
+                    }
+                    _ => return Ok(false),
+                }
+            }
+            // every other instruction is reported when the function is
+            // translated
+            _ => return Ok(false),
+        };
+
+        self.assign(function, result, typ)
+    }
+
+    fn infer_end(
+        &mut self,
+        id: LowerFnId,
+        function: &codegen::Function<LowerCtx>,
+        block: &LowerBlock,
+    ) -> SkeletonResult<bool> {
+        let mut changed = false;
+
+        for isa::BlockJump(target, args) in block.end.paths() {
+            let parameters = &function.blocks[target].parameters;
+            for (parameter, arg) in parameters.iter().zip(args.iter()) {
+                if let Some(typ) = self.type_of(id, *arg) {
+                    changed |= self.assign(id, *parameter, typ)?;
+                }
+            }
+        }
+
+        let returns = match &block.end {
+            EndInstruction::Return(isa::Return(Some(value))) => match self.type_of(id, *value) {
+                Some(typ) => ReturnType::Value(typ),
+                None => return Ok(changed),
+            },
+            EndInstruction::Return(isa::Return(None)) => ReturnType::Void,
+            _ => return Ok(changed),
+        };
+
+        match self.returns.get(&id) {
+            Some(typ) if *typ == returns => Ok(changed),
+            Some(_) => Err(SkeletonError::ReturnMismatch(id)),
+            None => {
+                self.returns.insert(id, returns);
+                Ok(true)
+            }
+        }
+    }
+
+    fn type_of(&self, function: LowerFnId, register: LowerRegId) -> Option<ValueType> {
+        (self.types.get(&function)).and_then(|types| types.get(&register).cloned())
+    }
+
+    /// Gives the register a type, returning whether it didn't have one yet.
+    fn assign(
+        &mut self,
+        function: LowerFnId,
+        register: LowerRegId,
+        typ: ValueType,
+    ) -> SkeletonResult<bool> {
+        let types = self.types.entry(function).or_default();
+
+        match types.get(&register) {
+            Some(found) if *found == typ => Ok(false),
+            Some(found) => Err(SkeletonError::TypeMismatch {
+                register,
+                expected: typ,
+                found: found.clone(),
+            }),
+            None => {
+                types.insert(register, typ);
+                Ok(true)
+            }
+        }
+    }
+
+    fn expect_type(&self, function: LowerFnId, register: LowerRegId) -> SkeletonResult<ValueType> {
+        self.type_of(function, register)
+            .ok_or(SkeletonError::UnknownType(register))
+    }
+
+    fn function<'name>(
+        &self,
+        id: LowerFnId,
+        function: &codegen::Function<LowerCtx>,
+        name: &'name str,
+    ) -> SkeletonResult<Function<'name>> {
+        // registers are shared by every block of a function, so temporary
+        // registers are made after the last of them
+        let mut last_register = 0;
+        for block in function.blocks.values() {
+            let registers = (block.parameters.iter().copied())
+                .chain(
+                    block
+                        .instructions
+                        .iter()
+                        .flat_map(|i| i.declared_register()),
+                )
+                .chain(block.instructions.iter().flat_map(|i| i.used_registers()))
+                .chain(block.end.used_registers());
+
+            for register in registers {
+                last_register = last_register.max(register.value() + 1);
+            }
+        }
+
+        let temporaries = Counter::new_with_value(last_register);
+        let runtime = temporaries.next();
+        let translator = FnTranslator {
+            skeleton: self,
+            id,
+            temporaries,
+            runtime,
+        };
+
+        let mut parameters = vec![Parameter {
+            r#type: ValueType::Opaque(self.structs.runtime).into_ptr(),
+            register: translator.runtime,
+        }];
+
+        for parameter in function.blocks[&function.entry].parameters.iter() {
+            parameters.push(Parameter {
+                r#type: self.expect_type(id, *parameter)?,
+                register: parameter.convert(),
+            });
+        }
+
+        let phis = phis(function)?;
+
+        let mut blocks = FxHashMap::default();
+        for (block_id, block) in function.blocks.iter() {
+            let mut instructions = Vec::new();
+
+            for (parameter, incoming) in (phis.get(block_id).into_iter()).flatten() {
+                instructions.push(Instruction::Phi(parameter.convert(), incoming.clone()));
+            }
+
+            for inst in block.instructions.iter() {
+                translator.inst(inst, &mut instructions)?;
+            }
+
+            instructions.push(translator.end(&block.end)?);
+            blocks.insert(block_id.convert(), instructions);
+        }
+
+        Ok(Function {
+            name,
+            linkage: None,
+            return_type: self.returns.get(&id).cloned().unwrap_or(ReturnType::Void),
+            parameters,
+            entry_block: function.entry.convert(),
+            blocks,
+        })
+    }
+
+    /// The `main` function, which runs the entrypoint with a new runtime.
+    fn main(&self) -> Function<'static> {
+        let runtime = RegisterId::new_with_value(0);
+        let entry_block = BlockId::new_with_value(0);
+
+        let instructions = vec![
+            Instruction::Call(
+                Some(runtime),
+                Callable::External(self.helpers.runtime_new),
+                vec![],
+            ),
+            Instruction::Call(
+                None,
+                Callable::Static(self.program.entrypoint.convert()),
+                vec![runtime],
+            ),
+            Instruction::Call(
+                None,
+                Callable::External(self.helpers.runtime_drop),
+                vec![runtime],
+            ),
+            Instruction::Return(None),
+        ];
+
+        let mut blocks = FxHashMap::default();
+        blocks.insert(entry_block, instructions);
+
+        Function {
+            name: "main",
+            linkage: Some(LLVMLinkage::External),
+            return_type: ReturnType::Void,
+            parameters: vec![],
+            entry_block,
+            blocks,
+        }
+    }
+}
+
+/// The parameters of each block, along with the value each block that jumps to
+/// it gives the parameter.
+type Phis = FxHashMap<LowerBlkId, Vec<(LowerRegId, Vec<(BlockId, RegisterId)>)>>;
+
+/// The phis that each block starts with, which choose the value of each of its
+/// parameters by the block that jumped to it.
+fn phis(function: &codegen::Function<LowerCtx>) -> SkeletonResult<Phis> {
+    let mut phis = Phis::default();
+
+    for (block_id, block) in function.blocks.iter() {
+        for isa::BlockJump(target, args) in block.end.paths() {
+            if *target == function.entry {
+                return Err(SkeletonError::Unsupported("jumps to the entry block"));
+            }
+
+            let parameters = &function.blocks[target].parameters;
+            let target_phis = phis.entry(*target).or_insert_with(|| {
+                (parameters.iter())
+                    .map(|parameter| (*parameter, Vec::new()))
+                    .collect()
+            });
+
+            for ((_, incoming), arg) in target_phis.iter_mut().zip(args.iter()) {
+                let from = block_id.convert();
+                let value = arg.convert();
+
+                match incoming.iter().find(|(block, _)| *block == from) {
+                    Some((_, existing)) if *existing == value => {}
+                    Some(_) => return Err(SkeletonError::ConflictingJumps(*block_id, *target)),
+                    None => incoming.push((from, value)),
+                }
+            }
+        }
+    }
+
+    Ok(phis)
+}
+
+struct FnTranslator<'s, 'p> {
+    skeleton: &'s Skeleton<'p>,
+    id: LowerFnId,
+    temporaries: Counter<RegisterId>,
+    runtime: RegisterId,
+}
+
+impl FnTranslator<'_, '_> {
+    fn inst(
+        &self,
+        inst: &codegen::Instruction<LowerCtx>,
+        instructions: &mut Vec<Instruction>,
+    ) -> SkeletonResult<()> {
+        use codegen::Instruction::*;
+
+        let inst = match inst {
+            Noop(_) | Comment(_) => return Ok(()),
+            MakeInteger(i) => Instruction::LoadNumber {
+                result: i.result.convert(),
+                value: NumberValue::SignedArbitrary(64, i.item),
+            },
+            MakeBoolean(i) => Instruction::LoadNumber {
+                result: i.result.convert(),
+                value: NumberValue::UnsignedArbitrary(1, i.item as u64),
+            },
+            MakeAtom(i) => Instruction::LoadNumber {
+                result: i.result.convert(),
+                value: NumberValue::UnsignedArbitrary(16, i.item.0.get() as u64),
+            },
+            MakeBytes(i) => {
+                let payload = self.temporaries.next();
+                let length = self.temporaries.next();
+                let constant = i.item.convert();
+
+                instructions.push(Instruction::LoadConstantPtr(payload, constant));
+                instructions.push(Instruction::LoadConstantLen(length, constant));
+                Instruction::Call(
+                    Some(i.result.convert()),
+                    Callable::External(self.skeleton.helpers.bytes_new),
+                    vec![self.runtime, payload, length],
+                )
+            }
+            BinOp(i) => {
+                let typ = self.skeleton.expect_type(self.id, i.lhs)?;
+                let found = self.skeleton.expect_type(self.id, i.rhs)?;
+                if typ != found {
+                    return Err(SkeletonError::TypeMismatch {
+                        register: i.rhs,
+                        expected: typ,
+                        found,
+                    });
+                }
+
+                if let Some(unsupported) = unsupported_binop(i.op, &typ) {
+                    return Err(SkeletonError::Unsupported(unsupported));
+                }
+
+                Instruction::BinOp(isa::BinOp {
+                    result: i.result.convert(),
+                    op: i.op,
+                    lhs: i.lhs.convert(),
+                    rhs: i.rhs.convert(),
+                })
+            }
+            Negate(i) => {
+                // negating integers may produce `-0` or overflow into a float
+                if self.skeleton.expect_type(self.id, i.operand)? != ValueType::Float64 {
+                    return Err(SkeletonError::Unsupported("negating integers"));
+                }
+
+                Instruction::Negate(isa::Negate {
+                    result: i.result.convert(),
+                    operand: i.operand.convert(),
+                })
+            }
+            Not(i) => Instruction::Not(isa::Not {
+                result: i.result.convert(),
+                operand: i.operand.convert(),
+            }),
+            CallStatic(i) => {
+                let args = std::iter::once(self.runtime)
+                    .chain(i.args.iter().map(|r| r.convert()))
+                    .collect();
+
+                Instruction::Call(
+                    i.result.map(|r| r.convert()),
+                    Callable::Static(i.calling.convert()),
+                    args,
+                )
+            }
+            CallExtern(i) => {
+                let ext_fn = &self.skeleton.program.external_functions[&i.calling];
+
+                if let Returns::Value(FFIValueType::Nullable(_)) = ext_fn.return_type {
+                    return Err(SkeletonError::Unsupported("nullable results"));
+                }
+
+                let mut args = Vec::new();
+                for (typ, arg) in ext_fn.parameters.iter().zip(i.args.iter()) {
+                    if let FFIValueType::Runtime = typ {
+                        args.push(self.runtime);
+                        continue;
+                    }
+
+                    let expected = ValueType::from_ffi(typ, &self.skeleton.structs);
+                    let found = self.skeleton.expect_type(self.id, *arg)?;
+                    if expected != found {
+                        return Err(SkeletonError::TypeMismatch {
+                            register: *arg,
+                            expected,
+                            found,
+                        });
+                    }
+
+                    args.push(arg.convert());
+                }
+
+                Instruction::Call(
+                    i.result.map(|r| r.convert()),
+                    Callable::External(i.calling.convert()),
+                    args,
+                )
+            }
+            NewRecord(_) | RecordGet(_) | RecordSet(_) | RecordHasKey(_) => {
+                return Err(SkeletonError::Unsupported("records"))
+            }
+            NewTuple(_) | TupleGet(_) | TupleLen(_) => {
+                return Err(SkeletonError::Unsupported("tuples"))
+            }
+            CallVirt(_) | GetFnPtr(_) => {
+                return Err(SkeletonError::Unsupported("function pointers"))
+            }
+        };
+
+        instructions.push(inst);
+        Ok(())
+    }
+
+    fn end(&self, end: &EndInstruction<LowerCtx, LowerBlkId>) -> SkeletonResult<Instruction> {
+        Ok(match end {
+            EndInstruction::Unreachable(_) => Instruction::Unreachable,
+            EndInstruction::Return(isa::Return(value)) => {
+                Instruction::Return(value.map(|r| r.convert()))
+            }
+            EndInstruction::Jump(isa::Jump(isa::BlockJump(target, _))) => {
+                Instruction::Jump(target.convert())
+            }
+            EndInstruction::JumpIf(i) => Instruction::JumpIf {
+                condition: i.condition.convert(),
+                true_path: i.if_so.0.convert(),
+                false_path: i.other.0.convert(),
+            },
+            EndInstruction::Switch(i) => {
+                let typ = self.skeleton.expect_type(self.id, i.value)?;
+
+                // only the cases of the same kind as the value can be taken
+                let cases = (i.cases.iter())
+                    .filter_map(|(case, path)| {
+                        let case = match (case, &typ) {
+                            (SwitchValue::Atom(atom), ValueType::BitType(16)) => {
+                                atom.0.get() as u64
+                            }
+                            (SwitchValue::Integer(integer), ValueType::BitType(64)) => {
+                                *integer as u64
+                            }
+                            (SwitchValue::Boolean(boolean), ValueType::BitType(1)) => {
+                                *boolean as u64
+                            }
+                            _ => return None,
+                        };
+
+                        Some((case, path.0.convert()))
+                    })
+                    .collect();
+
+                Instruction::Switch {
+                    value: i.value.convert(),
+                    cases,
+                    default: i.default.0.convert(),
+                }
+            }
+        })
+    }
+}
+
+/// What the backend can't compile about a binary operator on operands of the
+/// type, if it can't perform it the way the interpreter does.
+fn unsupported_binop(op: BinaryOperator, typ: &ValueType) -> Option<&'static str> {
+    use BinaryOperator::*;
+
+    match (typ, op) {
+        // the results of arithmetic on integers are promoted to floats when
+        // they aren't integers, which the type of the register can't change to
+        (ValueType::BitType(64), Add | Subtract | Multiply | Divide | Remainder | Exponent) => {
+            Some("arithmetic on integers")
+        }
+        (ValueType::BitType(64), And | Or) => Some("logical operators on integers"),
+        (ValueType::BitType(64), _) => None,
+        (ValueType::Float64, Exponent) => Some("exponentiation of floats"),
+        (ValueType::Float64, SameValue) => Some("comparing floats by their same value"),
+        (
+            ValueType::Float64,
+            Add | Subtract | Multiply | Divide | Remainder | Equals | LessThan,
+        ) => None,
+        (ValueType::Float64, _) => Some("bitwise and logical operators on floats"),
+        (ValueType::BitType(1), And | Or | Equals | SameValue) => None,
+        (ValueType::BitType(16), Equals | SameValue) => None,
+        _ => Some("binary operators on values of this type"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustc_hash::FxHashMap;
+
+    use super::*;
+    use crate::codegen::{Block, Instruction as Inst};
+    use crate::frontend::ir;
+    use crate::isa::{BinOp, BlockJump, Call, Jump, Make, NewRecord, Return};
+    use crate::symbolic_execution::types::TypeBag;
+
+    fn r(value: usize) -> LowerRegId {
+        LowerRegId::new_with_value(value)
+    }
+
+    fn b(value: usize) -> LowerBlkId {
+        LowerBlkId::new_with_value(value)
+    }
+
+    fn block(
+        parameters: Vec<LowerRegId>,
+        instructions: Vec<Inst<LowerCtx>>,
+        end: EndInstruction<LowerCtx, LowerBlkId>,
+    ) -> LowerBlock {
+        Block {
+            parameters,
+            instructions,
+            end,
+            type_info: TypeBag::default(),
+        }
+    }
+
+    /// A program of a single function made of the given blocks, the first of
+    /// which is its entry block.
+    fn program(blocks: Vec<LowerBlock>, external_functions: Vec<ir::ExternalFunction>) -> Program {
+        let entrypoint = LowerFnId::new_with_value(0);
+        let function = codegen::Function {
+            entry: b(0),
+            blocks: (blocks.into_iter().enumerate())
+                .map(|(i, block)| (b(i), block))
+                .collect(),
+        };
+
+        Program {
+            entrypoint,
+            external_functions: (external_functions.into_iter().enumerate())
+                .map(|(i, ext_fn)| (IdCompat::new_with_value(i), ext_fn))
+                .collect(),
+            constants: FxHashMap::default(),
+            functions: [(entrypoint, function)].into_iter().collect(),
+        }
+    }
+
+    fn function<'a, 'n>(ir: &'a BackendIR<'n>, name: &str) -> &'a Function<'n> {
+        (ir.functions.values())
+            .find(|f| f.name == name)
+            .unwrap_or_else(|| panic!("no function `{}`", name))
+    }
+
+    /// Confirm that external functions are declared and called with the types
+    /// of their FFI signatures, and are given the runtime
+    #[test]
+    pub fn calls_external_functions_with_their_ffi_types() {
+        let print = ir::ExternalFunction {
+            name: "jssatrt_print_i64".into(),
+            parameters: vec![FFIValueType::Runtime, FFIValueType::I64],
+            return_type: Returns::Void,
+        };
+
+        let program = program(
+            vec![block(
+                vec![],
+                vec![
+                    Inst::MakeInteger(Make {
+                        result: r(0),
+                        item: 5,
+                    }),
+                    Inst::CallExtern(Call {
+                        result: None,
+                        calling: IdCompat::new_with_value(0),
+                        args: vec![r(1), r(0)],
+                    }),
+                ],
+                EndInstruction::Return(Return(None)),
+            )],
+            vec![print],
+        );
+
+        let names = Names::new(&program);
+        let ir = translate(&program, &names).unwrap();
+
+        let (print_id, print) = (ir.external_functions.iter())
+            .find(|(_, f)| f.name == "jssatrt_print_i64")
+            .unwrap();
+        let runtime = ValueType::Opaque(OpaqueStructId::new_with_value(0)).into_ptr();
+        assert_eq!(print.parameters, [runtime, ValueType::BitType(64)]);
+
+        let entrypoint = function(&ir, "jssat_fn0");
+        let runtime = entrypoint.parameters[0].register;
+        let instructions = &entrypoint.blocks[&entrypoint.entry_block];
+        assert!(matches!(
+            &instructions[1],
+            Instruction::Call(None, Callable::External(id), args)
+                if id == print_id && *args == [runtime, RegisterId::new_with_value(0)]
+        ));
+
+        // `main` runs the entrypoint with a new runtime
+        let main = function(&ir, "main");
+        assert!(main.blocks[&main.entry_block]
+            .iter()
+            .any(|i| matches!(i, Instruction::Call(_, Callable::Static(_), _))));
+    }
+
+    /// Confirm that the parameters of blocks are typed by the values jumped to
+    /// them with, and are chosen by phis
+    #[test]
+    pub fn block_parameters_become_phis() {
+        let program = program(
+            vec![
+                block(
+                    vec![],
+                    vec![Inst::MakeInteger(Make {
+                        result: r(0),
+                        item: 1,
+                    })],
+                    EndInstruction::Jump(Jump(BlockJump(b(1), vec![r(0)]))),
+                ),
+                block(
+                    vec![r(1)],
+                    vec![],
+                    EndInstruction::Return(Return(Some(r(1)))),
+                ),
+            ],
+            vec![],
+        );
+
+        let names = Names::new(&program);
+        let ir = translate(&program, &names).unwrap();
+
+        let entrypoint = function(&ir, "jssat_fn0");
+        assert_eq!(
+            entrypoint.return_type,
+            ReturnType::Value(ValueType::BitType(64))
+        );
+
+        let (entry, param) = (BlockId::new_with_value(0), RegisterId::new_with_value(1));
+        assert!(matches!(
+            &entrypoint.blocks[&BlockId::new_with_value(1)][0],
+            Instruction::Phi(result, incoming)
+                if *result == param && *incoming == [(entry, RegisterId::new_with_value(0))]
+        ));
+    }
+
+    /// Instructions the backend can't compile are reported instead of panicking
+    #[test]
+    pub fn unsupported_instructions_are_reported() {
+        let program = program(
+            vec![block(
+                vec![],
+                vec![Inst::NewRecord(NewRecord { result: r(0) })],
+                EndInstruction::Return(Return(None)),
+            )],
+            vec![],
+        );
+
+        let names = Names::new(&program);
+        assert!(matches!(
+            translate(&program, &names),
+            Err(SkeletonError::Unsupported("records"))
+        ));
+    }
+
+    /// Operators that the backend would compute differently from the
+    /// interpreter are reported instead of being miscompiled
+    #[test]
+    pub fn only_faithful_binary_operators_are_lowered() {
+        let translate_op = |op| {
+            let program = program(
+                vec![block(
+                    vec![],
+                    vec![
+                        Inst::MakeInteger(Make {
+                            result: r(0),
+                            item: 3,
+                        }),
+                        Inst::MakeInteger(Make {
+                            result: r(1),
+                            item: 2,
+                        }),
+                        Inst::BinOp(BinOp {
+                            result: r(2),
+                            op,
+                            lhs: r(0),
+                            rhs: r(1),
+                        }),
+                    ],
+                    EndInstruction::Return(Return(Some(r(2)))),
+                )],
+                vec![],
+            );
+
+            let names = Names::new(&program);
+            translate(&program, &names).map(|_| ())
+        };
+
+        // `3 / 2` is `1.5`, which doesn't fit the integer register
+        assert!(matches!(
+            translate_op(BinaryOperator::Divide),
+            Err(SkeletonError::Unsupported("arithmetic on integers"))
+        ));
+        assert!(translate_op(BinaryOperator::ShiftLeft).is_ok());
+        assert!(translate_op(BinaryOperator::LessThan).is_ok());
+    }
+}
@@ -90,6 +90,7 @@ pub fn display_program(program: &Program) -> String {
             match &block.end {
                 EndInstruction::Jump(inst) => inst.display(&mut text),
                 EndInstruction::JumpIf(inst) => inst.display(&mut text),
+                EndInstruction::Switch(inst) => inst.display(&mut text),
                 EndInstruction::Return(inst) => inst.display(&mut text),
                 _ => todo!(),
                 /* EndInstruction::Unreachable(inst) => {
@@ -26,6 +26,23 @@ macro_rules! iwl {
 pub fn display_typed(program: &TypedProgram) -> String {
     let mut text = String::new();
 
+    let optimizations = &program.optimizations;
+    if let (Some(first), Some(last)) = (optimizations.first(), optimizations.last()) {
+        iwl!(text, "-- optimized from {} to {}", first.before, last.after);
+
+        for report in optimizations.iter() {
+            iwl!(
+                text,
+                "--   {}: {} -> {}",
+                report.pass,
+                report.before,
+                report.after
+            );
+        }
+
+        iwl!(text);
+    }
+
     for (id, ext_fn) in program.external_functions.iter() {
         iw!(text, "ext fn @@{}(", id);
 
@@ -84,6 +101,7 @@ pub fn display_typed(program: &TypedProgram) -> String {
         match &block.end {
             EndInstruction::Jump(inst) => inst.display(&mut text),
             EndInstruction::JumpIf(inst) => inst.display(&mut text),
+            EndInstruction::Switch(inst) => inst.display(&mut text),
             EndInstruction::Return(inst) => inst.display(&mut text),
             _ => todo!(),
             /* EndInstruction::Unreachable(inst) => {
@@ -99,6 +99,11 @@ impl<'a> Lowerer<'a> {
                     block_list.enqueue(inst.if_so.0);
                     block_list.enqueue(inst.other.0);
                 }
+                super::EndInstruction::Switch(inst) => {
+                    for path in inst.paths() {
+                        block_list.enqueue(path.0);
+                    }
+                }
             };
         }
     }
@@ -135,6 +140,11 @@ impl<'a> Lowerer<'a> {
                     block_list.enqueue(inst.if_so.0);
                     block_list.enqueue(inst.other.0);
                 }
+                Switch(inst) => {
+                    for path in inst.paths() {
+                        block_list.enqueue(path.0);
+                    }
+                }
             };
         }
 
@@ -199,13 +209,16 @@ impl<'a> Lowerer<'a> {
                     CallVirt(_) => panic!("virtual instructions not supported"),
                     // CallVirt(i.retag(&mut reg_retagger)),
                     GetFnPtr(i) => GetFnPtr(i.retag(&mut reg_retagger, &fnptr_retagger)),
-                    // MakeAtom(i) => MakeAtom(i.retag(&mut reg_retagger)),
+                    MakeAtom(i) => MakeAtom(i.retag(&mut reg_retagger)),
                     MakeBytes(i) => MakeBytes(i.retag(&mut reg_retagger, &const_retagger)),
                     MakeInteger(i) => MakeInteger(i.retag(&mut reg_retagger)),
                     MakeBoolean(i) => MakeBoolean(i.retag(&mut reg_retagger)),
                     BinOp(i) => BinOp(i.retag(&mut reg_retagger)),
                     Negate(i) => Negate(i.retag(&mut reg_retagger)),
-                    _ => todo!(),
+                    Not(i) => Not(i.retag(&mut reg_retagger)),
+                    NewTuple(i) => NewTuple(i.retag(&mut reg_retagger)),
+                    TupleGet(i) => TupleGet(i.retag(&mut reg_retagger)),
+                    TupleLen(i) => TupleLen(i.retag(&mut reg_retagger)),
                 };
                 instructions.push(new_inst);
             }
@@ -215,7 +228,7 @@ impl<'a> Lowerer<'a> {
             use super::EndInstruction::*;
 
             let end = match block.end.clone() {
-                Unreachable(i) => todo!(),
+                Unreachable(i) => Unreachable(i.retag(&mut reg_retagger)),
                 Return(i) => Return(i.retag(&reg_retagger)),
                 Jump(inst) => {
                     let BlockJump(target, args) = inst.0;
@@ -252,6 +265,27 @@ impl<'a> Lowerer<'a> {
                         other,
                     })
                 }
+                Switch(inst) => {
+                    let mut retag_path = |BlockJump(target, args): BlockJump<_, _>| {
+                        block_list.enqueue(target);
+                        BlockJump(
+                            block_id_mapper.retag_old(target.convert()),
+                            args.into_iter()
+                                .map(|r| reg_retagger.retag_old(r))
+                                .collect(),
+                        )
+                    };
+
+                    let cases = (inst.cases.into_iter())
+                        .map(|(value, path)| (value, retag_path(path)))
+                        .collect();
+
+                    Switch(isa::Switch {
+                        value: reg_retagger.retag_old(inst.value),
+                        cases,
+                        default: retag_path(inst.default),
+                    })
+                }
             };
 
             blocks.insert(
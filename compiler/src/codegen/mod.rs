@@ -6,7 +6,7 @@ mod lower;
 pub use lower::lower;
 
 mod typed_program;
-pub use typed_program::type_program;
+pub use typed_program::{type_program, TypingError};
 
 mod display_typed;
 pub use display_typed::display_typed;
@@ -48,6 +48,9 @@ pub struct TypedProgram<T: Tag = AssemblerCtx> {
     pub external_functions: FxHashMap<ExternalFunctionId, ExternalFunction>,
     pub constants: FxHashMap<ConstantId, Constant>,
     pub functions: FxHashMap<FunctionId<T>, Block<T>>,
+    /// What each optimization pass did to the program, in the order that they
+    /// ran. Empty if the program has not been optimized.
+    pub optimizations: Vec<crate::opt::PassReport>,
 }
 
 #[derive(Clone)]
@@ -98,6 +101,10 @@ pub enum Instruction<T: Tag> {
     MakeBoolean(Make<T, bool>),
     BinOp(BinOp<T>),
     Negate(Negate<T>),
+    Not(Not<T>),
+    NewTuple(NewTuple<T>),
+    TupleGet(TupleGet<T>),
+    TupleLen(TupleLen<T>),
 }
 
 impl<T: Tag> Instruction<T> {
@@ -119,10 +126,14 @@ impl<T: Tag> Instruction<T> {
             Instruction::MakeBoolean(_) => Make::<T, FunctionId<T>>::is_pure(),
             Instruction::BinOp(_) => BinOp::<T>::is_pure(),
             Instruction::Negate(_) => Negate::<T>::is_pure(),
+            Instruction::Not(_) => Not::<T>::is_pure(),
+            Instruction::NewTuple(_) => NewTuple::<T>::is_pure(),
+            Instruction::TupleGet(_) => TupleGet::<T>::is_pure(),
+            Instruction::TupleLen(_) => TupleLen::<T>::is_pure(),
         }
     }
 
-    fn declared_register(&self) -> Option<RegisterId<T>> {
+    pub(crate) fn declared_register(&self) -> Option<RegisterId<T>> {
         match self {
             Instruction::Noop(i) => i.declared_register(),
             Instruction::Comment(i) => i.declared_register(),
@@ -140,10 +151,14 @@ impl<T: Tag> Instruction<T> {
             Instruction::MakeBoolean(i) => i.declared_register(),
             Instruction::BinOp(i) => i.declared_register(),
             Instruction::Negate(i) => i.declared_register(),
+            Instruction::Not(i) => i.declared_register(),
+            Instruction::NewTuple(i) => i.declared_register(),
+            Instruction::TupleGet(i) => i.declared_register(),
+            Instruction::TupleLen(i) => i.declared_register(),
         }
     }
 
-    fn used_registers(&self) -> TinyVec<[RegisterId<T>; 3]> {
+    pub(crate) fn used_registers(&self) -> TinyVec<[RegisterId<T>; 3]> {
         match self {
             Instruction::Noop(i) => i.used_registers(),
             Instruction::Comment(i) => i.used_registers(),
@@ -161,10 +176,37 @@ impl<T: Tag> Instruction<T> {
             Instruction::MakeBoolean(i) => i.used_registers(),
             Instruction::BinOp(i) => i.used_registers(),
             Instruction::Negate(i) => i.used_registers(),
+            Instruction::Not(i) => i.used_registers(),
+            Instruction::NewTuple(i) => i.used_registers(),
+            Instruction::TupleGet(i) => i.used_registers(),
+            Instruction::TupleLen(i) => i.used_registers(),
         }
     }
 
-    fn used_registers_mut(&mut self) -> Vec<&mut RegisterId<T>> {
+    pub(crate) fn declared_register_mut(&mut self) -> Option<&mut RegisterId<T>> {
+        match self {
+            Instruction::Noop(_) | Instruction::Comment(_) | Instruction::RecordSet(_) => None,
+            Instruction::NewRecord(i) => Some(&mut i.result),
+            Instruction::RecordGet(i) => Some(&mut i.result),
+            Instruction::RecordHasKey(i) => Some(&mut i.result),
+            Instruction::CallStatic(i) => i.result.as_mut(),
+            Instruction::CallExtern(i) => i.result.as_mut(),
+            Instruction::CallVirt(i) => i.result.as_mut(),
+            Instruction::GetFnPtr(i) => Some(&mut i.result),
+            Instruction::MakeAtom(i) => Some(&mut i.result),
+            Instruction::MakeBytes(i) => Some(&mut i.result),
+            Instruction::MakeInteger(i) => Some(&mut i.result),
+            Instruction::MakeBoolean(i) => Some(&mut i.result),
+            Instruction::BinOp(i) => Some(&mut i.result),
+            Instruction::Negate(i) => Some(&mut i.result),
+            Instruction::Not(i) => Some(&mut i.result),
+            Instruction::NewTuple(i) => Some(&mut i.result),
+            Instruction::TupleGet(i) => Some(&mut i.result),
+            Instruction::TupleLen(i) => Some(&mut i.result),
+        }
+    }
+
+    pub(crate) fn used_registers_mut(&mut self) -> Vec<&mut RegisterId<T>> {
         match self {
             Instruction::Noop(i) => i.used_registers_mut(),
             Instruction::Comment(i) => i.used_registers_mut(),
@@ -182,6 +224,10 @@ impl<T: Tag> Instruction<T> {
             Instruction::MakeBoolean(i) => i.used_registers_mut(),
             Instruction::BinOp(i) => i.used_registers_mut(),
             Instruction::Negate(i) => i.used_registers_mut(),
+            Instruction::Not(i) => i.used_registers_mut(),
+            Instruction::NewTuple(i) => i.used_registers_mut(),
+            Instruction::TupleGet(i) => i.used_registers_mut(),
+            Instruction::TupleLen(i) => i.used_registers_mut(),
         }
     }
 
@@ -203,6 +249,10 @@ impl<T: Tag> Instruction<T> {
             Instruction::MakeBoolean(i) => i.display(w),
             Instruction::BinOp(i) => i.display(w),
             Instruction::Negate(i) => i.display(w),
+            Instruction::Not(i) => i.display(w),
+            Instruction::NewTuple(i) => i.display(w),
+            Instruction::TupleGet(i) => i.display(w),
+            Instruction::TupleLen(i) => i.display(w),
         }
     }
 }
@@ -215,5 +265,65 @@ where
     Unreachable(Unreachable<T>),
     Jump(Jump<B, T>),
     JumpIf(JumpIf<B, T>),
+    Switch(Switch<B, T>),
     Return(Return<T>),
 }
+
+impl<T: Tag, B: IdCompat> EndInstruction<T, B> {
+    pub(crate) fn used_registers(&self) -> Vec<RegisterId<T>> {
+        match self {
+            EndInstruction::Unreachable(_) => Vec::new(),
+            EndInstruction::Jump(Jump(path)) => path.1.clone(),
+            EndInstruction::JumpIf(i) => std::iter::once(i.condition)
+                .chain(i.if_so.1.iter().copied())
+                .chain(i.other.1.iter().copied())
+                .collect(),
+            EndInstruction::Switch(i) => std::iter::once(i.value)
+                .chain(
+                    i.paths()
+                        .into_iter()
+                        .flat_map(|path| path.1.iter().copied()),
+                )
+                .collect(),
+            EndInstruction::Return(Return(value)) => value.iter().copied().collect(),
+        }
+    }
+
+    pub(crate) fn used_registers_mut(&mut self) -> Vec<&mut RegisterId<T>> {
+        match self {
+            EndInstruction::Unreachable(_) => Vec::new(),
+            EndInstruction::Jump(Jump(path)) => path.1.iter_mut().collect(),
+            EndInstruction::JumpIf(i) => std::iter::once(&mut i.condition)
+                .chain(i.if_so.1.iter_mut())
+                .chain(i.other.1.iter_mut())
+                .collect(),
+            EndInstruction::Switch(i) => {
+                let mut used_registers = vec![&mut i.value];
+                for path in i.cases.iter_mut().map(|(_, path)| path) {
+                    used_registers.extend(path.1.iter_mut());
+                }
+                used_registers.extend(i.default.1.iter_mut());
+                used_registers
+            }
+            EndInstruction::Return(Return(value)) => value.iter_mut().collect(),
+        }
+    }
+
+    pub(crate) fn paths(&self) -> Vec<&BlockJump<B, T>> {
+        match self {
+            EndInstruction::Unreachable(_) | EndInstruction::Return(_) => Vec::new(),
+            EndInstruction::Jump(Jump(path)) => vec![path],
+            EndInstruction::JumpIf(i) => vec![&i.if_so, &i.other],
+            EndInstruction::Switch(i) => i.paths(),
+        }
+    }
+
+    pub(crate) fn paths_mut(&mut self) -> Vec<&mut BlockJump<B, T>> {
+        match self {
+            EndInstruction::Unreachable(_) | EndInstruction::Return(_) => Vec::new(),
+            EndInstruction::Jump(Jump(path)) => vec![path],
+            EndInstruction::JumpIf(i) => vec![&mut i.if_so, &mut i.other],
+            EndInstruction::Switch(i) => i.paths_mut(),
+        }
+    }
+}
@@ -2,11 +2,12 @@ use std::collections::VecDeque;
 
 use derive_more::{Deref, DerefMut};
 use rustc_hash::{FxHashMap, FxHashSet};
+use thiserror::Error;
 use tinyvec::TinyVec;
 
 use crate::{
     id::{AssemblerCtx, FunctionId, LiftedCtx, RegisterId, SymbolicCtx},
-    isa::{BlockJump, Call, Comment, Jump, JumpIf, Make, Noop},
+    isa::{BlockJump, Call, Comment, Jump, JumpIf, Make, Noop, SwitchValue},
     lifted::LiftedProgram,
     retag::{
         CnstPassRetagger, CnstRetagger, ExtFnPassRetagger, ExtFnRetagger, FnPassRetagger,
@@ -23,10 +24,22 @@ use crate::{
 
 use super::{Block, EndInstruction, Instruction, TypedProgram};
 
+/// Why a run of a program could not be typed.
+#[derive(Clone, Debug, Error)]
+pub enum TypingError {
+    /// The value controlling a jump may take more than one path, but a typed
+    /// block can only end in a single jump.
+    #[error("cannot type every path of a jump on a `{0:?}`")]
+    Divergent(RegisterType),
+    /// The value controlling a jump is of a type that cannot be jumped on.
+    #[error("cannot jump on a value of type `{0:?}`")]
+    NotJumpable(RegisterType),
+}
+
 /// Given a run of a program after symbolic execution, this will produce a
 /// version of the program free of the original `LiftedProgram`, allowing for
 /// optimizations to be made on a per-function basis as necessary.
-pub fn type_program(system_run: SystemRun) -> TypedProgram {
+pub fn type_program(system_run: SystemRun) -> Result<TypedProgram, TypingError> {
     let SystemRun {
         program,
         entry_fn,
@@ -77,16 +90,17 @@ pub fn type_program(system_run: SystemRun) -> TypedProgram {
             continue;
         }
 
-        let (id, function) = fn_typer.process(id);
+        let (id, function) = fn_typer.process(id)?;
         functions.insert(id, function).expect_free();
     }
 
-    TypedProgram {
+    Ok(TypedProgram {
         entrypoint,
         external_functions,
         constants,
         functions,
-    }
+        optimizations: Vec::new(),
+    })
 }
 
 struct FnTyperFactory<'a> {
@@ -103,7 +117,7 @@ impl<'a> FnTyperFactory<'a> {
     pub fn process(
         &mut self,
         id: FunctionId<SymbolicCtx>,
-    ) -> (FunctionId<AssemblerCtx>, Block<AssemblerCtx>) {
+    ) -> Result<(FunctionId<AssemblerCtx>, Block<AssemblerCtx>), TypingError> {
         let function_id = self.fn_id_mapper.retag_new(id);
         let results = self.all_results.get(&id).unwrap();
         let (lifted_fn_id, _) = self.fn_ids.types_of(id);
@@ -120,9 +134,9 @@ impl<'a> FnTyperFactory<'a> {
             reg_retagger,
         };
 
-        let block = fn_typer.process();
+        let block = fn_typer.process()?;
 
-        (function_id, block)
+        Ok((function_id, block))
     }
 }
 
@@ -140,7 +154,7 @@ struct FnTyper<'resource, 'factory> {
 }
 
 impl<'r> FnTyper<'r, '_> {
-    pub fn process(&mut self) -> Block<AssemblerCtx> {
+    pub fn process(&mut self) -> Result<Block<AssemblerCtx>, TypingError> {
         let parameters = (self.lifted_fn.parameters.iter())
             .map(|r| self.reg_retagger.retag_new(*r))
             .collect();
@@ -149,16 +163,16 @@ impl<'r> FnTyper<'r, '_> {
             .map(|(idx, inst)| self.process_inst(inst, InstIdx::Inst(idx)))
             .collect();
 
-        let end = self.process_end(&self.lifted_fn.end);
+        let end = self.process_end(&self.lifted_fn.end)?;
 
         let type_info = self.results.types.clone();
 
-        Block {
+        Ok(Block {
             parameters,
             instructions,
             end,
             type_info,
-        }
+        })
     }
 
     fn process_inst(
@@ -270,22 +284,35 @@ impl<'r> FnTyper<'r, '_> {
     fn process_end(
         &mut self,
         end_inst: &crate::lifted::EndInstruction,
-    ) -> EndInstruction<AssemblerCtx, FunctionId<AssemblerCtx>> {
-        match end_inst {
+    ) -> Result<EndInstruction<AssemblerCtx, FunctionId<AssemblerCtx>>, TypingError> {
+        Ok(match end_inst {
             crate::lifted::EndInstruction::Jump(i) => {
                 EndInstruction::Jump(Jump(self.translate_blockjump(&i.0)))
             }
-            crate::lifted::EndInstruction::JumpIf(i) => {
-                if let RegisterType::Bool(b) = self.results.types.get(i.condition) {
+            crate::lifted::EndInstruction::JumpIf(i) => match self.results.types.get(i.condition) {
+                RegisterType::Bool(b) => {
                     EndInstruction::Jump(Jump(self.translate_blockjump(i.take_path(b))))
-                } else {
-                    panic!("TODO: handle both paths")
                 }
+                r @ RegisterType::Boolean => return Err(TypingError::Divergent(r)),
+                r => return Err(TypingError::NotJumpable(r)),
+            },
+            crate::lifted::EndInstruction::Switch(i) => {
+                let value = match self.results.types.get(i.value) {
+                    RegisterType::Atom(atom) => SwitchValue::Atom(atom),
+                    RegisterType::Int(integer) => SwitchValue::Integer(integer),
+                    RegisterType::Bool(boolean) => SwitchValue::Boolean(boolean),
+                    r @ (RegisterType::Number | RegisterType::Boolean) => {
+                        return Err(TypingError::Divergent(r))
+                    }
+                    r => return Err(TypingError::NotJumpable(r)),
+                };
+
+                EndInstruction::Jump(Jump(self.translate_blockjump(i.take_path(value))))
             }
             crate::lifted::EndInstruction::Return(i) => {
                 EndInstruction::Return(i.retag(&self.reg_retagger))
             }
-        }
+        })
     }
 
     fn translate_blockjump(
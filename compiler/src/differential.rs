@@ -0,0 +1,410 @@
+//! Differential testing of the abstract interpreter against the interpreter.
+//!
+//! A program is run with both, and at every instruction the interpreter
+//! executes, the concrete value of every register used must be an instance of
+//! one of the types the abstract interpreter recorded for that register. The
+//! same goes for the value returned. Anything else means the abstract
+//! interpreter is unsound for the program.
+//!
+//! ```ignore
+//! let builder = InterpreterBuilder::new(&program);
+//!
+//! for mismatch in differential::check(&builder, &program, program.entrypoint)? {
+//!     println!("{}", mismatch);
+//! }
+//! ```
+
+use std::fmt::{self, Display, Formatter};
+
+use ordered_float::OrderedFloat;
+use rustc_hash::{FxHashMap, FxHashSet};
+use thiserror::Error;
+
+use crate::{
+    abst_interp::{AbsIntCollector, AbsIntEngine, AbsIntError, EvaluationStateKind},
+    id::LiftedCtx,
+    interpreter::{display_value, FrameView, InstErr, InterpreterBuilder, Resume, Stop, Value},
+    isa::{decode_utf16, Atom, AtomDealer},
+    lifted::{EndInstruction, FunctionId, LiftedProgram, RegisterId},
+    types::{Type, TypeCtx},
+};
+
+/// An owned copy of a [`Type`]. The types recorded by the abstract interpreter
+/// only live as long as the state they were recorded in, so they are copied
+/// out. Records and lists are mutated in place, so only their kind is kept.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    Any,
+    Nothing,
+    Bytes,
+    Number,
+    BigNumber,
+    Boolean,
+    Atom(Atom),
+    Int(i64),
+    Float(OrderedFloat<f64>),
+    Bool(bool),
+    FnPtr(FunctionId),
+    Byts(Vec<u8>),
+    List,
+    Tuple(Vec<Shape>),
+    Record,
+    Union(Vec<Shape>),
+}
+
+impl Shape {
+    pub fn new(typ: Type<LiftedCtx>) -> Self {
+        match typ {
+            Type::Any => Shape::Any,
+            Type::Nothing => Shape::Nothing,
+            Type::Bytes => Shape::Bytes,
+            Type::Number => Shape::Number,
+            Type::BigNumber => Shape::BigNumber,
+            Type::Boolean => Shape::Boolean,
+            Type::Atom(atom) => Shape::Atom(atom),
+            Type::Int(int) => Shape::Int(int),
+            Type::Float(float) => Shape::Float(float),
+            Type::Bool(boolean) => Shape::Bool(boolean),
+            Type::FnPtr(function) => Shape::FnPtr(function),
+            Type::Byts(bytes) => Shape::Byts(bytes.to_vec()),
+            Type::List(_) => Shape::List,
+            Type::Tuple(tuple) => Shape::Tuple(tuple.iter().map(|t| Shape::new(*t)).collect()),
+            Type::Record(_) => Shape::Record,
+            Type::Union(union) => {
+                Shape::Union(union.borrow().iter().map(|t| Shape::new(*t)).collect())
+            }
+        }
+    }
+
+    /// Whether the value is one of the values the type represents.
+    pub fn admits(&self, value: &Value) -> bool {
+        match (self, value) {
+            (Shape::Any, _) => true,
+            (Shape::Union(shapes), value) => shapes.iter().any(|shape| shape.admits(value)),
+            (Shape::Bytes, Value::Bytes(_)) => true,
+            (Shape::Byts(expected), Value::Bytes(bytes)) => expected == bytes,
+            (Shape::Number, Value::Number(_) | Value::Float(_)) => true,
+            (Shape::Int(expected), Value::Number(number)) => expected == number,
+            (Shape::Float(expected), Value::Float(float)) => *expected == OrderedFloat(*float),
+            (Shape::BigNumber, Value::BigInt(_)) => true,
+            (Shape::Boolean, Value::Boolean(_)) => true,
+            (Shape::Bool(expected), Value::Boolean(boolean)) => expected == boolean,
+            (Shape::Atom(expected), Value::Atom(atom)) => expected == atom,
+            (Shape::FnPtr(expected), Value::FnPtr(function)) => expected == function,
+            (Shape::List, Value::List(_)) | (Shape::Record, Value::Record(_)) => true,
+            (Shape::Tuple(shapes), Value::Tuple(tuple)) => {
+                shapes.len() == tuple.len()
+                    && (shapes.iter().zip(tuple.iter())).all(|(shape, value)| shape.admits(value))
+            }
+            _ => false,
+        }
+    }
+
+    pub fn display<'a>(&'a self, dealer: &'a AtomDealer) -> DisplayShape<'a> {
+        DisplayShape {
+            shape: self,
+            dealer,
+        }
+    }
+}
+
+pub struct DisplayShape<'a> {
+    shape: &'a Shape,
+    dealer: &'a AtomDealer,
+}
+
+impl DisplayShape<'_> {
+    fn join(&self, f: &mut Formatter<'_>, shapes: &[Shape], separator: &str) -> fmt::Result {
+        for (idx, shape) in shapes.iter().enumerate() {
+            if idx > 0 {
+                write!(f, "{}", separator)?;
+            }
+
+            shape.display(self.dealer).fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for DisplayShape<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.shape {
+            Shape::Any => write!(f, "any"),
+            Shape::Nothing => write!(f, "nothing"),
+            Shape::Bytes => write!(f, "bytes"),
+            Shape::Number => write!(f, "number"),
+            Shape::BigNumber => write!(f, "bigint"),
+            Shape::Boolean => write!(f, "boolean"),
+            Shape::Atom(atom) => match self.dealer.try_resolve_name(*atom) {
+                Some(name) => write!(f, ":{}", name),
+                None => write!(f, ":?{}?", atom.0),
+            },
+            Shape::Int(int) => write!(f, "{}", int),
+            Shape::Float(float) => write!(f, "{}", float),
+            Shape::Bool(boolean) => write!(f, "{}", boolean),
+            Shape::FnPtr(function) => write!(f, "@{}", function),
+            Shape::Byts(bytes) => match decode_utf16(bytes) {
+                Some(string) => write!(f, "{:?}", string),
+                None => write!(f, "{:?}", bytes),
+            },
+            Shape::List => write!(f, "list"),
+            Shape::Record => write!(f, "record"),
+            Shape::Tuple(shapes) => {
+                write!(f, "(")?;
+                self.join(f, shapes, ", ")?;
+                write!(f, ")")
+            }
+            Shape::Union(shapes) => self.join(f, shapes, " | "),
+        }
+    }
+}
+
+/// An [`AbsIntCollector`] which records every type a register was given, in
+/// every evaluation of the lifted function it is in.
+#[derive(Default)]
+pub struct TypeCollector {
+    stack: Vec<FunctionId>,
+    /// The lifted functions the abstract interpreter evaluated.
+    pub reached: FxHashSet<FunctionId>,
+    pub types: FxHashMap<(FunctionId, RegisterId), Vec<Shape>>,
+}
+
+impl TypeCollector {
+    pub fn types(&self, function: FunctionId, register: RegisterId) -> &[Shape] {
+        match self.types.get(&(function, register)) {
+            Some(shapes) => shapes.as_slice(),
+            None => &[],
+        }
+    }
+}
+
+impl AbsIntCollector<LiftedCtx> for TypeCollector {
+    fn set_inst_position(&mut self, _index: usize) {}
+    fn set_inst_position_end(&mut self) {}
+
+    fn record(&mut self, register: RegisterId, typ: Type<LiftedCtx>) {
+        let function = match self.stack.last() {
+            Some(function) => *function,
+            None => return,
+        };
+
+        let shape = Shape::new(typ);
+        let shapes = self.types.entry((function, register)).or_default();
+
+        if !shapes.contains(&shape) {
+            shapes.push(shape);
+        }
+    }
+
+    fn commit_changes(&mut self) {}
+
+    fn fn_start(&mut self, function: FunctionId, _kind: EvaluationStateKind) {
+        self.reached.insert(function);
+        self.stack.push(function);
+    }
+
+    fn fn_end(&mut self) {
+        self.stack.pop();
+    }
+}
+
+/// What a [`Mismatch`] was found in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Site {
+    /// A register used by the instruction at the index, which is the amount of
+    /// instructions in the function for the end instruction.
+    Register {
+        inst_idx: usize,
+        register: RegisterId,
+    },
+    /// The value returned by the function checked.
+    Return,
+    /// The function was executed, but the abstract interpreter never evaluated
+    /// it.
+    Unreached,
+}
+
+/// A place where the interpreter and the abstract interpreter disagree.
+#[derive(Debug)]
+pub struct Mismatch {
+    pub function: FunctionId,
+    pub name: Option<String>,
+    pub site: Site,
+    /// The concrete value, or `nothing` if no value was returned.
+    pub value: String,
+    /// The abstract types, or `nothing` if there are none.
+    pub types: String,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "in {} (@{})", name, self.function)?,
+            None => write!(f, "in @{}", self.function)?,
+        };
+
+        match self.site {
+            Site::Register { inst_idx, register } => write!(
+                f,
+                ", %{} at instruction {} is {}, which is not an instance of {}",
+                register, inst_idx, self.value, self.types
+            ),
+            Site::Return => write!(
+                f,
+                ", returned {}, which is not an instance of {}",
+                self.value, self.types
+            ),
+            Site::Unreached => write!(f, ", executed code the abstract interpreter never reached"),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum DifferentialError {
+    #[error("abstract interpreter failed: {0}")]
+    Abstract(#[from] AbsIntError),
+    #[error("interpreter failed: {0}")]
+    Concrete(#[from] InstErr),
+}
+
+struct Checker<'a> {
+    program: &'a LiftedProgram,
+    collector: &'a TypeCollector,
+    mismatches: Vec<Mismatch>,
+    /// Where mismatches were already found, so that each is only reported the
+    /// first time, rather than on every iteration of a loop.
+    seen: FxHashSet<(FunctionId, Site)>,
+}
+
+impl Checker<'_> {
+    fn report(&mut self, function: FunctionId, site: Site, value: String, types: &[Shape]) {
+        if !self.seen.insert((function, site)) {
+            return;
+        }
+
+        self.mismatches.push(Mismatch {
+            function,
+            name: self.program.functions[&function].name.clone(),
+            site,
+            value,
+            types: self.display_types(types),
+        });
+    }
+
+    fn display_types(&self, types: &[Shape]) -> String {
+        match types {
+            [] => "nothing".to_string(),
+            types => (types.iter())
+                .map(|shape| shape.display(&self.program.dealer).to_string())
+                .collect::<Vec<_>>()
+                .join(" | "),
+        }
+    }
+
+    fn check_frame(&mut self, frame: &FrameView) {
+        if !self.collector.reached.contains(&frame.function) {
+            self.report(frame.function, Site::Unreached, String::new(), &[]);
+            return;
+        }
+
+        let (used, condition) = match frame.instruction() {
+            Some(inst) => (inst.used_registers().to_vec(), None),
+            None => {
+                let end = &self.program.functions[&frame.function].end;
+                (end.used_registers().to_vec(), branch_condition(end))
+            }
+        };
+
+        for register in used {
+            let value = match frame.register(register) {
+                Some(value) => value,
+                None => continue,
+            };
+
+            // the abstract interpreter only decides which paths to take with
+            // the condition of a branch, without recording it
+            let types = self.collector.types(frame.function, register);
+            if types.is_empty() && Some(register) == condition {
+                continue;
+            }
+
+            if types.iter().any(|shape| shape.admits(value)) {
+                continue;
+            }
+
+            let site = Site::Register {
+                inst_idx: frame.inst_idx,
+                register,
+            };
+
+            let value = display_value(value, Some(&self.program.dealer)).to_string();
+            self.report(frame.function, site, value, types);
+        }
+    }
+}
+
+/// The register that decides which path a branch or switch takes.
+fn branch_condition(end: &EndInstruction) -> Option<RegisterId> {
+    match end {
+        EndInstruction::JumpIf(i) => Some(i.condition),
+        EndInstruction::Switch(i) => Some(i.value),
+        EndInstruction::Jump(_) | EndInstruction::Return(_) => None,
+    }
+}
+
+/// Runs a function taking no arguments with both the abstract interpreter and
+/// an interpreter built by the builder, returning the mismatches found between
+/// them.
+pub fn check<'p>(
+    builder: &InterpreterBuilder<'p>,
+    program: &'p LiftedProgram,
+    function: FunctionId,
+) -> Result<Vec<Mismatch>, DifferentialError> {
+    let mut engine = AbsIntEngine::new_with_collector(program, TypeCollector::default());
+    let result = engine.call(function, TypeCtx::new())?;
+    let returns = (result.return_type()).borrow(|ctx| ctx.get(&()).map(Shape::new));
+
+    let mut checker = Checker {
+        program,
+        collector: &engine.collector,
+        mismatches: Vec::new(),
+        seen: FxHashSet::default(),
+    };
+
+    let mut interpreter = builder.build();
+    let mut debugger = interpreter.debug(function, vec![])?;
+
+    let value = loop {
+        let frame = debugger
+            .frame()
+            .expect("debugger must be paused in a frame");
+        checker.check_frame(&frame);
+
+        if let Stop::Finished(value) = debugger.resume(Resume::StepIn)? {
+            break value;
+        }
+    };
+
+    let admitted = match (&value, &returns) {
+        (Some(value), Some(shape)) => shape.admits(value),
+        (None, None) => true,
+        _ => false,
+    };
+
+    if !admitted {
+        let value = match &value {
+            Some(value) => display_value(value, Some(&program.dealer)).to_string(),
+            None => "nothing".to_string(),
+        };
+
+        let types = match &returns {
+            Some(shape) => std::slice::from_ref(shape),
+            None => &[],
+        };
+
+        checker.report(function, Site::Return, value, types);
+    }
+
+    Ok(checker.mismatches)
+}
@@ -28,6 +28,7 @@ use crate::{
 pub mod abst_interp;
 pub mod backend;
 pub mod codegen;
+pub mod differential;
 pub use jssat_ir::collections;
 pub mod frontend;
 pub use jssat_ir::id;
@@ -38,7 +39,11 @@ pub use jssat_ir::lifted;
 pub mod my_tests;
 pub mod opt;
 pub use jssat_ir::retag;
-use jssat_ir::{frontend::source_map::SourceMap, isa::AtomDealer, lifted::LiftedProgram};
+use jssat_ir::{
+    frontend::source_map::{JsSourceMap, SourceMap},
+    isa::AtomDealer,
+    lifted::LiftedProgram,
+};
 use symbolic_execution::SystemRun;
 pub mod symbolic_execution;
 pub mod types;
@@ -156,6 +161,21 @@ fn link_binary(build: &[u8]) {
 }
 
 fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if let [command, paths @ ..] = args.as_slice() {
+        if command == "differential" {
+            differential(paths);
+            return;
+        }
+    }
+
+    if let [command, old, new] = args.as_slice() {
+        if command == "diff" {
+            diff(old, new);
+            return;
+        }
+    }
+
     // let file_name = std::env::args()
     //     .into_iter()
     //     .skip(1)
@@ -182,6 +202,7 @@ f(print);
         .parse(&content, &mut b, &mut JSSATHostEnvironment::new())
         .expect("should parse js");
     let source_map = frontend.ecma_methods.source_map;
+    let js_source_map = frontend.js_source_map;
 
     f.end_block(b.ret(Some(result)));
     builder.end_function(f);
@@ -195,7 +216,7 @@ f(print);
     let program = time(move || lifted::lift(ir));
 
     println!("executing program");
-    interpret(&program, dealer, source_map);
+    interpret(&program, dealer, source_map, js_source_map);
     let (result, collector) = time(|| {
         let mut engine = abst_interp::AbsIntEngine::new_with_collector(
             &program,
@@ -222,9 +243,115 @@ f(print);
     }
 }
 
+/// `jssat diff <old> <new>`: prints the structural differences between two
+/// programs, each either saved with [`jssat_ir::binary`] or in the textual IR.
+fn diff(old: &str, new: &str) {
+    let (old, new) = match (load_program(old), load_program(new)) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let diff = match jssat_ir::diff::diff(&old, &new) {
+        Ok(diff) => diff,
+        Err(e) => {
+            eprintln!("couldn't diff the programs: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match diff.is_empty() {
+        true => println!("no differences"),
+        false => print!("{}", diff),
+    }
+}
+
+/// `jssat differential <program>...`: runs the entrypoint of every program with
+/// both the interpreter and the abstract interpreter, and prints where the
+/// abstract interpreter is unsound. Exits unsuccessfully if it is anywhere.
+fn differential(paths: &[String]) {
+    let mut sound = true;
+
+    for path in paths {
+        let program = match load_program(path) {
+            Ok(program) => program,
+            Err(e) => {
+                sound = false;
+                eprintln!("{}", e);
+                continue;
+            }
+        };
+        let builder = interpreter::InterpreterBuilder::new(&program);
+
+        match differential::check(&builder, &program, program.entrypoint) {
+            Ok(mismatches) if mismatches.is_empty() => println!("{}: ok", path),
+            Ok(mismatches) => {
+                sound = false;
+
+                for mismatch in mismatches {
+                    println!("{}: {}", path, mismatch);
+                }
+            }
+            Err(e) => {
+                sound = false;
+                eprintln!("{}: {}", path, e);
+            }
+        }
+    }
+
+    if !sound {
+        std::process::exit(1);
+    }
+}
+
+/// Loads a program saved with [`jssat_ir::binary`] or in the textual IR, and
+/// verifies that it is well formed. Fails with a message saying why the
+/// program couldn't be loaded.
+fn load_program(path: &str) -> Result<LiftedProgram, String> {
+    use jssat_ir::{
+        binary,
+        frontend::parse_jssatir,
+        verify::{display_violations, verify, verify_lifted, Violation},
+    };
+
+    let malformed = |violations: Vec<Violation>| {
+        format!(
+            "`{}` is malformed:\n{}",
+            path,
+            display_violations(&violations)
+        )
+    };
+
+    let bytes = std::fs::read(path).map_err(|e| format!("couldn't read `{}`: {}", path, e))?;
+
+    let ir = match binary::load_lifted(bytes.as_slice()) {
+        Ok(program) => {
+            verify_lifted(&program).map_err(malformed)?;
+            return Ok(program);
+        }
+        Err(binary::LoadError::WrongKind) => binary::load_ir(bytes.as_slice())
+            .map_err(|e| format!("couldn't load `{}`: {}", path, e))?,
+        Err(binary::LoadError::NotJssat) => {
+            let text =
+                String::from_utf8(bytes).map_err(|e| format!("couldn't read `{}`: {}", path, e))?;
+            parse_jssatir::parse(&text).map_err(|e| format!("couldn't parse `{}`: {}", path, e))?
+        }
+        Err(e) => return Err(format!("couldn't load `{}`: {}", path, e)),
+    };
+
+    verify(&ir).map_err(malformed)?;
+    Ok(lifted::lift(ir))
+}
+
 fn rest(program: SystemRun) {
     println!("typing program");
     let program = time(move || codegen::type_program(program));
+    let program = program.unwrap_or_else(|e| {
+        eprintln!("couldn't type the program: {}", e);
+        std::process::exit(1)
+    });
 
     println!("optimizing system run");
     let program = time(move || opt::opt(program));
@@ -238,6 +365,10 @@ fn rest(program: SystemRun) {
 
     println!("compiling");
     let build = time(move || backend::compile(program));
+    let build = build.unwrap_or_else(|e| {
+        eprintln!("couldn't compile the program: {}", e);
+        std::process::exit(1)
+    });
 
     eprintln!("OUTPUT LLVM IR (use unix pipes to redirect this into a file):");
     println!("{}", build.llvm_ir);
@@ -257,31 +388,43 @@ where
     result
 }
 
-fn interpret(program: &LiftedProgram, dealer: Arc<AtomDealer>, source_map: SourceMap) -> ! {
+fn interpret(
+    program: &LiftedProgram,
+    dealer: Arc<AtomDealer>,
+    source_map: SourceMap,
+    js_source_map: JsSourceMap,
+) -> ! {
     // let program = time(|| symbolic_execution::execute(&program));
-    let builder = crate::interpreter::InterpreterBuilder::new(program);
-    let mut interpreter = builder.build();
+    let mut builder = crate::interpreter::InterpreterBuilder::new(program);
+    let mut interpreter = builder.with_parse_node_key(js_source_map.key).build();
 
     let (interpreter, interpreter_result) = time(|| {
-        let result = interpreter.execute_fn_id(program.entrypoint, vec![]);
+        let result = interpreter.execute_fn_id_with_report(program.entrypoint, vec![]);
         (interpreter, result)
     });
 
     println!("executed");
 
-    println!(
-        "executed: {:?}",
-        match interpreter_result {
-            Ok(value) => format!("success: {:?}", value),
-            // Ok(_) => "success".to_string(),
-            Err(err) => format!("error: {}", err),
-            // Err(_) => "error".to_string(),
+    let source_map = source_map.try_into();
+
+    match interpreter_result {
+        Ok(value) => println!("executed: success: {:?}", value),
+        Err(report) => {
+            let sources = crate::interpreter::ReportSources {
+                dealer: Some(&dealer),
+                source_map: Some(&source_map),
+                js_source_map: Some(&js_source_map),
+            };
+
+            println!("executed: {}", report.display(sources));
         }
-    );
+    };
 
     let listen_url = "127.0.0.1:8000";
     println!("preparing data for domino");
-    let data = interpreter.moment.into_data_with(dealer, source_map);
+    let data = (interpreter.collector)
+        .into_data_with(dealer, source_map.into())
+        .with_js_sources(js_source_map);
     println!("starting domino on http://{listen_url}");
     domino::launch(listen_url, &data).unwrap();
 
@@ -151,7 +151,7 @@ pub fn mutations_in_function_propagate_to_caller() {
     let engine = symbolic_execution::make_system(&lifted);
     let SystemRun {
         entry_fn, results, ..
-    } = symbolic_execution::system_run(engine, lifted.entrypoint, |_| Vec::new());
+    } = symbolic_execution::system_run(engine, lifted.entrypoint, |_| Vec::new()).unwrap();
     let results = results.get(&entry_fn).unwrap();
 
     assert_eq!(
@@ -236,7 +236,7 @@ pub fn shape_weirdness_does_not_happen() {
 
     let engine = symbolic_execution::make_system(&lifted);
     let SystemRun { results, .. } =
-        symbolic_execution::system_run(engine, lifted.entrypoint, |_| Vec::new());
+        symbolic_execution::system_run(engine, lifted.entrypoint, |_| Vec::new()).unwrap();
 
     // there should be 3 total functions invoked:
     // - main()
@@ -244,3 +244,163 @@ pub fn shape_weirdness_does_not_happen() {
     // - mutate(small)
     assert_eq!(results.len(), 3);
 }
+
+/// Every program in the `differential` corpus must run without the abstract
+/// interpreter disagreeing with the interpreter.
+#[test]
+pub fn abstract_interpreter_is_sound_for_differential_corpus() {
+    let corpus = concat!(env!("CARGO_MANIFEST_DIR"), "/differential");
+
+    let mut paths = (std::fs::read_dir(corpus).unwrap())
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    paths.sort();
+    assert!(!paths.is_empty());
+
+    for path in paths {
+        let path = path.to_str().unwrap();
+        let program = crate::load_program(path).unwrap_or_else(|e| panic!("{}", e));
+        let builder = crate::interpreter::InterpreterBuilder::new(&program);
+
+        let mismatches = crate::differential::check(&builder, &program, program.entrypoint)
+            .unwrap_or_else(|e| panic!("{}: {}", path, e));
+
+        for mismatch in mismatches.iter() {
+            eprintln!("{}: {}", path, mismatch);
+        }
+
+        assert!(mismatches.is_empty(), "{} is not sound", path);
+    }
+}
+
+/// A switch or branch on a value that may take several paths evaluates every
+/// path it may take, and joins the types they return into a union.
+#[test]
+pub fn abstract_interpreter_joins_every_path_taken() {
+    use crate::abst_interp::AbsIntEngine;
+    use crate::isa::SwitchValue;
+    use crate::types::{Type, TypeCtx};
+
+    let mut program = ProgramBuilder::new();
+
+    let diverge = {
+        let (mut diverge, [number, boolean]) = program.start_function();
+        let block = diverge.start_block_main();
+        let (mut branch, [condition]) = diverge.start_block();
+        let (mut ten, []) = diverge.start_block();
+        let (mut twenty, []) = diverge.start_block();
+
+        diverge.end_block(block.switch_dynargs(
+            number,
+            vec![
+                (SwitchValue::Integer(1), branch.signature().id, vec![boolean]),
+                (SwitchValue::Boolean(true), twenty.signature().id, vec![]),
+            ],
+            ten.signature().id,
+            vec![],
+        ));
+        diverge.end_block(branch.jmpif(condition, ten.signature(), [], twenty.signature(), []));
+        let value = ten.make_number_decimal(10);
+        diverge.end_block(ten.ret(Some(value)));
+        let value = twenty.make_number_decimal(20);
+        diverge.end_block(twenty.ret(Some(value)));
+
+        program.end_function(diverge)
+    };
+
+    let mut main = program.start_function_main();
+    let block = main.start_block_main();
+    main.end_block(block.ret(None));
+    program.end_function(main);
+
+    let lifted = crate::lifted::lift(program.finish());
+    let (&function, entry) = (lifted.functions.iter())
+        .find(|(_, f)| f.ir_fn_id == diverge.id && f.parameters.len() == 2)
+        .unwrap();
+
+    let mut args = TypeCtx::new();
+    args.borrow_mut(|mut args| {
+        args.insert(entry.parameters[0], Type::Number);
+        args.insert(entry.parameters[1], Type::Boolean);
+    });
+
+    let mut engine = AbsIntEngine::new(&lifted);
+    let result = engine.call(function, args).unwrap();
+
+    let mut returned = result.return_type().borrow(|ctx| match ctx.get(&()) {
+        Some(Type::Union(union)) => (union.borrow().iter())
+            .map(|t| match t {
+                Type::Int(x) => *x,
+                t => panic!("expected an integer, got {:?}", t),
+            })
+            .collect::<Vec<_>>(),
+        t => panic!("expected a union, got {:?}", t),
+    });
+    returned.sort_unstable();
+
+    // the case for `true` can never be taken by a number, and the union the
+    // branch returns is flattened into the union the switch returns
+    assert_eq!(returned, vec![10, 20]);
+}
+
+/// Frames record the parse node of the JavaScript frontend they evaluate, which
+/// resolves against the source map of the JavaScript.
+#[test]
+pub fn interpreter_records_the_javascript_being_evaluated() {
+    use crate::frontend::js::{hosts::JSSATHostEnvironment, JavaScriptFrontend};
+    use crate::interpreter::{InterpreterBuilder, MomentCollector};
+    use crate::lifted::FunctionId;
+    use jssat_ir::{frontend::source_map::SourceMapIdx, value_snapshot::ValueSnapshotArena};
+
+    /// Collects the parse nodes that frames are entered with.
+    struct ParseNodes(Vec<SourceMapIdx>);
+
+    impl MomentCollector for ParseNodes {
+        fn enter(&mut self, _: FunctionId, js_source: Option<SourceMapIdx>) {
+            self.0.extend(js_source);
+        }
+
+        fn exit(&mut self) {}
+
+        fn record_instruction(&mut self) -> bool {
+            false
+        }
+
+        fn snapshot(&mut self, _: usize, _: Option<SourceMapIdx>, _: ValueSnapshotArena) {}
+    }
+
+    let mut program = ProgramBuilder::new();
+    let mut main = program.start_function_main();
+    let mut block = main.start_block_main();
+
+    let mut frontend = JavaScriptFrontend::new(&mut program);
+    let result = frontend
+        .parse("1 + 2;", &mut block, &mut JSSATHostEnvironment::new())
+        .unwrap();
+    let js_source_map = frontend.js_source_map;
+
+    main.end_block(block.ret(Some(result)));
+    program.end_function(main);
+    let lifted = crate::lifted::lift(program.finish());
+
+    let mut builder = InterpreterBuilder::new(&lifted);
+    let mut run = (builder.with_parse_node_key(js_source_map.key))
+        .with_fuel(50_000_000)
+        .build_with_collector(ParseNodes(vec![]));
+
+    // only the parse nodes evaluated matter, not whether the script finishes
+    let _ = run.execute_fn_id(lifted.entrypoint, vec![]);
+
+    let parse_nodes = run.collector.0;
+    assert!(!parse_nodes.is_empty());
+
+    for idx in parse_nodes.iter() {
+        let span = (js_source_map.sources.span(*idx)).expect("parse node is in the source map");
+        assert_eq!((span.start.line, span.end.line), (1, 1));
+    }
+
+    let distinct = (parse_nodes.iter())
+        .map(|idx| idx.0)
+        .collect::<std::collections::BTreeSet<_>>();
+    assert!(distinct.len() > 1, "evaluates more than the script itself");
+}
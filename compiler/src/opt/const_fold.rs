@@ -0,0 +1,153 @@
+use crate::codegen::{Block, EndInstruction, Instruction, TypedProgram};
+use crate::id::AssemblerCtx;
+use crate::isa::{Jump, Make, SwitchValue};
+use crate::symbolic_execution::types::RegisterType;
+
+use super::{for_each_function, Pass};
+
+/// Replaces pure instructions that are known to produce a constant with an
+/// instruction that makes that constant, and conditional jumps and switches on
+/// a constant with a jump to the path that is always taken.
+pub struct ConstantFolding;
+
+impl Pass for ConstantFolding {
+    fn name(&self) -> &'static str {
+        "constant folding"
+    }
+
+    fn run(&mut self, program: &mut TypedProgram) -> bool {
+        for_each_function(&mut program.functions, fold_constants)
+    }
+}
+
+fn fold_constants(function: &mut Block<AssemblerCtx>) -> bool {
+    let mut changed = false;
+
+    for instruction in function.instructions.iter_mut() {
+        let already_constant = matches!(
+            instruction,
+            Instruction::MakeInteger(_) | Instruction::MakeBoolean(_) | Instruction::MakeBytes(_)
+        );
+
+        if already_constant || !instruction.is_pure() {
+            continue;
+        }
+
+        let result = match instruction.declared_register() {
+            Some(result) => result,
+            None => continue,
+        };
+
+        let folded = match function.type_info.try_get(result.map_context()) {
+            Some(RegisterType::Int(item)) => Instruction::MakeInteger(Make { result, item }),
+            Some(RegisterType::Bool(item)) => Instruction::MakeBoolean(Make { result, item }),
+            // known bytes refer to constants interned by the type system, not
+            // to the constants of the program, so they can't be made here
+            _ => continue,
+        };
+
+        *instruction = folded;
+        changed = true;
+    }
+
+    if let EndInstruction::JumpIf(jump_if) = &function.end {
+        if let Some(RegisterType::Bool(condition)) =
+            function.type_info.try_get(jump_if.condition.map_context())
+        {
+            let path = jump_if.take_path(condition).clone();
+            function.end = EndInstruction::Jump(Jump(path));
+            changed = true;
+        }
+    }
+
+    if let EndInstruction::Switch(switch) = &function.end {
+        let value = match function.type_info.try_get(switch.value.map_context()) {
+            Some(RegisterType::Atom(atom)) => Some(SwitchValue::Atom(atom)),
+            Some(RegisterType::Int(integer)) => Some(SwitchValue::Integer(integer)),
+            Some(RegisterType::Bool(boolean)) => Some(SwitchValue::Boolean(boolean)),
+            _ => None,
+        };
+
+        if let Some(value) = value {
+            let path = switch.take_path(value).clone();
+            function.end = EndInstruction::Jump(Jump(path));
+            changed = true;
+        }
+    }
+
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::isa::{BinOp, BinaryOperator, BlockJump, JumpIf, Return};
+
+    use super::super::tests::{block, f, program, r};
+    use super::*;
+
+    /// Confirm that instructions known to produce an integer become that
+    /// integer, and that branches on known booleans become jumps
+    #[test]
+    pub fn folds_known_values() {
+        use RegisterType::*;
+
+        let mut program = program(vec![
+            block(
+                vec![r(0), r(1)],
+                vec![Instruction::BinOp(BinOp {
+                    result: r(2),
+                    op: BinaryOperator::LessThan,
+                    lhs: r(0),
+                    rhs: r(1),
+                })],
+                EndInstruction::JumpIf(JumpIf {
+                    condition: r(2),
+                    if_so: BlockJump(f(1), vec![]),
+                    other: BlockJump(f(1), vec![]),
+                }),
+                &[(0, Int(1)), (1, Int(2)), (2, Bool(true))],
+            ),
+            block(vec![], vec![], EndInstruction::Return(Return(None)), &[]),
+        ]);
+
+        assert!(ConstantFolding.run(&mut program));
+        let main = &program.functions[&f(0)];
+
+        assert!(matches!(
+            main.instructions[0],
+            Instruction::MakeBoolean(Make { item: true, .. })
+        ));
+        assert!(matches!(
+            &main.end,
+            EndInstruction::Jump(Jump(BlockJump(target, _))) if *target == f(1)
+        ));
+
+        assert!(!ConstantFolding.run(&mut program));
+    }
+
+    /// Confirm that registers of which only the kind is known are left alone
+    #[test]
+    pub fn keeps_unknown_values() {
+        let mut program = program(vec![block(
+            vec![r(0), r(1)],
+            vec![Instruction::BinOp(BinOp {
+                result: r(2),
+                op: BinaryOperator::Add,
+                lhs: r(0),
+                rhs: r(1),
+            })],
+            EndInstruction::Return(Return(Some(r(2)))),
+            &[
+                (0, RegisterType::Number),
+                (1, RegisterType::Number),
+                (2, RegisterType::Number),
+            ],
+        )]);
+
+        assert!(!ConstantFolding.run(&mut program));
+        assert!(matches!(
+            program.functions[&f(0)].instructions[0],
+            Instruction::BinOp(_)
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+use jssat_ir::analysis::{CallGraph, ControlFlow};
+use rustc_hash::FxHashSet;
+
+use crate::codegen::{Block, Instruction, TypedProgram};
+use crate::id::AssemblerCtx;
+
+use super::{for_each_function, remove, Pass};
+
+/// Removes pure instructions whose results are never used.
+pub struct DeadCodeElimination;
+
+impl Pass for DeadCodeElimination {
+    fn name(&self) -> &'static str {
+        "dead code elimination"
+    }
+
+    fn run(&mut self, program: &mut TypedProgram) -> bool {
+        for_each_function(&mut program.functions, eliminate_dead_code)
+    }
+}
+
+fn eliminate_dead_code(function: &mut Block<AssemblerCtx>) -> bool {
+    let mut changed = false;
+
+    // removing an instruction may make the registers it used unused, so this
+    // is repeated until nothing more is removed
+    loop {
+        let mut used = FxHashSet::default();
+        for instruction in function.instructions.iter() {
+            used.extend(instruction.used_registers());
+        }
+        used.extend(function.end.used_registers());
+
+        let mut removed_any = false;
+        for instruction in function.instructions.iter_mut() {
+            let is_dead = match instruction.declared_register() {
+                Some(register) => instruction.is_pure() && !used.contains(&register),
+                None => false,
+            };
+
+            if is_dead {
+                remove(instruction);
+                removed_any = true;
+            }
+        }
+
+        if !removed_any {
+            break;
+        }
+
+        changed = true;
+    }
+
+    changed
+}
+
+/// Removes functions that can no longer be reached from the entrypoint, as
+/// other passes leave behind functions that are no longer called or jumped to.
+pub struct DeadFunctionElimination;
+
+impl Pass for DeadFunctionElimination {
+    fn name(&self) -> &'static str {
+        "dead function elimination"
+    }
+
+    fn run(&mut self, program: &mut TypedProgram) -> bool {
+        if takes_fn_ptrs(program) {
+            return false;
+        }
+
+        let call_graph = match CallGraph::new(program) {
+            Ok(call_graph) => call_graph,
+            // a reference to a function that isn't part of the program can't be
+            // followed to know what is reachable
+            Err(_) => return false,
+        };
+
+        let mut reachable = FxHashSet::default();
+        for function in call_graph.functions() {
+            match ControlFlow::new(program, function) {
+                Ok(control_flow) => reachable.extend(control_flow.blocks()),
+                Err(_) => return false,
+            }
+        }
+
+        let before = program.functions.len();
+        program.functions.retain(|id, _| reachable.contains(id));
+        program.functions.len() != before
+    }
+}
+
+/// Whether any function of the program takes a pointer to a function. Function
+/// pointers refer to functions of the lifted program, of which any of the
+/// typed variants may end up being called, so passes that need to know every
+/// reference to a function can't run on such programs.
+pub(super) fn takes_fn_ptrs(program: &TypedProgram) -> bool {
+    (program.functions.values())
+        .flat_map(|f| f.instructions.iter())
+        .any(|i| matches!(i, Instruction::GetFnPtr(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::codegen::EndInstruction;
+    use crate::id::IdCompat;
+    use crate::isa::{BlockJump, Call, Jump, Make, Negate, Return};
+    use crate::symbolic_execution::types::RegisterType;
+
+    use super::super::tests::{block, f, instructions, program, r};
+    use super::*;
+
+    /// Confirm that instructions with side effects are kept even if their
+    /// results are unused
+    #[test]
+    pub fn dead_code_elimination_keeps_side_effects() {
+        let mut program = program(vec![block(
+            vec![],
+            vec![
+                Instruction::CallStatic(Call {
+                    result: Some(r(0)),
+                    calling: f(0),
+                    args: vec![],
+                }),
+                Instruction::MakeInteger(Make {
+                    result: r(1),
+                    item: 1,
+                }),
+            ],
+            EndInstruction::Return(Return(None)),
+            &[(0, RegisterType::Number), (1, RegisterType::Int(1))],
+        )]);
+
+        assert!(DeadCodeElimination.run(&mut program));
+        let main = &program.functions[&f(0)];
+
+        assert!(matches!(main.instructions[0], Instruction::CallStatic(_)));
+        assert!(matches!(main.instructions[1], Instruction::Noop(_)));
+    }
+
+    /// Confirm that instructions only used by dead instructions are removed
+    /// too
+    #[test]
+    pub fn dead_code_elimination_removes_chains() {
+        let mut program = program(vec![block(
+            vec![],
+            vec![
+                Instruction::MakeInteger(Make {
+                    result: r(0),
+                    item: 1,
+                }),
+                Instruction::Negate(Negate {
+                    result: r(1),
+                    operand: r(0),
+                }),
+            ],
+            EndInstruction::Return(Return(None)),
+            &[(0, RegisterType::Int(1)), (1, RegisterType::Number)],
+        )]);
+
+        assert!(DeadCodeElimination.run(&mut program));
+        assert!(instructions(&program.functions[&f(0)]).is_empty());
+    }
+
+    /// Confirm that only functions that can't be reached from the entrypoint
+    /// are removed
+    #[test]
+    pub fn dead_function_elimination_keeps_reachable_functions() {
+        let returns = || block(vec![], vec![], EndInstruction::Return(Return(None)), &[]);
+
+        let mut program = program(vec![
+            block(
+                vec![],
+                vec![Instruction::CallStatic(Call {
+                    result: None,
+                    calling: f(1),
+                    args: vec![],
+                })],
+                EndInstruction::Jump(Jump(BlockJump(f(2), vec![]))),
+                &[],
+            ),
+            returns(),
+            returns(),
+            returns(),
+        ]);
+
+        assert!(DeadFunctionElimination.run(&mut program));
+
+        let mut remaining = program.functions.keys().copied().collect::<Vec<_>>();
+        remaining.sort();
+        assert_eq!(remaining, vec![f(0), f(1), f(2)]);
+    }
+
+    /// Confirm that no functions are removed from programs that take function
+    /// pointers, as the functions they may call aren't known
+    #[test]
+    pub fn dead_function_elimination_keeps_functions_of_fn_ptrs() {
+        let mut program = program(vec![
+            block(
+                vec![],
+                vec![Instruction::GetFnPtr(Make {
+                    result: r(0),
+                    item: crate::id::FunctionId::new_with_value(1),
+                })],
+                EndInstruction::Return(Return(Some(r(0)))),
+                &[],
+            ),
+            block(vec![], vec![], EndInstruction::Return(Return(None)), &[]),
+        ]);
+
+        assert!(!DeadFunctionElimination.run(&mut program));
+        assert_eq!(program.functions.len(), 2);
+    }
+}
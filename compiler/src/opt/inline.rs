@@ -0,0 +1,235 @@
+use jssat_ir::analysis::CallGraph;
+use rustc_hash::FxHashMap;
+
+use crate::codegen::{Block, EndInstruction, Instruction, TypedProgram};
+use crate::id::AssemblerCtx;
+use crate::isa::Return;
+
+use super::{has_records, rename, splice_registers, FunctionId, Pass, RegisterId};
+
+/// Replaces calls to small functions with the body of the function. Symbolic
+/// execution produces a variant of a function for every combination of types
+/// it is called with, so many such variants fold down to only a few
+/// instructions.
+///
+/// Only functions without control flow, that never end up calling themselves,
+/// are inlined.
+pub struct Inlining {
+    /// The most instructions a function may have to be inlined.
+    pub max_instructions: usize,
+}
+
+impl Default for Inlining {
+    fn default() -> Self {
+        Self {
+            max_instructions: 8,
+        }
+    }
+}
+
+impl Pass for Inlining {
+    fn name(&self) -> &'static str {
+        "inlining"
+    }
+
+    fn run(&mut self, program: &mut TypedProgram) -> bool {
+        let call_graph = match CallGraph::new(program) {
+            Ok(call_graph) => call_graph,
+            Err(_) => return false,
+        };
+
+        // functions that can't be reached aren't part of the call graph, and
+        // are only ever called by other such functions
+        let is_reachable = |id: FunctionId| call_graph.graph.contains_node(id);
+
+        let inlinable = (program.functions.iter())
+            .filter(|(id, _)| is_reachable(**id) && !call_graph.is_recursive(**id))
+            .filter(|(_, f)| self.is_inlinable(f))
+            .map(|(id, f)| (*id, f.clone()))
+            .collect::<FxHashMap<_, _>>();
+
+        if inlinable.is_empty() {
+            return false;
+        }
+
+        let mut changed = false;
+        for function in program.functions.values_mut() {
+            // inlining moves the instructions after the call, so it can't be
+            // done in functions with facts about records at those instructions
+            if has_records(function) {
+                continue;
+            }
+
+            // calls within inlined functions are left for the next run. going
+            // back to front keeps the indices of the calls yet to be inlined
+            // intact
+            for idx in inlinable_calls(function, &inlinable).into_iter().rev() {
+                inline_call(function, idx, &inlinable);
+                changed = true;
+            }
+        }
+
+        changed
+    }
+}
+
+impl Inlining {
+    fn is_inlinable(&self, function: &Block<AssemblerCtx>) -> bool {
+        let is_small = (function.instructions.iter())
+            .filter(|i| !matches!(i, Instruction::Noop(_)))
+            .count()
+            <= self.max_instructions;
+
+        matches!(function.end, EndInstruction::Return(_)) && is_small && !has_records(function)
+    }
+}
+
+fn inlinable_calls(
+    function: &Block<AssemblerCtx>,
+    inlinable: &FxHashMap<FunctionId, Block<AssemblerCtx>>,
+) -> Vec<usize> {
+    let calls = function.instructions.iter().enumerate();
+    let calls = calls.filter(|(_, i)| match i {
+        Instruction::CallStatic(call) => match inlinable.get(&call.calling) {
+            // a call can't use the result of a function returning nothing
+            Some(callee) => call.result.is_none() || returned(callee).is_some(),
+            None => false,
+        },
+        _ => false,
+    });
+
+    calls.map(|(idx, _)| idx).collect()
+}
+
+fn returned(function: &Block<AssemblerCtx>) -> Option<RegisterId> {
+    match &function.end {
+        EndInstruction::Return(Return(value)) => *value,
+        _ => None,
+    }
+}
+
+fn inline_call(
+    function: &mut Block<AssemblerCtx>,
+    idx: usize,
+    inlinable: &FxHashMap<FunctionId, Block<AssemblerCtx>>,
+) {
+    let call = match &function.instructions[idx] {
+        Instruction::CallStatic(call) => call.clone(),
+        _ => unreachable!("should be inlining a call"),
+    };
+
+    let callee = inlinable.get(&call.calling).unwrap();
+    let mut renames = splice_registers(function, callee, &call.args);
+
+    // uses of the result of the call are replaced with whatever was returned
+    let mut result_renames = FxHashMap::default();
+    if let (Some(result), Some(returned)) = (call.result, returned(callee)) {
+        match callee.parameters.contains(&returned) {
+            // a parameter is returned, so the result is one of the arguments
+            true => {
+                result_renames.insert(result, *renames.get(&returned).unwrap());
+            }
+            false => {
+                renames.insert(returned, result);
+            }
+        }
+    }
+
+    let body = callee.instructions.iter().cloned().map(|mut instruction| {
+        rename(&mut instruction, &renames);
+        instruction
+    });
+
+    function
+        .instructions
+        .splice(idx..=idx, body.collect::<Vec<_>>());
+
+    if !result_renames.is_empty() {
+        for instruction in function.instructions[idx..].iter_mut() {
+            rename(instruction, &result_renames);
+        }
+
+        for register in function.end.used_registers_mut() {
+            if let Some(renamed) = result_renames.get(register) {
+                *register = *renamed;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::isa::{Call, Negate};
+    use crate::symbolic_execution::types::RegisterType;
+
+    use super::super::tests::{block, f, instructions, program, r};
+    use super::*;
+
+    fn negate() -> Block<AssemblerCtx> {
+        block(
+            vec![r(0)],
+            vec![Instruction::Negate(Negate {
+                result: r(1),
+                operand: r(0),
+            })],
+            EndInstruction::Return(Return(Some(r(1)))),
+            &[(0, RegisterType::Number), (1, RegisterType::Number)],
+        )
+    }
+
+    /// Confirm that calls to small functions are replaced with their body,
+    /// and that uses of the result of the call use what the body returns
+    #[test]
+    pub fn inlines_small_functions() {
+        let mut program = program(vec![
+            block(
+                vec![r(0)],
+                vec![Instruction::CallStatic(Call {
+                    result: Some(r(1)),
+                    calling: f(1),
+                    args: vec![r(0)],
+                })],
+                EndInstruction::Return(Return(Some(r(1)))),
+                &[(0, RegisterType::Number), (1, RegisterType::Number)],
+            ),
+            negate(),
+        ]);
+
+        assert!(Inlining::default().run(&mut program));
+
+        let main = &program.functions[&f(0)];
+        let instructions = instructions(main);
+        assert_eq!(instructions.len(), 1);
+        assert!(matches!(
+            instructions[0],
+            Instruction::Negate(Negate { result, operand }) if *result == r(1) && *operand == r(0)
+        ));
+    }
+
+    /// Confirm that functions larger than the limit are left alone
+    #[test]
+    pub fn keeps_calls_to_large_functions() {
+        let mut program = program(vec![
+            block(
+                vec![r(0)],
+                vec![Instruction::CallStatic(Call {
+                    result: None,
+                    calling: f(1),
+                    args: vec![r(0)],
+                })],
+                EndInstruction::Return(Return(None)),
+                &[(0, RegisterType::Number)],
+            ),
+            negate(),
+        ]);
+
+        let mut inlining = Inlining {
+            max_instructions: 0,
+        };
+        assert!(!inlining.run(&mut program));
+        assert!(matches!(
+            program.functions[&f(0)].instructions[0],
+            Instruction::CallStatic(_)
+        ));
+    }
+}
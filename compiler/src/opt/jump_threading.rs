@@ -0,0 +1,318 @@
+use jssat_ir::analysis::{CallGraph, ControlFlow};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::codegen::{Block, EndInstruction, Instruction, TypedProgram};
+use crate::id::AssemblerCtx;
+use crate::isa::{BlockJump, Jump};
+
+use super::dce::takes_fn_ptrs;
+use super::{has_records, rename, splice_registers, FunctionId, Pass, RegisterId};
+
+/// Lifting turns every block into a function, and many of them do nothing but
+/// jump to another function. Jumps and calls to such functions are redirected
+/// to wherever they would have jumped to.
+pub struct JumpThreading;
+
+impl Pass for JumpThreading {
+    fn name(&self) -> &'static str {
+        "jump threading"
+    }
+
+    fn run(&mut self, program: &mut TypedProgram) -> bool {
+        let forwards = (program.functions.iter())
+            .filter_map(|(id, f)| forwarding(f).map(|forward| (*id, forward)))
+            .collect::<FxHashMap<_, _>>();
+
+        if forwards.is_empty() {
+            return false;
+        }
+
+        let mut changed = false;
+        for function in program.functions.values_mut() {
+            for instruction in function.instructions.iter_mut() {
+                if let Instruction::CallStatic(call) = instruction {
+                    let mut path = BlockJump(call.calling, std::mem::take(&mut call.args));
+                    changed = thread(&mut path, &forwards) || changed;
+
+                    let BlockJump(calling, args) = path;
+                    call.calling = calling;
+                    call.args = args;
+                }
+            }
+
+            for path in function.end.paths_mut() {
+                changed = thread(path, &forwards) || changed;
+            }
+        }
+
+        changed
+    }
+}
+
+type Path = BlockJump<FunctionId, AssemblerCtx>;
+
+/// A function that does nothing but jump to another function, as its
+/// parameters and the jump it makes in terms of them.
+type Forward = (Vec<RegisterId>, Path);
+
+fn forwarding(function: &Block<AssemblerCtx>) -> Option<Forward> {
+    let does_nothing = (function.instructions.iter())
+        .all(|i| matches!(i, Instruction::Noop(_) | Instruction::Comment(_)));
+
+    match &function.end {
+        EndInstruction::Jump(Jump(path)) if does_nothing => {
+            Some((function.parameters.clone(), path.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Follows a path through functions that forward it elsewhere, returning
+/// whether the path was changed.
+fn thread(path: &mut Path, forwards: &FxHashMap<FunctionId, Forward>) -> bool {
+    let mut visited = FxHashSet::default();
+    let mut changed = false;
+
+    while let Some((parameters, forward)) = forwards.get(&path.0) {
+        // functions that forward to one another would never finish
+        if !visited.insert(path.0) {
+            break;
+        }
+
+        let args = (parameters.iter().copied())
+            .zip(path.1.iter().copied())
+            .collect::<FxHashMap<_, _>>();
+
+        // every register a forwarding function passes along is one of its
+        // parameters, as it has no instructions to define any
+        let forwarded_args = (forward.1.iter())
+            .map(|r| args.get(r).copied())
+            .collect::<Option<Vec<_>>>();
+
+        match forwarded_args {
+            Some(forwarded_args) => *path = BlockJump(forward.0, forwarded_args),
+            None => break,
+        }
+
+        changed = true;
+    }
+
+    changed
+}
+
+/// Merges a function into the function that jumps to it, if nothing else jumps
+/// to or calls it.
+pub struct BlockMerging;
+
+impl Pass for BlockMerging {
+    fn name(&self) -> &'static str {
+        "block merging"
+    }
+
+    fn run(&mut self, program: &mut TypedProgram) -> bool {
+        if takes_fn_ptrs(program) {
+            return false;
+        }
+
+        let mut changed = false;
+
+        while let Some(references) = references(program) {
+            // only reachable functions merge, as a function that can't be
+            // reached isn't counted as jumping to its target
+            let mergeable = references.keys().find_map(|id| {
+                let function = program.functions.get(id)?;
+                let target = match &function.end {
+                    EndInstruction::Jump(Jump(path)) => path.0,
+                    _ => return None,
+                };
+
+                let target_function = program.functions.get(&target)?;
+
+                let can_merge = target != *id
+                    && target != program.entrypoint
+                    && references.get(&target) == Some(&1)
+                    && !has_records(target_function);
+
+                can_merge.then(|| (*id, target))
+            });
+
+            let (id, target) = match mergeable {
+                Some(mergeable) => mergeable,
+                None => break,
+            };
+
+            let target = program.functions.remove(&target).unwrap();
+            let function = program.functions.get_mut(&id).unwrap();
+            merge(function, target);
+            changed = true;
+        }
+
+        changed
+    }
+}
+
+/// How many functions jump to or call each function reachable from the
+/// entrypoint, or [`None`] if a function that isn't part of the program is
+/// jumped to or called.
+///
+/// Functions that can't be reached aren't counted, and are left for
+/// [`DeadFunctionElimination`] to remove.
+///
+/// [`DeadFunctionElimination`]: super::DeadFunctionElimination
+fn references(program: &TypedProgram) -> Option<FxHashMap<FunctionId, usize>> {
+    let call_graph = CallGraph::new(program).ok()?;
+
+    let mut predecessors = FxHashMap::<FunctionId, FxHashSet<FunctionId>>::default();
+    for function in call_graph.functions() {
+        let control_flow = ControlFlow::new(program, function).ok()?;
+
+        for block in control_flow.blocks() {
+            (predecessors.entry(block).or_default()).extend(control_flow.predecessors(block));
+        }
+    }
+
+    let references = predecessors.into_iter().map(|(block, predecessors)| {
+        let callers = call_graph.callers(block).count();
+        (block, predecessors.len() + callers)
+    });
+
+    Some(references.collect())
+}
+
+fn merge(function: &mut Block<AssemblerCtx>, target: Block<AssemblerCtx>) {
+    let args = match &function.end {
+        EndInstruction::Jump(Jump(path)) => path.1.clone(),
+        _ => unreachable!("can only merge into functions that jump"),
+    };
+
+    let renames = splice_registers(function, &target, &args);
+
+    for mut instruction in target.instructions {
+        rename(&mut instruction, &renames);
+        function.instructions.push(instruction);
+    }
+
+    let mut end = target.end;
+    for register in end.used_registers_mut() {
+        if let Some(renamed) = renames.get(register) {
+            *register = *renamed;
+        }
+    }
+
+    function.end = end;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::isa::{Make, Negate, Return};
+    use crate::symbolic_execution::types::RegisterType;
+
+    use super::super::tests::{block, f, instructions, program, r};
+    use super::*;
+
+    /// Confirm that jumps to a function which only jumps elsewhere are
+    /// redirected, passing along the arguments the forwarding function would
+    /// have
+    #[test]
+    pub fn threads_jumps_through_forwarding_functions() {
+        let mut program = program(vec![
+            block(
+                vec![r(0), r(1)],
+                vec![],
+                EndInstruction::Jump(Jump(BlockJump(f(1), vec![r(0), r(1)]))),
+                &[],
+            ),
+            // forwards its parameters to the next function, swapped
+            block(
+                vec![r(0), r(1)],
+                vec![],
+                EndInstruction::Jump(Jump(BlockJump(f(2), vec![r(1), r(0)]))),
+                &[],
+            ),
+            block(
+                vec![r(0), r(1)],
+                vec![],
+                EndInstruction::Return(Return(Some(r(0)))),
+                &[],
+            ),
+        ]);
+
+        assert!(JumpThreading.run(&mut program));
+
+        let main = &program.functions[&f(0)];
+        assert!(matches!(
+            &main.end,
+            EndInstruction::Jump(Jump(BlockJump(target, args)))
+                if *target == f(2) && *args == vec![r(1), r(0)]
+        ));
+    }
+
+    /// Confirm that functions forwarding to one another don't keep jump
+    /// threading from finishing
+    #[test]
+    pub fn threads_jumps_through_forwarding_cycles() {
+        let forward_to = |target| {
+            block(
+                vec![],
+                vec![],
+                EndInstruction::Jump(Jump(BlockJump(f(target), vec![]))),
+                &[],
+            )
+        };
+
+        let mut program = program(vec![forward_to(1), forward_to(2), forward_to(1)]);
+        JumpThreading.run(&mut program);
+
+        assert!(matches!(
+            &program.functions[&f(0)].end,
+            EndInstruction::Jump(Jump(BlockJump(target, _))) if [f(1), f(2)].contains(target)
+        ));
+    }
+
+    /// Confirm that a function jumped to from only one place is merged into
+    /// the function jumping to it, with its registers renamed
+    #[test]
+    pub fn merges_blocks_jumped_to_once() {
+        let mut program = program(vec![
+            block(
+                vec![],
+                vec![Instruction::MakeInteger(Make {
+                    result: r(0),
+                    item: 1,
+                })],
+                EndInstruction::Jump(Jump(BlockJump(f(1), vec![r(0)]))),
+                &[(0, RegisterType::Int(1))],
+            ),
+            block(
+                vec![r(0)],
+                vec![Instruction::Negate(Negate {
+                    result: r(1),
+                    operand: r(0),
+                })],
+                EndInstruction::Return(Return(Some(r(1)))),
+                &[(0, RegisterType::Int(1)), (1, RegisterType::Int(-1))],
+            ),
+        ]);
+
+        assert!(BlockMerging.run(&mut program));
+        assert_eq!(program.functions.len(), 1);
+
+        let main = &program.functions[&f(0)];
+        let instructions = instructions(main);
+        assert_eq!(instructions.len(), 2);
+
+        let negated = match instructions[1] {
+            Instruction::Negate(negate) => {
+                assert_eq!(negate.operand, r(0));
+                negate.result
+            }
+            _ => panic!("expected a negation"),
+        };
+
+        assert_ne!(negated, r(0));
+        assert!(matches!(
+            main.end,
+            EndInstruction::Return(Return(Some(result))) if result == negated
+        ));
+    }
+}
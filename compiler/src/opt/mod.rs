@@ -5,9 +5,433 @@
 //!
 //! In addition, whole-program optimization is done after individual
 //! optimizations.
+//!
+//! Optimizations are [`Pass`]es, which the [`PassManager`] runs over and over
+//! until none of them change the program anymore.
+//!
+//! The type information of a block records facts about records per instruction
+//! index, so passes replace instructions with [`Noop`]s rather than removing
+//! them, and only move instructions around when no records are involved.
+
+mod const_fold;
+mod dce;
+mod inline;
+mod jump_threading;
+
+pub use const_fold::ConstantFolding;
+pub use dce::{DeadCodeElimination, DeadFunctionElimination};
+pub use inline::Inlining;
+pub use jump_threading::{BlockMerging, JumpThreading};
+
+use std::fmt::Display;
 
-use crate::codegen::TypedProgram;
+use jssat_ir::analysis::{CallKind, Calls, ControlFlow, MissingFunction, ProgramFlow};
+use rustc_hash::FxHashMap;
 
-pub fn opt(program: TypedProgram) -> TypedProgram {
+use crate::codegen::{Block, Instruction, TypedProgram};
+use crate::id::{AssemblerCtx, IdCompat};
+use crate::isa::Noop;
+use crate::symbolic_execution::types::RegisterType;
+
+type FunctionId = crate::id::FunctionId<AssemblerCtx>;
+type RegisterId = crate::id::RegisterId<AssemblerCtx>;
+
+pub fn opt(mut program: TypedProgram) -> TypedProgram {
+    let mut passes = PassManager::new()
+        .with(ConstantFolding)
+        .with(DeadCodeElimination)
+        .with(JumpThreading)
+        .with(BlockMerging)
+        .with(Inlining::default())
+        .with(DeadFunctionElimination);
+
+    program.optimizations = passes.run(&mut program);
     program
 }
+
+/// An optimization over a [`TypedProgram`].
+pub trait Pass {
+    fn name(&self) -> &'static str;
+
+    /// Optimizes the program, returning `true` if anything was changed.
+    fn run(&mut self, program: &mut TypedProgram) -> bool;
+}
+
+/// The size of a program, as the amount of functions and instructions in it.
+/// [`Noop`]s are not counted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Size {
+    pub functions: usize,
+    pub instructions: usize,
+}
+
+impl Size {
+    pub fn of(program: &TypedProgram) -> Self {
+        let instructions = (program.functions.values())
+            .flat_map(|f| f.instructions.iter())
+            .filter(|i| !matches!(i, Instruction::Noop(_)))
+            .count();
+
+        Size {
+            functions: program.functions.len(),
+            instructions,
+        }
+    }
+}
+
+impl Display for Size {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} fns, {} insts", self.functions, self.instructions)
+    }
+}
+
+/// The result of running a [`Pass`] once.
+#[derive(Clone, Debug)]
+pub struct PassReport {
+    pub pass: &'static str,
+    pub before: Size,
+    pub after: Size,
+}
+
+pub struct PassManager {
+    passes: Vec<Box<dyn Pass>>,
+    max_iterations: usize,
+}
+
+impl PassManager {
+    pub fn new() -> Self {
+        Self {
+            passes: Vec::new(),
+            max_iterations: 16,
+        }
+    }
+
+    pub fn with(mut self, pass: impl Pass + 'static) -> Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    /// Limits how many times all passes are run, in case passes keep undoing
+    /// the work of one another.
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Runs every pass in order until none of them change the program. Only
+    /// the passes that changed the program are reported.
+    pub fn run(&mut self, program: &mut TypedProgram) -> Vec<PassReport> {
+        let mut reports = Vec::new();
+
+        for _ in 0..self.max_iterations {
+            let mut changed = false;
+
+            for pass in self.passes.iter_mut() {
+                let before = Size::of(program);
+
+                if pass.run(program) {
+                    changed = true;
+
+                    reports.push(PassReport {
+                        pass: pass.name(),
+                        before,
+                        after: Size::of(program),
+                    });
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        reports
+    }
+}
+
+impl Default for PassManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgramFlow for TypedProgram {
+    type FunctionId = FunctionId;
+
+    fn entrypoint(&self) -> FunctionId {
+        self.entrypoint
+    }
+
+    fn jumps(&self, block: FunctionId) -> Option<Vec<FunctionId>> {
+        let paths = self.functions.get(&block)?.end.paths();
+        Some(paths.into_iter().map(|path| path.0).collect())
+    }
+
+    fn calls(&self, control_flow: &ControlFlow<FunctionId>) -> Calls<FunctionId> {
+        let mut calls = Vec::new();
+
+        for block in control_flow.blocks() {
+            let function = self.functions.get(&block).ok_or(MissingFunction(block))?;
+
+            for instruction in function.instructions.iter() {
+                if let Instruction::CallStatic(call) = instruction {
+                    calls.push((call.calling, CallKind::Static));
+                }
+            }
+        }
+
+        Ok(calls)
+    }
+
+    /// Function pointers refer to functions of the lifted program rather than
+    /// their typed variants, so there are none to report. Passes that need to
+    /// know every reference to a function don't run on programs that
+    /// [`dce::takes_fn_ptrs`].
+    fn fn_ptrs(
+        &self,
+        _control_flow: &ControlFlow<FunctionId>,
+    ) -> Result<Vec<FunctionId>, MissingFunction<FunctionId>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Whether any register of the block is a record, or a union that may contain
+/// records.
+fn has_records(block: &Block<AssemblerCtx>) -> bool {
+    (block.type_info.registers.values())
+        .any(|t| matches!(t, RegisterType::Record(_) | RegisterType::Union(_)))
+}
+
+/// Registers that are defined in `from` are given new ids, so that they do not
+/// conflict with the registers of `into`, and the parameters of `from` become
+/// `args`. The type information of the registers is brought along to `into`.
+///
+/// The instructions of `from` can then be copied into `into` with [`rename`].
+fn splice_registers(
+    into: &mut Block<AssemblerCtx>,
+    from: &Block<AssemblerCtx>,
+    args: &[RegisterId],
+) -> FxHashMap<RegisterId, RegisterId> {
+    debug_assert!(!has_records(from));
+
+    let mut renames = FxHashMap::default();
+    for (parameter, arg) in from.parameters.iter().zip(args.iter()) {
+        renames.insert(*parameter, *arg);
+    }
+
+    let declared = from
+        .instructions
+        .iter()
+        .filter_map(|i| i.declared_register());
+    let fresh = (max_register(into) + 1..).map(RegisterId::new_with_value);
+
+    for (register, fresh) in declared.zip(fresh) {
+        renames.insert(register, fresh);
+
+        if let Some(typ) = from.type_info.try_get(register.map_context()) {
+            into.type_info.registers.insert(fresh.map_context(), typ);
+        }
+    }
+
+    renames
+}
+
+fn max_register(block: &Block<AssemblerCtx>) -> usize {
+    let typed = block.type_info.registers.keys().map(|r| r.value());
+    let parameters = block.parameters.iter().map(|r| r.value());
+    let declared = (block.instructions.iter())
+        .filter_map(|i| i.declared_register())
+        .map(|r| r.value());
+
+    typed.chain(parameters).chain(declared).max().unwrap_or(0)
+}
+
+fn rename(
+    instruction: &mut Instruction<AssemblerCtx>,
+    renames: &FxHashMap<RegisterId, RegisterId>,
+) {
+    let rename = |register: &mut RegisterId| {
+        if let Some(renamed) = renames.get(register) {
+            *register = *renamed;
+        }
+    };
+
+    if let Some(register) = instruction.declared_register_mut() {
+        rename(register);
+    }
+
+    instruction
+        .used_registers_mut()
+        .into_iter()
+        .for_each(rename);
+}
+
+/// Replaces an instruction with a [`Noop`], keeping the indices of the other
+/// instructions the same.
+fn remove(instruction: &mut Instruction<AssemblerCtx>) {
+    *instruction = Instruction::Noop(Noop);
+}
+
+/// Calls `f` with every function of the program. Functions are optimized
+/// independently of one another by such passes.
+fn for_each_function<F>(
+    functions: &mut FxHashMap<FunctionId, Block<AssemblerCtx>>,
+    mut f: F,
+) -> bool
+where
+    F: FnMut(&mut Block<AssemblerCtx>) -> bool,
+{
+    let mut changed = false;
+    for function in functions.values_mut() {
+        changed = f(function) || changed;
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use rustc_hash::FxHashMap;
+
+    use crate::{
+        codegen::{self, Block, EndInstruction, Instruction, TypedProgram},
+        id::{AssemblerCtx, IdCompat},
+        isa::{BinOp, BinaryOperator, BlockJump, Call, Jump, Make, Negate, Return},
+        symbolic_execution::types::{RegisterType, TypeBag},
+    };
+
+    use super::{FunctionId, RegisterId};
+
+    pub(super) fn r(value: usize) -> RegisterId {
+        RegisterId::new_with_value(value)
+    }
+
+    pub(super) fn f(value: usize) -> FunctionId {
+        FunctionId::new_with_value(value)
+    }
+
+    /// A function of a typed program, of which the registers have the given
+    /// types.
+    pub(super) fn block(
+        parameters: Vec<RegisterId>,
+        instructions: Vec<Instruction<AssemblerCtx>>,
+        end: EndInstruction<AssemblerCtx, FunctionId>,
+        types: &[(usize, RegisterType)],
+    ) -> Block<AssemblerCtx> {
+        let mut type_info = TypeBag::default();
+        for (register, typ) in types.iter() {
+            type_info.assign_type(r(*register).map_context(), *typ);
+        }
+
+        Block {
+            parameters,
+            instructions,
+            end,
+            type_info,
+        }
+    }
+
+    /// A typed program of the given functions, which are given ids in order.
+    /// The first function is the entrypoint.
+    pub(super) fn program(functions: Vec<Block<AssemblerCtx>>) -> TypedProgram {
+        TypedProgram {
+            entrypoint: f(0),
+            external_functions: FxHashMap::default(),
+            constants: FxHashMap::default(),
+            functions: functions
+                .into_iter()
+                .enumerate()
+                .map(|(i, b)| (f(i), b))
+                .collect(),
+            optimizations: Vec::new(),
+        }
+    }
+
+    /// The instructions of a function that aren't [`Instruction::Noop`]s.
+    pub(super) fn instructions(function: &Block<AssemblerCtx>) -> Vec<&Instruction<AssemblerCtx>> {
+        (function.instructions.iter())
+            .filter(|i| !matches!(i, Instruction::Noop(_)))
+            .collect()
+    }
+
+    /// Confirm that the pass manager folds constants, removes dead code,
+    /// threads jumps, merges blocks and inlines functions
+    #[test]
+    pub fn optimizes_typed_programs() {
+        use RegisterType::*;
+
+        let program = program(vec![
+            block(
+                vec![],
+                vec![
+                    Instruction::MakeInteger(Make {
+                        result: r(0),
+                        item: 1,
+                    }),
+                    Instruction::MakeInteger(Make {
+                        result: r(1),
+                        item: 2,
+                    }),
+                    Instruction::BinOp(BinOp {
+                        result: r(2),
+                        op: BinaryOperator::Add,
+                        lhs: r(0),
+                        rhs: r(1),
+                    }),
+                    Instruction::CallStatic(Call {
+                        result: Some(r(3)),
+                        calling: f(1),
+                        args: vec![r(2)],
+                    }),
+                ],
+                EndInstruction::Jump(Jump(BlockJump(f(2), vec![r(3)]))),
+                &[(0, Int(1)), (1, Int(2)), (2, Int(3)), (3, Number)],
+            ),
+            // negates its argument, which is small enough to be inlined
+            block(
+                vec![r(0)],
+                vec![Instruction::Negate(Negate {
+                    result: r(1),
+                    operand: r(0),
+                })],
+                EndInstruction::Return(Return(Some(r(1)))),
+                &[(0, Number), (1, Number)],
+            ),
+            // only forwards to the next block
+            block(
+                vec![r(0)],
+                vec![],
+                EndInstruction::Jump(Jump(BlockJump(f(3), vec![r(0)]))),
+                &[(0, Number)],
+            ),
+            block(
+                vec![r(0)],
+                vec![],
+                EndInstruction::Return(Return(Some(r(0)))),
+                &[(0, Number)],
+            ),
+        ]);
+
+        let program = super::opt(program);
+
+        assert_eq!(program.functions.len(), 1);
+        let main = program.functions.get(&f(0)).unwrap();
+
+        let instructions = instructions(main);
+        assert_eq!(instructions.len(), 2);
+        assert!(matches!(
+            instructions[0],
+            Instruction::MakeInteger(Make { item: 3, .. })
+        ));
+        assert!(matches!(
+            instructions[1],
+            Instruction::Negate(Negate { result, .. }) if *result == r(3)
+        ));
+        assert!(matches!(
+            main.end,
+            EndInstruction::Return(Return(Some(result))) if result == r(3)
+        ));
+
+        let display = codegen::display_typed(&program);
+        assert!(display.starts_with("-- optimized from 4 fns, 5 insts to 1 fns, 2 insts\n"));
+    }
+}
@@ -20,6 +20,7 @@ use self::types::TypeBag;
 use self::unique_id::{UniqueFnId, UniqueFnIdShared};
 use self::worker::CurrentInstruction;
 use self::worker::SymbWorker;
+use self::worker::SymbolicResult;
 use self::worker::WorkerResults;
 
 pub mod graph_system;
@@ -34,7 +35,7 @@ pub struct Engine<'a> {
     system: ComputeGraphSys<SymbWorker<'a>, SymbFactory<'a>>,
 }
 
-pub fn execute(program: &LiftedProgram) -> SystemRun {
+pub fn execute(program: &LiftedProgram) -> SymbolicResult<SystemRun> {
     let engine = make_system(program);
     system_run(engine, program.entrypoint, |_| Vec::new())
 }
@@ -77,7 +78,7 @@ pub fn system_run(
     engine: Engine,
     fn_id: FunctionId<LiftedCtx>,
     args: impl FnOnce(&mut TypeBag) -> Vec<RegisterType>,
-) -> SystemRun {
+) -> SymbolicResult<SystemRun> {
     let mut types = TypeBag::default();
     let args = args(&mut types);
 
@@ -127,12 +128,18 @@ pub fn system_run(
         .try_into_results()
         .expect("system should be dead");
 
-    SystemRun {
+    // an error in any worker is propagated to the workers that spawned it, so
+    // every error makes it to the entry worker
+    let results = (results.into_iter())
+        .map(|(id, result)| result.map(|result| (id, result)))
+        .collect::<SymbolicResult<_>>()?;
+
+    Ok(SystemRun {
         program: engine.program,
         fn_ids: engine.fn_ids,
         entry_fn: engine_fn_id,
         results,
-    }
+    })
 }
 
 fn handle_panic<'p>(system: ComputeGraphSys<SymbWorker<'p>, SymbFactory<'p>>) {
@@ -37,7 +37,20 @@ impl BinOpExecutor<'_> {
             BinaryOperator::Add => self.add(lhs, rhs),
             BinaryOperator::And => self.and(lhs, rhs),
             BinaryOperator::Or => self.or(lhs, rhs),
-            BinaryOperator::Equals => self.equals(lhs, rhs),
+            BinaryOperator::Equals | BinaryOperator::SameValue => self.equals(lhs, rhs),
+            BinaryOperator::Subtract
+            | BinaryOperator::Multiply
+            | BinaryOperator::Divide
+            | BinaryOperator::Remainder
+            | BinaryOperator::Exponent
+            | BinaryOperator::BitwiseAnd
+            | BinaryOperator::BitwiseOr
+            | BinaryOperator::BitwiseXor
+            | BinaryOperator::ShiftLeft
+            | BinaryOperator::ShiftRight
+            | BinaryOperator::UnsignedShiftRight => {
+                Err(BinaryOperatorExecutionError::Unimplemented)
+            }
             BinaryOperator::LessThan => todo!(),
             // _ => Err(BinaryOperatorExecutionError::Unimplemented),
         };
@@ -2,10 +2,12 @@
 
 use std::sync::Arc;
 
+use thiserror::Error;
+
 use crate::frontend::ir;
-use crate::frontend::ir::Returns;
+use crate::frontend::ir::{FFIValueType, Returns};
 use crate::id::*;
-use crate::isa::Make;
+use crate::isa::{Make, SwitchValue};
 use crate::lifted;
 use crate::lifted::{Function, LiftedProgram};
 use crate::retag::ExtFnPassRetagger;
@@ -44,6 +46,23 @@ pub struct SymbWorker<'program> {
     pub unique_allocation_id: Arc<Counter<UniqueRecordId<SymbolicCtx>>>,
 }
 
+/// Why symbolic execution could not evaluate a program.
+#[derive(Clone, Debug, Error)]
+pub enum SymbolicError {
+    /// Symbolic execution does not know how to evaluate the instruction.
+    #[error("symbolic execution does not support `{0}`")]
+    Unsupported(String),
+    /// The value controlling a jump may take more than one path, which
+    /// symbolic execution cannot follow yet.
+    #[error("symbolic execution cannot follow every path of a `{0:?}`")]
+    Divergent(RegisterType),
+    /// The value controlling a jump is of a type that cannot be jumped on.
+    #[error("cannot jump on a value of type `{0:?}`")]
+    NotJumpable(RegisterType),
+}
+
+pub type SymbolicResult<T> = Result<T, SymbolicError>;
+
 #[derive(Clone)]
 pub struct WorkerResults {
     /// mimics <SymbWorker as Worker>::Id
@@ -60,12 +79,18 @@ impl<'p> Worker for SymbWorker<'p> {
     // TODO: the result of a worker should be a return type like Never
     // or something idk. maybe it's fine to leave it as self and hope that
     // callers only use the return type and not any other values
-    type Result = WorkerResults;
+    type Result = SymbolicResult<WorkerResults>;
 
     fn work(&mut self, system: &impl System<Self>) -> Computation<Self::Result> {
+        Computation::Result(self.try_work(system))
+    }
+}
+
+impl SymbWorker<'_> {
+    fn try_work(&mut self, system: &impl System<Self>) -> SymbolicResult<WorkerResults> {
         for (inst_idx, inst) in self.func.instructions.iter().enumerate() {
             self.inst_on = CurrentInstruction::Sequential(inst);
-            self.exec_inst(inst, InstIdx::Inst(inst_idx), system);
+            self.exec_inst(inst, InstIdx::Inst(inst_idx), system)?;
 
             if self.never_infected {
                 break;
@@ -82,18 +107,32 @@ impl<'p> Worker for SymbWorker<'p> {
             let inst_idx = InstIdx::Epilogue;
             match &self.func.end {
                 crate::lifted::EndInstruction::Jump(i) => {
-                    self.exec_types(system, i.0 .0, &i.0 .1, inst_idx, last_inst)
+                    self.exec_types(system, i.0 .0, &i.0 .1, inst_idx, last_inst)?
                 }
                 crate::lifted::EndInstruction::JumpIf(i) => match self.types.get(i.condition) {
                     RegisterType::Bool(true) => {
-                        self.exec_types(system, i.if_so.0, &i.if_so.1, inst_idx, last_inst)
+                        self.exec_types(system, i.if_so.0, &i.if_so.1, inst_idx, last_inst)?
                     }
                     RegisterType::Bool(false) => {
-                        self.exec_types(system, i.other.0, &i.other.1, inst_idx, last_inst)
+                        self.exec_types(system, i.other.0, &i.other.1, inst_idx, last_inst)?
                     }
-                    RegisterType::Boolean => todo!("cannot handle divergence atm"),
-                    r => unimplemented!("cannot use non-boolean register as conditional {:?}", r),
+                    r @ RegisterType::Boolean => return Err(SymbolicError::Divergent(r)),
+                    r => return Err(SymbolicError::NotJumpable(r)),
                 },
+                crate::lifted::EndInstruction::Switch(i) => {
+                    let value = match self.types.get(i.value) {
+                        RegisterType::Atom(atom) => SwitchValue::Atom(atom),
+                        RegisterType::Int(integer) => SwitchValue::Integer(integer),
+                        RegisterType::Bool(boolean) => SwitchValue::Boolean(boolean),
+                        r @ (RegisterType::Number | RegisterType::Boolean) => {
+                            return Err(SymbolicError::Divergent(r))
+                        }
+                        r => return Err(SymbolicError::NotJumpable(r)),
+                    };
+
+                    let path = i.take_path(value);
+                    self.exec_types(system, path.0, &path.1, inst_idx, last_inst)?
+                }
                 crate::lifted::EndInstruction::Return(i) => match i.0 {
                     Some(r) => ReturnType::Value(self.types.get(r)),
                     None => ReturnType::Void,
@@ -107,7 +146,7 @@ impl<'p> Worker for SymbWorker<'p> {
         // this is only here temporarily
         assert_ne!(return_type, ReturnType::Never, "why is ret typ never");
 
-        Computation::Result(WorkerResults {
+        Ok(WorkerResults {
             id: self.id,
             lifted_id: self.lifted_id,
             is_entry_fn: self.is_entry_fn,
@@ -115,15 +154,15 @@ impl<'p> Worker for SymbWorker<'p> {
             types: self.types.clone(),
         })
     }
-}
 
-impl SymbWorker<'_> {
     fn exec_inst(
         &mut self,
         inst: &ir::Instruction<LiftedCtx, LiftedCtx>,
         inst_idx: InstIdx,
         system: &impl System<Self>,
-    ) {
+    ) -> SymbolicResult<()> {
+        let unsupported = || Err(SymbolicError::Unsupported(inst.as_display().to_string()));
+
         match &inst.data {
             ir::InstructionData::Comment(_) => {}
             ir::InstructionData::NewRecord(i) => self
@@ -158,7 +197,7 @@ impl SymbWorker<'_> {
                 };
             }
             &ir::InstructionData::GetFnPtr(i) => self.make(i, RegisterType::FnPtr),
-            &ir::InstructionData::MakeAtom(i) => todo!(),
+            ir::InstructionData::MakeAtom(_) => return unsupported(),
             &ir::InstructionData::MakeInteger(i) => self.make(i, RegisterType::Int),
             &ir::InstructionData::MakeBoolean(i) => self.make(i, RegisterType::Bool),
             ir::InstructionData::MakeBytes(i) => {
@@ -169,7 +208,7 @@ impl SymbWorker<'_> {
             ir::InstructionData::BinOp(i) => {
                 let (lhs, rhs) = (self.types.get(i.lhs), self.types.get(i.rhs));
 
-                todo!()
+                return unsupported();
                 // let res_typ =
                 //     i.op.make_executor(&mut self.types)
                 //         .execute(lhs, rhs)
@@ -180,20 +219,31 @@ impl SymbWorker<'_> {
             ir::InstructionData::Negate(i) => {
                 let o = self.types.get(i.operand);
 
+                let res_typ = match o {
+                    RegisterType::Number => RegisterType::Number,
+                    RegisterType::Int(n) => RegisterType::Int(-n),
+                    o => panic!("cannot negate for {:?}", o),
+                };
+
+                self.types.assign_type(i.result, res_typ);
+            }
+            ir::InstructionData::Not(i) => {
+                let o = self.types.get(i.operand);
+
                 let res_typ = match o {
                     RegisterType::Boolean => RegisterType::Boolean,
                     RegisterType::Bool(b) => RegisterType::Bool(!b),
-                    o => panic!("cannot negate for {:?}", o),
+                    o => panic!("cannot not for {:?}", o),
                 };
 
                 self.types.assign_type(i.result, res_typ);
             }
             ir::InstructionData::CallVirt(i) => {
                 let fn_id = self.types.get_fnptr(i.calling);
-                self.call_fn(system, i.result, fn_id, &i.args, inst_idx);
+                self.call_fn(system, i.result, fn_id, &i.args, inst_idx)?;
             }
             ir::InstructionData::CallStatic(i) => {
-                self.call_fn(system, i.result, i.calling, &i.args, inst_idx)
+                self.call_fn(system, i.result, i.calling, &i.args, inst_idx)?
             }
             ir::InstructionData::CallExtern(i) => {
                 // TODO: ensure/make args are coercible into `fn_id`,
@@ -203,23 +253,29 @@ impl SymbWorker<'_> {
                 match (i.result, &ext_fn.return_type) {
                     (Some(_), Returns::Void) => panic!("cannot assign `void` to register"),
                     (None, _) => {}
-                    (Some(_), Returns::Value(_)) => {
-                        todo!("handle return types of ext fns")
-                    }
+                    (Some(result), Returns::Value(typ)) => match typ {
+                        FFIValueType::Record => {
+                            (self.types).new_record(result, self.unique_allocation_id.next())
+                        }
+                        typ => self.types.assign_type(result, ffi_register_type(typ)),
+                    },
                 };
             }
-            ir::InstructionData::Generalize(_) => {
-                todo!("generalization algorithm");
-            }
-            ir::InstructionData::Assert(_) => todo!(),
-            ir::InstructionData::IsType(_) => todo!(),
-            ir::InstructionData::NewList(_) => todo!(),
-            ir::InstructionData::ListGet(_) => todo!(),
-            ir::InstructionData::ListSet(_) => todo!(),
-            ir::InstructionData::ListHasKey(_) => todo!(),
-            ir::InstructionData::ListLen(_) => todo!(),
-            _ => todo!(),
+            // TODO: generalization algorithm
+            ir::InstructionData::Generalize(_) => return unsupported(),
+            // lists and tuples have no register type yet
+            ir::InstructionData::NewList(_)
+            | ir::InstructionData::ListGet(_)
+            | ir::InstructionData::ListSet(_)
+            | ir::InstructionData::ListHasKey(_)
+            | ir::InstructionData::ListLen(_)
+            | ir::InstructionData::NewTuple(_)
+            | ir::InstructionData::TupleGet(_)
+            | ir::InstructionData::TupleLen(_) => return unsupported(),
+            _ => return unsupported(),
         };
+
+        Ok(())
     }
 
     fn make<I: Copy>(&mut self, make: Make<LiftedCtx, I>, constructor: impl Fn(I) -> RegisterType) {
@@ -233,8 +289,8 @@ impl SymbWorker<'_> {
         fn_id: FunctionId<LiftedCtx>,
         args: &[RegisterId<LiftedCtx>],
         inst_idx: InstIdx,
-    ) {
-        let return_type = self.exec_types(system, fn_id, args, inst_idx, None);
+    ) -> SymbolicResult<()> {
+        let return_type = self.exec_types(system, fn_id, args, inst_idx, None)?;
 
         match (result, return_type) {
             (_, ReturnType::Never) => {
@@ -248,6 +304,8 @@ impl SymbWorker<'_> {
             // TODO: better error message
             (a, b) => panic!("incompatible return state {:?} {:?}", a, b),
         };
+
+        Ok(())
     }
 }
 
@@ -259,7 +317,7 @@ impl SymbWorker<'_> {
         fn_args: &[RegisterId<LiftedCtx>],
         inst_idx: InstIdx,
         prev: Option<InstIdx>,
-    ) -> ReturnType {
+    ) -> SymbolicResult<ReturnType> {
         let target_fn = self.program.functions.get(&fn_id).unwrap();
         debug_assert_eq!(
             target_fn.parameters.len(),
@@ -273,14 +331,30 @@ impl SymbWorker<'_> {
 
         let target_id = self.fn_ids.id_of(fn_id, subset.child(), false);
         let results = system.spawn(target_id);
+        let results = (*results).as_ref().map_err(Clone::clone)?;
 
         target_fn
             .parameters
             .iter()
             .for_each(|reg| subset.update_reg(&results.types, *reg, inst_idx, InstIdx::Epilogue));
 
-        results
+        Ok(results
             .return_type
-            .map(|v| subset.update_typ(&results.types, v, inst_idx, InstIdx::Epilogue))
+            .map(|v| subset.update_typ(&results.types, v, inst_idx, InstIdx::Epilogue)))
+    }
+}
+
+/// The type of a value returned by an external function, other than a record.
+fn ffi_register_type(typ: &FFIValueType) -> RegisterType {
+    match typ {
+        FFIValueType::String | FFIValueType::Bytes => RegisterType::Bytes,
+        // opaque pointers are carried around as integers
+        FFIValueType::I64 | FFIValueType::F64 | FFIValueType::Opaque => RegisterType::Number,
+        FFIValueType::Bool => RegisterType::Boolean,
+        // there is no type for the tuple a nullable value is given as
+        FFIValueType::Any
+        | FFIValueType::Runtime
+        | FFIValueType::Record
+        | FFIValueType::Nullable(_) => RegisterType::Any,
     }
 }
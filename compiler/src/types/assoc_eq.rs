@@ -123,7 +123,11 @@ impl<'ctx1, 'ctx2, T: Tag> EqualityResolver<'ctx1, 'ctx2, T> {
                 self.constraints.assume_equal(TypeRefEq(*a), TypeRefEq(*b));
                 MaybeEqual::Maybe
             }
-            (Any, Any) | (Bytes, Bytes) | (Number, Number) | (Boolean, Boolean) => MaybeEqual::Yes,
+            (Any, Any)
+            | (Bytes, Bytes)
+            | (Number, Number)
+            | (BigNumber, BigNumber)
+            | (Boolean, Boolean) => MaybeEqual::Yes,
             (Atom(a), Atom(b)) => MaybeEqual::from(a == b),
             (Int(a), Int(b)) => MaybeEqual::from(a == b),
             (Float(a), Float(b)) => MaybeEqual::from(a == b),
@@ -134,6 +138,16 @@ impl<'ctx1, 'ctx2, T: Tag> EqualityResolver<'ctx1, 'ctx2, T> {
                 let b: &[u8] = b;
                 MaybeEqual::from(a == b)
             }
+            (Tuple(a), Tuple(b)) => {
+                let any_not_equal = a.unique_id() != b.unique_id()
+                    || a.len() != b.len()
+                    || (a.iter().zip(b.iter())).any(|(a, b)| self.are_not_equal(a, b));
+
+                match any_not_equal {
+                    true => MaybeEqual::No,
+                    false => MaybeEqual::Maybe,
+                }
+            }
             _ => MaybeEqual::No,
         };
 
@@ -182,6 +196,7 @@ impl<'ctx1, 'ctx2, T: Tag> EqualityResolver<'ctx1, 'ctx2, T> {
                 Type::Nothing => EitherKey::Naive(Type::Nothing),
                 Type::Bytes => EitherKey::Naive(Type::Bytes),
                 Type::Number => EitherKey::Naive(Type::Number),
+                Type::BigNumber => EitherKey::Naive(Type::BigNumber),
                 Type::Boolean => EitherKey::Naive(Type::Boolean),
                 Type::Atom(x) => EitherKey::Naive(Type::Atom(*x)),
                 Type::Int(x) => EitherKey::Naive(Type::Int(*x)),
@@ -190,6 +205,7 @@ impl<'ctx1, 'ctx2, T: Tag> EqualityResolver<'ctx1, 'ctx2, T> {
                 Type::FnPtr(x) => EitherKey::Naive(Type::FnPtr(*x)),
                 Type::Byts(_) => EitherKey::Awful(*k),
                 Type::List(_) => EitherKey::Awful(*k),
+                Type::Tuple(_) => EitherKey::Awful(*k),
                 Type::Record(_) => EitherKey::Awful(*k),
                 Type::Union(_) => EitherKey::Awful(*k),
             };
@@ -34,6 +34,9 @@ pub use list::List;
 mod rec_ctx;
 pub use rec_ctx::Record;
 
+mod tuple;
+pub use tuple::Tuple;
+
 mod union;
 pub use union::Union;
 
@@ -100,6 +103,10 @@ pub enum Type<'ctx, T: Tag> {
     /// [`Type::Number`] means a number at the most general level, which could
     /// include integers of varying sizes or floats.
     Number,
+    /// [`Type::BigNumber`] means a BigInt of any value. Unlike numbers, the
+    /// exact value of a BigInt is not tracked, as arbitrary-precision integers
+    /// cannot be cheaply copied around.
+    BigNumber,
     /// [`Type::Boolean`] represents a variable which can hold either `true` or
     /// `false`.
     ///
@@ -167,6 +174,15 @@ pub enum Type<'ctx, T: Tag> {
     /// [`TypeCtx`], call the `borrow` method to borrow it immutably, then call
     /// `make_list`. See `make_type_list` on [`type_ctx::TypeCtxImmut`].
     List(ListHandle<'ctx, T>),
+    /// [`Type::Tuple`] represents a JSSAT tuple, which is a fixed-size
+    /// collection of JSSAT types. Unlike lists, the type of every element in a
+    /// tuple is tracked individually.
+    ///
+    /// As tuples are immutable, the types of their elements never change after
+    /// construction. To construct one, use a [`TypeCtx`], call the `borrow`
+    /// method to borrow it immutably, then call `make_type_tuple`. See
+    /// `make_type_tuple` on [`type_ctx::TypeCtxImmut`].
+    Tuple(TupleHandle<'ctx, T>),
     /// [`Type::Record`] represents a JSSAT record, which is a key-value mapping
     /// of JSSAT types to JSSAT types.
     ///
@@ -200,6 +216,7 @@ impl<'ctx, T: Tag> PartialEq for TypeRefEq<'ctx, T> {
             | (Type::Nothing, Type::Nothing)
             | (Type::Bytes, Type::Bytes)
             | (Type::Number, Type::Number)
+            | (Type::BigNumber, Type::BigNumber)
             | (Type::Boolean, Type::Boolean) => true,
             (Type::Atom(l0), Type::Atom(r0)) => l0 == r0,
             (Type::Int(l0), Type::Int(r0)) => l0 == r0,
@@ -208,6 +225,11 @@ impl<'ctx, T: Tag> PartialEq for TypeRefEq<'ctx, T> {
             (Type::FnPtr(l0), Type::FnPtr(r0)) => l0 == r0,
             (Type::Byts(l0), Type::Byts(r0)) => l0 == r0,
             (Type::List(l), Type::List(r)) => DoublyPtrHandle::ptr_eq(&l, &r),
+            (Type::Tuple(l), Type::Tuple(r)) => {
+                l.unique_id() == r.unique_id()
+                    && l.len() == r.len()
+                    && (l.iter().zip(r.iter())).all(|(l, r)| TypeRefEq(*l) == TypeRefEq(*r))
+            }
             (Type::Record(l), Type::Record(r)) => DoublyPtrHandle::ptr_eq(&l, &r),
             (Type::Union(l), Type::Union(r)) => DoublyPtrHandle::ptr_eq(&l, &r),
             _ => false,
@@ -222,6 +244,7 @@ impl<'ctx1, 'ctx2, T: Tag> PartialEq<Type<'ctx2, T>> for Type<'ctx1, T> {
             | (Type::Nothing, Type::Nothing)
             | (Type::Bytes, Type::Bytes)
             | (Type::Number, Type::Number)
+            | (Type::BigNumber, Type::BigNumber)
             | (Type::Boolean, Type::Boolean) => true,
             (Type::Atom(l0), Type::Atom(r0)) => l0 == r0,
             (Type::Int(l0), Type::Int(r0)) => l0 == r0,
@@ -230,6 +253,7 @@ impl<'ctx1, 'ctx2, T: Tag> PartialEq<Type<'ctx2, T>> for Type<'ctx1, T> {
             (Type::FnPtr(l0), Type::FnPtr(r0)) => l0 == r0,
             (Type::Byts(l0), Type::Byts(r0)) => l0 == r0,
             (Type::List(l0), Type::List(r0)) => **l0.borrow() == **r0.borrow(),
+            (Type::Tuple(l0), Type::Tuple(r0)) => **l0 == **r0,
             (Type::Record(l0), Type::Record(r0)) => **l0.borrow() == **r0.borrow(),
             (Type::Union(l0), Type::Union(r0)) => **l0.borrow() == **r0.borrow(),
             _ => false,
@@ -262,6 +286,18 @@ impl<'ctx, T: Tag> Type<'ctx, T> {
         self.try_into_list().unwrap()
     }
 
+    pub fn try_into_tuple(self) -> Option<TupleHandle<'ctx, T>> {
+        match self {
+            Type::Tuple(handle) => Some(handle),
+            _ => None,
+        }
+    }
+
+    #[track_caller]
+    pub fn unwrap_tuple(&self) -> TupleHandle<'ctx, T> {
+        self.try_into_tuple().unwrap()
+    }
+
     pub fn try_into_record(self) -> Option<RecordHandle<'ctx, T>> {
         match self {
             Type::Record(handle) => Some(handle),
@@ -310,6 +346,32 @@ impl<'ctx> Debug for BytsHandle<'ctx> {
     }
 }
 
+/// The handle to a tuple. Tuples are immutable, so unlike lists and records, no
+/// interior mutability is needed.
+#[repr(transparent)]
+#[derive(Deref, Derivative)]
+#[derivative(
+    Copy(bound = ""),
+    Clone(bound = ""),
+    Hash(bound = ""),
+    PartialEq(bound = ""),
+    Eq(bound = "")
+)]
+pub struct TupleHandle<'ctx, T: Tag>(#[deref] CtxBox<'ctx, Tuple<'ctx, T>>);
+
+impl<'ctx, T: Tag> Debug for TupleHandle<'ctx, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut tuple = f.debug_tuple("Tuple");
+        tuple.field(&self.unique_id());
+
+        for element in self.iter() {
+            tuple.field(element);
+        }
+
+        tuple.finish()
+    }
+}
+
 pub type ListHandle<'ctx, T: Tag> = DoublyPtrHandle<'ctx, List<'ctx, T>>;
 pub type RecordHandle<'ctx, T: Tag> = DoublyPtrHandle<'ctx, Record<'ctx, T>>;
 pub type UnionHandle<'ctx, T: Tag> = DoublyPtrHandle<'ctx, Union<'ctx, T>>;
@@ -0,0 +1,44 @@
+use std::ops::Deref;
+
+use bumpalo::boxed::Box;
+use jssat_ir::id::{Tag, UniqueTupleId};
+
+use super::Type;
+
+/// The element types of a tuple. Tuples are immutable, so the elements are
+/// known as soon as the tuple is made.
+pub struct Tuple<'ctx, T: Tag> {
+    unique_id: UniqueTupleId<T>,
+    elements: Box<'ctx, [Type<'ctx, T>]>,
+}
+
+impl<'ctx, T: Tag> Tuple<'ctx, T> {
+    pub fn new(unique_id: UniqueTupleId<T>, elements: Box<'ctx, [Type<'ctx, T>]>) -> Self {
+        Self {
+            unique_id,
+            elements,
+        }
+    }
+
+    pub fn unique_id(&self) -> UniqueTupleId<T> {
+        self.unique_id
+    }
+}
+
+impl<'ctx, T: Tag> Deref for Tuple<'ctx, T> {
+    type Target = [Type<'ctx, T>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.elements
+    }
+}
+
+impl<'ctx1, 'ctx2, T: Tag> PartialEq<Tuple<'ctx2, T>> for Tuple<'ctx1, T> {
+    fn eq(&self, other: &Tuple<'ctx2, T>) -> bool {
+        self.unique_id == other.unique_id
+            && self.len() == other.len()
+            && (self.iter().zip(other.iter())).all(|(l, r)| l == r)
+    }
+}
+
+impl<'ctx, T: Tag> Eq for Tuple<'ctx, T> {}
@@ -14,8 +14,8 @@ use super::{
     TypeRefEq, Union, UnionHandle,
 };
 use crate::{
-    id::{Counter, RegisterId, Tag, UnionId, UniqueListId, UniqueRecordId},
-    types::{BytsHandle, List},
+    id::{Counter, RegisterId, Tag, UnionId, UniqueListId, UniqueRecordId, UniqueTupleId},
+    types::{BytsHandle, List, Tuple, TupleHandle},
 };
 
 /// A datastructure used to keep track of registers and records.
@@ -629,6 +629,7 @@ impl<'b, 'a, 'd, 't, T: Tag, K: Hash + Eq> TypeDuplication<'b, 'a, 'd, 't, T, K>
             Type::Nothing => Type::Nothing,
             Type::Bytes => Type::Bytes,
             Type::Number => Type::Number,
+            Type::BigNumber => Type::BigNumber,
             Type::Boolean => Type::Boolean,
             Type::Atom(x) => Type::Atom(x),
             Type::Int(x) => Type::Int(x),
@@ -655,6 +656,20 @@ impl<'b, 'a, 'd, 't, T: Tag, K: Hash + Eq> TypeDuplication<'b, 'a, 'd, 't, T, K>
                 list.extend(src_list.iter().map(|t| self.duplicate_type(*t)));
                 list_typ
             }
+            Type::Tuple(handle) => {
+                cache!(typ);
+
+                // tuples cannot contain themselves, so the elements can be
+                // duplicated before the tuple is made
+                let elements = (handle.iter())
+                    .map(|t| self.duplicate_type(*t))
+                    .collect::<Vec<_>>();
+
+                let dest = (self.type_ctx).make_type_tuple(handle.unique_id(), &elements);
+                self.duplications.insert(TypeRefEq(typ), TypeRefEq(dest));
+
+                dest
+            }
             Type::Record(handle) => {
                 cache!(typ);
 
@@ -754,6 +769,37 @@ impl<'borrow, 'arena, T: Tag, K> TypeCtxImmut<'borrow, 'arena, T, K> {
         Type::Byts(BytsHandle(CtxBox::new_unsized(self.arena, payload)))
     }
 
+    /// Constructs an instance of [`Type::Tuple`] for this [`TypeCtx`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crate::id::{Counter, NoContext};
+    /// let mut ctx = TypeCtx::<NoContext>::new();
+    /// let counter = Counter::new();
+    ///
+    /// let is_tuple = ctx.borrow(|ctx| {
+    ///     let tuple: Type<_> = ctx.make_type_tuple(counter.next(), &[Type::Int(1), Type::Bytes]);
+    ///     matches!(tuple, Type::Tuple(_))
+    /// });
+    ///
+    /// assert!(is_tuple)
+    /// ```
+    pub fn make_type_tuple(
+        &self,
+        unique_id: UniqueTupleId<T>,
+        elements: &[Type<'arena, T>],
+    ) -> Type<'arena, T> {
+        use bumpalo::collections::Vec;
+
+        let mut space_for_elements_in_bumpalo = Vec::with_capacity_in(elements.len(), self.arena);
+        space_for_elements_in_bumpalo.extend_from_slice(elements);
+
+        let elements = space_for_elements_in_bumpalo.into_boxed_slice();
+        let tuple = Tuple::new(unique_id, elements);
+        Type::Tuple(TupleHandle(CtxBox::new(self.arena, tuple)))
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (&K, &Type<'arena, T>)> {
         self.lookup.iter()
     }
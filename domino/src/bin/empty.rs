@@ -47,12 +47,12 @@ fn main() {
         .next()
         .unwrap();
 
-    moment.enter(lifted.entrypoint);
+    moment.enter(lifted.entrypoint, None);
     moment.snapshot(0, None, Default::default());
     moment.snapshot(1, None, Default::default());
     moment.snapshot(2, None, Default::default());
     moment.snapshot(3, None, Default::default());
-    moment.enter(child_fn);
+    moment.enter(child_fn, None);
     moment.snapshot(0, None, Default::default());
     moment.snapshot(1, None, Default::default());
     moment.snapshot(2, None, Default::default());
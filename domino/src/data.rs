@@ -1,9 +1,9 @@
 use std::{rc::Rc, sync::Arc};
 
 use jssat_ir::{
-    frontend::source_map::{SourceMapIdx, SourceMapImpl},
+    frontend::source_map::{JsSourceMap, SourceMapIdx, SourceMapImpl},
     id::IdCompat,
-    isa::AtomDealer,
+    isa::{decode_utf16, AtomDealer},
     lifted::FunctionId,
     value_snapshot::{SnapshotValue, ValueSnapshotArena},
     UnwrapNone,
@@ -15,6 +15,7 @@ use crate::server_data::*;
 pub struct Data {
     pub snapshots: Vec<Snapshot>,
     pub sources: SourceMapImpl,
+    pub js_sources: Option<JsSourceMap>,
     pub dealer: Arc<AtomDealer>,
 }
 
@@ -38,17 +39,36 @@ pub struct RawFrame {
     pub prev_moment: Option<usize>,
     pub parent: Option<Rc<RawFrame>>,
     pub source_idx: Option<SourceMapIdx>,
+    /// The JavaScript parse node the frame is evaluating, see
+    /// [`JsSourceMap`].
+    pub js_source_idx: Option<SourceMapIdx>,
     pub values: Rc<ValueSnapshotArena>,
 }
 
 impl Data {
+    /// Attaches the source map of the JavaScript that the program was
+    /// generated from, so that moments show the JavaScript being evaluated.
+    pub fn with_js_sources(mut self, js_sources: JsSourceMap) -> Self {
+        self.js_sources = Some(js_sources);
+        self
+    }
+
     pub fn overview(&self) -> Overview {
+        let mut sources = vec![Source {
+            name: None,
+            text: self.sources.source.clone(),
+        }];
+
+        if let Some(js_sources) = &self.js_sources {
+            sources.push(Source {
+                name: None,
+                text: js_sources.sources.source.clone(),
+            });
+        }
+
         Overview {
             total_moments: self.snapshots.len(),
-            sources: vec![Source {
-                name: None,
-                text: self.sources.source.clone(),
-            }],
+            sources,
         }
     }
 
@@ -85,40 +105,25 @@ impl Data {
             header: snapshot.code.header.clone(),
         };
 
-        let source = snapshot.frame.source_idx.map(|s| {
-            let mut snapshot = (self.sources.pyramid.snapshots)
-                .get(s.0)
-                .expect("should be valid sourcemapidx");
-
-            let mut locations = vec![];
-            while let Some(x) = &snapshot.0 {
-                locations.push(SourceLocation {
-                    start: SourceSpan {
-                        line: x.info.start.line,
-                        column: x.info.start.column,
-                    },
-                    end: SourceSpan {
-                        line: x.info.end.line,
-                        column: x.info.end.column,
-                    },
-                });
-
-                snapshot = &x.parent
-            }
-
-            MomentSource {
-                // we don't support multiple source maps yet
-                source_id: 0,
-                locations,
-            }
+        let source = (snapshot.frame.source_idx).map(|s| MomentSource {
+            source_id: 0,
+            locations: locations(&self.sources, s),
         });
 
+        let js_source = (self.js_sources.as_ref())
+            .zip(snapshot.frame.js_source_idx)
+            .map(|(js_sources, s)| MomentSource {
+                source_id: 1,
+                locations: locations(&js_sources.sources, s),
+            });
+
         let values = &snapshot.frame.values;
 
         let mut mvalues = MomentValues {
             registers: FxHashMap::default(),
             records: FxHashMap::default(),
             lists: FxHashMap::default(),
+            tuples: FxHashMap::default(),
         };
 
         mvalues.port(&self.dealer, values);
@@ -127,11 +132,36 @@ impl Data {
             callstack,
             code,
             source,
+            js_source,
             values: mvalues,
         })
     }
 }
 
+fn locations(sources: &SourceMapImpl, idx: SourceMapIdx) -> Vec<SourceLocation> {
+    let mut snapshot = (sources.pyramid.snapshots)
+        .get(idx.0)
+        .expect("should be valid sourcemapidx");
+
+    let mut locations = vec![];
+    while let Some(x) = &snapshot.0 {
+        locations.push(SourceLocation {
+            start: SourceSpan {
+                line: x.info.start.line,
+                column: x.info.start.column,
+            },
+            end: SourceSpan {
+                line: x.info.end.line,
+                column: x.info.end.column,
+            },
+        });
+
+        snapshot = &x.parent
+    }
+
+    locations
+}
+
 impl MomentValues {
     fn port(&mut self, dealer: &Arc<AtomDealer>, arena: &ValueSnapshotArena) {
         for (register, value) in arena.registers.iter() {
@@ -158,6 +188,16 @@ impl MomentValues {
 
             self.lists.insert(*id, mlist);
         }
+
+        for (id, tuple) in arena.tuples.iter() {
+            let mut mtuple = vec![];
+
+            for value in tuple.0.iter() {
+                mtuple.push(self.port_value(dealer, value));
+            }
+
+            self.tuples.insert(*id, mtuple);
+        }
     }
 
     fn port_value(&mut self, dealer: &Arc<AtomDealer>, value: &SnapshotValue) -> MomentValue {
@@ -171,27 +211,26 @@ impl MomentValues {
                 MomentValue::new_atom(name)
             }
             SnapshotValue::Bytes(x) => {
+                // UTF-16 strings of ASCII characters are also valid UTF-8, but
+                // every other byte of them is a nul
                 let s = (String::from_utf8(x.clone()).ok())
-                    .or_else(|| from_utf16(x))
+                    .filter(|s| !s.contains('\0'))
+                    .or_else(|| decode_utf16(x))
+                    .or_else(|| String::from_utf8(x.clone()).ok())
                     .unwrap_or_else(|| String::from_utf8_lossy(x).to_string());
 
                 MomentValue::new_bytes(s)
             }
             SnapshotValue::Number(x) => MomentValue::new_num(*x),
+            SnapshotValue::Float(x) => MomentValue::new_float(x.0),
+            SnapshotValue::BigInt(x) => MomentValue::new_bigint(x.to_string()),
+            SnapshotValue::BigNumber => MomentValue::new_bignumber(),
             SnapshotValue::Boolean(x) => MomentValue::new_bool(*x),
             SnapshotValue::FnPtr(x) => MomentValue::new_fnptr(x.value()),
             SnapshotValue::Record(x) => MomentValue::new_rec(*x),
             SnapshotValue::List(x) => MomentValue::new_list(*x),
+            SnapshotValue::Tuple(x) => MomentValue::new_tuple(*x),
             SnapshotValue::Runtime => MomentValue::new_runtime(),
         }
     }
 }
-
-fn from_utf16(bytes: &[u8]) -> Option<String> {
-    let bytes = bytes
-        .chunks_exact(2)
-        .map(|x| u16::from_ne_bytes([x[0], x[1]]))
-        .collect::<Vec<u16>>();
-
-    String::from_utf16(&bytes).ok()
-}
@@ -47,6 +47,7 @@ impl MomentApi {
                 })
                 .collect(),
             sources: SourceMapImpl::new("".to_owned()),
+            js_sources: None,
         }
     }
 
@@ -77,6 +78,7 @@ impl MomentApi {
                 })
                 .collect(),
             sources: source_map.try_into(),
+            js_sources: None,
         }
     }
 }
@@ -96,6 +98,7 @@ fn into_raw_frame(
         prev_moment: call_frame.prev_moment,
         parent: into_raw_frame(call_frame.parent.clone(), functions),
         source_idx: call_frame.info.source,
+        js_source_idx: call_frame.info.js_source,
         values: call_frame.info.values.clone(),
     });
 
@@ -142,6 +145,7 @@ pub struct FrameInfo {
     func: FunctionId,
     inst_idx: usize,
     source: Option<SourceMapIdx>,
+    js_source: Option<SourceMapIdx>,
     values: Rc<ValueSnapshotArena>,
 }
 
@@ -155,11 +159,14 @@ impl MomentApi {
         }
     }
 
-    pub fn enter(&mut self, func: FunctionId) {
+    /// Begins a frame of a function, which evaluates the JavaScript parse node
+    /// at `js_source` if it is evaluating one.
+    pub fn enter(&mut self, func: FunctionId, js_source: Option<SourceMapIdx>) {
         let info = FrameInfo {
             func,
             inst_idx: 0,
             source: None,
+            js_source,
             values: Rc::new(ValueSnapshotArena::default()),
         };
         self.pyramid_api.begin(info);
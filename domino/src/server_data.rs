@@ -39,6 +39,9 @@ pub struct Moment {
     pub code: FrameCode,
     /// The source code correlating to this moment, if any.
     pub source: Option<MomentSource>,
+    /// The JavaScript source code being evaluated at this moment, if any.
+    #[serde(rename = "jsSource")]
+    pub js_source: Option<MomentSource>,
     /// The values present at this moment
     pub values: MomentValues,
 }
@@ -100,6 +103,7 @@ pub struct SourceLocation {
 pub struct MomentValues {
     pub registers: FxHashMap<usize, MomentValue>,
     pub lists: FxHashMap<usize, Vec<MomentValue>>,
+    pub tuples: FxHashMap<usize, Vec<MomentValue>>,
     pub records: FxHashMap<usize, Vec<(MomentValue, MomentValue)>>,
 }
 
@@ -109,11 +113,17 @@ pub struct MomentValue {
     pub kind: &'static str,
     pub atom: Option<String>,
     pub num: Option<i64>,
+    /// Floats are serialized as strings, as JSON has no `NaN` or `Infinity`.
+    pub float: Option<String>,
+    /// BigInts are serialized as strings, as they may not fit in a JSON
+    /// number.
+    pub bigint: Option<String>,
     pub bool: Option<bool>,
     pub bytes: Option<String>,
     pub fnptr: Option<usize>,
     pub rec: Option<usize>,
     pub list: Option<usize>,
+    pub tuple: Option<usize>,
 }
 
 impl MomentValue {
@@ -122,11 +132,14 @@ impl MomentValue {
             kind: "atom",
             atom: Some(x),
             num: None,
+            float: None,
+            bigint: None,
             bool: None,
             bytes: None,
             fnptr: None,
             rec: None,
             list: None,
+            tuple: None,
         }
     }
 
@@ -135,11 +148,63 @@ impl MomentValue {
             kind: "num",
             atom: None,
             num: Some(x),
+            float: None,
+            bigint: None,
             bool: None,
             bytes: None,
             fnptr: None,
             rec: None,
             list: None,
+            tuple: None,
+        }
+    }
+
+    pub fn new_float(x: f64) -> Self {
+        Self {
+            kind: "float",
+            atom: None,
+            num: None,
+            float: Some(x.to_string()),
+            bigint: None,
+            bool: None,
+            bytes: None,
+            fnptr: None,
+            rec: None,
+            list: None,
+            tuple: None,
+        }
+    }
+
+    pub fn new_bigint(x: String) -> Self {
+        Self {
+            kind: "bigint",
+            atom: None,
+            num: None,
+            float: None,
+            bigint: Some(x),
+            bool: None,
+            bytes: None,
+            fnptr: None,
+            rec: None,
+            list: None,
+            tuple: None,
+        }
+    }
+
+    /// A BigInt of an unknown value, which has no `bigint`.
+    pub fn new_bignumber() -> Self {
+        Self {
+            kind: "bigint",
+            atom: None,
+            num: None,
+            float: None,
+            bigint: None,
+            bool: None,
+            bytes: None,
+            fnptr: None,
+            rec: None,
+            list: None,
+            tuple: None,
         }
     }
 
@@ -148,11 +213,14 @@ impl MomentValue {
             kind: "bool",
             atom: None,
             num: None,
+            float: None,
+            bigint: None,
             bool: Some(x),
             bytes: None,
             fnptr: None,
             rec: None,
             list: None,
+            tuple: None,
         }
     }
 
@@ -161,11 +229,14 @@ impl MomentValue {
             kind: "bytes",
             atom: None,
             num: None,
+            float: None,
+            bigint: None,
             bool: None,
             bytes: Some(x),
             fnptr: None,
             rec: None,
             list: None,
+            tuple: None,
         }
     }
 
@@ -174,11 +245,14 @@ impl MomentValue {
             kind: "fnptr",
             atom: None,
             num: None,
+            float: None,
+            bigint: None,
             bool: None,
             bytes: None,
             fnptr: Some(x),
             rec: None,
             list: None,
+            tuple: None,
         }
     }
 
@@ -187,11 +261,14 @@ impl MomentValue {
             kind: "rec",
             atom: None,
             num: None,
+            float: None,
+            bigint: None,
             bool: None,
             bytes: None,
             fnptr: None,
             rec: Some(x),
             list: None,
+            tuple: None,
         }
     }
 
@@ -200,11 +277,30 @@ impl MomentValue {
             kind: "list",
             atom: None,
             num: None,
+            float: None,
+            bigint: None,
             bool: None,
             bytes: None,
             fnptr: None,
             rec: None,
             list: Some(x),
+            tuple: None,
+        }
+    }
+
+    pub fn new_tuple(x: usize) -> Self {
+        Self {
+            kind: "tuple",
+            atom: None,
+            num: None,
+            float: None,
+            bigint: None,
+            bool: None,
+            bytes: None,
+            fnptr: None,
+            rec: None,
+            list: None,
+            tuple: Some(x),
         }
     }
 
@@ -213,11 +309,14 @@ impl MomentValue {
             kind: "runtime",
             atom: None,
             num: None,
+            float: None,
+            bigint: None,
             bool: None,
             bytes: None,
             fnptr: None,
             rec: None,
             list: None,
+            tuple: None,
         }
     }
 }
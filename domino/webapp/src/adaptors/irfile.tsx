@@ -1,11 +1,12 @@
 import { Accessor, createEffect, createSignal } from "solid-js";
-import { Moment, Source, SourceLocation } from "../api";
+import { Moment, MomentSource, Source, SourceLocation } from "../api";
 import { CodeViewProps } from "../panels/codeview/CodeView";
 import { Frame } from "../panels/codeview/FrameView";
 
 export function IRFileAdaptor(
   sources: Source[],
-  moment: Accessor<Moment | undefined>
+  moment: Accessor<Moment | undefined>,
+  select: (moment: Moment) => MomentSource | undefined = (m) => m.source
 ): () => CodeViewProps | undefined {
   const sourceLines = sources.map(({ text }) => text.split("\n"));
 
@@ -18,7 +19,10 @@ export function IRFileAdaptor(
   });
 
   const derivedMoment = () => {
-    const source = moment()?.source;
+    const now = moment();
+    if (!now) return undefined;
+
+    const source = select(now);
     if (!source) return undefined;
 
     return source;
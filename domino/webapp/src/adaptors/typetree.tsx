@@ -46,7 +46,10 @@ function DisplayValue(props: DisplayValueProps) {
     case "bytes": return `bytes(${value.bytes})`;
     case "fnptr": return `fn(${value.fnptr})`;
     case "num":   return `num(${value.num})`;
+    case "float": return `float(${value.float})`;
+    case "bigint": return `bigint(${value.bigint ?? "?"})`;
     case "list":  return <DisplayList   values={values} id={value.list} />;
+    case "tuple": return <DisplayTuple  values={values} id={value.tuple} />;
     case "rec":   return <DisplayRecord values={values} id={value.rec}  />;
   }
 }
@@ -70,6 +73,25 @@ function DisplayList(props: DisplayListProps) {
   );
 }
 
+interface DisplayTupleProps {
+  values: MomentValues;
+  id: string;
+}
+
+function DisplayTuple(props: DisplayTupleProps) {
+  const tuple = props.values.tuples[props.id];
+  const name = `tuple(len = ${tuple.length})`;
+
+  return (
+    <span>
+      {name}
+      {tuple.length !== 0 && (
+        <DisplayTreeNode treeNode={nodeList(props.values, tuple)} />
+      )}
+    </span>
+  );
+}
+
 function nodeList(values: MomentValues, list: MomentValue[]): TreeNode {
   return {
     expand: () =>
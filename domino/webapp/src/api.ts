@@ -32,6 +32,7 @@ export interface Moment {
   callstack: Frame[];
   code: FrameCode;
   source?: MomentSource;
+  jsSource?: MomentSource;
   values: MomentValues;
 }
 
@@ -65,21 +66,27 @@ export interface SourceLocation {
 export interface MomentValues {
   registers: Record<string, MomentValue>;
   lists: Record<string, MomentValue[]>;
+  tuples: Record<string, MomentValue[]>;
   records: Record<string, [MomentRecordKey, MomentValue][]>;
 }
 
 export type MomentValue =
   | { kind: "atom"; atom: string }
   | { kind: "num"; num: number }
+  | { kind: "float"; float: string }
+  | { kind: "bigint"; bigint: string | null }
   | { kind: "bool"; bool: boolean }
   | { kind: "bytes"; bytes: string }
   | { kind: "fnptr"; fnptr: number }
   | { kind: "rec"; rec: string }
-  | { kind: "list"; list: string };
+  | { kind: "list"; list: string }
+  | { kind: "tuple"; tuple: string };
 
 export type MomentRecordKey =
   | { kind: "atom"; atom: string }
   | { kind: "num"; num: number }
+  | { kind: "float"; float: string }
+  | { kind: "bigint"; bigint: string }
   | { kind: "bool"; bool: boolean }
   | { kind: "bytes"; bytes: string }
   | { kind: "fnptr"; fnptr: number };
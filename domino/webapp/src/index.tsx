@@ -78,6 +78,10 @@ const AppContainer = (props: AppContainerProps) => {
   const panels = [
     adaptPanel(adaptJssatIR([moment, setValue]), CodeView),
     adaptPanel(adaptIRFile(props.overview.sources, moment), CodeView),
+    adaptPanel(
+      adaptIRFile(props.overview.sources, moment, (m) => m.jsSource),
+      CodeView
+    ),
     adaptSplitView(adaptPanel(adaptTypeTree(moment), TreeView), () => (
       <CallstackView
         overview={props.overview}
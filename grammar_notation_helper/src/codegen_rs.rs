@@ -17,6 +17,47 @@ use jssat_ir::frontend::builder::ProgramBuilder;
 
 use derive_more::Display;
 
+/// The byte offsets of the source text that a parse node was parsed from.
+#[derive(Debug, Clone, Copy)]
+pub struct Span {
+    pub lo: usize,
+    pub hi: usize,
+}
+
+/// A parse node nested within another parse node, along with the span of
+/// source text it was parsed from, if known.
+#[derive(Debug, Clone)]
+pub struct Spanned<T> {
+    pub span: Option<Span>,
+    pub node: Box<T>,
+}
+
+impl<T> Spanned<T> {
+    pub fn new(node: T, span: Span) -> Self {
+        Self {
+            span: Some(span),
+            node: Box::new(node),
+        }
+    }
+}
+
+impl<T> From<T> for Spanned<T> {
+    fn from(node: T) -> Self {
+        Self {
+            span: None,
+            node: Box::new(node),
+        }
+    }
+}
+
+impl<T> std::ops::Deref for Spanned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.node
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LineTerminator;
 
@@ -33,7 +74,10 @@ pub struct NullLiteral;
 pub struct StringLiteral(pub String);
 
 #[derive(Debug, Clone)]
-pub struct NumericLiteral(pub f64);
+pub enum NumericLiteral {
+    Number(f64),
+    BigInt(num_bigint::BigInt),
+}
 
 #[derive(Debug, Clone)]
 pub struct BooleanLiteral(pub bool);
@@ -112,7 +156,7 @@ pub fn generate_production(production: &Production, formatter: &mut Formatter) {
         let mut elems = 0;
 
         inner_parse_nodes.for_each(|name| {
-            let ident = format!("Box<{}>", name.name);
+            let ident = format!("Spanned<{}>", name.name);
 
             let ident = match name.optional.is_some() {
                 true => format!("Option<{}>", ident),
@@ -120,7 +164,8 @@ pub fn generate_production(production: &Production, formatter: &mut Formatter) {
             };
 
             // TODO(performance): use cool graphs to figure out when enums are
-            //   recursive and automatically insert `Box<>`s when necessary
+            //   recursive and automatically insert `Box<>`s (which `Spanned`
+            //   contains) when necessary
             variant.tuple(ident.as_str());
             elems += 1;
         });
@@ -270,6 +315,13 @@ fn generate_visitor_ast(productions: &Productions, formatter: &mut Formatter) {
         .arg("variant_idx", "usize");
     visitor.new_fn("post_visit").arg_mut_self();
 
+    // called with the span of every child parse node right before it's visited
+    visitor
+        .new_fn("visit_span")
+        .arg_mut_self()
+        .arg("span", "Option<Span>")
+        .push_block(Block::new(""));
+
     let mut gen = |n: &str| {
         visitor
             .new_fn(&format!("visit_{}", n.to_case(Case::Snake)))
@@ -353,6 +405,7 @@ fn generate_visitor_ast(productions: &Productions, formatter: &mut Formatter) {
 
                         let mut visit_block = Block::new("");
 
+                        visit_block.line(format!("self.visit_span({}.span);", variant_name(idx)));
                         visit_block.line(format!(
                             "self.visit_{}({});",
                             variant.name.to_case(Case::Snake),
@@ -360,6 +413,7 @@ fn generate_visitor_ast(productions: &Productions, formatter: &mut Formatter) {
                         ));
                         visit.push_block(visit_block);
                     } else {
+                        visit.line(format!("self.visit_span({}.span);", variant_name(idx)));
                         visit.line(format!(
                             "self.visit_{}({});",
                             variant.name.to_case(Case::Snake),
@@ -11,6 +11,7 @@ fn criterion_benchmark(c: &mut Criterion) {
                 black_box(include_str!(
                     "../../jssat_frontend_js/src/ecmascript/ECMA262Methods.lisp"
                 )),
+                ir_file::StringEncoding::Utf16,
             )
         })
     });
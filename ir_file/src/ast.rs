@@ -1,4 +1,6 @@
 use lexpr::datum::Span;
+use num_bigint::BigInt;
+use ordered_float::OrderedFloat;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AST {
@@ -161,6 +163,16 @@ pub enum ExpressionData {
     ListLen {
         list: Box<Expression>,
     },
+    TupleNew {
+        elements: Vec<Expression>,
+    },
+    TupleGet {
+        tuple: Box<Expression>,
+        index: usize,
+    },
+    TupleLen {
+        tuple: Box<Expression>,
+    },
     GetFnPtr {
         function_name: FnName,
     },
@@ -185,6 +197,12 @@ pub enum ExpressionData {
     MakeInteger {
         value: i64,
     },
+    MakeFloat {
+        value: OrderedFloat<f64>,
+    },
+    MakeBigInt {
+        value: BigInt,
+    },
     MakeBoolean {
         value: bool,
     },
@@ -196,6 +214,34 @@ pub enum ExpressionData {
     Negate {
         expr: Box<Expression>,
     },
+    Not {
+        expr: Box<Expression>,
+    },
+    NumberToBigInt {
+        expr: Box<Expression>,
+    },
+    StringConcat {
+        lhs: Box<Expression>,
+        rhs: Box<Expression>,
+    },
+    StringLen {
+        string: Box<Expression>,
+    },
+    StringSlice {
+        string: Box<Expression>,
+        start: Box<Expression>,
+        end: Box<Expression>,
+    },
+    StringCodeUnitAt {
+        string: Box<Expression>,
+        index: Box<Expression>,
+    },
+    NumberToString {
+        expr: Box<Expression>,
+    },
+    StringToNumber {
+        expr: Box<Expression>,
+    },
     IsTypeOf {
         expr: Box<Expression>,
         kind: String,
@@ -209,9 +255,21 @@ pub enum ExpressionData {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BinOpKind {
     Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Exp,
     And,
     Or,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+    UShr,
     Eq,
+    SameValue,
     Lt,
 }
 
@@ -414,6 +472,15 @@ pub trait Visitor {
             ExpressionData::ListLen { list } => {
                 self.visit_expr(list);
             }
+            ExpressionData::TupleNew { elements } => {
+                self.visit_exprs(elements);
+            }
+            ExpressionData::TupleGet { tuple, index: _ } => {
+                self.visit_expr(tuple);
+            }
+            ExpressionData::TupleLen { tuple } => {
+                self.visit_expr(tuple);
+            }
             ExpressionData::CallStatic {
                 function_name: _,
                 args,
@@ -431,6 +498,34 @@ pub trait Visitor {
             ExpressionData::Negate { expr } => {
                 self.visit_expr(expr);
             }
+            ExpressionData::Not { expr } => {
+                self.visit_expr(expr);
+            }
+            ExpressionData::NumberToBigInt { expr } => {
+                self.visit_expr(expr);
+            }
+            ExpressionData::StringConcat { lhs, rhs } => {
+                self.visit_expr(lhs);
+                self.visit_expr(rhs);
+            }
+            ExpressionData::StringLen { string } => {
+                self.visit_expr(string);
+            }
+            ExpressionData::StringSlice { string, start, end } => {
+                self.visit_expr(string);
+                self.visit_expr(start);
+                self.visit_expr(end);
+            }
+            ExpressionData::StringCodeUnitAt { string, index } => {
+                self.visit_expr(string);
+                self.visit_expr(index);
+            }
+            ExpressionData::NumberToString { expr } => {
+                self.visit_expr(expr);
+            }
+            ExpressionData::StringToNumber { expr } => {
+                self.visit_expr(expr);
+            }
             ExpressionData::IsTypeOf { expr, kind: _ } => {
                 self.visit_expr(expr);
             }
@@ -444,6 +539,8 @@ pub trait Visitor {
             ExpressionData::GetFnPtr { function_name: _ }
             | ExpressionData::MakeBytes { bytes: _ }
             | ExpressionData::MakeInteger { value: _ }
+            | ExpressionData::MakeFloat { value: _ }
+            | ExpressionData::MakeBigInt { value: _ }
             | ExpressionData::MakeBoolean { value: _ }
             | ExpressionData::VarReference { variable: _ }
             | ExpressionData::GetGlobal
@@ -825,6 +825,25 @@ fn emit_expr(counter: &mut usize, block: &mut Block, expr: &Expression) -> Strin
                 block.line(sample(span));
             }
         }
+        ExpressionData::MakeFloat { value } => {
+            block.line(format!(
+                "let {} = e.make_number_float(f64::from_bits({:#x}));",
+                result,
+                value.to_bits()
+            ));
+            if let Some(span) = expr.span {
+                block.line(sample(span));
+            }
+        }
+        ExpressionData::MakeBigInt { value } => {
+            block.line(format!(
+                "let {} = e.make_bigint(\"{}\".parse().unwrap());",
+                result, value
+            ));
+            if let Some(span) = expr.span {
+                block.line(sample(span));
+            }
+        }
         ExpressionData::MakeBoolean { value } => {
             block.line(format!("let {} = e.make_bool({});", result, value));
             if let Some(span) = expr.span {
@@ -863,6 +882,15 @@ fn emit_expr(counter: &mut usize, block: &mut Block, expr: &Expression) -> Strin
                         block.line(sample(span));
                     }
                 }
+                crate::BinOpKind::SameValue => {
+                    block.line(format!(
+                        "let {} = e.compare_same_value({}, {});",
+                        result, lhs, rhs
+                    ));
+                    if let Some(span) = expr.span {
+                        block.line(sample(span));
+                    }
+                }
                 crate::BinOpKind::Lt => {
                     block.line(format!(
                         "let {} = e.compare_less_than({}, {});",
@@ -872,6 +900,37 @@ fn emit_expr(counter: &mut usize, block: &mut Block, expr: &Expression) -> Strin
                         block.line(sample(span));
                     }
                 }
+                crate::BinOpKind::Sub
+                | crate::BinOpKind::Mul
+                | crate::BinOpKind::Div
+                | crate::BinOpKind::Rem
+                | crate::BinOpKind::Exp
+                | crate::BinOpKind::BitAnd
+                | crate::BinOpKind::BitOr
+                | crate::BinOpKind::BitXor
+                | crate::BinOpKind::Shl
+                | crate::BinOpKind::Shr
+                | crate::BinOpKind::UShr => {
+                    let method = match kind {
+                        crate::BinOpKind::Sub => "subtract",
+                        crate::BinOpKind::Mul => "multiply",
+                        crate::BinOpKind::Div => "divide",
+                        crate::BinOpKind::Rem => "remainder",
+                        crate::BinOpKind::Exp => "exponent",
+                        crate::BinOpKind::BitAnd => "bitwise_and",
+                        crate::BinOpKind::BitOr => "bitwise_or",
+                        crate::BinOpKind::BitXor => "bitwise_xor",
+                        crate::BinOpKind::Shl => "shift_left",
+                        crate::BinOpKind::Shr => "shift_right",
+                        crate::BinOpKind::UShr => "unsigned_shift_right",
+                        _ => unreachable!(),
+                    };
+
+                    block.line(format!("let {} = e.{}({}, {});", result, method, lhs, rhs));
+                    if let Some(span) = expr.span {
+                        block.line(sample(span));
+                    }
+                }
             };
         }
         ExpressionData::Negate { expr } => {
@@ -881,6 +940,75 @@ fn emit_expr(counter: &mut usize, block: &mut Block, expr: &Expression) -> Strin
                 block.line(sample(span));
             }
         }
+        ExpressionData::Not { expr } => {
+            let texpr = emit_expr(counter, block, expr);
+            block.line(format!("let {} = e.not({});", result, texpr));
+            if let Some(span) = expr.span {
+                block.line(sample(span));
+            }
+        }
+        ExpressionData::NumberToBigInt { expr } => {
+            let texpr = emit_expr(counter, block, expr);
+            block.line(format!("let {} = e.number_to_bigint({});", result, texpr));
+            if let Some(span) = expr.span {
+                block.line(sample(span));
+            }
+        }
+        ExpressionData::StringConcat { lhs, rhs } => {
+            let lhs = emit_expr(counter, block, lhs);
+            let rhs = emit_expr(counter, block, rhs);
+            block.line(format!(
+                "let {} = e.string_concat({}, {});",
+                result, lhs, rhs
+            ));
+            if let Some(span) = expr.span {
+                block.line(sample(span));
+            }
+        }
+        ExpressionData::StringLen { string } => {
+            let string = emit_expr(counter, block, string);
+            block.line(format!("let {} = e.string_len({});", result, string));
+            if let Some(span) = expr.span {
+                block.line(sample(span));
+            }
+        }
+        ExpressionData::StringSlice { string, start, end } => {
+            let string = emit_expr(counter, block, string);
+            let start = emit_expr(counter, block, start);
+            let end = emit_expr(counter, block, end);
+            block.line(format!(
+                "let {} = e.string_slice({}, {}, {});",
+                result, string, start, end
+            ));
+            if let Some(span) = expr.span {
+                block.line(sample(span));
+            }
+        }
+        ExpressionData::StringCodeUnitAt { string, index } => {
+            let string = emit_expr(counter, block, string);
+            let index = emit_expr(counter, block, index);
+            block.line(format!(
+                "let {} = e.string_code_unit_at({}, {});",
+                result, string, index
+            ));
+            if let Some(span) = expr.span {
+                block.line(sample(span));
+            }
+        }
+        ExpressionData::NumberToString { expr } => {
+            let texpr = emit_expr(counter, block, expr);
+            block.line(format!("let {} = e.number_to_string({});", result, texpr));
+            if let Some(span) = expr.span {
+                block.line(sample(span));
+            }
+        }
+        ExpressionData::StringToNumber { expr } => {
+            let texpr = emit_expr(counter, block, expr);
+            block.line(format!("let {} = e.string_to_number({});", result, texpr));
+            if let Some(span) = expr.span {
+                block.line(sample(span));
+            }
+        }
         ExpressionData::IsTypeOf { expr, kind } => {
             let texpr = emit_expr(counter, block, expr);
             block.line(format!(
@@ -934,6 +1062,35 @@ fn emit_expr(counter: &mut usize, block: &mut Block, expr: &Expression) -> Strin
                 block.line(sample(span));
             }
         }
+        ExpressionData::TupleNew { elements } => {
+            let elements = elements
+                .iter()
+                .map(|e| emit_expr(counter, block, e))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            block.line(format!("let {} = e.tuple_new(vec![{}]);", result, elements));
+            if let Some(span) = expr.span {
+                block.line(sample(span));
+            }
+        }
+        ExpressionData::TupleGet { tuple, index } => {
+            let tuple = emit_expr(counter, block, tuple);
+            block.line(format!(
+                "let {} = e.tuple_get({}, {});",
+                result, tuple, index
+            ));
+            if let Some(span) = expr.span {
+                block.line(sample(span));
+            }
+        }
+        ExpressionData::TupleLen { tuple } => {
+            let tuple = emit_expr(counter, block, tuple);
+            block.line(format!("let {} = e.tuple_len({});", result, tuple));
+            if let Some(span) = expr.span {
+                block.line(sample(span));
+            }
+        }
     };
 
     if has_basis {
@@ -16,10 +16,14 @@ pub use codegen_rs::*;
 mod parser;
 pub use parser::*;
 
+mod strings;
+pub use strings::StringEncoding;
+
 mod threaded_state;
 
-pub fn generate(name: &str, code: &str) -> String {
+pub fn generate(name: &str, code: &str, strings: StringEncoding) -> String {
     let mut ast = parser::parse(code);
     threaded_state::thread_state(&mut ast);
+    strings::encode_strings(&mut ast, strings);
     codegen_rs::gen(name, ast)
 }
@@ -2,6 +2,7 @@ use crate::parser::rules::apply_rule_recursively_inner;
 
 use self::rules::apply_rule_recursively;
 use super::*;
+use ordered_float::OrderedFloat;
 
 mod node;
 mod rules;
@@ -481,12 +482,12 @@ fn parse_expression(node: Node<&str>) -> Expression {
         },
         Node::Number(num, _) => Expression {
             span: node_span,
-            data: ExpressionData::MakeInteger {
-                value: if let Some(v) = num.as_i64() {
-                    v
-                } else {
-                    panic!("cannot do fp at this time")
+            data: match (num.as_i64(), num.as_f64()) {
+                (Some(value), _) => ExpressionData::MakeInteger { value },
+                (None, Some(value)) => ExpressionData::MakeFloat {
+                    value: OrderedFloat(value),
                 },
+                (None, None) => panic!("cannot represent number {}", num),
             },
         },
         Node::Parent(children, parent_span) => {
@@ -546,6 +547,23 @@ fn parse_expression(node: Node<&str>) -> Expression {
                         },
                     };
                 }
+                (
+                    Some(Node::Word("string-slice", _)),
+                    Some(string),
+                    Some(start),
+                    Some(end),
+                    None,
+                    None,
+                ) => {
+                    return Expression {
+                        span: node_span,
+                        data: ExpressionData::StringSlice {
+                            string: Box::new(parse_expression(string)),
+                            start: Box::new(parse_expression(start)),
+                            end: Box::new(parse_expression(end)),
+                        },
+                    };
+                }
                 _ => {}
             };
 
@@ -613,6 +631,32 @@ fn parse_expression(node: Node<&str>) -> Expression {
                         list: Box::new(parse_expression(list)),
                     },
                 },
+                (Some(Node::Word("tuple-new", _)), _, _) => Expression {
+                    span: node_span,
+                    data: ExpressionData::TupleNew {
+                        elements: children
+                            .iter()
+                            .skip(1)
+                            .map(|node| parse_expression(node.as_ref()))
+                            .collect(),
+                    },
+                },
+                (Some(Node::Word("tuple-get", _)), Some(tuple), Some(Node::Number(index, _))) => {
+                    Expression {
+                        span: node_span,
+                        data: ExpressionData::TupleGet {
+                            tuple: Box::new(parse_expression(tuple)),
+                            index: (index.as_i64().and_then(|i| i.try_into().ok()))
+                                .unwrap_or_else(|| panic!("invalid tuple index {}", index)),
+                        },
+                    }
+                }
+                (Some(Node::Word("tuple-len", _)), Some(tuple), None) => Expression {
+                    span: node_span,
+                    data: ExpressionData::TupleLen {
+                        tuple: Box::new(parse_expression(tuple)),
+                    },
+                },
                 (Some(Node::Word("get-fn-ptr", _)), Some(Node::Word(fn_name, _)), None) => {
                     Expression {
                         span: node_span,
@@ -651,7 +695,12 @@ fn parse_expression(node: Node<&str>) -> Expression {
                 },
                 (
                     Some(lhs),
-                    Some(Node::Word(kind @ ("+" | "and" | "==" | "<" | "or"), _)),
+                    Some(Node::Word(
+                        kind @ ("+" | "-" | "*" | "/" | "%" | "**" | "and" | "or" | "bit-and"
+                        | "bit-or" | "bit-xor" | "<<" | ">>" | ">>>" | "==" | "same-value"
+                        | "<"),
+                        _,
+                    )),
                     Some(rhs),
                 ) => Expression {
                     span: node_span,
@@ -660,10 +709,22 @@ fn parse_expression(node: Node<&str>) -> Expression {
                             // DEAR FUTURE EDITORS: make sure to edit the pattern up above too
                             // you're welcome
                             "+" => BinOpKind::Add,
+                            "-" => BinOpKind::Sub,
+                            "*" => BinOpKind::Mul,
+                            "/" => BinOpKind::Div,
+                            "%" => BinOpKind::Rem,
+                            "**" => BinOpKind::Exp,
                             "and" => BinOpKind::And,
+                            "or" => BinOpKind::Or,
+                            "bit-and" => BinOpKind::BitAnd,
+                            "bit-or" => BinOpKind::BitOr,
+                            "bit-xor" => BinOpKind::BitXor,
+                            "<<" => BinOpKind::Shl,
+                            ">>" => BinOpKind::Shr,
+                            ">>>" => BinOpKind::UShr,
                             "==" => BinOpKind::Eq,
+                            "same-value" => BinOpKind::SameValue,
                             "<" => BinOpKind::Lt,
-                            "or" => BinOpKind::Or,
                             _ => unreachable!("what"),
                         },
                         lhs: Box::new(parse_expression(lhs)),
@@ -671,11 +732,64 @@ fn parse_expression(node: Node<&str>) -> Expression {
                     },
                 },
                 (Some(Node::Word("not", _)), Some(expr), None) => Expression {
+                    span: node_span,
+                    data: ExpressionData::Not {
+                        expr: Box::new(parse_expression(expr)),
+                    },
+                },
+                (Some(Node::Word("negate", _)), Some(expr), None) => Expression {
                     span: node_span,
                     data: ExpressionData::Negate {
                         expr: Box::new(parse_expression(expr)),
                     },
                 },
+                (Some(Node::Word("bigint", _)), Some(Node::String(value, _)), None) => Expression {
+                    span: node_span,
+                    data: ExpressionData::MakeBigInt {
+                        value: (value.parse())
+                            .unwrap_or_else(|_| panic!("invalid bigint {}", value)),
+                    },
+                },
+                (Some(Node::Word("number-to-bigint", _)), Some(expr), None) => Expression {
+                    span: node_span,
+                    data: ExpressionData::NumberToBigInt {
+                        expr: Box::new(parse_expression(expr)),
+                    },
+                },
+                (Some(Node::Word("string-concat", _)), Some(lhs), Some(rhs)) => Expression {
+                    span: node_span,
+                    data: ExpressionData::StringConcat {
+                        lhs: Box::new(parse_expression(lhs)),
+                        rhs: Box::new(parse_expression(rhs)),
+                    },
+                },
+                (Some(Node::Word("string-len", _)), Some(string), None) => Expression {
+                    span: node_span,
+                    data: ExpressionData::StringLen {
+                        string: Box::new(parse_expression(string)),
+                    },
+                },
+                (Some(Node::Word("string-code-unit-at", _)), Some(string), Some(index)) => {
+                    Expression {
+                        span: node_span,
+                        data: ExpressionData::StringCodeUnitAt {
+                            string: Box::new(parse_expression(string)),
+                            index: Box::new(parse_expression(index)),
+                        },
+                    }
+                }
+                (Some(Node::Word("number-to-string", _)), Some(expr), None) => Expression {
+                    span: node_span,
+                    data: ExpressionData::NumberToString {
+                        expr: Box::new(parse_expression(expr)),
+                    },
+                },
+                (Some(Node::Word("string-to-number", _)), Some(expr), None) => Expression {
+                    span: node_span,
+                    data: ExpressionData::StringToNumber {
+                        expr: Box::new(parse_expression(expr)),
+                    },
+                },
                 (Some(Node::Word("is-type-of", _)), Some(Node::Word(kind, _)), Some(expr)) => {
                     Expression {
                         span: node_span,
@@ -992,6 +1106,20 @@ fn parses_expression() {
     assert_eq!(expr!(r#"(69)"#), Expression::MakeInteger { value: 69 });
     assert_eq!(expr!(r#"((69))"#), Expression::MakeInteger { value: 69 });
 
+    assert_eq!(
+        expr!(r#"0.5"#),
+        Expression::MakeFloat {
+            value: OrderedFloat(0.5)
+        }
+    );
+
+    assert_eq!(
+        expr!(r#"(bigint "-18446744073709551616")"#),
+        Expression::MakeBigInt {
+            value: "-18446744073709551616".parse().unwrap()
+        }
+    );
+
     assert_eq!(expr!("true"), Expression::MakeBoolean { value: true });
     assert_eq!(expr!("false"), Expression::MakeBoolean { value: false });
 
@@ -1002,12 +1130,80 @@ fn parses_expression() {
     };
 
     assert_eq!(expr!("(:x + :y)"), binop(BinOpKind::Add));
+    assert_eq!(expr!("(:x - :y)"), binop(BinOpKind::Sub));
+    assert_eq!(expr!("(:x * :y)"), binop(BinOpKind::Mul));
+    assert_eq!(expr!("(:x / :y)"), binop(BinOpKind::Div));
+    assert_eq!(expr!("(:x % :y)"), binop(BinOpKind::Rem));
+    assert_eq!(expr!("(:x ** :y)"), binop(BinOpKind::Exp));
     assert_eq!(expr!("(:x and :y)"), binop(BinOpKind::And));
     assert_eq!(expr!("(:x or :y)"), binop(BinOpKind::Or));
+    assert_eq!(expr!("(:x bit-and :y)"), binop(BinOpKind::BitAnd));
+    assert_eq!(expr!("(:x bit-or :y)"), binop(BinOpKind::BitOr));
+    assert_eq!(expr!("(:x bit-xor :y)"), binop(BinOpKind::BitXor));
+    assert_eq!(expr!("(:x << :y)"), binop(BinOpKind::Shl));
+    assert_eq!(expr!("(:x >> :y)"), binop(BinOpKind::Shr));
+    assert_eq!(expr!("(:x >>> :y)"), binop(BinOpKind::UShr));
     assert_eq!(expr!("(:x == :y)"), binop(BinOpKind::Eq));
+    assert_eq!(expr!("(:x same-value :y)"), binop(BinOpKind::SameValue));
     assert_eq!(expr!("(:x < :y)"), binop(BinOpKind::Lt));
 
-    assert_eq!(expr!("(not :x)"), Expression::Negate { expr: x() });
+    assert_eq!(expr!("(not :x)"), Expression::Not { expr: x() });
+    assert_eq!(expr!("(negate :x)"), Expression::Negate { expr: x() });
+    assert_eq!(
+        expr!("(number-to-bigint :x)"),
+        Expression::NumberToBigInt { expr: x() }
+    );
+
+    assert_eq!(
+        expr!("(tuple-new :x :y 1)"),
+        Expression::TupleNew {
+            elements: vec![*x(), *y(), Expression::MakeInteger { value: 1 }]
+        }
+    );
+    assert_eq!(
+        expr!("(tuple-new)"),
+        Expression::TupleNew { elements: vec![] }
+    );
+    assert_eq!(
+        expr!("(tuple-get :x 1)"),
+        Expression::TupleGet {
+            tuple: x(),
+            index: 1
+        }
+    );
+    assert_eq!(expr!("(tuple-len :x)"), Expression::TupleLen { tuple: x() });
+
+    assert_eq!(
+        expr!("(string-concat :x :y)"),
+        Expression::StringConcat { lhs: x(), rhs: y() }
+    );
+    assert_eq!(
+        expr!("(string-len :x)"),
+        Expression::StringLen { string: x() }
+    );
+    assert_eq!(
+        expr!("(string-slice :x :y :z)"),
+        Expression::StringSlice {
+            string: x(),
+            start: y(),
+            end: z()
+        }
+    );
+    assert_eq!(
+        expr!("(string-code-unit-at :x :y)"),
+        Expression::StringCodeUnitAt {
+            string: x(),
+            index: y()
+        }
+    );
+    assert_eq!(
+        expr!("(number-to-string :x)"),
+        Expression::NumberToString { expr: x() }
+    );
+    assert_eq!(
+        expr!("(string-to-number :x)"),
+        Expression::StringToNumber { expr: x() }
+    );
 
     assert_eq!(
         expr!("(is-type-of String :x)"),
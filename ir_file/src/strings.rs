@@ -0,0 +1,37 @@
+//! String literals in an IR file are parsed as UTF-8. IR files that implement
+//! JavaScript semantics need their strings to be comparable with the strings
+//! of JavaScript programs, which are UTF-16, so the encoding of the constants
+//! generated for string literals is chosen by whoever generates the IR file.
+
+use crate::{Expression, ExpressionData, Visitor, AST};
+
+/// The encoding of the constants that string literals in an IR file become.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StringEncoding {
+    /// String literals are kept as the UTF-8 bytes they were parsed as.
+    Utf8,
+    /// String literals are re-encoded as UTF-16 code units in native byte
+    /// order, which is how JavaScript strings are represented.
+    Utf16,
+}
+
+pub fn encode_strings(ast: &mut AST, encoding: StringEncoding) {
+    if encoding == StringEncoding::Utf16 {
+        Utf16Visitor.visit_ast(ast);
+    }
+}
+
+struct Utf16Visitor;
+
+impl Visitor for Utf16Visitor {
+    fn visit_expr(&mut self, expr: &mut Expression) {
+        if let ExpressionData::MakeBytes { bytes } = &mut expr.data {
+            let string = std::str::from_utf8(bytes).expect("strings are parsed as UTF-8");
+            *bytes = (string.encode_utf16())
+                .flat_map(|x| x.to_ne_bytes())
+                .collect();
+        }
+
+        self.visit_expr_impl(expr);
+    }
+}
@@ -67,6 +67,9 @@ fn expr_is_global(expression: &Expression) -> bool {
             expr_is_global(list) || expr_is_global(property)
         }
         ExpressionData::ListLen { list } => expr_is_global(list),
+        ExpressionData::TupleNew { elements } => elements.iter().any(expr_is_global),
+        ExpressionData::TupleGet { tuple, index: _ } => expr_is_global(tuple),
+        ExpressionData::TupleLen { tuple } => expr_is_global(tuple),
         ExpressionData::CallStatic {
             function_name: _,
             args,
@@ -76,6 +79,18 @@ fn expr_is_global(expression: &Expression) -> bool {
         }
         ExpressionData::BinOp { kind: _, lhs, rhs } => expr_is_global(lhs) || expr_is_global(rhs),
         ExpressionData::Negate { expr } => expr_is_global(expr),
+        ExpressionData::Not { expr } => expr_is_global(expr),
+        ExpressionData::NumberToBigInt { expr } => expr_is_global(expr),
+        ExpressionData::StringConcat { lhs, rhs } => expr_is_global(lhs) || expr_is_global(rhs),
+        ExpressionData::StringLen { string } => expr_is_global(string),
+        ExpressionData::StringSlice { string, start, end } => {
+            expr_is_global(string) || expr_is_global(start) || expr_is_global(end)
+        }
+        ExpressionData::StringCodeUnitAt { string, index } => {
+            expr_is_global(string) || expr_is_global(index)
+        }
+        ExpressionData::NumberToString { expr } => expr_is_global(expr),
+        ExpressionData::StringToNumber { expr } => expr_is_global(expr),
         ExpressionData::IsTypeOf { expr, kind: _ } => expr_is_global(expr),
         ExpressionData::IsTypeAs { lhs, rhs } => expr_is_global(lhs) || expr_is_global(rhs),
         ExpressionData::GetFnPtr { function_name: _ }
@@ -85,6 +100,8 @@ fn expr_is_global(expression: &Expression) -> bool {
         | ExpressionData::MakeBytes { bytes: _ }
         | ExpressionData::MakeAtom { atom: _ }
         | ExpressionData::MakeInteger { value: _ }
+        | ExpressionData::MakeFloat { value: _ }
+        | ExpressionData::MakeBigInt { value: _ }
         | ExpressionData::MakeBoolean { value: _ }
         | ExpressionData::VarReference { variable: _ } => false,
     }
@@ -304,6 +321,17 @@ fn thread_expression(expression: &mut Expression) {
         ExpressionData::ListLen { list } => {
             thread_expression(list);
         }
+        ExpressionData::TupleNew { elements } => {
+            for element in elements.iter_mut() {
+                thread_expression(element);
+            }
+        }
+        ExpressionData::TupleGet { tuple, index: _ } => {
+            thread_expression(tuple);
+        }
+        ExpressionData::TupleLen { tuple } => {
+            thread_expression(tuple);
+        }
         ExpressionData::GetFnPtr { function_name: _ } => {}
         ExpressionData::CallStatic {
             function_name: _,
@@ -320,6 +348,8 @@ fn thread_expression(expression: &mut Expression) {
         ExpressionData::MakeAtom { atom: _ } => {}
         ExpressionData::MakeBytes { bytes: _ } => {}
         ExpressionData::MakeInteger { value: _ } => {}
+        ExpressionData::MakeFloat { value: _ } => {}
+        ExpressionData::MakeBigInt { value: _ } => {}
         ExpressionData::MakeBoolean { value: _ } => {}
         ExpressionData::BinOp { kind: _, lhs, rhs } => {
             thread_expression(lhs);
@@ -328,6 +358,34 @@ fn thread_expression(expression: &mut Expression) {
         ExpressionData::Negate { expr } => {
             thread_expression(expr);
         }
+        ExpressionData::Not { expr } => {
+            thread_expression(expr);
+        }
+        ExpressionData::NumberToBigInt { expr } => {
+            thread_expression(expr);
+        }
+        ExpressionData::StringConcat { lhs, rhs } => {
+            thread_expression(lhs);
+            thread_expression(rhs);
+        }
+        ExpressionData::StringLen { string } => {
+            thread_expression(string);
+        }
+        ExpressionData::StringSlice { string, start, end } => {
+            thread_expression(string);
+            thread_expression(start);
+            thread_expression(end);
+        }
+        ExpressionData::StringCodeUnitAt { string, index } => {
+            thread_expression(string);
+            thread_expression(index);
+        }
+        ExpressionData::NumberToString { expr } => {
+            thread_expression(expr);
+        }
+        ExpressionData::StringToNumber { expr } => {
+            thread_expression(expr);
+        }
         ExpressionData::IsTypeOf { expr, kind: _ } => {
             thread_expression(expr);
         }
@@ -5,6 +5,7 @@ use std::{
     path::Path,
 };
 
+use ir_file::StringEncoding;
 use walkdir::WalkDir;
 
 fn main() {
@@ -50,7 +51,9 @@ fn compile_irfiles() {
             println!("cargo:rerun-if-changed={}", entry.path().to_str().unwrap());
 
             let code = match kind {
-                GenKind::IrFile => ir_file::generate(&name, &src),
+                // IR files implement JavaScript semantics, so their strings must be
+                // UTF-16 like the strings of JavaScript programs
+                GenKind::IrFile => ir_file::generate(&name, &src, StringEncoding::Utf16),
                 GenKind::ParseNodes => grammar_notation_helper::generate(&src),
             };
 
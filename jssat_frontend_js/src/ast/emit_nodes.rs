@@ -4,7 +4,10 @@ use rustc_hash::FxHashMap;
 
 use crate::ecmascript::ECMA262Methods;
 use jssat_ir::{
-    frontend::builder::{DynBlockBuilder, FnSignature, ProgramBuilder, RegisterId},
+    frontend::{
+        builder::{DynBlockBuilder, FnSignature, ProgramBuilder, RegisterId},
+        source_map::JsSourceMap,
+    },
     isa::Atom,
     UnwrapNone,
 };
@@ -50,9 +53,13 @@ pub struct NodeEmitter<'scope> {
     simple_fns: FxHashMap<Atom, FnSignature<2>>,
     ecma_methods: &'scope ECMA262Methods,
     dealer: &'scope js::Dealer,
+    source_map: &'scope mut JsSourceMap,
+    /// The span of the parse node about to be visited, see [`Visitor::visit_span`]
+    next_span: Option<js::Span>,
     slots: [Atom; 3],
     identifier_name_data: Atom,
     string_literal_data: Atom,
+    numeric_literal_data: Atom,
 }
 
 impl<'s> NodeEmitter<'s> {
@@ -61,6 +68,7 @@ impl<'s> NodeEmitter<'s> {
         program: &'s mut ProgramBuilder,
         ecma_methods: &'s ECMA262Methods,
         dealer: &'s js::Dealer,
+        source_map: &'s mut JsSourceMap,
     ) -> Self {
         let simple_fns = Self::generate_simple_fns(
             ecma_methods.atoms.JSSATParseNodeSlot1,
@@ -82,9 +90,12 @@ impl<'s> NodeEmitter<'s> {
             simple_fns,
             ecma_methods,
             dealer,
+            source_map,
+            next_span: None,
             slots,
             identifier_name_data: ecma_methods.atoms.JSSATParseNode_Identifier_StringValue,
             string_literal_data: ecma_methods.atoms.JSSATParseNode_StringLiteral_StringValue,
+            numeric_literal_data: ecma_methods.atoms.JSSATParseNode_NumericLiteral_NumericValue,
         }
     }
 
@@ -140,6 +151,7 @@ pub struct ParseNode {
     kind: js::ParseNodeKind,
     variant_idx: usize,
     parse_node_slot: usize,
+    spanned: bool,
 }
 
 impl ParseNode {
@@ -156,11 +168,25 @@ impl ParseNode {
         let variant_atom = emitter.ecma_methods.atoms.JSSATParseNodeVariant;
         block.record_set_atom(parse_node, variant_atom, variant_kind);
 
+        // every instruction executed while evaluating this parse node can find
+        // where in the JavaScript source it is through this index
+        let source_map = &mut emitter.source_map;
+        let span = (emitter.next_span.take()).and_then(|span| source_map.span(span.lo, span.hi));
+        if let Some(span) = span {
+            source_map.sources.begin(span);
+            let idx = source_map.sources.sample(span);
+
+            let idx_i64: i64 = idx.0.try_into().unwrap();
+            let idx = block.make_number_decimal(idx_i64);
+            block.record_set_atom(parse_node, source_map.key, idx);
+        }
+
         Self {
             parse_node,
             kind,
             variant_idx,
             parse_node_slot: 0,
+            spanned: span.is_some(),
         }
     }
 
@@ -211,6 +237,10 @@ impl<'b> Visitor for NodeEmitter<'b> {
         let node = (self.stack.pop())
             .expect("post_visit should be called exactly same amount as pre_visit");
 
+        if node.spanned {
+            self.source_map.sources.end();
+        }
+
         let node = node.finish(self.block, &self.simple_fns, self.ecma_methods);
 
         if let Some(parent) = self.stack.last_mut() {
@@ -220,6 +250,10 @@ impl<'b> Visitor for NodeEmitter<'b> {
         self.last_completed = Some(node);
     }
 
+    fn visit_span(&mut self, span: Option<js::Span>) {
+        self.next_span = span;
+    }
+
     fn visit_identifier_name(&mut self, node: &js::IdentifierName) {
         let parse_node = self.stack.last_mut().expect("it");
 
@@ -244,6 +278,22 @@ impl<'b> Visitor for NodeEmitter<'b> {
             .record_set_atom(parse_node.parse_node, self.string_literal_data, string);
     }
 
+    fn visit_numeric_literal(&mut self, node: &js::NumericLiteral) {
+        let parse_node = self.stack.last_mut().expect("it");
+
+        let value = match node {
+            // integral literals are kept as integers so that they can be folded
+            js::NumericLiteral::Number(x) if x.fract() == 0.0 && x.abs() <= i64::MAX as f64 => {
+                self.block.make_number_decimal(*x as i64)
+            }
+            js::NumericLiteral::Number(x) => self.block.make_number_float(*x),
+            js::NumericLiteral::BigInt(x) => self.block.make_bigint(x.clone()),
+        };
+
+        self.block
+            .record_set_atom(parse_node.parse_node, self.numeric_literal_data, value);
+    }
+
     // rust doesn't have calling `super` so we have to sort of implement `visit_x`
     // instead of `visit_impl_x`
 
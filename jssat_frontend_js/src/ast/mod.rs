@@ -8,7 +8,10 @@
 
 use swc_ecmascript::parser::PResult;
 
-use jssat_ir::frontend::builder::{DynBlockBuilder, ProgramBuilder, RegisterId};
+use jssat_ir::frontend::{
+    builder::{DynBlockBuilder, ProgramBuilder, RegisterId},
+    source_map::JsSourceMap,
+};
 
 use self::parse_nodes::Dealer;
 
@@ -27,9 +30,11 @@ pub fn emit_nodes(
     block: &mut DynBlockBuilder,
     ecma_methods: &ECMA262Methods,
     dealer: &Dealer,
+    source_map: &mut JsSourceMap,
     visit_initial_node: impl FnOnce(&mut emit_nodes::NodeEmitter),
 ) -> RegisterId {
-    let mut node_emitter = emit_nodes::NodeEmitter::new(block, program, ecma_methods, dealer);
+    let mut node_emitter =
+        emit_nodes::NodeEmitter::new(block, program, ecma_methods, dealer, source_map);
     visit_initial_node(&mut node_emitter);
 
     let last_visited = node_emitter
@@ -4,7 +4,8 @@
 use crate::ast::parse_nodes::{ArgumentList, IdentifierName};
 
 use super::super::parse_nodes as js;
-use super::ToParseNode;
+use super::{spanned, ToParseNode};
+use swc_common::Spanned;
 use swc_ecmascript::ast as swc;
 
 impl ToParseNode<js::Expression> for swc::Expr {
@@ -27,15 +28,21 @@ fn parse(expr: swc::Expr) -> js::Expression {
         Seq(seq) => seq
             .exprs
             .into_iter()
-            .map(|e| parse_assignment(*e))
+            .map(|e| {
+                let span = e.span();
+                spanned(parse_assignment(*e), span)
+            })
             .fold(None, |a: Option<js::Expression>, b| {
                 Some(match a {
-                    None => js::Expression::Variant0(b.into()),
-                    Some(e) => js::Expression::Variant1(e.into(), b.into()),
+                    None => js::Expression::Variant0(b),
+                    Some(e) => js::Expression::Variant1(e.into(), b),
                 })
             })
             .unwrap(),
-        other => js::Expression::Variant0(parse_assignment(other).into()),
+        other => {
+            let span = other.span();
+            js::Expression::Variant0(spanned(parse_assignment(other), span))
+        }
     }
 }
 
@@ -171,7 +178,8 @@ fn parse_left_hand_side(expr: swc::Expr) -> js::LeftHandSideExpression {
     use swc::Expr::*;
 
     if let Call(call_expr) = expr {
-        return js::LeftHandSideExpression::Variant1(parse_call(call_expr).into());
+        let span = call_expr.span;
+        return js::LeftHandSideExpression::Variant1(spanned(parse_call(call_expr), span));
     }
 
     match expr {
@@ -216,14 +224,15 @@ fn parse_arguments(exprs: Vec<swc::ExprOrSpread>) -> js::Arguments {
     let arg_list = exprs
         .into_iter()
         .fold(None, |acc: Option<js::ArgumentList>, x| {
-            let expr = parse_assignment(*x.expr);
+            let span = x.expr.span();
+            let expr = spanned(parse_assignment(*x.expr), span);
             let spreads = x.spread.is_some();
 
             Some(match (acc, spreads) {
-                (None, false) => ArgumentList::Variant0(expr.into()),
-                (None, true) => ArgumentList::Variant1(expr.into()),
-                (Some(args), false) => ArgumentList::Variant2(args.into(), expr.into()),
-                (Some(args), true) => ArgumentList::Variant3(args.into(), expr.into()),
+                (None, false) => ArgumentList::Variant0(expr),
+                (None, true) => ArgumentList::Variant1(expr),
+                (Some(args), false) => ArgumentList::Variant2(args.into(), expr),
+                (Some(args), true) => ArgumentList::Variant3(args.into(), expr),
             })
         })
         .unwrap();
@@ -254,23 +263,25 @@ fn parse_primary(expr: swc::Expr) -> js::PrimaryExpression {
 
     match expr {
         Ident(ident) => {
+            let span = ident.span;
             let identifier = ident.to_parse_node();
             let ident_ref = js::IdentifierReference::Variant0(identifier.into());
 
-            js::PrimaryExpression::Variant1(ident_ref.into())
+            js::PrimaryExpression::Variant1(spanned(ident_ref, span))
         }
         Lit(lit) => {
+            let span = lit.span();
             let literal = match lit {
                 Str(x) => js::Literal::Variant3(js::StringLiteral(x.value.to_string()).into()),
                 Bool(_) => todo!(),
                 Null(_) => todo!(),
-                Num(_) => todo!(),
-                BigInt(_) => todo!(),
+                Num(x) => js::Literal::Variant2(js::NumericLiteral::Number(x.value).into()),
+                BigInt(x) => js::Literal::Variant2(js::NumericLiteral::BigInt(x.value).into()),
                 Regex(_) => todo!(),
                 JSXText(_) => todo!(),
             };
 
-            js::PrimaryExpression::Variant2(literal.into())
+            js::PrimaryExpression::Variant2(spanned(literal, span))
         }
         _ => todo!(),
     }
@@ -4,7 +4,7 @@ pub(crate) mod stmts;
 
 use std::convert::TryInto;
 
-use swc_common::{input::StringInput, BytePos};
+use swc_common::{input::StringInput, BytePos, Span};
 use swc_ecmascript::parser::{PResult, Parser};
 
 use super::parse_nodes as js;
@@ -29,6 +29,16 @@ fn to_swc_script(source: &str) -> PResult<swc::Script> {
     parser.parse_script()
 }
 
+/// Wraps a parse node with the span of the SWC node it was parsed from.
+fn spanned<T>(node: T, span: Span) -> js::Spanned<T> {
+    let span = js::Span {
+        lo: span.lo.0 as usize,
+        hi: span.hi.0 as usize,
+    };
+
+    js::Spanned::new(node, span)
+}
+
 trait ToParseNode<P> {
     fn to_parse_node(self) -> P;
 }
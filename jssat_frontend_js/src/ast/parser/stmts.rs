@@ -1,5 +1,6 @@
 use super::super::parse_nodes as js;
-use super::ToParseNode;
+use super::{spanned, ToParseNode};
+use swc_common::Spanned;
 use swc_ecmascript::ast as swc;
 
 impl ToParseNode<js::StatementList> for Vec<swc::Stmt> {
@@ -42,7 +43,8 @@ impl ToParseNode<js::StatementListItem> for swc::Stmt {
         use js::Statement::*;
         use swc::Stmt::*;
 
-        let s = |x: js::Statement| js::StatementListItem::Variant0(x.into());
+        let span = self.span();
+        let s = |x: js::Statement| js::StatementListItem::Variant0(spanned(x, span));
         let i = |x: js::IterationStatement| {
             s(Variant5(js::BreakableStatement::Variant0(x.into()).into()))
         };
@@ -71,7 +73,7 @@ impl ToParseNode<js::StatementListItem> for swc::Stmt {
             ForIn(_) | ForOf(_) => todo!(),
             // ForIn(x) => s(Variant15(x.to_parse_node().into())),
             // ForOf(x) => s(Variant16(x.to_parse_node().into())),
-            Decl(x) => js::StatementListItem::Variant1(x.to_parse_node().into()),
+            Decl(x) => js::StatementListItem::Variant1(spanned(x.to_parse_node(), span)),
             Expr(x) => s(Variant3(x.to_parse_node().into())),
         }
     }
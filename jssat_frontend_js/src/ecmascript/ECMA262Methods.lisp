@@ -37,7 +37,6 @@
 (def (both :1 :2 :f) (and (:f :1) (:f :2)))
 (def (either :a :b (:x :y)) (or (:a :x :y) (:b :x :y)))
 (def (todo) (assert false "TODO"))
-(def (:a - :b) (:a + (not :b)))
 (def (throw :x) (return (ThrowCompletion :x)))
 (def (ret-comp :x) (return (NormalCompletion :x)))
 
@@ -220,6 +219,8 @@
 (def (is-symbol :x) (false))
 (def (is-number :x) (is-type-of Number :x))
 (def (is-bigint :x) (is-type-of BigInt :x))
+; 7.2.6 IsIntegralNumber, as NaN and the infinities leave a remainder of NaN
+(def (is-integral-number :x) (lazyAnd (is-number :x) ((:x % 1) == 0)))
 (def (is-bool :x) (is-type-of Boolean :x))
 (def (is-record :x) (is-type-of Record :x))
 (def (isnt-record :x) (not (is-record :x)))
@@ -394,6 +395,7 @@
 ; same for TypeError (:env can be gotten via curr-exec-ctx)
 (def (TypeError :msg) (:msg))
 (def (ReferenceError :msg) (:msg))
+(def (RangeError :msg) (:msg))
 
 ; "Let <thing> be the sole element of <list>"
 (def
@@ -583,9 +585,17 @@
 (def (is-fn-obj :x) (record-has-slot :x Call))
 
 (section
-  (:6.1.6.1.14 Number::sameValue (x, y))
+  (:6.1.6.1.7 Number::add (x, y))
+  ((return (:x + :y))))
+
+(section
+  (:6.1.6.1.13 Number::equal (x, y))
   ((return (:x == :y))))
 
+(section
+  (:6.1.6.1.14 Number::sameValue (x, y))
+  ((return (:x same-value :y))))
+
 (section
   (:6.1.6.2.14 BigInt::sameValue (x, y))
   ((return (:x == :y))))
@@ -769,20 +779,45 @@
    (if (is-bool :argument)
        ((return :argument)))
    (if (is-number :argument)
-       ((return ((:argument == 0) or (:argument == (not 0))))))
+       ((return ((:argument == 0) or (:argument == (negate 0))))))
    (if (:argument == "")
        ((return false))
        ((return true)))
    (if (is-symbol :argument)
        ((return true)))
    (if (is-bigint :argument)
-       ((if (:argument == 0)
+       ((if (:argument == (bigint "0"))
             ((return false))
             ((return true)))))
    (if (is-object :argument)
        ((return true)))
    (return unreachable)))
 
+(section
+  (:7.1.13 ToBigInt (argument))
+  (;;; 1. Let prim be ? ToPrimitive(argument, number).
+   ; TODO: implement ToPrimitive, objects are not converted yet
+   (prim = :argument)
+   ;;; 2. Return the value that prim corresponds to in Table 13.
+   (if (is-undef :prim)
+       ((throw (TypeError "cannot convert undefined to a BigInt"))))
+   (if (is-null :prim)
+       ((throw (TypeError "cannot convert null to a BigInt"))))
+   (if (is-bool :prim)
+       ((if (is-true :prim)
+            ((return (bigint "1")))
+            ((return (bigint "0"))))))
+   (if (is-bigint :prim)
+       ((return :prim)))
+   (if (is-number :prim)
+       ((throw (TypeError "cannot convert a Number to a BigInt"))))
+   (if (is-string :prim)
+       (; TODO: implement StringToBigInt
+        (todo)))
+   (if (is-symbol :prim)
+       ((throw (TypeError "cannot convert a Symbol to a BigInt"))))
+   (return unreachable)))
+
 (section
   (:7.1.17 ToString (argument))
   ((if (is-undef :argument)
@@ -794,7 +829,9 @@
    (if (lazyAnd (is-bool :argument) (is-false :argument))
        ((return "false")))
    (if (is-number :argument)
-       ((return "0.00TODO: 7.1.17 ToString (number)00")))
+       ((return (number-to-string :argument))))
+   (if (is-string :argument)
+       ((return :argument)))
    (if (is-symbol :argument)
        ((throw (TypeError "cant do symbol to string"))))
    (if (is-bigint :argument)
@@ -833,6 +870,19 @@
        ((return :argument)))
    (return unreachable)))
 
+(section
+  (:7.1.19 ToPropertyKey (argument))
+  (;;; 1. Let key be ? ToPrimitive(argument, string).
+   ; TODO: implement ToPrimitive, objects are not converted yet
+   (key = :argument)
+   ;;; 2. If Type(key) is Symbol, then
+   (if (is-symbol :key)
+       (;;; a. Return key.
+        (return :key)))
+   ;;; 3. Return ! ToString(key).
+   (return (! (call ToString :key)))))
+
+
 (section
   (:7.2.3 IsCallable (argument))
   (;;; 1. If Type(argument) is not Object, return false.
@@ -1797,6 +1847,8 @@
    (:intrinsics "%Object.prototype%" <== (! (call OrdinaryObjectCreate null list-new)))
    (:intrinsics "%GeneratorFunction.prototype.prototype.next%" <==
                 (! (call CreateBuiltinFunction (get-fn-ptr GeneratorPrototype_next) 1 "next" list-new :realmRec null undefined)))
+   (:intrinsics "%BigInt%" <==
+                (! (call CreateBuiltinFunction (get-fn-ptr BigIntConstructor) 1 "BigInt" list-new :realmRec null undefined)))
    ;;; 4. Perform AddRestrictedFunctionProperties(intrinsics.[[%Function.prototype%]], realmRec).
    ;;; 5. Return intrinsics.
    (return :intrinsics)))
@@ -1837,6 +1889,13 @@
    ;;; a. Let name be the String value of the property name.
    ;;; b. Let desc be the fully populated data Property Descriptor for the property, containing the specified attributes for the property. For properties listed in 19.2, 19.3, or 19.4 the value of the [[Value]] attribute is the corresponding intrinsic object from realmRec.
    ;;; c. Perform ? DefinePropertyOrThrow(global, name, desc).
+   ; TODO: only the properties that have been implemented are defined
+   (desc = record-new)
+   (:desc Value <- ((:realmRec -> Intrinsics) => "%BigInt%"))
+   (:desc Writable <- true)
+   (:desc Enumerable <- false)
+   (:desc Configurable <- true)
+   (_dontCare = (? (call DefinePropertyOrThrow :global "BigInt" :desc)))
    ;;; 3. Return global.
    (return :global)))
 
@@ -2989,7 +3048,7 @@
    ; Literal : NumericLiteral
    (if (is-pn Literal 2)
        (;;; 1. Return the NumericValue of NumericLiteral as defined in 12.8.3.
-        (todo)))
+        (return (:parseNode -> JSSATParseNode_NumericLiteral_NumericValue))))
    ; Literal : StringLiteral
    (if (is-pn Literal 3)
        (;;; 1. Return the SV of StringLiteral as defined in 12.8.4.2.
@@ -3597,6 +3656,28 @@
    ;;; 18. Return NormalCompletion(empty).
    (return (NormalCompletion empty))))
 
+(section
+  (:21.2.1.1 BigIntConstructor (self, this, argumentsList))
+  ((value = (list-try-get-else :argumentsList 0 undefined))
+   ;;; 1. If NewTarget is not undefined, throw a TypeError exception.
+   ; built-in functions are only ever invoked through [[Call]], so NewTarget is always undefined
+   ;;; 2. Let prim be ? ToPrimitive(value, number).
+   ; TODO: implement ToPrimitive, objects are not converted yet
+   (prim = :value)
+   ;;; 3. If Type(prim) is Number, return ? NumberToBigInt(prim).
+   (if (is-number :prim)
+       ((return (? (call NumberToBigInt :prim)))))
+   ;;; 4. Otherwise, return ? ToBigInt(prim).
+   (return (? (call ToBigInt :prim)))))
+
+(section
+  (:21.2.1.1.1 NumberToBigInt (number))
+  (;;; 1. If IsIntegralNumber(number) is false, throw a RangeError exception.
+   (if (not (is-integral-number :number))
+       ((throw (RangeError "number is not an integral number"))))
+   ;;; 2. Return the BigInt value that represents ℝ(number).
+   (return (number-to-bigint :number))))
+
 (section
   (:27.5.3.1 GeneratorStart (generator, generatorBody))
   (;;; 1. Assert: The value of generator.[[GeneratorState]] is undefined.
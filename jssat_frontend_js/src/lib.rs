@@ -9,7 +9,10 @@ use jssat_ir::isa::Atom;
 
 use self::{ast::parse_nodes::Visitor, ecmascript::ECMA262Methods};
 
-use jssat_ir::frontend::builder::{DynBlockBuilder, ProgramBuilder, RegisterId};
+use jssat_ir::frontend::{
+    builder::{DynBlockBuilder, ProgramBuilder, RegisterId},
+    source_map::JsSourceMap,
+};
 
 pub mod ast;
 pub mod ecmascript;
@@ -18,6 +21,9 @@ pub mod hosts;
 pub struct JavaScriptFrontend<'program> {
     program: &'program mut ProgramBuilder,
     pub ecma_methods: ECMA262Methods,
+    /// Maps the parse nodes of the JavaScript most recently passed to
+    /// [`JavaScriptFrontend::parse`] back to their source text.
+    pub js_source_map: JsSourceMap,
 }
 
 impl<'p> JavaScriptFrontend<'p> {
@@ -25,10 +31,15 @@ impl<'p> JavaScriptFrontend<'p> {
     /// within the [`ProgramBuilder`] specified.
     pub fn new(program: &'p mut ProgramBuilder) -> Self {
         let ecma_methods = ECMA262Methods::new(program);
+        let js_source_map = JsSourceMap::new(
+            String::new(),
+            program.dealer.deal("JSSATParseNodeSourceMapIdx"),
+        );
 
         Self {
             program,
             ecma_methods,
+            js_source_map,
         }
     }
 
@@ -52,13 +63,18 @@ impl<'p> JavaScriptFrontend<'p> {
         host_environment: &mut impl hosts::HostEnvironment,
     ) -> PResult<RegisterId> {
         let script = ast::parse_script(source_text)?;
+        self.js_source_map = JsSourceMap::new(source_text.to_owned(), self.js_source_map.key);
 
         let dealer = Dealer::new(self.program);
 
-        let entry_parse_node =
-            ast::emit_nodes(self.program, block, &self.ecma_methods, &dealer, |v| {
-                v.visit_script(&script)
-            });
+        let entry_parse_node = ast::emit_nodes(
+            self.program,
+            block,
+            &self.ecma_methods,
+            &dealer,
+            &mut self.js_source_map,
+            |v| v.visit_script(&script),
+        );
 
         let threaded_global = block.record_new();
         block.call(
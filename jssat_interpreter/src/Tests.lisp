@@ -13,3 +13,63 @@
 (section
   (:0 GetList (list, idx))
   ((return (list-get :list :idx))))
+
+(section
+  (:0 AddHalf (x))
+  ((return (:x + 0.5))))
+
+(section
+  (:0 SameValue (x, y))
+  ((return (:x same-value :y))))
+
+(section
+  (:0 Halve (x))
+  ((return (:x / 2))))
+
+(section
+  (:0 ToUint32 (x))
+  ((return (:x >>> 0))))
+
+(section
+  (:0 BigIntSquare (x))
+  ((return ((number-to-bigint :x) ** (bigint "2")))))
+
+(section
+  (:0 StringRoundtrip (x))
+  ((return (string-to-number (number-to-string :x)))))
+
+(section
+  (:0 Greet (name))
+  ((greeting = (string-concat "hello, " :name))
+   (return (string-slice :greeting 0 ((string-len :greeting) - 1)))))
+
+(section
+  (:0 CodeUnitAt (string, idx))
+  ((return (string-code-unit-at :string :idx))))
+
+(section
+  (:0 Swap (x, y))
+  ((pair = (tuple-new :x :y))
+   (return (tuple-new (tuple-get :pair 1) (tuple-get :pair 0)))))
+
+(section
+  (:0 TupleLen (tuple))
+  ((return (tuple-len :tuple))))
+
+(section
+  (:0 CountTo (n))
+  ((loop
+    ((i = 0))
+    (:i < :n)
+    ((i = (:i + 1)))
+    ((if (:i == 3)
+         ((_ = (:i + 1))))))
+   (return :n)))
+
+(def (when :cond :then) (if :cond :then))
+
+(section
+  (:0 IsNegative (x))
+  ((when (:x < 0)
+     ((_ = (:x + 1))))
+   (return (:x < 0))))
@@ -1,17 +1,35 @@
+use std::time::Instant;
+
+use domino::moment::MomentApi;
 use rustc_hash::FxHashMap;
+use thiserror::Error;
 
 use jssat_ir::{
     id::Counter,
+    isa::Atom,
     lifted::{ExternalFunctionId, LiftedProgram},
     UnwrapNone,
 };
 
-use super::{ExtFnImpl, InstResult, Interpreter, Value};
+use super::{
+    ExtFnImpl, InstResult, Interpreter, Limits, MomentCollector, Value, DEFAULT_MAX_DEPTH,
+};
+
+/// Why an [`InterpreterBuilder`] could not be configured.
+#[derive(Debug, Error)]
+pub enum BuildError {
+    /// The program does not declare an external function of the name.
+    #[error("external function `{0}` is not declared")]
+    UndeclaredExtFn(String),
+}
 
 pub struct InterpreterBuilder<'program> {
     program: &'program LiftedProgram,
     ext_fns: FxHashMap<ExternalFunctionId, ExtFnImpl>,
     counter: Counter<ExternalFunctionId>,
+    max_depth: usize,
+    limits: Limits,
+    parse_node_key: Option<Atom>,
 }
 
 impl<'p> InterpreterBuilder<'p> {
@@ -20,6 +38,9 @@ impl<'p> InterpreterBuilder<'p> {
             program,
             ext_fns: Default::default(),
             counter: Default::default(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            limits: Limits::default(),
+            parse_node_key: None,
         }
     }
 
@@ -47,7 +68,72 @@ impl<'p> InterpreterBuilder<'p> {
         self
     }
 
+    /// Implements the external function that the program declares as `name`.
+    /// Arguments and results are checked against the types the external
+    /// function is declared with when it is called.
+    pub fn with_declared_ext_fn(
+        &mut self,
+        name: &str,
+        function: fn(Vec<Value>) -> InstResult<Option<Value>>,
+    ) -> Result<&mut Self, BuildError> {
+        let ext_fn_id = (self.program.external_functions.iter())
+            .find(|(_, ext_fn)| ext_fn.name == name)
+            .map(|(id, _)| *id)
+            .ok_or_else(|| BuildError::UndeclaredExtFn(name.to_owned()))?;
+
+        let ext_fn_impl = ExtFnImpl { function };
+        self.ext_fns.insert(ext_fn_id, ext_fn_impl).expect_free();
+
+        Ok(self)
+    }
+
+    /// Sets the maximum amount of calls that may be executed at once, after
+    /// which executing a call results in a stack overflow.
+    pub fn with_max_depth(&mut self, max_depth: usize) -> &mut Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets the maximum amount of instructions that may be executed.
+    pub fn with_fuel(&mut self, fuel: u64) -> &mut Self {
+        self.limits.fuel = Some(fuel);
+        self
+    }
+
+    /// Sets the point in time after which execution stops.
+    pub fn with_deadline(&mut self, deadline: Instant) -> &mut Self {
+        self.limits.deadline = Some(deadline);
+        self
+    }
+
+    /// Sets the maximum amount of records, lists and tuples that may be alive
+    /// at once.
+    pub fn with_max_allocations(&mut self, allocations: usize) -> &mut Self {
+        self.limits.allocations = Some(allocations);
+        self
+    }
+
+    /// Sets the record key that the parse nodes of the JavaScript frontend
+    /// store their source map index under, so that the interpreter records the
+    /// parse node each frame evaluates. This is the key of the [`JsSourceMap`]
+    /// the frontend emits.
+    ///
+    /// [`JsSourceMap`]: jssat_ir::frontend::source_map::JsSourceMap
+    pub fn with_parse_node_key(&mut self, key: Atom) -> &mut Self {
+        self.parse_node_key = Some(key);
+        self
+    }
+
     pub fn build(&self) -> Interpreter {
-        Interpreter::new(self.program, &self.ext_fns)
+        self.build_with_collector(MomentApi::new(self.program))
+    }
+
+    pub fn build_with_collector<C: MomentCollector>(&self, collector: C) -> Interpreter<C> {
+        let mut interpreter =
+            Interpreter::new_with_collector(self.program, &self.ext_fns, collector);
+        interpreter.max_depth = self.max_depth;
+        interpreter.limits = self.limits;
+        interpreter.parse_node_key = self.parse_node_key;
+        interpreter
     }
 }
@@ -0,0 +1,195 @@
+use domino::moment::MomentApi;
+use rustc_hash::FxHashSet;
+
+use jssat_ir::{
+    frontend::source_map::SourceMapIdx,
+    lifted::{FunctionId, LiftedProgram},
+    value_snapshot::ValueSnapshotArena,
+};
+
+/// Collects moments while the interpreter executes, for Domino the Debugger.
+pub trait MomentCollector {
+    /// Called when a lifted function is called. `js_source` is the JavaScript
+    /// parse node that the function evaluates, if any.
+    fn enter(&mut self, function: FunctionId, js_source: Option<SourceMapIdx>);
+    fn exit(&mut self);
+
+    /// Called when a lifted function jumps to another one. By default, this
+    /// exits the lifted function jumped from before entering the one jumped
+    /// to.
+    fn jump(&mut self, function: FunctionId, js_source: Option<SourceMapIdx>) {
+        self.exit();
+        self.enter(function, js_source);
+    }
+
+    /// Called when a record, list or tuple is allocated.
+    fn allocate(&mut self) {}
+
+    /// Called before every instruction, including the end instructions of
+    /// lifted functions. Returns whether the instruction should be recorded,
+    /// in which case the values the instruction uses are snapshotted before
+    /// and after it executes.
+    fn record_instruction(&mut self) -> bool;
+    fn snapshot(
+        &mut self,
+        inst_idx: usize,
+        source_map_idx: Option<SourceMapIdx>,
+        values: ValueSnapshotArena,
+    );
+}
+
+/// Records every instruction.
+impl MomentCollector for MomentApi {
+    fn enter(&mut self, function: FunctionId, js_source: Option<SourceMapIdx>) {
+        MomentApi::enter(self, function, js_source);
+    }
+
+    fn exit(&mut self) {
+        MomentApi::exit(self);
+    }
+
+    fn record_instruction(&mut self) -> bool {
+        true
+    }
+
+    fn snapshot(
+        &mut self,
+        inst_idx: usize,
+        source_map_idx: Option<SourceMapIdx>,
+        values: ValueSnapshotArena,
+    ) {
+        MomentApi::snapshot(self, inst_idx, source_map_idx, values);
+    }
+}
+
+/// Collector that implements [`MomentCollector`] but does not collect any
+/// moments. This is useful when wanting to run the interpreter at maximum
+/// speed, as no values are snapshotted.
+pub struct NilCollector;
+
+impl MomentCollector for NilCollector {
+    fn enter(&mut self, _function: FunctionId, _js_source: Option<SourceMapIdx>) {}
+    fn exit(&mut self) {}
+
+    fn record_instruction(&mut self) -> bool {
+        false
+    }
+
+    fn snapshot(&mut self, _: usize, _: Option<SourceMapIdx>, _: ValueSnapshotArena) {}
+}
+
+/// Only records moments while one of the selected sections is executing,
+/// including the functions it calls.
+pub struct SectionCollector {
+    pub moment: MomentApi,
+    sections: FxHashSet<FunctionId>,
+    depth: usize,
+    /// The depth of the section that is being recorded, if any.
+    recording: Option<usize>,
+}
+
+impl SectionCollector {
+    /// Creates a collector that records the functions with any of the given
+    /// names, which for IR files are the names of the sections.
+    pub fn new(code: &LiftedProgram, names: &[&str]) -> Self {
+        let sections = (code.functions.iter())
+            .filter(|(_, f)| matches!(f.name.as_deref(), Some(name) if names.contains(&name)))
+            .map(|(id, _)| *id)
+            .collect();
+
+        Self {
+            moment: MomentApi::new(code),
+            sections,
+            depth: 0,
+            recording: None,
+        }
+    }
+}
+
+impl MomentCollector for SectionCollector {
+    fn enter(&mut self, function: FunctionId, js_source: Option<SourceMapIdx>) {
+        self.depth += 1;
+
+        if self.recording.is_none() && self.sections.contains(&function) {
+            self.recording = Some(self.depth);
+        }
+
+        if self.recording.is_some() {
+            self.moment.enter(function, js_source);
+        }
+    }
+
+    fn exit(&mut self) {
+        if self.recording.is_some() {
+            self.moment.exit();
+        }
+
+        if self.recording == Some(self.depth) {
+            self.recording = None;
+        }
+
+        self.depth -= 1;
+    }
+
+    fn record_instruction(&mut self) -> bool {
+        self.recording.is_some()
+    }
+
+    fn snapshot(
+        &mut self,
+        inst_idx: usize,
+        source_map_idx: Option<SourceMapIdx>,
+        values: ValueSnapshotArena,
+    ) {
+        self.moment.snapshot(inst_idx, source_map_idx, values);
+    }
+}
+
+/// Only records every `n`th instruction.
+pub struct SampledCollector {
+    pub moment: MomentApi,
+    n: usize,
+    skipped: usize,
+}
+
+impl SampledCollector {
+    pub fn new(code: &LiftedProgram, n: usize) -> Self {
+        assert!(n > 0, "must record at least every instruction");
+
+        Self {
+            moment: MomentApi::new(code),
+            n,
+            skipped: 0,
+        }
+    }
+}
+
+impl MomentCollector for SampledCollector {
+    fn enter(&mut self, function: FunctionId, js_source: Option<SourceMapIdx>) {
+        self.moment.enter(function, js_source);
+    }
+
+    fn exit(&mut self) {
+        self.moment.exit();
+    }
+
+    fn record_instruction(&mut self) -> bool {
+        self.skipped += 1;
+
+        if self.skipped == self.n {
+            self.skipped = 0;
+            return true;
+        }
+
+        false
+    }
+
+    fn snapshot(
+        &mut self,
+        inst_idx: usize,
+        source_map_idx: Option<SourceMapIdx>,
+        values: ValueSnapshotArena,
+    ) {
+        self.moment.snapshot(inst_idx, source_map_idx, values);
+    }
+}
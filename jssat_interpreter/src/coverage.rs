@@ -0,0 +1,344 @@
+//! A [`MomentCollector`] that records which parts of the IR files a program
+//! was built from are executed, to find code that is never tested.
+//!
+//! Instructions emitted for IR files are connected to the span of the LISP
+//! they were emitted for through their [`SourceMapIdx`]. Once execution is
+//! done, the amount of times every span was executed is resolved against the
+//! LISP source into a [`Coverage`] of its sections, lines, statements and
+//! branches, which can be written in the lcov format.
+//!
+//! The source is parsed by [`ir_file`], so statements produced by rules are
+//! covered too. They are spanned by the rule that produced them, so the code of
+//! every use of a rule is counted together.
+
+use std::collections::BTreeMap;
+
+use ir_file::{Section, Statement, StatementData};
+use lexpr::datum::Span;
+use rustc_hash::FxHashMap;
+
+use jssat_ir::{
+    frontend::source_map::{SourceMapIdx, SourceMapImpl, SourcePos, SourceSpan},
+    lifted::{Function, FunctionId, LiftedProgram},
+    value_snapshot::ValueSnapshotArena,
+};
+
+use super::MomentCollector;
+
+pub struct CoverageCollector<'code> {
+    code: &'code LiftedProgram,
+    /// The lifted functions being executed, along with the index of the
+    /// instruction they execute next. Lifted functions don't branch, so every
+    /// instruction is executed in order.
+    stack: Vec<(&'code Function, usize)>,
+    /// How often the instruction connected to every [`SourceMapIdx`] was
+    /// executed.
+    pub hits: FxHashMap<usize, u64>,
+}
+
+impl<'code> CoverageCollector<'code> {
+    pub fn new(code: &'code LiftedProgram) -> Self {
+        Self {
+            code,
+            stack: Vec::new(),
+            hits: Default::default(),
+        }
+    }
+
+    /// Resolves the spans executed against the source map of the IR files.
+    pub fn coverage(&self, source_map: &SourceMapImpl) -> Coverage {
+        // every sample of the source map is connected to an instruction
+        let mut samples = (source_map.pyramid.snapshots.iter().enumerate())
+            .filter_map(|(idx, sample)| {
+                let span = sample.0.as_ref()?.info;
+                let hits = self.hits.get(&idx).copied().unwrap_or(0);
+                Some((pos(span.start), hits))
+            })
+            .collect::<Vec<_>>();
+        samples.sort_unstable();
+
+        let hits = Hits(&samples);
+
+        let mut lines = BTreeMap::new();
+        for ((line, _), hits) in samples.iter() {
+            let line_hits = lines.entry(*line).or_insert(0);
+            *line_hits = (*line_hits).max(*hits);
+        }
+
+        let sections = (ir_file::parse(&source_map.source).sections.iter())
+            .map(|section| SectionCoverage::new(section, &hits))
+            .collect();
+
+        Coverage { sections, lines }
+    }
+}
+
+impl MomentCollector for CoverageCollector<'_> {
+    fn enter(&mut self, function: FunctionId, _: Option<SourceMapIdx>) {
+        self.stack.push((&self.code.functions[&function], 0));
+    }
+
+    fn exit(&mut self) {
+        self.stack.pop();
+    }
+
+    fn jump(&mut self, function: FunctionId, _: Option<SourceMapIdx>) {
+        let top = self.stack.last_mut().expect("must be in function");
+        *top = (&self.code.functions[&function], 0);
+    }
+
+    fn record_instruction(&mut self) -> bool {
+        let (function, inst_idx) = self.stack.last_mut().expect("must be in function");
+
+        let inst = function.instructions.get(*inst_idx);
+        if let Some(SourceMapIdx(idx)) = inst.and_then(|inst| inst.source_map_idx) {
+            *self.hits.entry(idx).or_insert(0) += 1;
+        }
+
+        *inst_idx += 1;
+        false
+    }
+
+    fn snapshot(&mut self, _: usize, _: Option<SourceMapIdx>, _: ValueSnapshotArena) {}
+}
+
+/// The coverage of an IR file.
+#[derive(Debug)]
+pub struct Coverage {
+    pub sections: Vec<SectionCoverage>,
+    /// How often each line was executed, for every line with code emitted for
+    /// it.
+    pub lines: BTreeMap<usize, u64>,
+}
+
+#[derive(Debug)]
+pub struct SectionCoverage {
+    /// The header of the section, e.g. `7.3.2 Name ( params )`.
+    pub name: String,
+    pub span: SourceSpan,
+    /// How often the section was called.
+    pub hits: u64,
+    pub statements: Vec<StatementCoverage>,
+    pub branches: Vec<BranchCoverage>,
+}
+
+#[derive(Debug)]
+pub struct StatementCoverage {
+    pub span: SourceSpan,
+    pub hits: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BranchKind {
+    /// An `if`, which has the arms `then` and `else`. The `else` arm of an
+    /// `if` without one is taken whenever the `then` arm isn't.
+    If,
+    /// A `loop`, which has the arms `body` and `exit`.
+    Loop,
+}
+
+#[derive(Debug)]
+pub struct BranchCoverage {
+    pub kind: BranchKind,
+    pub span: SourceSpan,
+    /// How often the branch was reached.
+    pub hits: u64,
+    /// How often each arm was taken.
+    pub arms: [u64; 2],
+}
+
+impl SectionCoverage {
+    fn new(section: &Section, hits: &Hits) -> Self {
+        let header = &section.header;
+        let name = format!(
+            "{} {} ( {} )",
+            header.document_index,
+            header.method_name,
+            header.parameters.join(", ")
+        );
+
+        let mut section_coverage = Self {
+            name,
+            span: source_span(section.span),
+            hits: hits.count(header.span),
+            statements: Vec::new(),
+            branches: Vec::new(),
+        };
+
+        section_coverage.statements_of(&section.body, hits);
+        section_coverage
+    }
+
+    fn statements_of(&mut self, statements: &[Statement], hits: &Hits) {
+        for statement in statements.iter() {
+            self.statement(statement, hits);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement, hits: &Hits) {
+        // statements like `(return :x)` don't emit instructions of their own
+        if let Some(statement_hits) = hits.within(statement.span) {
+            self.statements.push(StatementCoverage {
+                span: source_span(statement.span),
+                hits: statement_hits,
+            });
+        }
+
+        match &statement.data {
+            StatementData::If {
+                condition,
+                then,
+                r#else,
+            } => {
+                let reached = condition.span.map_or(0, |span| hits.count(span));
+                let taken = hits.count_all(then);
+
+                let not_taken = match r#else {
+                    Some(r#else) => hits.count_all(r#else),
+                    None => reached.saturating_sub(taken),
+                };
+
+                self.branches.push(BranchCoverage {
+                    kind: BranchKind::If,
+                    span: source_span(statement.span),
+                    hits: reached,
+                    arms: [taken, not_taken],
+                });
+
+                self.statements_of(then, hits);
+                if let Some(r#else) = r#else {
+                    self.statements_of(r#else, hits);
+                }
+            }
+            StatementData::Loop {
+                init, cond, body, ..
+            } => {
+                let entered = (init.iter())
+                    .filter_map(|assign| assign.value.span)
+                    .map(|span| hits.count(span))
+                    .max()
+                    .unwrap_or(0);
+
+                self.branches.push(BranchCoverage {
+                    kind: BranchKind::Loop,
+                    span: source_span(statement.span),
+                    hits: cond.span.map_or(0, |span| hits.count(span)),
+                    arms: [hits.count_all(body), entered],
+                });
+
+                self.statements_of(body, hits);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Coverage {
+    /// Writes the coverage in the lcov tracefile format, with sections as
+    /// functions. `path` is the path of the IR file.
+    pub fn lcov(&self, path: &str) -> String {
+        let mut lcov = String::new();
+        let mut w = |line: String| {
+            lcov.push_str(&line);
+            lcov.push('\n');
+        };
+
+        w("TN:".to_string());
+        w(format!("SF:{}", path));
+
+        for section in self.sections.iter() {
+            w(format!("FN:{},{}", section.span.start.line, section.name));
+        }
+
+        for section in self.sections.iter() {
+            w(format!("FNDA:{},{}", section.hits, section.name));
+        }
+
+        let hit = self.sections.iter().filter(|s| s.hits > 0).count();
+        w(format!("FNF:{}", self.sections.len()));
+        w(format!("FNH:{}", hit));
+
+        let branches = self.sections.iter().flat_map(|s| s.branches.iter());
+        let (mut found, mut hit) = (0, 0);
+
+        for (block, branch) in branches.enumerate() {
+            for (arm, taken) in branch.arms.iter().enumerate() {
+                found += 1;
+
+                // lcov uses `-` for branches that were never reached
+                let taken = match (branch.hits, taken) {
+                    (0, _) => "-".to_string(),
+                    (_, taken) => taken.to_string(),
+                };
+
+                if !matches!(taken.as_str(), "-" | "0") {
+                    hit += 1;
+                }
+
+                let line = branch.span.start.line;
+                w(format!("BRDA:{},{},{},{}", line, block, arm, taken));
+            }
+        }
+
+        w(format!("BRF:{}", found));
+        w(format!("BRH:{}", hit));
+
+        for (line, hits) in self.lines.iter() {
+            w(format!("DA:{},{}", line, hits));
+        }
+
+        let hit = self.lines.values().filter(|hits| **hits > 0).count();
+        w(format!("LF:{}", self.lines.len()));
+        w(format!("LH:{}", hit));
+
+        w("end_of_record".to_string());
+        lcov
+    }
+}
+
+fn pos(pos: SourcePos) -> (usize, usize) {
+    (pos.line, pos.column)
+}
+
+/// Spans of the LISP source are positioned the same way as the spans of the
+/// source map, as the source map is built from them.
+fn source_span(span: Span) -> SourceSpan {
+    let pos = |pos: lexpr::parse::Position| SourcePos {
+        line: pos.line(),
+        column: pos.column(),
+    };
+
+    SourceSpan {
+        start: pos(span.start()),
+        end: pos(span.end()),
+    }
+}
+
+/// The samples of a source map by where they start, along with how often they
+/// were executed.
+struct Hits<'a>(&'a [((usize, usize), u64)]);
+
+impl Hits<'_> {
+    /// How often the code within a span was executed, which is the most any of
+    /// the samples within it was executed. Spans without samples were never
+    /// emitted any code for.
+    fn within(&self, span: Span) -> Option<u64> {
+        let span = source_span(span);
+        let start = self.0.partition_point(|(p, _)| *p < pos(span.start));
+        let end = self.0.partition_point(|(p, _)| *p < pos(span.end));
+
+        self.0[start..end].iter().map(|(_, hits)| *hits).max()
+    }
+
+    fn count(&self, span: Span) -> u64 {
+        self.within(span).unwrap_or(0)
+    }
+
+    /// How often any of the statements was executed.
+    fn count_all(&self, statements: &[Statement]) -> u64 {
+        (statements.iter())
+            .map(|statement| self.count(statement.span))
+            .max()
+            .unwrap_or(0)
+    }
+}
@@ -0,0 +1,271 @@
+//! An interactive debugging API for the interpreter. Rather than recording
+//! moments and inspecting them after execution, a [`Debugger`] pauses
+//! execution at breakpoints or after stepping, at which point the frames being
+//! executed can be inspected.
+
+use rustc_hash::FxHashSet;
+
+use jssat_ir::{
+    frontend::{
+        ir::{Instruction, InstructionData},
+        source_map::SourceMapIdx,
+    },
+    id::{IdCompat, LiftedCtx},
+    lifted::{FunctionId, RegisterId},
+};
+
+use super::{Frame, InstResult, Interpreter, MomentCollector, Value};
+
+/// Where a [`Breakpoint`] pauses execution.
+pub enum BreakAt {
+    /// Before the first instruction of any call to a function with the name.
+    Function(String),
+    /// Before the first instruction of any call to the section of an IR file
+    /// with the number, e.g. `:7.3.2`.
+    Section(String),
+    /// Before any instruction with the source map index.
+    Source(SourceMapIdx),
+}
+
+pub type Condition = Box<dyn Fn(&FrameView) -> bool>;
+
+pub struct Breakpoint {
+    pub at: BreakAt,
+    /// If present, execution is only paused if the condition holds for the
+    /// frame about to execute the instruction.
+    pub condition: Option<Condition>,
+}
+
+impl Breakpoint {
+    pub fn new(at: BreakAt) -> Self {
+        Self {
+            at,
+            condition: None,
+        }
+    }
+
+    pub fn when(mut self, condition: impl Fn(&FrameView) -> bool + 'static) -> Self {
+        self.condition = Some(Box::new(condition));
+        self
+    }
+}
+
+/// How to continue execution once paused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resume {
+    /// Continue until a breakpoint is hit.
+    Continue,
+    /// Pause before the next instruction, which may be in a called function.
+    StepIn,
+    /// Pause before the next instruction of the current function, or of its
+    /// caller if it returns. Jumps between lifted functions do not leave the
+    /// current function.
+    StepOver,
+    /// Pause once the current function has returned to its caller.
+    StepOut,
+}
+
+/// Why a [`Debugger`] stopped executing.
+#[derive(Debug)]
+pub enum Stop {
+    /// A breakpoint was hit, identified by the index returned from
+    /// [`Debugger::add_breakpoint`].
+    Breakpoint(usize),
+    /// The step requested has been completed.
+    Step,
+    /// The function being debugged returned.
+    Finished(Option<Value>),
+}
+
+/// A view into a frame of a paused [`Debugger`].
+pub struct FrameView<'a> {
+    pub function: FunctionId,
+    pub name: Option<&'a str>,
+    /// The index of the instruction about to be executed, which is the amount
+    /// of instructions in the function if the end instruction is about to be
+    /// executed.
+    pub inst_idx: usize,
+    frame: &'a Frame<'a>,
+}
+
+impl<'a> FrameView<'a> {
+    fn new(frame: &'a Frame<'a>) -> Self {
+        Self {
+            function: frame.id,
+            name: frame.function.name.as_deref(),
+            inst_idx: frame.inst_idx,
+            frame,
+        }
+    }
+
+    /// The instruction about to be executed, which is [`None`] if it is the end
+    /// instruction.
+    pub fn instruction(&self) -> Option<&'a Instruction<LiftedCtx, LiftedCtx>> {
+        self.frame.function.instructions.get(self.inst_idx)
+    }
+
+    pub fn source_map_idx(&self) -> Option<SourceMapIdx> {
+        self.instruction()?.source_map_idx
+    }
+
+    pub fn register(&self, register: RegisterId) -> Option<&'a Value> {
+        self.frame.registers.get(&register)
+    }
+
+    /// The registers defined so far, ordered by register.
+    pub fn registers(&self) -> Vec<(RegisterId, &'a Value)> {
+        let mut registers = (self.frame.registers.iter())
+            .map(|(register, value)| (*register, value))
+            .collect::<Vec<_>>();
+
+        registers.sort_by_key(|(register, _)| register.value());
+        registers
+    }
+}
+
+struct Armed {
+    breakpoint: Breakpoint,
+    /// The functions to pause at the start of, for [`BreakAt::Function`] and
+    /// [`BreakAt::Section`].
+    functions: FxHashSet<FunctionId>,
+}
+
+impl Armed {
+    fn hit(&self, frame: &Frame) -> bool {
+        let at = match &self.breakpoint.at {
+            BreakAt::Function(_) | BreakAt::Section(_) => {
+                frame.called && frame.inst_idx == 0 && self.functions.contains(&frame.id)
+            }
+            BreakAt::Source(source) => {
+                let inst = frame.function.instructions.get(frame.inst_idx);
+                let idx = inst.and_then(|inst| inst.source_map_idx);
+                matches!(idx, Some(idx) if idx.0 == source.0)
+            }
+        };
+
+        at && match &self.breakpoint.condition {
+            Some(condition) => condition(&FrameView::new(frame)),
+            None => true,
+        }
+    }
+}
+
+/// A paused execution of a function. Created with [`Interpreter::debug`].
+pub struct Debugger<'i, 'p, C> {
+    interpreter: &'i mut Interpreter<'p, C>,
+    frames: Vec<Frame<'p>>,
+    breakpoints: Vec<Option<Armed>>,
+    result: Option<Option<Value>>,
+}
+
+impl<'p, C: MomentCollector> Interpreter<'p, C> {
+    /// Starts executing a function with a [`Debugger`], which is paused before
+    /// the first instruction of the function.
+    pub fn debug(&mut self, id: FunctionId, args: Vec<Value>) -> InstResult<Debugger<'_, 'p, C>> {
+        let frame = self.enter(id, args, None, true, None)?;
+
+        Ok(Debugger {
+            interpreter: self,
+            frames: vec![frame],
+            breakpoints: Vec::new(),
+            result: None,
+        })
+    }
+}
+
+impl<'i, 'p, C: MomentCollector> Debugger<'i, 'p, C> {
+    /// Adds a breakpoint, returning the index it is identified by.
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> usize {
+        let code = self.interpreter.code;
+
+        let functions = match &breakpoint.at {
+            BreakAt::Function(name) => (code.functions.iter())
+                .filter(|(_, f)| f.name.as_deref() == Some(name.as_str()))
+                .map(|(id, _)| *id)
+                .collect(),
+            BreakAt::Section(number) => {
+                // sections begin with a comment of their number and header
+                let number = number.trim_start_matches(':');
+                let is_section = |inst: &Instruction<LiftedCtx, LiftedCtx>| match &inst.data {
+                    InstructionData::Comment(c) => c.message.split(' ').next() == Some(number),
+                    _ => false,
+                };
+
+                (code.functions.iter())
+                    .filter(|(_, f)| f.instructions.first().map_or(false, is_section))
+                    .map(|(id, _)| *id)
+                    .collect()
+            }
+            BreakAt::Source(_) => FxHashSet::default(),
+        };
+
+        self.breakpoints.push(Some(Armed {
+            breakpoint,
+            functions,
+        }));
+        self.breakpoints.len() - 1
+    }
+
+    pub fn remove_breakpoint(&mut self, index: usize) {
+        self.breakpoints[index] = None;
+    }
+
+    /// Continues execution until the next pause, or until the function being
+    /// debugged returns.
+    pub fn resume(&mut self, resume: Resume) -> InstResult<Stop> {
+        if let Some(result) = &self.result {
+            return Ok(Stop::Finished(result.clone()));
+        }
+
+        let start_depth = self.frames.len();
+        let breakpoints = &self.breakpoints;
+        let mut stop = Stop::Step;
+
+        // the first instruction is the one execution is paused at
+        let mut first = true;
+
+        let mut pause = |frame: &Frame<'p>, depth: usize| {
+            if std::mem::take(&mut first) {
+                return false;
+            }
+
+            let hit = (breakpoints.iter().enumerate())
+                .find(|(_, armed)| matches!(armed, Some(armed) if armed.hit(frame)));
+
+            if let Some((index, _)) = hit {
+                stop = Stop::Breakpoint(index);
+                return true;
+            }
+
+            match resume {
+                Resume::Continue => false,
+                Resume::StepIn => true,
+                Resume::StepOver => depth <= start_depth,
+                Resume::StepOut => depth < start_depth,
+            }
+        };
+
+        let result = (self.interpreter)
+            .execute(&mut self.frames, &mut pause)
+            .map_err(|report| report.error)?;
+
+        match result {
+            Some(result) => {
+                self.result = Some(result.clone());
+                Ok(Stop::Finished(result))
+            }
+            None => Ok(stop),
+        }
+    }
+
+    /// The frame about to execute an instruction, if the function being
+    /// debugged has not returned.
+    pub fn frame(&self) -> Option<FrameView> {
+        self.frames.last().map(FrameView::new)
+    }
+
+    /// All frames being executed, starting with the function being debugged.
+    pub fn frames(&self) -> Vec<FrameView> {
+        self.frames.iter().map(FrameView::new).collect()
+    }
+}
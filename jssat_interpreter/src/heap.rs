@@ -0,0 +1,176 @@
+//! Snapshots of the heap of an interpreter, which are restored into other
+//! interpreters to skip the work that produced the heap.
+//!
+//! Initializing a realm executes a large portion of the ECMAScript
+//! specification before any script is run. Rather than repeating that for
+//! every script, the realm can be initialized once, captured into a
+//! [`HeapSnapshot`], and restored into a fresh [`Interpreter`] for every
+//! script. Every restore produces its own copy of the heap, so scripts cannot
+//! observe each other's mutations.
+//!
+//! ```ignore
+//! let realm = interpreter.execute_fn_id(initialize_realm, vec![])?.unwrap();
+//! let snapshot = interpreter.snapshot_heap(&[realm]);
+//!
+//! let mut interpreter = builder.build();
+//! let realm = interpreter.restore_heap(&snapshot).remove(0);
+//! interpreter.execute_fn_id(run_script, vec![realm, script])?;
+//! ```
+
+use gc::{Gc, GcCell};
+use rustc_hash::FxHashMap;
+
+use jssat_ir::value_snapshot::{
+    SnapshotList, SnapshotRecord, SnapshotTuple, SnapshotValue, ValueSnapshotArena,
+};
+
+use super::{
+    Alive, Interpreter, List, MomentCollector, Record, RecordKey, Tuple, Value,
+    ValueSnapshotArenaExt,
+};
+
+/// The records, lists and tuples reachable from a set of root values, along
+/// with their allocation ids.
+#[derive(Clone, Debug)]
+pub struct HeapSnapshot {
+    arena: ValueSnapshotArena,
+    roots: Vec<SnapshotValue>,
+    /// The allocation id the interpreter the snapshot was captured from would
+    /// have given to its next allocation.
+    alloc_id: usize,
+}
+
+impl HeapSnapshot {
+    /// The amount of records, lists and tuples in the snapshot.
+    pub fn allocations(&self) -> usize {
+        self.arena.records.len() + self.arena.lists.len() + self.arena.tuples.len()
+    }
+}
+
+impl<'p, C: MomentCollector> Interpreter<'p, C> {
+    /// Captures the roots and every record, list and tuple reachable from them.
+    pub fn snapshot_heap(&self, roots: &[Value]) -> HeapSnapshot {
+        let mut arena = ValueSnapshotArena::new();
+        let roots = roots.iter().map(|root| arena.map_value(root)).collect();
+
+        HeapSnapshot {
+            arena,
+            roots,
+            alloc_id: self.alloc_id,
+        }
+    }
+
+    /// Restores a copy of the heap in the snapshot, returning the roots it was
+    /// captured with. Values keep the allocation ids they were captured with,
+    /// and values allocated afterwards are given ids after them, so snapshots
+    /// should be restored into interpreters that have not allocated anything.
+    pub fn restore_heap(&mut self, snapshot: &HeapSnapshot) -> Vec<Value> {
+        self.alloc_id = self.alloc_id.max(snapshot.alloc_id);
+
+        let mut restore = Restore {
+            arena: &snapshot.arena,
+            alive: &self.alive,
+            records: Default::default(),
+            lists: Default::default(),
+            tuples: Default::default(),
+        };
+
+        (snapshot.roots.iter())
+            .map(|root| restore.value(root))
+            .collect()
+    }
+}
+
+/// The values restored so far, by allocation id, so that values referenced more
+/// than once (or by themselves) are only restored once.
+struct Restore<'a> {
+    arena: &'a ValueSnapshotArena,
+    alive: &'a Alive,
+    records: FxHashMap<usize, Gc<GcCell<Record>>>,
+    lists: FxHashMap<usize, Gc<GcCell<List>>>,
+    tuples: FxHashMap<usize, Gc<Tuple>>,
+}
+
+impl Restore<'_> {
+    fn value(&mut self, value: &SnapshotValue) -> Value {
+        match value {
+            SnapshotValue::Atom(x) => Value::Atom(*x),
+            SnapshotValue::Bytes(x) => Value::Bytes(x.clone()),
+            SnapshotValue::Number(x) => Value::Number(*x),
+            SnapshotValue::Float(x) => Value::Float(x.0),
+            SnapshotValue::BigInt(x) => Value::BigInt(x.clone()),
+            SnapshotValue::Boolean(x) => Value::Boolean(*x),
+            SnapshotValue::FnPtr(x) => Value::FnPtr(*x),
+            SnapshotValue::Record(alloc_id) => {
+                if let Some(record) = self.records.get(alloc_id) {
+                    return Value::Record(record.clone());
+                }
+
+                // the record is restored before its values, as they may refer
+                // back to it
+                let record = Record::allocated(self.alive.allocation(*alloc_id));
+                self.records.insert(*alloc_id, record.clone());
+
+                let SnapshotRecord(values) = &self.arena.records[alloc_id];
+                for (key, value) in values.iter() {
+                    let value = self.value(value);
+                    record.borrow_mut().insert(restore_key(key), value);
+                }
+
+                Value::Record(record)
+            }
+            SnapshotValue::List(alloc_id) => {
+                if let Some(list) = self.lists.get(alloc_id) {
+                    return Value::List(list.clone());
+                }
+
+                let list = List::allocated(self.alive.allocation(*alloc_id));
+                self.lists.insert(*alloc_id, list.clone());
+
+                let SnapshotList(values) = &self.arena.lists[alloc_id];
+                for value in values.iter() {
+                    let value = self.value(value);
+                    list.borrow_mut().push(value);
+                }
+
+                Value::List(list)
+            }
+            SnapshotValue::Tuple(alloc_id) => {
+                if let Some(tuple) = self.tuples.get(alloc_id) {
+                    return Value::Tuple(tuple.clone());
+                }
+
+                // tuples are immutable, so they can't refer back to themselves
+                let SnapshotTuple(values) = &self.arena.tuples[alloc_id];
+                let values = values.iter().map(|value| self.value(value)).collect();
+
+                let tuple = Tuple::allocated(self.alive.allocation(*alloc_id), values);
+                self.tuples.insert(*alloc_id, tuple.clone());
+                Value::Tuple(tuple)
+            }
+            SnapshotValue::Runtime => Value::Runtime,
+            SnapshotValue::BigNumber => {
+                unreachable!("snapshots of interpreters only contain known values")
+            }
+        }
+    }
+}
+
+fn restore_key(key: &SnapshotValue) -> RecordKey {
+    match key {
+        SnapshotValue::Atom(x) => RecordKey::Atom(*x),
+        SnapshotValue::Bytes(x) => RecordKey::Bytes(x.clone()),
+        SnapshotValue::Number(x) => RecordKey::Number(*x),
+        SnapshotValue::Float(x) => RecordKey::Float(*x),
+        SnapshotValue::BigInt(x) => RecordKey::BigInt(x.clone()),
+        SnapshotValue::Boolean(x) => RecordKey::Boolean(*x),
+        SnapshotValue::FnPtr(x) => RecordKey::FnPtr(*x),
+        SnapshotValue::Record(_)
+        | SnapshotValue::List(_)
+        | SnapshotValue::Tuple(_)
+        | SnapshotValue::Runtime => unreachable!("records are only keyed by primitives"),
+        SnapshotValue::BigNumber => {
+            unreachable!("snapshots of interpreters only contain known values")
+        }
+    }
+}
@@ -11,38 +11,113 @@
 
 mod build;
 pub use build::*;
+mod collector;
+pub use collector::*;
+mod coverage;
+pub use coverage::*;
+mod debugger;
+pub use debugger::*;
+mod heap;
+pub use heap::*;
+mod profiler;
+pub use profiler::*;
+mod report;
 use jssat_ir::UnwrapNone;
+pub use report::*;
 
 #[cfg(test)]
 mod tests;
 
 use domino::moment::MomentApi;
 
+use std::borrow::Cow;
+use std::cell::Cell;
 use std::convert::TryInto;
 use std::panic::Location;
+use std::rc::Rc;
+use std::time::Instant;
 
-use derive_more::{Deref, DerefMut};
+use derive_more::{Deref, DerefMut, Display};
 use gc::{custom_trace, BorrowError, BorrowMutError, Finalize, Gc, GcCell, Trace};
+use num_bigint::BigInt;
+use num_traits::FromPrimitive;
+use ordered_float::OrderedFloat;
 use rustc_hash::FxHashMap;
 use thiserror::Error;
 
-use jssat_ir::isa::{Atom, CompareType, ValueType};
+use jssat_ir::isa::{self, Atom, CompareType, NumericValue, SwitchValue, ValueType};
 use jssat_ir::lifted::Function;
 use jssat_ir::{collections::StrictZip, isa::BinaryOperator};
 
 use jssat_ir::{
-    frontend::ir::Instruction,
+    frontend::{
+        ir::{ExternalFunction, FFIReturnType, FFIValueType, Instruction, Returns},
+        source_map::SourceMapIdx,
+    },
     id::LiftedCtx,
     lifted::{
         ConstantId, EndInstruction, ExternalFunctionId, FunctionId, LiftedProgram, RegisterId,
     },
 };
 
-pub struct Interpreter<'parent> {
+pub struct Interpreter<'parent, C = MomentApi> {
     alloc_id: usize,
+    /// The records, lists and tuples allocated that haven't been collected,
+    /// counted against [`Limits::allocations`].
+    alive: Alive,
     code: &'parent LiftedProgram,
     external_fns: &'parent FxHashMap<ExternalFunctionId, ExtFnImpl>,
-    pub moment: MomentApi,
+    pub collector: C,
+    /// The maximum amount of calls that may be executed at once before a stack
+    /// overflow occurs. Defaults to [`DEFAULT_MAX_DEPTH`].
+    pub max_depth: usize,
+    pub limits: Limits,
+    /// The record key that the parse nodes of the JavaScript frontend store
+    /// their [`SourceMapIdx`] under. When set, a frame entered with a parse
+    /// node as an argument records that it evaluates that parse node.
+    pub parse_node_key: Option<Atom>,
+    /// The amount of instructions executed so far, counted against
+    /// [`Limits::fuel`].
+    executed: u64,
+}
+
+/// The default maximum amount of calls that may be executed at once.
+pub const DEFAULT_MAX_DEPTH: usize = 100_000;
+
+/// Limits on how much work the interpreter may do before execution stops with
+/// [`InstErr::LimitExceeded`]. By default, nothing is limited.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Limits {
+    /// The maximum amount of instructions to execute, including the end
+    /// instructions of lifted functions.
+    pub fuel: Option<u64>,
+    /// The point in time after which execution stops. This is checked whenever
+    /// a call, jump or return is executed.
+    pub deadline: Option<Instant>,
+    /// The maximum amount of records, lists and tuples that may be alive at
+    /// once. Values are alive until they are garbage collected, so garbage is
+    /// collected before the limit is considered exceeded.
+    pub allocations: Option<usize>,
+}
+
+/// Which of the [`Limits`] was exceeded.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+pub enum Limit {
+    #[display(fmt = "executed more than {} instructions", _0)]
+    Fuel(u64),
+    #[display(fmt = "exceeded the deadline")]
+    Deadline,
+    #[display(fmt = "kept more than {} values alive", _0)]
+    Allocations(usize),
+}
+
+/// A call on the call stack of the interpreter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StackFrame {
+    pub function: FunctionId,
+    /// The index of the instruction being executed, which is the amount of
+    /// instructions in the function if the end instruction is being executed.
+    pub inst_idx: usize,
 }
 
 pub struct Edge {
@@ -62,6 +137,10 @@ pub enum RecordKey {
     // TODO: use GC'd Vec<u8>s to prevent excessive cloning
     Bytes(Vec<u8>),
     Number(i64),
+    /// Floats which represent an integer are keyed as a
+    /// [`RecordKey::Number`], so that `1.0` and `1` are the same key.
+    Float(OrderedFloat<f64>),
+    BigInt(BigInt),
     Boolean(bool),
     FnPtr(FunctionId),
 }
@@ -77,10 +156,13 @@ pub enum Value {
     // TODO: use GC'd Vec<u8>s to prevent excessive cloning
     Bytes(Vec<u8>),
     Number(i64),
+    Float(f64),
+    BigInt(#[unsafe_ignore_trace] BigInt),
     Boolean(bool),
     FnPtr(#[unsafe_ignore_trace] FunctionId),
     Record(Gc<GcCell<Record>>),
     List(Gc<GcCell<List>>),
+    Tuple(Gc<Tuple>),
     Runtime,
 }
 
@@ -89,11 +171,13 @@ impl Value {
         match self {
             Value::Atom(_) => ValueType::Atom,
             Value::Bytes(_) => ValueType::Bytes,
-            Value::Number(_) => ValueType::Number,
+            Value::Number(_) | Value::Float(_) => ValueType::Number,
+            Value::BigInt(_) => ValueType::BigNumber,
             Value::Boolean(_) => ValueType::Boolean,
             Value::FnPtr(_) => ValueType::FnPtr,
             Value::Record(_) => ValueType::Record,
             Value::List(_) => ValueType::List,
+            Value::Tuple(_) => ValueType::Tuple,
             Value::Runtime => ValueType::Runtime,
         }
     }
@@ -121,6 +205,16 @@ impl Value {
     value_unwrap!(try_into_boolean, Boolean, bool);
     value_unwrap!(try_into_fnptr, FnPtr, FunctionId);
 
+    /// Interprets a number as an IEEE-754 double, which is how JavaScript
+    /// treats all numbers.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(value) => Some(*value as f64),
+            Self::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
     #[track_caller]
     pub fn try_into_bytes(&self) -> InstResult<&Vec<u8>> {
         match self {
@@ -180,6 +274,54 @@ impl Value {
             _ => Err(InvalidType(Location::caller())),
         }
     }
+
+    #[track_caller]
+    pub fn try_into_tuple(&self) -> InstResult<&Tuple> {
+        match self {
+            Self::Tuple(tuple) => Ok(tuple),
+            _ => Err(InvalidType(Location::caller())),
+        }
+    }
+}
+
+/// The allocation id of a new record, list or tuple, which is counted as alive
+/// until it is dropped.
+struct Allocation {
+    id: usize,
+    live: Live,
+}
+
+/// Counts the records, lists and tuples of an interpreter that are alive.
+#[derive(Clone, Debug, Default)]
+struct Alive(Rc<Cell<usize>>);
+
+impl Alive {
+    fn count(&self) -> usize {
+        self.0.get()
+    }
+
+    fn allocation(&self, id: usize) -> Allocation {
+        self.0.set(self.0.get() + 1);
+
+        Allocation {
+            id,
+            live: Live(Some(self.clone())),
+        }
+    }
+}
+
+/// Held by a record, list or tuple to count it as [`Alive`] until it is
+/// dropped, which happens once it is garbage collected. Values that weren't
+/// allocated by an interpreter aren't counted.
+#[derive(Debug, Default)]
+struct Live(Option<Alive>);
+
+impl Drop for Live {
+    fn drop(&mut self) {
+        if let Some(Alive(count)) = &self.0 {
+            count.set(count.get() - 1);
+        }
+    }
 }
 
 #[derive(Deref, DerefMut, Debug, Finalize)]
@@ -188,6 +330,7 @@ pub struct Record {
     #[deref]
     #[deref_mut]
     values: FxHashMap<RecordKey, Value>,
+    _live: Live,
 }
 
 impl Record {
@@ -195,6 +338,7 @@ impl Record {
         Self {
             alloc_id,
             values: Default::default(),
+            _live: Live::default(),
         }
     }
 
@@ -203,6 +347,12 @@ impl Record {
         Gc::new(GcCell::new(me))
     }
 
+    fn allocated(allocation: Allocation) -> Gc<GcCell<Self>> {
+        let mut me = Self::new(allocation.id);
+        me._live = allocation.live;
+        Gc::new(GcCell::new(me))
+    }
+
     #[track_caller]
     pub fn try_get(&self, key: &RecordKey) -> InstResult<&Value> {
         self.get(key)
@@ -233,6 +383,8 @@ pub struct List {
     #[deref]
     #[deref_mut]
     values: Vec<Value>,
+    #[unsafe_ignore_trace]
+    _live: Live,
 }
 
 impl List {
@@ -240,6 +392,7 @@ impl List {
         Self {
             alloc_id,
             values: Default::default(),
+            _live: Live::default(),
         }
     }
 
@@ -248,6 +401,12 @@ impl List {
         Gc::new(GcCell::new(me))
     }
 
+    fn allocated(allocation: Allocation) -> Gc<GcCell<Self>> {
+        let mut me = Self::new(allocation.id);
+        me._live = allocation.live;
+        Gc::new(GcCell::new(me))
+    }
+
     #[track_caller]
     pub fn try_get(&self, key: &ListKey) -> InstResult<&Value> {
         let ListKey::Index(idx) = key;
@@ -265,6 +424,68 @@ impl List {
     }
 }
 
+/// Tuples are immutable, so unlike [`Record`]s and [`List`]s they aren't
+/// wrapped in a [`GcCell`].
+#[derive(Deref, Debug, Finalize, Trace)]
+pub struct Tuple {
+    alloc_id: usize,
+    #[deref]
+    values: Vec<Value>,
+    #[unsafe_ignore_trace]
+    _live: Live,
+}
+
+impl Tuple {
+    pub fn new_gc(alloc_id: usize, values: Vec<Value>) -> Gc<Self> {
+        Gc::new(Self {
+            alloc_id,
+            values,
+            _live: Live::default(),
+        })
+    }
+
+    fn allocated(allocation: Allocation, values: Vec<Value>) -> Gc<Self> {
+        Gc::new(Self {
+            alloc_id: allocation.id,
+            values,
+            _live: allocation.live,
+        })
+    }
+
+    #[track_caller]
+    pub fn try_get(&self, index: usize) -> InstResult<&Value> {
+        self.get(index)
+            .ok_or_else(|| InstErr::TupleDNCIndex(index, Location::caller()))
+    }
+}
+
+/// Whether a value can be passed to or returned from an external function as a
+/// value of the given type.
+fn is_ffi_type(value: &Value, typ: &FFIValueType) -> bool {
+    match typ {
+        FFIValueType::Any => true,
+        FFIValueType::Runtime => matches!(value, Value::Runtime),
+        FFIValueType::String | FFIValueType::Bytes => matches!(value, Value::Bytes(_)),
+        FFIValueType::I64 | FFIValueType::Opaque => matches!(value, Value::Number(_)),
+        FFIValueType::F64 => matches!(value, Value::Number(_) | Value::Float(_)),
+        FFIValueType::Bool => matches!(value, Value::Boolean(_)),
+        FFIValueType::Record => matches!(value, Value::Record(_)),
+        // only the return type may be nullable, which is checked on its own
+        FFIValueType::Nullable(_) => false,
+    }
+}
+
+/// The integer a float is equal to, if it is integral and can be told apart
+/// from every other float by it. `-0.0` is not, as it would be the same as
+/// `0.0`.
+fn float_as_integer(value: f64) -> Option<i64> {
+    let is_integer = value.fract() == 0.0
+        && (i64::MIN as f64..i64::MAX as f64).contains(&value)
+        && !(value == 0.0 && value.is_sign_negative());
+
+    is_integer.then(|| value as i64)
+}
+
 pub fn ensure_arg_count(expected: usize, got: usize) -> InstResult<()> {
     if expected != got {
         return Err(NotEnoughArgs(expected, got, Location::caller()));
@@ -292,6 +513,8 @@ impl ValueSnapshotArenaExt for ValueSnapshotArena {
             Value::Atom(x) => SnapshotValue::Atom(*x),
             Value::Bytes(x) => SnapshotValue::Bytes(x.clone()),
             Value::Number(x) => SnapshotValue::Number(*x),
+            Value::Float(x) => SnapshotValue::Float(OrderedFloat(*x)),
+            Value::BigInt(x) => SnapshotValue::BigInt(x.clone()),
             Value::Boolean(x) => SnapshotValue::Boolean(*x),
             Value::FnPtr(x) => SnapshotValue::FnPtr(*x),
             Value::Record(x) => {
@@ -329,6 +552,21 @@ impl ValueSnapshotArenaExt for ValueSnapshotArena {
 
                 SnapshotValue::List(list.alloc_id)
             }
+            Value::Tuple(tuple) => {
+                if !self.tuples.contains_key(&tuple.alloc_id) {
+                    // put in a temp nonsense value to prevent recursion
+                    self.tuples
+                        .insert(tuple.alloc_id, SnapshotTuple(Default::default()))
+                        .expect_none("should not be reinserting");
+
+                    let values = (tuple.values.iter())
+                        .map(|v| self.map_value(v))
+                        .collect::<Vec<_>>();
+                    self.tuples.insert(tuple.alloc_id, SnapshotTuple(values));
+                }
+
+                SnapshotValue::Tuple(tuple.alloc_id)
+            }
             Value::Runtime => SnapshotValue::Runtime,
         }
     }
@@ -339,124 +577,316 @@ impl ValueSnapshotArenaExt for ValueSnapshotArena {
             RecordKey::Atom(x) => SnapshotValue::Atom(*x),
             RecordKey::Bytes(x) => SnapshotValue::Bytes(x.clone()),
             RecordKey::Number(x) => SnapshotValue::Number(*x),
+            RecordKey::Float(x) => SnapshotValue::Float(*x),
+            RecordKey::BigInt(x) => SnapshotValue::BigInt(x.clone()),
             RecordKey::Boolean(x) => SnapshotValue::Boolean(*x),
             RecordKey::FnPtr(x) => SnapshotValue::FnPtr(*x),
         }
     }
 }
 
-impl<'p> Interpreter<'p> {
+impl<'p> Interpreter<'p, MomentApi> {
     pub fn new(
         code: &'p LiftedProgram,
         ext_fns: &'p FxHashMap<ExternalFunctionId, ExtFnImpl>,
+    ) -> Self {
+        Self::new_with_collector(code, ext_fns, MomentApi::new(code))
+    }
+}
+
+impl<'p, C: MomentCollector> Interpreter<'p, C> {
+    pub fn new_with_collector(
+        code: &'p LiftedProgram,
+        ext_fns: &'p FxHashMap<ExternalFunctionId, ExtFnImpl>,
+        collector: C,
     ) -> Self {
         Interpreter {
             alloc_id: 0,
+            alive: Alive::default(),
             code,
             external_fns: ext_fns,
-            moment: MomentApi::new(code),
+            collector,
+            max_depth: DEFAULT_MAX_DEPTH,
+            limits: Limits::default(),
+            parse_node_key: None,
+            executed: 0,
         }
     }
 
-    fn next_alloc_id(&mut self) -> usize {
+    #[track_caller]
+    fn allocate(&mut self) -> InstResult<Allocation> {
+        if let Some(allocations) = self.limits.allocations {
+            // values that are no longer reachable are alive until collected
+            if self.alive.count() >= allocations {
+                gc::force_collect();
+            }
+
+            if self.alive.count() >= allocations {
+                let limit = Limit::Allocations(allocations);
+                return Err(LimitExceeded(limit, vec![], Location::caller()));
+            }
+        }
+
+        self.collector.allocate();
+
         let id = self.alloc_id;
         self.alloc_id += 1;
-        id
+        Ok(self.alive.allocation(id))
+    }
+
+    #[track_caller]
+    fn consume_fuel(&mut self) -> InstResult<()> {
+        if let Some(fuel) = self.limits.fuel {
+            if self.executed >= fuel {
+                return Err(LimitExceeded(Limit::Fuel(fuel), vec![], Location::caller()));
+            }
+        }
+
+        self.executed += 1;
+        Ok(())
     }
 
-    #[allow(non_upper_case_globals)]
+    /// Executes a function, returning the value it returns.
+    ///
+    /// Jumps between the lifted functions that make up a JSSAT function replace
+    /// the frame that is executing, so only calls push frames. Once more than
+    /// [`Interpreter::max_depth`] calls are being executed at once, a
+    /// [`InstErr::StackOverflow`] is returned. Exceeding any of the
+    /// [`Interpreter::limits`] results in a [`InstErr::LimitExceeded`], along
+    /// with the call stack at that point.
     pub fn execute_fn_id(&mut self, id: FunctionId, args: Vec<Value>) -> InstResult<Option<Value>> {
-        const KiB: usize = 1024;
-        const MiB: usize = 1024 * KiB;
-        self.moment.enter(id);
-        let results = stacker::maybe_grow(32 * KiB, 4 * MiB, || self.execute_fn_id_impl(id, args));
+        self.execute_fn_id_with_report(id, args)
+            .map_err(|report| report.error)
+    }
 
-        if results.is_ok() {
-            self.moment.exit();
-        }
+    /// Executes a function like [`Interpreter::execute_fn_id`], but wraps any
+    /// error in an [`ErrorReport`] of the call stack the error occurred in.
+    pub fn execute_fn_id_with_report(
+        &mut self,
+        id: FunctionId,
+        args: Vec<Value>,
+    ) -> Result<Option<Value>, ErrorReport> {
+        let mut frames =
+            vec![
+                (self.enter(id, args, None, true, None)).map_err(|error| ErrorReport {
+                    error,
+                    stack: vec![],
+                })?,
+            ];
+
+        let result = self.execute(&mut frames, &mut |_, _| false)?;
+        Ok(result.expect("never paused"))
+    }
+
+    /// Executes the frames until the last one returns, returning what it
+    /// returns. Before executing an instruction, `pause` is asked whether to
+    /// pause execution instead, in which case [`None`] is returned and the
+    /// frames can be executed further later.
+    fn execute(
+        &mut self,
+        frames: &mut Vec<Frame<'p>>,
+        pause: &mut impl FnMut(&Frame<'p>, usize) -> bool,
+    ) -> Result<Option<Option<Value>>, ErrorReport> {
+        self.execute_frames(frames, pause).map_err(|error| {
+            let error = match error {
+                LimitExceeded(limit, _, location) => {
+                    let stack = (frames.iter())
+                        .map(|frame| StackFrame {
+                            function: frame.id,
+                            inst_idx: frame.inst_idx,
+                        })
+                        .collect();
+
+                    LimitExceeded(limit, stack, location)
+                }
+                error => error,
+            };
+
+            ErrorReport {
+                error,
+                stack: frames.iter().map(TraceFrame::new).collect(),
+            }
+        })
+    }
+
+    fn execute_frames(
+        &mut self,
+        frames: &mut Vec<Frame<'p>>,
+        pause: &mut impl FnMut(&Frame<'p>, usize) -> bool,
+    ) -> InstResult<Option<Option<Value>>> {
+        loop {
+            let depth = frames.len();
+            let frame = frames.last_mut().expect("must be in function");
+
+            let step = self.run(frame, depth, pause)?;
+
+            match step {
+                Step::Call(call) => {
+                    let js_source = frame.js_source;
+
+                    if frames.len() >= self.max_depth {
+                        return Err(StackOverflow(self.max_depth, Location::caller()));
+                    }
 
-        results
+                    let callee = self.enter(call.fn_id, call.args, call.result, true, js_source)?;
+                    frames.push(callee);
+                }
+                Step::Jump(id, args) => {
+                    *frame = self.enter(id, args, frame.result, false, frame.js_source)?;
+                }
+                Step::Return(value) => {
+                    self.collector.exit();
+
+                    let callee = frames.pop().expect("must be in function");
+                    match frames.last_mut() {
+                        Some(caller) => self.resume(caller, callee.result, value)?,
+                        None => return Ok(Some(value)),
+                    };
+                }
+                Step::Pause => return Ok(None),
+            }
+        }
     }
 
-    fn execute_fn_id_impl(
+    /// Creates the frame of a function being called or jumped to. The frame
+    /// evaluates the parse node it is passed, or otherwise keeps evaluating the
+    /// parse node `js_source` of the frame it is entered from.
+    fn enter(
         &mut self,
         id: FunctionId,
         args: Vec<Value>,
-    ) -> InstResult<Option<Value>> {
+        result: Option<RegisterId>,
+        called: bool,
+        js_source: Option<SourceMapIdx>,
+    ) -> InstResult<Frame<'p>> {
         let function = (self.code.functions)
             .get(&id)
-            .expect("expected valid function id");
+            .ok_or(FunctionDNE(id, Location::caller()))?;
 
+        // the collector is only told about frames that can be entered, so that
+        // it doesn't wait for a frame that never exits
         ensure_arg_count(function.parameters.len(), args.len())?;
 
+        let js_source = self.parse_node(&args).or(js_source);
+
+        match called {
+            true => self.collector.enter(id, js_source),
+            false => self.collector.jump(id, js_source),
+        };
+
         let mut registers = FxHashMap::default();
         for (register, value) in function.parameters.iter().strict_zip(args) {
             registers.insert(*register, value);
         }
 
-        let mut inst_exec = InstExec {
-            registers,
-            interpreter: self,
+        Ok(Frame {
+            id,
             function,
-        };
+            registers,
+            inst_idx: 0,
+            record: false,
+            called,
+            result,
+            js_source,
+        })
+    }
+
+    /// Finds the [`SourceMapIdx`] of the first argument that is a parse node,
+    /// which is a record holding its index under the
+    /// [`Interpreter::parse_node_key`].
+    fn parse_node(&self, args: &[Value]) -> Option<SourceMapIdx> {
+        let key = RecordKey::Atom(self.parse_node_key?);
+
+        args.iter().find_map(|arg| match arg {
+            Value::Record(record) => match record.try_borrow().ok()?.get(&key)? {
+                Value::Number(idx) => usize::try_from(*idx).ok().map(SourceMapIdx),
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+
+    /// Executes the instructions of a frame, until either a call is made, the
+    /// end instruction is reached, or `pause` asks to pause.
+    fn run(
+        &mut self,
+        frame: &mut Frame<'p>,
+        depth: usize,
+        pause: &mut impl FnMut(&Frame<'p>, usize) -> bool,
+    ) -> InstResult<Step> {
+        let function = frame.function;
+
+        if let Some(deadline) = self.limits.deadline {
+            if Instant::now() >= deadline {
+                return Err(LimitExceeded(Limit::Deadline, vec![], Location::caller()));
+            }
+        }
+
+        while let Some(inst) = function.instructions.get(frame.inst_idx) {
+            if pause(frame, depth) {
+                return Ok(Step::Pause);
+            }
+
+            self.consume_fuel()?;
+            frame.record = self.collector.record_instruction();
 
-        for (idx, inst) in function.instructions.iter().enumerate() {
             // only take a pre snapshot if it's a non-trivial instruction that
             // could mutate args, like call
             use jssat_ir::frontend::ir::InstructionData;
-            if matches!(
-                inst.data,
-                InstructionData::CallVirt(_) | InstructionData::CallStatic(_)
-            ) {
+            if frame.record
+                && matches!(
+                    inst.data,
+                    InstructionData::CallVirt(_) | InstructionData::CallStatic(_)
+                )
+            {
                 let mut pre = ValueSnapshotArena::new();
-
-                for r in inst.used_registers() {
-                    pre.snapshot(r, inst_exec.registers.get(&r).unwrap());
-                }
-
-                inst_exec
-                    .interpreter
-                    .moment
-                    .snapshot(idx, inst.source_map_idx, pre);
+                frame.snapshot(&mut pre, inst.used_registers())?;
+                (self.collector).snapshot(frame.inst_idx, inst.source_map_idx, pre);
             }
 
-            let result = inst_exec.exec(inst);
+            let mut inst_exec = InstExec {
+                registers: &mut frame.registers,
+                interpreter: self,
+                call: None,
+            };
 
-            let mut post = ValueSnapshotArena::new();
+            let result = inst_exec.exec(inst);
 
-            if let Some(r) = inst.assigned_to() {
-                // may not be assigned if instruction fails
-                if let Some(v) = inst_exec.registers.get(&r) {
-                    post.snapshot(r, v);
-                }
+            // the post snapshot of a call is taken once the call returns
+            if let Some(call) = inst_exec.call.take() {
+                return Ok(Step::Call(call));
             }
 
-            for r in inst.used_registers() {
-                post.snapshot(r, inst_exec.registers.get(&r).unwrap());
-            }
+            let snapshotted = self.snapshot_post(frame, inst);
+            result?;
+            snapshotted?;
 
-            inst_exec
-                .interpreter
-                .moment
-                .snapshot(idx, inst.source_map_idx, post);
+            frame.inst_idx += 1;
+        }
 
-            result?;
+        if pause(frame, depth) {
+            return Ok(Step::Pause);
         }
 
+        self.consume_fuel()?;
+        let record = self.collector.record_instruction();
+
         let inst_idx = function.instructions.len();
-        let mut pre = ValueSnapshotArena::new();
 
-        for r in function.end.used_registers() {
-            pre.snapshot(r, inst_exec.registers.get(&r).unwrap());
+        if record {
+            let mut pre = ValueSnapshotArena::new();
+            frame.snapshot(&mut pre, function.end.used_registers())?;
+            self.collector.snapshot(inst_idx, None, pre);
         }
 
-        (inst_exec.interpreter.moment).snapshot(inst_idx, None, pre);
+        let inst_exec = InstExec {
+            registers: &mut frame.registers,
+            interpreter: self,
+            call: None,
+        };
 
-        let (registers, result) = match &function.end {
-            EndInstruction::Jump(i) => {
-                let args = inst_exec.load_args(&i.0 .1)?;
-                (inst_exec.registers, self.execute_fn_id(i.0 .0, args))
-            }
+        let step = match &function.end {
+            EndInstruction::Jump(i) => Step::Jump(i.0 .0, inst_exec.load_args(&i.0 .1)?),
             EndInstruction::JumpIf(i) => {
                 let condition = inst_exec.get(i.condition)?;
 
@@ -472,34 +902,156 @@ impl<'p> Interpreter<'p> {
 
                 let jump = if condition { &i.if_so } else { &i.other };
 
-                let args = inst_exec.load_args(&jump.1)?;
-                (inst_exec.registers, self.execute_fn_id(jump.0, args))
+                Step::Jump(jump.0, inst_exec.load_args(&jump.1)?)
+            }
+            EndInstruction::Switch(i) => {
+                let value = inst_exec.get(i.value)?;
+
+                let switch_value = match value {
+                    Value::Atom(atom) => Some(SwitchValue::Atom(*atom)),
+                    Value::Number(integer) => Some(SwitchValue::Integer(*integer)),
+                    // integral floats are the same as the integer they're equal
+                    // to, as they are when used as record keys
+                    Value::Float(float) => float_as_integer(*float).map(SwitchValue::Integer),
+                    Value::Boolean(boolean) => Some(SwitchValue::Boolean(*boolean)),
+                    _ => None,
+                };
+
+                let value = match switch_value {
+                    Some(value) => value,
+                    None => {
+                        return Err(InstErr::SwitchNotSwitchable(
+                            value.clone(),
+                            Location::caller(),
+                        ))
+                    }
+                };
+
+                let jump = i.take_path(value);
+
+                Step::Jump(jump.0, inst_exec.load_args(&jump.1)?)
             }
             EndInstruction::Return(i) => match i.0 {
-                Some(register) => {
-                    let value = inst_exec.get(register)?.clone();
-                    (inst_exec.registers, Ok(Some(value)))
-                }
-                None => (inst_exec.registers, Ok(None)),
+                Some(register) => Step::Return(Some(inst_exec.get(register)?.clone())),
+                None => Step::Return(None),
             },
         };
 
-        let mut post = ValueSnapshotArena::new();
+        if record {
+            let mut post = ValueSnapshotArena::new();
+            frame.snapshot(&mut post, function.end.used_registers())?;
+            self.collector.snapshot(inst_idx, None, post);
+        }
+
+        Ok(step)
+    }
+
+    /// Continues a frame after the call it made has returned.
+    fn resume(
+        &mut self,
+        frame: &mut Frame<'p>,
+        result: Option<RegisterId>,
+        value: Option<Value>,
+    ) -> InstResult<()> {
+        let mut inst_exec = InstExec {
+            registers: &mut frame.registers,
+            interpreter: self,
+            call: None,
+        };
+
+        let stored = inst_exec.store_fn_result(value, result);
 
-        for r in function.end.used_registers() {
-            post.snapshot(r, registers.get(&r).unwrap());
+        let inst = &frame.function.instructions[frame.inst_idx];
+        let snapshotted = self.snapshot_post(frame, inst);
+        stored?;
+        snapshotted?;
+
+        frame.inst_idx += 1;
+        Ok(())
+    }
+
+    fn snapshot_post(
+        &mut self,
+        frame: &Frame<'p>,
+        inst: &Instruction<LiftedCtx, LiftedCtx>,
+    ) -> InstResult<()> {
+        if !frame.record {
+            return Ok(());
         }
 
-        self.moment.snapshot(inst_idx, None, post);
+        let mut post = ValueSnapshotArena::new();
+
+        if let Some(r) = inst.assigned_to() {
+            // may not be assigned if instruction fails
+            if let Some(v) = frame.registers.get(&r) {
+                post.snapshot(r, v);
+            }
+        }
 
-        result
+        frame.snapshot(&mut post, inst.used_registers())?;
+        (self.collector).snapshot(frame.inst_idx, inst.source_map_idx, post);
+        Ok(())
     }
 }
 
-struct InstExec<'interpreter, 'code> {
-    registers: FxHashMap<RegisterId, Value>,
-    interpreter: &'interpreter mut Interpreter<'code>,
+/// A call being executed by the interpreter.
+struct Frame<'code> {
+    id: FunctionId,
     function: &'code Function,
+    registers: FxHashMap<RegisterId, Value>,
+    /// The index of the instruction being executed.
+    inst_idx: usize,
+    /// Whether the instruction being executed is recorded by the
+    /// [`MomentCollector`].
+    record: bool,
+    /// Whether the frame was entered by a call, rather than a jump.
+    called: bool,
+    /// The register of the calling frame that the result of the call is stored
+    /// in once it returns.
+    result: Option<RegisterId>,
+    /// The JavaScript parse node being evaluated, if any.
+    js_source: Option<SourceMapIdx>,
+}
+
+impl Frame<'_> {
+    /// Snapshots the values of the registers into the arena.
+    #[track_caller]
+    fn snapshot(
+        &self,
+        arena: &mut ValueSnapshotArena,
+        registers: impl IntoIterator<Item = RegisterId>,
+    ) -> InstResult<()> {
+        for register in registers {
+            let value = (self.registers.get(&register))
+                .ok_or(SnapshotRegisterDNE(register, Location::caller()))?;
+
+            arena.snapshot(register, value);
+        }
+
+        Ok(())
+    }
+}
+
+/// What a frame does once [`Interpreter::run`] stops executing it.
+enum Step {
+    Call(PendingCall),
+    Jump(FunctionId, Vec<Value>),
+    Return(Option<Value>),
+    Pause,
+}
+
+struct PendingCall {
+    fn_id: FunctionId,
+    args: Vec<Value>,
+    result: Option<RegisterId>,
+}
+
+struct InstExec<'interpreter, 'code, C> {
+    registers: &'interpreter mut FxHashMap<RegisterId, Value>,
+    interpreter: &'interpreter mut Interpreter<'code, C>,
+    /// Set when the instruction executed is a call, which the interpreter
+    /// executes by pushing a frame.
+    call: Option<PendingCall>,
 }
 
 pub type InstResult<T> = Result<T, InstErr>;
@@ -509,6 +1061,12 @@ type PanicLocation = &'static Location<'static>;
 pub enum InstErr {
     #[error("Register does not exist: {}", .0)]
     RegisterDNE(RegisterId, PanicLocation),
+    #[error("Register is assigned to more than once, violating SSA form: {}", .0)]
+    SSAViolation(RegisterId, PanicLocation),
+    #[error("Function does not exist: {}", .0)]
+    FunctionDNE(FunctionId, PanicLocation),
+    #[error("Register to snapshot does not exist: {}", .0)]
+    SnapshotRegisterDNE(RegisterId, PanicLocation),
     // TODO: wrap `Value` in something that formats better
     #[error("Invalid key used as record key: {:?}", .0)]
     InvalidRecKey(Value, PanicLocation),
@@ -519,10 +1077,16 @@ pub enum InstErr {
     RecordDNCKey(RecordKey, PanicLocation),
     #[error("List does not contain key: {:?}", .0)]
     ListDNCKey(ListKey, PanicLocation),
+    #[error("Tuple does not contain index: {}", .0)]
+    TupleDNCIndex(usize, PanicLocation),
     #[error("Invalid index for list: {:?}", .0)]
     ListInvalidIndex(i64, PanicLocation),
     #[error("Invalid index value for list: {:?}", .0)]
     ListInvalidIndexRegister(Value, PanicLocation),
+    #[error("Invalid range for string: {}..{}", .0, .1)]
+    StringInvalidRange(i64, i64, PanicLocation),
+    #[error("Invalid index for string: {}", .0)]
+    StringInvalidIndex(i64, PanicLocation),
     #[error("Constant does not exist: {}", .0)]
     ConstantDNE(ConstantId, PanicLocation),
     #[error("Unable to perform binary operation: {:?} `{}` {:?}", .0, .2, .1)]
@@ -535,6 +1099,8 @@ pub enum InstErr {
     ExpectedNonVoid(PanicLocation),
     #[error("Expected conditional value to be a boolean, but got: {:?}", .0)]
     ConditionalNotBoolean(Value, PanicLocation),
+    #[error("Expected switch value to be an atom, integer or boolean, but got: {:?}", .0)]
+    SwitchNotSwitchable(Value, PanicLocation),
     #[error("An invalid amount of arguments were supplied: expected {}, got {}", .0, .1)]
     NotEnoughArgs(usize, usize, PanicLocation),
     #[error("External function does not exist: {}", .0)]
@@ -545,18 +1111,19 @@ pub enum InstErr {
     #[error("An error occurred borrowing the record")]
     BorrowError(#[from] BorrowErrorWrapper),
     #[error("An assertion failed: {}", .0)]
-    AssertionFailed(&'static str, PanicLocation),
+    AssertionFailed(Cow<'static, str>, PanicLocation),
 
-    // TODO: support external function calls
-    // they can be implemented by having some kind of rust function be paired
-    // with an external function id, and then running that function when
-    // the interpreter encounters it. the external function has to be given the
-    // parameters it wants and then can do something with interpreter state idk
-    #[error("Unable to perform external function calls at this time")]
-    ExternalFnCall,
+    #[error("Invalid argument for external function, expected {:?} but got: {:?}", .1, .0)]
+    ExtFnArgType(Value, FFIValueType, PanicLocation),
+    #[error("Invalid result of external function, expected {:?} but got: {:?}", .1, .0)]
+    ExtFnReturnType(Option<Value>, FFIReturnType, PanicLocation),
 
     #[error("An `unreachable` instruction, which is suppose to be unreachable, was reached.")]
     Unreachable(PanicLocation),
+    #[error("Stack overflow: more than {} calls were executed at once", .0)]
+    StackOverflow(usize, PanicLocation),
+    #[error("Execution limit exceeded: {}", .0)]
+    LimitExceeded(Limit, Vec<StackFrame>, PanicLocation),
 }
 
 #[derive(Error, Debug)]
@@ -569,13 +1136,12 @@ pub enum BorrowErrorWrapper {
 
 use InstErr::*;
 
-impl<'i, 'c> InstExec<'i, 'c> {
+impl<'i, 'c, C: MomentCollector> InstExec<'i, 'c, C> {
     pub fn exec(&mut self, inst: &Instruction<LiftedCtx, LiftedCtx>) -> InstResult<()> {
         if let Some(result) = inst.assigned_to() {
-            assert!(
-                !self.registers.contains_key(&result),
-                "SSA form must be maintained"
-            );
+            if self.registers.contains_key(&result) {
+                return Err(SSAViolation(result, Location::caller()));
+            }
         }
 
         use jssat_ir::frontend::ir::InstructionData::*;
@@ -584,7 +1150,7 @@ impl<'i, 'c> InstExec<'i, 'c> {
             NewRecord(i) => {
                 self.registers.insert(
                     i.result,
-                    Value::Record(Record::new_gc(self.interpreter.next_alloc_id())),
+                    Value::Record(Record::allocated(self.interpreter.allocate()?)),
                 );
             }
             RecordGet(i) => {
@@ -623,9 +1189,17 @@ impl<'i, 'c> InstExec<'i, 'c> {
                 self.call_fn(&i.args, i.calling, i.result)?;
             }
             CallExtern(i) => {
-                let ext_fn = self.get_ext_fn(i.calling)?;
+                let function = self.get_ext_fn(i.calling)?.function;
                 let args = self.load_args(&i.args)?;
-                let result = (ext_fn.function)(args)?;
+                let code = self.interpreter.code;
+
+                // external functions given to the interpreter without being
+                // declared in the program have no signature to check against
+                let result = match code.external_functions.get(&i.calling) {
+                    Some(signature) => self.call_ext_fn(function, signature, args)?,
+                    None => function(args)?,
+                };
+
                 self.store_fn_result(result, i.result)?;
             }
             CallVirt(i) => {
@@ -648,6 +1222,13 @@ impl<'i, 'c> InstExec<'i, 'c> {
             MakeInteger(i) => {
                 self.registers.insert(i.result, Value::Number(i.item));
             }
+            MakeFloat(i) => {
+                self.registers.insert(i.result, Value::Float(i.item.0));
+            }
+            MakeBigInt(i) => {
+                self.registers
+                    .insert(i.result, Value::BigInt(i.item.clone()));
+            }
             MakeBoolean(i) => {
                 self.registers.insert(i.result, Value::Boolean(i.item));
             }
@@ -675,8 +1256,16 @@ impl<'i, 'c> InstExec<'i, 'c> {
                             combined.extend(rhs);
                             Bytes(combined)
                         }
-                        (Number(lhs), Number(rhs)) => Number(*lhs + *rhs),
-                        _ => return fail(),
+                        (Number(lhs), Number(rhs)) => match i.op.fold_integers(*lhs, *rhs) {
+                            Some(NumericValue::Integer(value)) => Number(value),
+                            Some(NumericValue::Float(value)) => Float(value),
+                            None => return fail(),
+                        },
+                        (BigInt(lhs), BigInt(rhs)) => BigInt(lhs + rhs),
+                        _ => match (lhs.as_f64(), rhs.as_f64()) {
+                            (Some(lhs), Some(rhs)) => Float(lhs + rhs),
+                            _ => return fail(),
+                        },
                     },
                     And => match (lhs, rhs) {
                         (Boolean(lhs), Boolean(rhs)) => Boolean(*lhs && *rhs),
@@ -686,18 +1275,57 @@ impl<'i, 'c> InstExec<'i, 'c> {
                         (Boolean(lhs), Boolean(rhs)) => Boolean(*lhs || *rhs),
                         _ => return fail(),
                     },
-                    Equals => Boolean(match (lhs, rhs) {
+                    Equals | SameValue => Boolean(match (lhs, rhs) {
                         (Number(lhs), Number(rhs)) => lhs == rhs,
+                        (BigInt(lhs), BigInt(rhs)) => lhs == rhs,
                         (Bytes(lhs), Bytes(rhs)) => lhs == rhs,
                         (Boolean(lhs), Boolean(rhs)) => lhs == rhs,
                         (Atom(lhs), Atom(rhs)) => lhs == rhs,
                         (Record(lhs), Record(rhs)) => Gc::ptr_eq(lhs, rhs),
+                        (Tuple(lhs), Tuple(rhs)) => Gc::ptr_eq(lhs, rhs),
                         (Record(_), Atom(_)) | (Atom(_), Record(_)) => false,
-                        _ => return fail(),
+                        _ => match (lhs.as_f64(), rhs.as_f64()) {
+                            // Number::sameValue
+                            (Some(lhs), Some(rhs)) if i.op == SameValue => {
+                                (lhs.is_nan() && rhs.is_nan())
+                                    || (lhs == rhs
+                                        && lhs.is_sign_negative() == rhs.is_sign_negative())
+                            }
+                            // Number::equal
+                            (Some(lhs), Some(rhs)) => lhs == rhs,
+                            _ => return fail(),
+                        },
                     }),
+                    Subtract | Multiply | Divide | Remainder | Exponent | BitwiseAnd
+                    | BitwiseOr | BitwiseXor | ShiftLeft | ShiftRight | UnsignedShiftRight => {
+                        let numeric = |value| match value {
+                            NumericValue::Integer(value) => Number(value),
+                            NumericValue::Float(value) => Float(value),
+                        };
+
+                        let folded = match (lhs, rhs) {
+                            (BigInt(lhs), BigInt(rhs)) => i.op.fold_bigints(lhs, rhs).map(BigInt),
+                            (Number(lhs), Number(rhs)) => {
+                                i.op.fold_integers(*lhs, *rhs).map(numeric)
+                            }
+                            _ => match (lhs.as_f64(), rhs.as_f64()) {
+                                (Some(lhs), Some(rhs)) => i.op.fold_floats(lhs, rhs).map(numeric),
+                                _ => return fail(),
+                            },
+                        };
+
+                        match folded {
+                            Some(value) => value,
+                            None => return fail(),
+                        }
+                    }
                     LessThan => Boolean(match (lhs, rhs) {
                         (Number(lhs), Number(rhs)) => lhs < rhs,
-                        _ => return fail(),
+                        (BigInt(lhs), BigInt(rhs)) => lhs < rhs,
+                        _ => match (lhs.as_f64(), rhs.as_f64()) {
+                            (Some(lhs), Some(rhs)) => lhs < rhs,
+                            _ => return fail(),
+                        },
                     }),
                 };
 
@@ -706,17 +1334,50 @@ impl<'i, 'c> InstExec<'i, 'c> {
             Negate(i) => {
                 let value = self.get(i.operand)?;
 
-                let fail = || Err(UnaryOpFail(value.clone(), "!", Location::caller()));
+                let fail = || Err(UnaryOpFail(value.clone(), "-", Location::caller()));
 
                 use Value::*;
                 let value = match value {
-                    Boolean(b) => Boolean(!*b),
-                    Number(n) => Number(-*n),
+                    // `-0` and `-i64::MIN` aren't integers
+                    Number(n) => match n.checked_neg() {
+                        Some(0) | None => Float(-(*n as f64)),
+                        Some(n) => Number(n),
+                    },
+                    Float(n) => Float(-*n),
+                    BigInt(n) => BigInt(-n),
+                    _ => return fail(),
+                };
+
+                self.registers.insert(i.result, value);
+            }
+            Not(i) => {
+                let value = self.get(i.operand)?;
+
+                let fail = || Err(UnaryOpFail(value.clone(), "!", Location::caller()));
+
+                let value = match value {
+                    Value::Boolean(b) => Value::Boolean(!*b),
                     _ => return fail(),
                 };
 
                 self.registers.insert(i.result, value);
             }
+            NumberToBigInt(i) => {
+                let value = self.get(i.operand)?;
+
+                let fail = || Err(UnaryOpFail(value.clone(), "BigInt", Location::caller()));
+
+                let value = match value {
+                    Value::Number(n) => BigInt::from(*n),
+                    Value::Float(n) if n.fract() == 0.0 => match BigInt::from_f64(*n) {
+                        Some(n) => n,
+                        None => return fail(),
+                    },
+                    _ => return fail(),
+                };
+
+                self.registers.insert(i.result, Value::BigInt(value));
+            }
             Generalize(_) => {
                 todo!("generalize not used yet")
             }
@@ -725,7 +1386,7 @@ impl<'i, 'c> InstExec<'i, 'c> {
                 let assertion = value.try_into_boolean()?;
 
                 if !assertion {
-                    return Err(AssertionFailed(i.message, Location::caller()));
+                    return Err(AssertionFailed(i.message.clone(), Location::caller()));
                 }
             }
             IsType(i) => {
@@ -742,10 +1403,13 @@ impl<'i, 'c> InstExec<'i, 'c> {
                     (Value::Atom(_), ValueType::Atom)
                     | (Value::Bytes(_), ValueType::Bytes)
                     | (Value::Number(_), ValueType::Number)
+                    | (Value::Float(_), ValueType::Number)
+                    | (Value::BigInt(_), ValueType::BigNumber)
                     | (Value::Boolean(_), ValueType::Boolean)
                     | (Value::FnPtr(_), ValueType::FnPtr)
                     | (Value::Record(_), ValueType::Record)
                     | (Value::List(_), ValueType::List)
+                    | (Value::Tuple(_), ValueType::Tuple)
                     | (Value::Runtime, ValueType::Runtime) => Value::Boolean(true),
                     _ => Value::Boolean(false),
                 };
@@ -755,7 +1419,7 @@ impl<'i, 'c> InstExec<'i, 'c> {
             NewList(i) => {
                 self.registers.insert(
                     i.result,
-                    Value::List(List::new_gc(self.interpreter.next_alloc_id())),
+                    Value::List(List::allocated(self.interpreter.allocate()?)),
                 );
             }
             ListGet(i) => {
@@ -793,6 +1457,83 @@ impl<'i, 'c> InstExec<'i, 'c> {
                 drop(list);
                 self.registers.insert(i.result, Value::Number(len as i64));
             }
+            NewTuple(i) => {
+                let values = (i.elements.iter())
+                    .map(|r| self.get(*r).cloned())
+                    .collect::<InstResult<Vec<_>>>()?;
+
+                let tuple = Tuple::allocated(self.interpreter.allocate()?, values);
+                self.registers.insert(i.result, Value::Tuple(tuple));
+            }
+            TupleGet(i) => {
+                let tuple = self.get(i.tuple)?.try_into_tuple()?;
+                let value = tuple.try_get(i.index)?.clone();
+                self.registers.insert(i.result, value);
+            }
+            TupleLen(i) => {
+                let tuple = self.get(i.tuple)?.try_into_tuple()?;
+                let len = tuple.len();
+                self.registers.insert(i.result, Value::Number(len as i64));
+            }
+            StringConcat(i) => {
+                let lhs = self.get(i.lhs)?.try_into_bytes()?;
+                let rhs = self.get(i.rhs)?.try_into_bytes()?;
+
+                let mut combined = lhs.clone();
+                combined.extend(rhs);
+                self.registers.insert(i.result, Value::Bytes(combined));
+            }
+            StringLen(i) => {
+                let string = self.get(i.string)?.try_into_bytes()?;
+                let len = isa::string_len(string);
+                self.registers.insert(i.result, Value::Number(len as i64));
+            }
+            StringSlice(i) => {
+                let string = self.get(i.string)?.try_into_bytes()?;
+                let start = self.get(i.start)?.try_into_number()?;
+                let end = self.get(i.end)?.try_into_number()?;
+
+                let slice = isa::string_slice(string, start, end)
+                    .ok_or_else(|| StringInvalidRange(start, end, Location::caller()))?;
+                self.registers
+                    .insert(i.result, Value::Bytes(slice.to_vec()));
+            }
+            StringCodeUnitAt(i) => {
+                let string = self.get(i.string)?.try_into_bytes()?;
+                let index = self.get(i.index)?.try_into_number()?;
+
+                let code_unit = isa::string_code_unit_at(string, index)
+                    .ok_or_else(|| StringInvalidIndex(index, Location::caller()))?;
+                self.registers
+                    .insert(i.result, Value::Number(code_unit as i64));
+            }
+            NumberToString(i) => {
+                let value = self.get(i.number)?;
+
+                let number = match value {
+                    Value::Number(n) => NumericValue::Integer(*n),
+                    Value::Float(n) => NumericValue::Float(*n),
+                    _ => return Err(UnaryOpFail(value.clone(), "ToString", Location::caller())),
+                };
+
+                let string = isa::encode_utf16(&isa::number_to_string(number));
+                self.registers.insert(i.result, Value::Bytes(string));
+            }
+            StringToNumber(i) => {
+                let string = self.get(i.string)?.try_into_bytes()?;
+
+                // code units that are not valid UTF-16 can never form a numeric
+                // literal, so they produce `NaN`
+                let value = match isa::decode_utf16(string) {
+                    Some(string) => match isa::string_to_number(&string) {
+                        NumericValue::Integer(n) => Value::Number(n),
+                        NumericValue::Float(n) => Value::Float(n),
+                    },
+                    None => Value::Float(f64::NAN),
+                };
+
+                self.registers.insert(i.result, value);
+            }
             GetRuntime(i) => {
                 self.registers.insert(i.result, Value::Runtime);
             }
@@ -841,6 +1582,11 @@ impl<'i, 'c> InstExec<'i, 'c> {
             Value::FnPtr(value) => RecordKey::FnPtr(*value),
             Value::Atom(value) => RecordKey::Atom(*value),
             Value::Number(value) => RecordKey::Number(*value),
+            Value::BigInt(value) => RecordKey::BigInt(value.clone()),
+            Value::Float(value) => match float_as_integer(*value) {
+                Some(integer) => RecordKey::Number(integer),
+                None => RecordKey::Float(OrderedFloat(*value)),
+            },
             // TODO: support using records as keys, as this is possible
             // in python (and i think lua)
             Value::Record(_) => {
@@ -849,6 +1595,9 @@ impl<'i, 'c> InstExec<'i, 'c> {
             Value::List(_) => {
                 return Err(InstErr::InvalidRecKey(value.clone(), Location::caller()))
             }
+            Value::Tuple(_) => {
+                return Err(InstErr::InvalidRecKey(value.clone(), Location::caller()))
+            }
             // will never be possible
             Value::Runtime => {
                 return Err(InstErr::InvalidRecKey(value.clone(), Location::caller()))
@@ -916,6 +1665,43 @@ impl<'i, 'c> InstExec<'i, 'c> {
             })
     }
 
+    #[track_caller]
+    fn call_ext_fn(
+        &mut self,
+        function: fn(Vec<Value>) -> InstResult<Option<Value>>,
+        signature: &ExternalFunction,
+        args: Vec<Value>,
+    ) -> InstResult<Option<Value>> {
+        ensure_arg_count(signature.parameters.len(), args.len())?;
+
+        for (arg, typ) in args.iter().zip(signature.parameters.iter()) {
+            if !is_ffi_type(arg, typ) {
+                return Err(ExtFnArgType(arg.clone(), typ.clone(), Location::caller()));
+            }
+        }
+
+        let result = function(args)?;
+
+        match (&signature.return_type, result) {
+            (Returns::Void, None) => Ok(None),
+            // nullable values are given to JSSAT code as a tuple which holds the
+            // value, or is empty if it is null
+            (Returns::Value(FFIValueType::Nullable(typ)), result)
+                if result.iter().all(|value| is_ffi_type(value, typ)) =>
+            {
+                let values = result.into_iter().collect();
+                let tuple = Tuple::allocated(self.interpreter.allocate()?, values);
+                Ok(Some(Value::Tuple(tuple)))
+            }
+            (Returns::Value(typ), Some(value)) if is_ffi_type(&value, typ) => Ok(Some(value)),
+            (return_type, result) => Err(ExtFnReturnType(
+                result,
+                return_type.clone(),
+                Location::caller(),
+            )),
+        }
+    }
+
     #[track_caller]
     fn call_fn(
         &mut self,
@@ -925,21 +1711,15 @@ impl<'i, 'c> InstExec<'i, 'c> {
     ) -> InstResult<()> {
         let args = self.load_args(args)?;
 
-        // TODO: maybe carry around the calling interpreter?
-        // explicitly not using `Interpreter::new` to show that we are
-        // explicitly relying on internal implementation details (that
-        // making an interpreter is cheap and stateless)
-        //
-        // EDIT^: yeah interpreter shouldn't be stateless
-
-        // in the future, we want to provide a stacktrace of function
-        // execution when we come across an error
-        //
-        // A*: at this point we'd want more detailed error information
-        // about the stack trace
-        let result = self.interpreter.execute_fn_id(fn_id, args)?; // <- A*
-
-        self.store_fn_result(result, register)
+        // the interpreter pushes a frame for the call once the instruction is
+        // done executing, and stores the result in `register` once it returns
+        self.call = Some(PendingCall {
+            fn_id,
+            args,
+            result: register,
+        });
+
+        Ok(())
     }
 
     #[track_caller]
@@ -0,0 +1,325 @@
+//! A [`MomentCollector`] that profiles execution rather than recording
+//! moments, to find out which parts of a program dominate execution.
+//!
+//! Execution is profiled both per lifted function, and per section of the IR
+//! files the program was built from. A section is entered by calling a JSSAT
+//! function, and includes every lifted function it jumps to.
+//!
+//! ```ignore
+//! let mut interpreter = builder.build_with_collector(Profiler::new(&program));
+//! interpreter.execute_fn_id(program.entrypoint, vec![])?;
+//!
+//! print!("{}", interpreter.collector.section_table());
+//! std::fs::write("out.folded", interpreter.collector.folded_stacks(Metric::Time))?;
+//! ```
+
+use std::{
+    fmt::Write,
+    time::{Duration, Instant},
+};
+
+use rustc_hash::FxHashMap;
+
+use jssat_ir::{
+    frontend::{ir::InstructionData, source_map::SourceMapIdx},
+    lifted::{FunctionId, LiftedProgram},
+    value_snapshot::ValueSnapshotArena,
+};
+
+use super::MomentCollector;
+
+/// What was measured for a lifted function or section.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Profile {
+    /// The amount of instructions executed, including end instructions.
+    pub instructions: u64,
+    /// The amount of times it was called, or jumped to for lifted functions.
+    pub calls: u64,
+    /// The amount of records, lists and tuples allocated.
+    pub allocations: u64,
+    /// The time spent executing it, excluding the functions it called.
+    pub self_time: Duration,
+    /// The time spent executing it, including the functions it called. Time
+    /// spent in recursive calls is only counted once.
+    pub total_time: Duration,
+}
+
+/// What folded stacks are weighted by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    Instructions,
+    /// Self time, in microseconds.
+    Time,
+}
+
+/// The measurements made since a section or lifted function was entered.
+#[derive(Clone, Copy, Default)]
+struct Counts {
+    instructions: u64,
+    allocations: u64,
+    self_time: Duration,
+}
+
+impl Counts {
+    fn add_to(&self, profile: &mut Profile) {
+        profile.instructions += self.instructions;
+        profile.allocations += self.allocations;
+        profile.self_time += self.self_time;
+    }
+}
+
+/// A section being executed.
+struct Active {
+    section: String,
+    /// The sections on the stack, up to and including this one, joined with
+    /// `;`.
+    path: String,
+    entered: Instant,
+    counts: Counts,
+    function: FunctionId,
+    function_entered: Instant,
+    function_counts: Counts,
+}
+
+pub struct Profiler<'code> {
+    code: &'code LiftedProgram,
+    pub functions: FxHashMap<FunctionId, Profile>,
+    /// Profiles of sections, keyed by their header, e.g.
+    /// `7.3.2 Name ( params )`. Functions that are not the start of a section
+    /// are keyed by their name.
+    pub sections: FxHashMap<String, Profile>,
+    /// The instructions executed and self time spent in every stack of
+    /// sections.
+    folded: FxHashMap<String, (u64, Duration)>,
+    stack: Vec<Active>,
+    /// When self time was last attributed to the top of the stack.
+    last: Instant,
+}
+
+impl<'code> Profiler<'code> {
+    pub fn new(code: &'code LiftedProgram) -> Self {
+        Self {
+            code,
+            functions: Default::default(),
+            sections: Default::default(),
+            folded: Default::default(),
+            stack: Vec::new(),
+            last: Instant::now(),
+        }
+    }
+
+    fn function_name(&self, id: FunctionId) -> String {
+        let function = self.code.functions.get(&id);
+
+        match function.and_then(|f| f.name.as_ref()) {
+            Some(name) => format!("{} (@{})", name, id),
+            None => format!("@{}", id),
+        }
+    }
+
+    /// Sections of IR files begin with a comment of their header, which
+    /// starts with the number of the section.
+    fn section_name(&self, function: FunctionId) -> String {
+        let function = &self.code.functions[&function];
+
+        match function.instructions.first().map(|inst| &inst.data) {
+            Some(InstructionData::Comment(comment))
+                if comment.message.starts_with(|c: char| c.is_ascii_digit()) =>
+            {
+                comment.message.to_string()
+            }
+            _ => match &function.name {
+                Some(name) => name.clone(),
+                None => "<unnamed>".to_string(),
+            },
+        }
+    }
+
+    /// Attributes the time since the last event to the top of the stack.
+    fn tick(&mut self) -> Instant {
+        let now = Instant::now();
+
+        if let Some(active) = self.stack.last_mut() {
+            let elapsed = now - self.last;
+            active.counts.self_time += elapsed;
+            active.function_counts.self_time += elapsed;
+        }
+
+        self.last = now;
+        now
+    }
+
+    fn exit_function(&mut self, now: Instant) {
+        let active = self.stack.last().expect("must be in function");
+        let function = active.function;
+        let recursive = (self.stack.iter().rev().skip(1)).any(|a| a.function == function);
+
+        let profile = self.functions.entry(function).or_default();
+        active.function_counts.add_to(profile);
+
+        if !recursive {
+            profile.total_time += now - active.function_entered;
+        }
+    }
+
+    /// A table of the lifted functions executed, sorted by self time.
+    pub fn function_table(&self) -> String {
+        let rows = (self.functions.iter())
+            .map(|(id, profile)| (self.function_name(*id), *profile))
+            .collect();
+
+        table("function", rows)
+    }
+
+    /// A table of the sections executed, sorted by self time.
+    pub fn section_table(&self) -> String {
+        let rows = (self.sections.iter())
+            .map(|(section, profile)| (section.clone(), *profile))
+            .collect();
+
+        table("section", rows)
+    }
+
+    /// The stacks of sections executed in the folded format, which tools like
+    /// `flamegraph.pl` and `inferno` turn into flamegraphs.
+    pub fn folded_stacks(&self, metric: Metric) -> String {
+        let mut stacks = (self.folded.iter())
+            .map(|(path, (instructions, time))| {
+                let weight = match metric {
+                    Metric::Instructions => *instructions,
+                    Metric::Time => time.as_micros() as u64,
+                };
+
+                (path, weight)
+            })
+            .filter(|(_, weight)| *weight > 0)
+            .collect::<Vec<_>>();
+        stacks.sort();
+
+        let mut folded = String::new();
+        for (path, weight) in stacks {
+            writeln!(folded, "{} {}", path, weight).unwrap();
+        }
+
+        folded
+    }
+}
+
+fn table(kind: &str, mut rows: Vec<(String, Profile)>) -> String {
+    rows.sort_by(|(a_name, a), (b_name, b)| {
+        (b.self_time, b.instructions, a_name).cmp(&(a.self_time, a.instructions, b_name))
+    });
+
+    let width = (rows.iter())
+        .map(|(name, _)| name.chars().count())
+        .chain(std::iter::once(kind.len()))
+        .max()
+        .unwrap_or(0);
+
+    let mut table = String::new();
+    writeln!(
+        table,
+        "{:width$}  {:>12}  {:>8}  {:>11}  {:>12}  {:>12}",
+        kind,
+        "instructions",
+        "calls",
+        "allocations",
+        "self (ms)",
+        "total (ms)",
+        width = width
+    )
+    .unwrap();
+
+    let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+
+    for (name, profile) in rows {
+        writeln!(
+            table,
+            "{:width$}  {:>12}  {:>8}  {:>11}  {:>12.3}  {:>12.3}",
+            name,
+            profile.instructions,
+            profile.calls,
+            profile.allocations,
+            ms(profile.self_time),
+            ms(profile.total_time),
+            width = width
+        )
+        .unwrap();
+    }
+
+    table
+}
+
+impl MomentCollector for Profiler<'_> {
+    fn enter(&mut self, function: FunctionId, _: Option<SourceMapIdx>) {
+        let now = self.tick();
+
+        let section = self.section_name(function);
+        let path = match self.stack.last() {
+            Some(caller) => format!("{};{}", caller.path, section),
+            None => section.clone(),
+        };
+
+        self.functions.entry(function).or_default().calls += 1;
+        self.sections.entry(section.clone()).or_default().calls += 1;
+
+        self.stack.push(Active {
+            section,
+            path,
+            entered: now,
+            counts: Counts::default(),
+            function,
+            function_entered: now,
+            function_counts: Counts::default(),
+        });
+    }
+
+    fn exit(&mut self) {
+        let now = self.tick();
+        self.exit_function(now);
+
+        let active = self.stack.pop().expect("must be in function");
+        let recursive = (self.stack.iter()).any(|a| a.section == active.section);
+
+        let profile = self.sections.get_mut(&active.section).unwrap();
+        active.counts.add_to(profile);
+
+        if !recursive {
+            profile.total_time += now - active.entered;
+        }
+
+        let (instructions, time) = self.folded.entry(active.path).or_default();
+        *instructions += active.counts.instructions;
+        *time += active.counts.self_time;
+    }
+
+    fn jump(&mut self, function: FunctionId, _: Option<SourceMapIdx>) {
+        let now = self.tick();
+        self.exit_function(now);
+
+        self.functions.entry(function).or_default().calls += 1;
+
+        let active = self.stack.last_mut().expect("must be in function");
+        active.function = function;
+        active.function_entered = now;
+        active.function_counts = Counts::default();
+    }
+
+    fn allocate(&mut self) {
+        if let Some(active) = self.stack.last_mut() {
+            active.counts.allocations += 1;
+            active.function_counts.allocations += 1;
+        }
+    }
+
+    fn record_instruction(&mut self) -> bool {
+        if let Some(active) = self.stack.last_mut() {
+            active.counts.instructions += 1;
+            active.function_counts.instructions += 1;
+        }
+
+        false
+    }
+
+    fn snapshot(&mut self, _: usize, _: Option<SourceMapIdx>, _: ValueSnapshotArena) {}
+}
@@ -0,0 +1,307 @@
+//! Error reports, which wrap an [`InstErr`] with the call stack of the
+//! interpreter at the point the error occurred.
+//!
+//! The [`PanicLocation`](std::panic::Location) of an [`InstErr`] only says
+//! where in the interpreter an error was raised, which says little about the
+//! JSSAT code being executed. An [`ErrorReport`] records every frame that was
+//! executing along with its registers, so that the report can be resolved
+//! against the source maps of the program once execution has stopped.
+
+use std::fmt::{self, Display, Formatter};
+
+use jssat_ir::{
+    frontend::source_map::{JsSourceMap, SourceMapIdx, SourceMapImpl, SourceSpan},
+    id::IdCompat,
+    isa::{decode_utf16, AtomDealer},
+    lifted::{FunctionId, RegisterId},
+};
+
+use super::{Frame, InstErr, RecordKey, Value};
+
+/// An [`InstErr`] along with the call stack it occurred in.
+#[derive(Debug)]
+pub struct ErrorReport {
+    pub error: InstErr,
+    /// The frames being executed, starting with the function that was called.
+    pub stack: Vec<TraceFrame>,
+}
+
+/// A frame of the call stack of an [`ErrorReport`].
+#[derive(Debug)]
+pub struct TraceFrame {
+    pub function: FunctionId,
+    pub name: Option<String>,
+    /// The index of the instruction being executed, which is the amount of
+    /// instructions in the function if the end instruction is being executed.
+    pub inst_idx: usize,
+    pub source_map_idx: Option<SourceMapIdx>,
+    /// The JavaScript parse node the frame is evaluating, which is resolved
+    /// against the [`JsSourceMap`].
+    pub js_source_idx: Option<SourceMapIdx>,
+    /// The registers the instruction being executed uses.
+    pub used: Vec<RegisterId>,
+    /// Every register defined in the frame, ordered by register.
+    pub registers: Vec<(RegisterId, Value)>,
+}
+
+impl TraceFrame {
+    pub(crate) fn new(frame: &Frame) -> Self {
+        let function = frame.function;
+
+        let (source_map_idx, used) = match function.instructions.get(frame.inst_idx) {
+            Some(inst) => (inst.source_map_idx, inst.used_registers()),
+            None => (None, function.end.used_registers()),
+        };
+
+        let mut registers = (frame.registers.iter())
+            .map(|(register, value)| (*register, value.clone()))
+            .collect::<Vec<_>>();
+        registers.sort_by_key(|(register, _)| register.value());
+
+        Self {
+            function: frame.id,
+            name: function.name.clone(),
+            inst_idx: frame.inst_idx,
+            source_map_idx,
+            js_source_idx: frame.js_source,
+            used: used.to_vec(),
+            registers,
+        }
+    }
+
+    pub fn register(&self, register: RegisterId) -> Option<&Value> {
+        (self.registers.iter())
+            .find(|(r, _)| *r == register)
+            .map(|(_, value)| value)
+    }
+}
+
+/// What an [`ErrorReport`] is resolved against when displayed. Anything not
+/// present is left out of the report.
+#[derive(Clone, Copy, Default)]
+pub struct ReportSources<'a> {
+    /// Used to print the names of atoms.
+    pub dealer: Option<&'a AtomDealer>,
+    /// The source map of the code that was lifted, e.g. the LISP of the IR
+    /// files.
+    pub source_map: Option<&'a SourceMapImpl>,
+    pub js_source_map: Option<&'a JsSourceMap>,
+}
+
+impl ErrorReport {
+    /// Displays the report, resolving positions and atoms with the sources.
+    /// The innermost frame is displayed first.
+    pub fn display<'a>(&'a self, sources: ReportSources<'a>) -> DisplayReport<'a> {
+        DisplayReport {
+            report: self,
+            sources,
+        }
+    }
+}
+
+impl Display for ErrorReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.display(ReportSources::default()).fmt(f)
+    }
+}
+
+impl std::error::Error for ErrorReport {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+pub struct DisplayReport<'a> {
+    report: &'a ErrorReport,
+    sources: ReportSources<'a>,
+}
+
+impl Display for DisplayReport<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "error: {}", self.report.error)?;
+
+        for frame in self.report.stack.iter().rev() {
+            match &frame.name {
+                Some(name) => write!(f, "  in {} (@{})", name, frame.function)?,
+                None => write!(f, "  in @{}", frame.function)?,
+            };
+            writeln!(f, " at instruction {}", frame.inst_idx)?;
+
+            let lisp = (self.sources.source_map)
+                .zip(frame.source_map_idx)
+                .and_then(|(source_map, idx)| source_map.span(idx));
+
+            if let Some(span) = lisp {
+                writeln!(f, "    at {}", DisplaySpan(span))?;
+            }
+
+            let js = (self.sources.js_source_map)
+                .zip(frame.js_source_idx)
+                .and_then(|(js_source_map, idx)| js_source_map.sources.span(idx));
+
+            if let Some(span) = js {
+                writeln!(f, "    evaluating JavaScript at {}", DisplaySpan(span))?;
+            }
+
+            for register in frame.used.iter() {
+                if let Some(value) = frame.register(*register) {
+                    let value = display_value(value, self.sources.dealer);
+                    writeln!(f, "    %{} = {}", register, value)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct DisplaySpan(SourceSpan);
+
+impl Display for DisplaySpan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let SourceSpan { start, end } = self.0;
+        write!(
+            f,
+            "{}:{}..{}:{}",
+            start.line, start.column, end.line, end.column
+        )
+    }
+}
+
+/// How deep into records, lists and tuples values are printed. Values of the
+/// ECMAScript specification are heavily nested (and cyclic), so only the
+/// outermost few levels are useful in a report.
+const MAX_DEPTH: usize = 2;
+
+/// Displays a value the same way values are displayed in reports.
+pub fn display_value<'a>(value: &'a Value, dealer: Option<&'a AtomDealer>) -> impl Display + 'a {
+    DisplayValue {
+        value,
+        dealer,
+        depth: 0,
+    }
+}
+
+struct DisplayValue<'a> {
+    value: &'a Value,
+    dealer: Option<&'a AtomDealer>,
+    depth: usize,
+}
+
+impl DisplayValue<'_> {
+    fn nested<'b>(&'b self, value: &'b Value) -> DisplayValue<'b> {
+        DisplayValue {
+            value,
+            dealer: self.dealer,
+            depth: self.depth + 1,
+        }
+    }
+
+    fn atom(&self, f: &mut Formatter<'_>, atom: jssat_ir::isa::Atom) -> fmt::Result {
+        match self.dealer.and_then(|dealer| dealer.try_resolve_name(atom)) {
+            Some(name) => write!(f, ":{}", name),
+            None => write!(f, ":?{}?", atom.0),
+        }
+    }
+
+    fn key(&self, f: &mut Formatter<'_>, key: &RecordKey) -> fmt::Result {
+        match key {
+            RecordKey::Atom(atom) => self.atom(f, *atom),
+            RecordKey::Bytes(bytes) => self.nested(&Value::Bytes(bytes.clone())).fmt(f),
+            RecordKey::Number(number) => write!(f, "{}", number),
+            RecordKey::Float(float) => write!(f, "{}", float),
+            RecordKey::BigInt(bigint) => write!(f, "{}n", bigint),
+            RecordKey::Boolean(boolean) => write!(f, "{}", boolean),
+            RecordKey::FnPtr(function) => write!(f, "@{}", function),
+        }
+    }
+
+    fn elements(&self, f: &mut Formatter<'_>, values: &[Value]) -> fmt::Result {
+        for (idx, value) in values.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+
+            self.nested(value).fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for DisplayValue<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let elided = self.depth >= MAX_DEPTH;
+
+        match self.value {
+            Value::Atom(atom) => self.atom(f, *atom),
+            Value::Bytes(bytes) => match decode_utf16(bytes) {
+                Some(string) => write!(f, "{:?}", string),
+                None => write!(f, "{:?}", bytes),
+            },
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Float(float) => write!(f, "{}", float),
+            Value::BigInt(bigint) => write!(f, "{}n", bigint),
+            Value::Boolean(boolean) => write!(f, "{}", boolean),
+            Value::FnPtr(function) => write!(f, "@{}", function),
+            Value::Runtime => write!(f, "runtime"),
+            Value::Record(record) => {
+                let record = match record.try_borrow() {
+                    Ok(record) => record,
+                    Err(_) => return write!(f, "record <borrowed>"),
+                };
+
+                write!(f, "record#{}", record.alloc_id)?;
+
+                if elided {
+                    return Ok(());
+                }
+
+                // order entries by their display, as records are unordered
+                let mut entries = (record.iter())
+                    .map(|(key, value)| {
+                        format!("{}: {}", DisplayKey(self, key), self.nested(value))
+                    })
+                    .collect::<Vec<_>>();
+                entries.sort();
+
+                write!(f, " {{ {} }}", entries.join(", "))
+            }
+            Value::List(list) => {
+                let list = match list.try_borrow() {
+                    Ok(list) => list,
+                    Err(_) => return write!(f, "list <borrowed>"),
+                };
+
+                write!(f, "list#{}", list.alloc_id)?;
+
+                if elided {
+                    return Ok(());
+                }
+
+                write!(f, " [")?;
+                self.elements(f, &list)?;
+                write!(f, "]")
+            }
+            Value::Tuple(tuple) => {
+                write!(f, "tuple#{}", tuple.alloc_id)?;
+
+                if elided {
+                    return Ok(());
+                }
+
+                write!(f, " (")?;
+                self.elements(f, tuple)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+struct DisplayKey<'a, 'b>(&'a DisplayValue<'b>, &'a RecordKey);
+
+impl Display for DisplayKey<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.key(f, self.1)
+    }
+}
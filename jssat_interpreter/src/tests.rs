@@ -1,4 +1,8 @@
-use jssat_ir::{frontend::builder::ProgramBuilder, lifted::lift};
+use jssat_ir::{
+    frontend::builder::ProgramBuilder,
+    id::{IdCompat, IrCtx},
+    lifted::{lift, FunctionId},
+};
 
 use super::*;
 use Value::*;
@@ -11,18 +15,53 @@ with_builtin_macros::with_builtin! {
     }
 }
 
-fn prepare() -> (Tests, Interpreter<'static>) {
+/// Maps the id of a function in the IR to the id of its entry block once
+/// lifted, as lifting does not preserve function ids.
+type FnIds = FxHashMap<jssat_ir::id::FunctionId<IrCtx>, FunctionId>;
+
+/// Lifts the tests and builds an interpreter for them, binding the tests, the
+/// interpreter and the ids of the functions to the given names. The program and
+/// builder that the interpreter borrows are bound in the caller's scope too.
+macro_rules! prepare {
+    ($tests: ident, $run: ident, $fn_ids: ident) => {
+        let ($tests, lifted, $fn_ids) = lift_tests();
+        let builder = InterpreterBuilder::new(&lifted);
+        let mut $run = builder.build();
+    };
+}
+
+fn lift_tests() -> (Tests, LiftedProgram, FnIds) {
     let mut builder = ProgramBuilder::new();
     builder.create_blank_entrypoint();
     let tests = Tests::new(&mut builder);
 
     let ir = builder.finish();
+    let entry_blocks = (ir.functions.iter())
+        .map(|(id, f)| (*id, f.entry_block))
+        .collect::<FxHashMap<_, _>>();
+
     let lifted = lift(ir);
-    let lifted = Box::leak(Box::new(lifted));
-    let run = InterpreterBuilder::new(lifted);
-    let run = Box::leak(Box::new(run));
-    let run = run.build();
-    (tests, run)
+    let fn_ids = (lifted.functions.iter())
+        .filter(|(_, f)| entry_blocks[&f.ir_fn_id] == f.ir_blk_id)
+        .map(|(id, f)| (f.ir_fn_id, *id))
+        .collect::<FnIds>();
+
+    (tests, lifted, fn_ids)
+}
+
+/// Parses a program written in the textual IR and lifts it.
+fn lift_text(source: &str) -> LiftedProgram {
+    lift(jssat_ir::frontend::parse_jssatir::parse(source).unwrap())
+}
+
+/// Finds the lifted function that the function of the IR named `name` begins
+/// with, which is the first of its blocks to be lifted.
+fn fn_id(lifted: &LiftedProgram, name: &str) -> FunctionId {
+    (lifted.functions.iter())
+        .filter(|(_, f)| f.name.as_deref() == Some(name))
+        .map(|(id, _)| *id)
+        .min_by_key(|id| id.value())
+        .unwrap()
 }
 
 macro_rules! list {
@@ -47,22 +86,902 @@ macro_rules! list {
 
 #[test]
 fn can_add() {
-    let (tests, mut run) = prepare();
+    prepare!(tests, run, fn_ids);
     let results = run
-        .execute_fn_id(tests.Add.id.map_context(), vec![Number(2), Number(3)])
+        .execute_fn_id(fn_ids[&tests.Add.id], vec![Number(2), Number(3)])
         .unwrap();
     assert!(matches!(results, Some(Number(5))));
+
+    // integers that overflow are promoted to floats, like other operators
+    let results = run
+        .execute_fn_id(fn_ids[&tests.Add.id], vec![Number(i64::MAX), Number(1)])
+        .unwrap();
+    assert!(matches!(results, Some(Float(x)) if x == i64::MAX as f64 + 1.0));
 }
 
 #[test]
 fn can_get() {
-    let (tests, mut run) = prepare();
+    prepare!(tests, run, fn_ids);
 
     let results = run
         .execute_fn_id(
-            tests.GetList.id.map_context(),
+            fn_ids[&tests.GetList.id],
             vec![list![Number(1), Number(2), Number(3)], Number(1)],
         )
         .unwrap();
     assert!(matches!(results, Some(Number(2))));
 }
+
+#[test]
+fn integer_results_that_are_not_integers_become_floats() {
+    let lifted = lift_text(
+        r#"entrypoint @0;
+
+fn @0 "multiply"(%0, %1, ) {
+  @0.$0():
+    %2 = BinOp Multiply %0, %1
+    Return %2;
+}
+
+fn @1 "remainder"(%0, %1, ) {
+  @1.$0():
+    %2 = BinOp Remainder %0, %1
+    Return %2;
+}
+
+fn @2 "negate"(%0, ) {
+  @2.$0():
+    %1 = Negate %0;
+    Return %1;
+}
+"#,
+    );
+
+    let builder = InterpreterBuilder::new(&lifted);
+    let mut run = builder.build();
+    let mut call = |name, args| run.execute_fn_id(fn_id(&lifted, name), args).unwrap();
+
+    let negative_zero = |value| matches!(value, Some(Float(x)) if x == 0.0 && x.is_sign_negative());
+    assert!(negative_zero(call("multiply", vec![Number(0), Number(-5)])));
+    assert!(negative_zero(call(
+        "remainder",
+        vec![Number(-5), Number(5)]
+    )));
+    assert!(negative_zero(call("negate", vec![Number(0)])));
+
+    let negated = call("negate", vec![Number(i64::MIN)]);
+    assert!(matches!(negated, Some(Float(x)) if x == -(i64::MIN as f64)));
+    assert!(matches!(call("negate", vec![Number(5)]), Some(Number(-5))));
+}
+
+#[test]
+fn can_add_floats() {
+    prepare!(tests, run, fn_ids);
+    let results = run
+        .execute_fn_id(fn_ids[&tests.AddHalf.id], vec![Number(1)])
+        .unwrap();
+    assert!(matches!(results, Some(Float(x)) if x == 1.5));
+}
+
+#[test]
+fn same_value_follows_number_semantics() {
+    prepare!(tests, run, fn_ids);
+
+    let mut same_value = |x, y| {
+        let result = run
+            .execute_fn_id(fn_ids[&tests.SameValue.id], vec![x, y])
+            .unwrap();
+        result.unwrap().try_into_boolean().unwrap()
+    };
+
+    assert!(same_value(Float(f64::NAN), Float(f64::NAN)));
+    assert!(!same_value(Float(0.0), Float(-0.0)));
+    assert!(same_value(Number(1), Float(1.0)));
+}
+
+#[test]
+fn arithmetic_follows_number_semantics() {
+    prepare!(tests, run, fn_ids);
+
+    let mut call = |id, x| {
+        run.execute_fn_id(fn_ids[&id], vec![x])
+            .unwrap()
+            .unwrap()
+            .as_f64()
+            .unwrap()
+    };
+
+    assert_eq!(call(tests.Halve.id, Number(4)), 2.0);
+    assert_eq!(call(tests.Halve.id, Number(3)), 1.5);
+    assert_eq!(call(tests.ToUint32.id, Number(-1)), 4294967295.0);
+    assert_eq!(call(tests.ToUint32.id, Float(f64::NAN)), 0.0);
+}
+
+#[test]
+fn bigints_are_arbitrary_precision() {
+    prepare!(tests, run, fn_ids);
+    let square = fn_ids[&tests.BigIntSquare.id];
+
+    let results = run.execute_fn_id(square, vec![Number(1 << 32)]).unwrap();
+    assert!(matches!(results, Some(BigInt(x)) if x.to_string() == "18446744073709551616"));
+
+    // only integral numbers can be converted to a BigInt
+    assert!(run.execute_fn_id(square, vec![Float(0.5)]).is_err());
+}
+
+#[test]
+fn strings_are_utf16_code_units() {
+    prepare!(tests, run, fn_ids);
+    let string = |x| Bytes(isa::encode_utf16(x));
+
+    let mut call = |id, args| run.execute_fn_id(fn_ids[&id], args);
+
+    let roundtrip = tests.StringRoundtrip.id;
+    assert!(matches!(
+        call(roundtrip, vec![Number(-42)]),
+        Ok(Some(Number(-42)))
+    ));
+    assert!(matches!(call(roundtrip, vec![Float(0.1)]), Ok(Some(Float(x))) if x == 0.1));
+    assert!(matches!(call(roundtrip, vec![Float(1e21)]), Ok(Some(Float(x))) if x == 1e21));
+
+    let greeting = call(tests.Greet.id, vec![string("world")]).unwrap();
+    assert!(matches!(greeting, Some(Bytes(x)) if x == isa::encode_utf16("hello, worl")));
+
+    let code_unit_at = tests.CodeUnitAt.id;
+    assert!(matches!(
+        call(code_unit_at, vec![string("é"), Number(0)]),
+        Ok(Some(Number(0xE9)))
+    ));
+    assert!(call(code_unit_at, vec![string("é"), Number(1)]).is_err());
+}
+
+#[test]
+fn tuples_are_fixed_size() {
+    prepare!(tests, run, fn_ids);
+
+    let swapped = run
+        .execute_fn_id(fn_ids[&tests.Swap.id], vec![Number(1), Boolean(true)])
+        .unwrap();
+    let swapped = swapped.unwrap();
+    let swapped = swapped.try_into_tuple().unwrap();
+    assert!(matches!(swapped.as_slice(), [Boolean(true), Number(1)]));
+
+    let pair = Value::Tuple(crate::Tuple::new_gc(0, vec![Number(1), Number(2)]));
+    let len = run
+        .execute_fn_id(fn_ids[&tests.TupleLen.id], vec![pair])
+        .unwrap();
+    assert!(matches!(len, Some(Number(2))));
+}
+
+#[test]
+fn switch_takes_matching_case() {
+    let lifted = lift_text(
+        r#"entrypoint @0;
+
+atom 1 = "Key";
+
+fn @0(%0, ) {
+  @0.$0():
+    Switch %0:
+     case atom 1 => $1();
+     case -3 => $2(%0);
+     case true => $3();
+else $3();
+  @0.$1():
+    %1 = MakeInteger 1;
+    Return %1;
+  @0.$2(%2, ):
+    Return %2;
+  @0.$3():
+    %3 = MakeInteger 3;
+    Return %3;
+}
+"#,
+    );
+
+    let builder = InterpreterBuilder::new(&lifted);
+    let mut run = builder.build();
+
+    let mut switch = |value| run.execute_fn_id(lifted.entrypoint, vec![value]);
+
+    let key = Value::Atom(isa::Atom(std::num::NonZeroU16::new(1).unwrap()));
+    assert!(matches!(switch(key), Ok(Some(Number(1)))));
+    assert!(matches!(switch(Number(-3)), Ok(Some(Number(-3)))));
+    assert!(matches!(switch(Boolean(true)), Ok(Some(Number(3)))));
+    assert!(matches!(switch(Number(7)), Ok(Some(Number(3)))));
+
+    // integral floats are switched on as the integer they're equal to
+    assert!(matches!(switch(Float(-3.0)), Ok(Some(Float(f))) if f == -3.0));
+
+    // only atoms, integers and booleans can be switched on
+    assert!(switch(Float(0.5)).is_err());
+}
+
+#[test]
+fn external_functions_are_checked_against_their_signature() {
+    let lifted = lift_text(
+        r#"entrypoint @0;
+
+ext fn @@0 "scale"(I64, F64, ) -> F64;
+
+ext fn @@1 "find"(Bool, ) -> Nullable(I64);
+
+fn @0 "scale"(%0, %1, ) {
+  @0.$0():
+    %2 = CallExtern @@0(%0, %1)
+    Return %2;
+}
+
+fn @1 "find"(%0, ) {
+  @1.$0():
+    %1 = CallExtern @@1(%0)
+    Return %1;
+}
+"#,
+    );
+
+    let (scale, find) = (fn_id(&lifted, "scale"), fn_id(&lifted, "find"));
+
+    let mut builder = InterpreterBuilder::new(&lifted);
+    builder
+        .with_declared_ext_fn("scale", |args| match args.as_slice() {
+            [Number(x), y] => Ok(Some(Float(*x as f64 * y.as_f64().unwrap()))),
+            _ => unreachable!(),
+        })
+        .unwrap();
+    builder
+        .with_declared_ext_fn("find", |args| match args.as_slice() {
+            [Boolean(true)] => Ok(Some(Number(1))),
+            _ => Ok(None),
+        })
+        .unwrap();
+    assert!(matches!(
+        builder.with_declared_ext_fn("missing", |_| Ok(None)),
+        Err(BuildError::UndeclaredExtFn(name)) if name == "missing"
+    ));
+    let mut run = builder.build();
+
+    let scaled = run.execute_fn_id(scale, vec![Number(3), Float(0.5)]);
+    assert!(matches!(scaled, Ok(Some(Float(x))) if x == 1.5));
+    assert!(run
+        .execute_fn_id(scale, vec![Float(3.0), Number(1)])
+        .is_err());
+
+    // nullable results are tuples, which are empty if the result is null
+    let found = run.execute_fn_id(find, vec![Boolean(true)]).unwrap();
+    let found = found.unwrap();
+    let found = found.try_into_tuple().unwrap();
+    assert!(matches!(found.as_slice(), [Number(1)]));
+
+    let found = run.execute_fn_id(find, vec![Boolean(false)]).unwrap();
+    assert!(found.unwrap().try_into_tuple().unwrap().is_empty());
+}
+
+#[test]
+fn jumps_do_not_push_frames() {
+    let lifted = lift_text(
+        r#"entrypoint @0;
+
+fn @0 "count"(%0, ) {
+  @0.$0():
+    %1 = MakeInteger 0;
+    Jump $1(%1)
+  @0.$1(%2, ):
+    %3 = BinOp LessThan %2, %0
+    If %3:
+     $2(%2);
+else $3(%2);
+  @0.$2(%4, ):
+    %5 = MakeInteger 1;
+    %6 = BinOp Add %4, %5
+    Jump $1(%6)
+  @0.$3(%7, ):
+    Return %7;
+}
+
+fn @1 "recurse"(%0, ) {
+  @1.$0():
+    %1 = CallStatic @1(%0)
+    Return %1;
+}
+"#,
+    );
+
+    let (count, recurse) = (fn_id(&lifted, "count"), fn_id(&lifted, "recurse"));
+
+    let mut builder = InterpreterBuilder::new(&lifted);
+    let mut run = builder.with_max_depth(100).build();
+
+    let counted = run.execute_fn_id(count, vec![Number(10_000)]);
+    assert!(matches!(counted, Ok(Some(Number(10_000)))));
+
+    let recursed = run.execute_fn_id(recurse, vec![Number(0)]);
+    assert!(matches!(recursed, Err(StackOverflow(100, _))));
+
+    // every block of the loop is executed as the same call
+    let data = run.collector.into_data(lifted.dealer.clone());
+    let counting = (data.snapshots.iter()).filter(|s| s.frame.function != recurse);
+    assert!(counting.into_iter().all(|s| s.frame.parent.is_none()));
+}
+
+#[test]
+fn limits_stop_execution() {
+    let lifted = lift_text(
+        r#"entrypoint @0;
+
+atom 1 = "Next";
+
+fn @0 "spin"() {
+  @0.$0():
+    %0 = NewRecord;
+    Jump $1(%0)
+  @0.$1(%1, ):
+    %2 = NewRecord;
+    RecordSet %2.[[1]] = %1
+    Jump $1(%2)
+}
+
+fn @1 "main"() {
+  @1.$0():
+    CallStatic @0()
+    Return;
+}
+"#,
+    );
+
+    let main = fn_id(&lifted, "main");
+
+    let run = |configure: fn(&mut InterpreterBuilder)| {
+        let mut builder = InterpreterBuilder::new(&lifted);
+        configure(&mut builder);
+        let result = builder.build().execute_fn_id(main, vec![]);
+
+        match result {
+            Err(LimitExceeded(limit, stack, _)) => {
+                assert_eq!(stack[0].function, main);
+                (limit, stack.len())
+            }
+            _ => panic!("expected a limit to be exceeded"),
+        }
+    };
+
+    // `main` is calling `spin` when it runs out of fuel or allocations
+    let limit = run(|b| {
+        b.with_fuel(100);
+    });
+    assert_eq!(limit, (Limit::Fuel(100), 2));
+
+    let limit = run(|b| {
+        b.with_max_allocations(100);
+    });
+    assert_eq!(limit, (Limit::Allocations(100), 2));
+
+    let limit = run(|b| {
+        b.with_deadline(std::time::Instant::now());
+    });
+    assert_eq!(limit, (Limit::Deadline, 1));
+}
+
+#[test]
+fn allocation_limits_count_values_alive() {
+    let lifted = lift_text(
+        r#"entrypoint @0;
+
+fn @0 "churn"(%0, ) {
+  @0.$0():
+    %1 = MakeInteger 0;
+    Jump $1(%1)
+  @0.$1(%2, ):
+    %3 = BinOp LessThan %2, %0
+    If %3:
+     $2(%2);
+else $3(%2);
+  @0.$2(%4, ):
+    %5 = NewRecord;
+    %6 = MakeInteger 1;
+    %7 = BinOp Add %4, %6
+    Jump $1(%7)
+  @0.$3(%8, ):
+    Return %8;
+}
+"#,
+    );
+
+    let churn = fn_id(&lifted, "churn");
+
+    // every record is garbage by the time the next is allocated
+    let mut builder = InterpreterBuilder::new(&lifted);
+    builder.with_max_allocations(10);
+    let mut run = builder.build_with_collector(NilCollector);
+
+    let result = run.execute_fn_id(churn, vec![Number(1000)]).unwrap();
+    assert!(matches!(result, Some(Number(1000))));
+}
+
+#[test]
+fn collectors_select_moments() {
+    let lifted = lift_text(
+        r#"entrypoint @0;
+
+fn @0 "main"() {
+  @0.$0():
+    %0 = MakeInteger 1;
+    %1 = CallStatic @1(%0)
+    %2 = CallStatic @1(%1)
+    Return %2;
+}
+
+fn @1 "inner"(%0, ) {
+  @1.$0():
+    %1 = BinOp Add %0, %0
+    Return %1;
+}
+"#,
+    );
+
+    let entrypoint = lifted.entrypoint;
+    let builder = InterpreterBuilder::new(&lifted);
+
+    let mut run = builder.build_with_collector(NilCollector);
+    assert!(matches!(
+        run.execute_fn_id(entrypoint, vec![]),
+        Ok(Some(Number(4)))
+    ));
+
+    let mut run = builder.build();
+    run.execute_fn_id(entrypoint, vec![]).unwrap();
+    let all = run.collector.into_data(lifted.dealer.clone()).snapshots;
+
+    let mut run = builder.build_with_collector(SectionCollector::new(&lifted, &["inner"]));
+    run.execute_fn_id(entrypoint, vec![]).unwrap();
+    let inner = (run.collector.moment)
+        .into_data(lifted.dealer.clone())
+        .snapshots;
+
+    // `inner` is recorded as if it were called on its own
+    let is_inner = |s: &domino::Snapshot| s.code.fn_name.as_deref() == Some("inner");
+    assert_eq!(inner.len(), all.iter().filter(|s| is_inner(s)).count());
+    assert!(inner
+        .iter()
+        .all(|s| is_inner(s) && s.frame.parent.is_none()));
+
+    let mut run = builder.build_with_collector(SampledCollector::new(&lifted, 2));
+    run.execute_fn_id(entrypoint, vec![]).unwrap();
+    let sampled = (run.collector.moment)
+        .into_data(lifted.dealer.clone())
+        .snapshots;
+    assert!(!sampled.is_empty() && sampled.len() < all.len());
+}
+
+#[test]
+fn debugger_pauses_at_breakpoints_and_steps() {
+    let lifted = lift_text(
+        r#"entrypoint @0;
+
+fn @0 "main"() {
+  @0.$0():
+    %0 = MakeInteger 1;
+    %1 = CallStatic @1(%0)
+    %2 = CallStatic @1(%1)
+    Return %2;
+}
+
+fn @1 "Double"(%0, ) {
+  @1.$0():
+    -- "1.2.3 Double ( x )", jssat_interpreter/src/tests.rs:1:1
+    %1 = BinOp Add %0, %0
+    Return %1;
+}
+"#,
+    );
+
+    let entrypoint = lifted.entrypoint;
+    let builder = InterpreterBuilder::new(&lifted);
+    let mut run = builder.build_with_collector(NilCollector);
+    let mut debugger = run.debug(entrypoint, vec![]).unwrap();
+
+    let name = |debugger: &Debugger<_>| debugger.frame().unwrap().name.unwrap().to_owned();
+    let param = |debugger: &Debugger<_>| {
+        let frame = debugger.frame().unwrap();
+        frame.registers()[0].1.clone()
+    };
+
+    // only pauses in the second call, where `x` is 2
+    let section = debugger.add_breakpoint(
+        Breakpoint::new(BreakAt::Section(":1.2.3".into()))
+            .when(|frame| matches!(frame.registers()[0].1, Number(2))),
+    );
+    let stop = debugger.resume(Resume::Continue).unwrap();
+    assert!(matches!(stop, Stop::Breakpoint(i) if i == section));
+    assert!(matches!(param(&debugger), Number(2)));
+    assert_eq!(debugger.frames().len(), 2);
+
+    debugger.resume(Resume::StepOut).unwrap();
+    assert_eq!(name(&debugger), "main");
+    assert!(matches!(
+        debugger.resume(Resume::StepOver),
+        Ok(Stop::Finished(Some(Number(4))))
+    ));
+
+    // step through from the start
+    let mut run = builder.build_with_collector(NilCollector);
+    let mut debugger = run.debug(entrypoint, vec![]).unwrap();
+    let function = debugger.add_breakpoint(Breakpoint::new(BreakAt::Function("Double".into())));
+
+    assert!(matches!(debugger.resume(Resume::Continue), Ok(Stop::Breakpoint(i)) if i == function));
+    assert!(matches!(param(&debugger), Number(1)));
+
+    debugger.remove_breakpoint(function);
+    debugger.resume(Resume::StepOut).unwrap();
+    assert_eq!(debugger.frame().unwrap().inst_idx, 2);
+
+    debugger.resume(Resume::StepIn).unwrap();
+    assert_eq!(name(&debugger), "Double");
+
+    // returning from the last call leaves `main` at its end instruction
+    debugger.resume(Resume::StepOut).unwrap();
+    assert!(debugger.frame().unwrap().instruction().is_none());
+    assert!(matches!(
+        debugger.resume(Resume::StepOver),
+        Ok(Stop::Finished(Some(Number(4))))
+    ));
+}
+
+#[test]
+fn errors_are_reported_with_the_call_stack() {
+    let lifted = lift_text(
+        r#"entrypoint @0;
+
+atom 1 = "Value";
+atom 2 = "Type";
+
+fn @0 "main"() {
+  @0.$0():
+    %0 = NewRecord;
+    %1 = MakeAtom 1;
+    RecordSet %0.[[1]] = %1
+    %2 = CallStatic @1(%0)
+    Return %2;
+}
+
+fn @1 "GetType"(%0, ) {
+  @1.$0():
+    %1 = RecordGet %0.[[2]];
+    Return %1;
+}
+"#,
+    );
+
+    let dealer = lifted.dealer.clone();
+    let builder = InterpreterBuilder::new(&lifted);
+    let mut run = builder.build_with_collector(NilCollector);
+
+    let report = (run.execute_fn_id_with_report(lifted.entrypoint, vec![]))
+        .expect_err("record does not have the key");
+
+    assert!(matches!(report.error, RecordDNCKey(..)));
+
+    let stack = (report.stack.iter())
+        .map(|frame| (frame.name.as_deref(), frame.inst_idx))
+        .collect::<Vec<_>>();
+    assert_eq!(stack, [(Some("main"), 3), (Some("GetType"), 0)]);
+
+    let sources = ReportSources {
+        dealer: Some(&dealer),
+        ..Default::default()
+    };
+
+    let report = report.display(sources).to_string();
+    let mut lines = report.lines();
+
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("error: Record does not contain key"));
+    assert!(lines.next().unwrap().starts_with("  in GetType"));
+    assert!(lines
+        .next()
+        .unwrap()
+        .ends_with(" = record#0 { :Value: :Value }"));
+    assert!(lines.next().unwrap().starts_with("  in main"));
+    assert!(lines
+        .next()
+        .unwrap()
+        .ends_with(" = record#0 { :Value: :Value }"));
+    assert_eq!(lines.next(), None);
+}
+
+#[test]
+fn frames_record_the_parse_node_they_evaluate() {
+    let lifted = lift_text(
+        r#"entrypoint @0;
+
+atom 1 = "SourceMapIdx";
+atom 2 = "Missing";
+
+fn @0 "main"() {
+  @0.$0():
+    %0 = NewRecord;
+    %1 = MakeInteger 7;
+    RecordSet %0.[[1]] = %1
+    %2 = NewRecord;
+    %3 = CallStatic @1(%2, %0)
+    Return %3;
+}
+
+fn @1 "Evaluate"(%0, %1, ) {
+  @1.$0():
+    %2 = CallStatic @2(%0)
+    Return %2;
+}
+
+fn @2 "Fail"(%0, ) {
+  @2.$0():
+    %1 = RecordGet %0.[[2]];
+    Return %1;
+}
+"#,
+    );
+
+    let key = (lifted.dealer.iter())
+        .find(|(_, name)| *name == "SourceMapIdx")
+        .map(|(atom, _)| atom)
+        .unwrap();
+    let mut builder = InterpreterBuilder::new(&lifted);
+    let mut run = builder.with_parse_node_key(key).build();
+
+    let report = (run.execute_fn_id_with_report(lifted.entrypoint, vec![]))
+        .expect_err("record does not have the key");
+
+    // helpers called while evaluating a parse node keep evaluating it
+    let stack = (report.stack.iter())
+        .map(|frame| (frame.name.as_deref(), frame.js_source_idx.map(|idx| idx.0)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        stack,
+        [
+            (Some("main"), None),
+            (Some("Evaluate"), Some(7)),
+            (Some("Fail"), Some(7))
+        ]
+    );
+
+    let data = run.collector.into_data(lifted.dealer.clone());
+    let last = data.snapshots.last().unwrap();
+    assert_eq!(last.frame.js_source_idx.map(|idx| idx.0), Some(7));
+}
+
+#[test]
+fn profiler_counts_functions_and_sections() {
+    let lifted = lift_text(
+        r#"entrypoint @0;
+
+fn @0 "main"() {
+  @0.$0():
+    %0 = MakeInteger 1;
+    %1 = CallStatic @1(%0)
+    %2 = CallStatic @1(%1)
+    Return %2;
+}
+
+fn @1 "Double"(%0, ) {
+  @1.$0():
+    -- "1.2.3 Double ( x )", jssat_interpreter/src/tests.rs:1:1
+    %1 = NewRecord;
+    Jump $1(%0)
+  @1.$1(%2, ):
+    %3 = BinOp Add %2, %2
+    Return %3;
+}
+"#,
+    );
+
+    let builder = InterpreterBuilder::new(&lifted);
+    let mut run = builder.build_with_collector(Profiler::new(&lifted));
+    run.execute_fn_id(lifted.entrypoint, vec![]).unwrap();
+
+    let profiler = &run.collector;
+
+    let main = &profiler.sections["main"];
+    assert_eq!((main.instructions, main.calls, main.allocations), (4, 1, 0));
+
+    let double = &profiler.sections["1.2.3 Double ( x )"];
+    assert_eq!(
+        (double.instructions, double.calls, double.allocations),
+        (10, 2, 2)
+    );
+    assert!(main.total_time >= double.total_time);
+
+    // the blocks of `Double` are profiled separately
+    let mut functions = (profiler.functions.values())
+        .map(|profile| (profile.instructions, profile.calls))
+        .collect::<Vec<_>>();
+    functions.sort_unstable();
+    assert_eq!(functions, [(4, 1), (4, 2), (6, 2)]);
+
+    assert_eq!(
+        profiler.folded_stacks(Metric::Instructions),
+        "main 4\nmain;1.2.3 Double ( x ) 10\n"
+    );
+
+    let table = profiler.section_table();
+    assert_eq!(table.lines().count(), 3);
+    assert!(table.lines().next().unwrap().starts_with("section"));
+}
+
+#[test]
+fn coverage_is_resolved_against_ir_files() {
+    let (tests, lifted, fn_ids) = lift_tests();
+    let builder = InterpreterBuilder::new(&lifted);
+    let mut run = builder.build_with_collector(CoverageCollector::new(&lifted));
+
+    for n in [2, 4] {
+        let result = run.execute_fn_id(fn_ids[&tests.CountTo.id], vec![Number(n)]);
+        assert!(matches!(result, Ok(Some(Number(x))) if x == n));
+    }
+
+    let source_map = tests.source_map.try_into();
+    let coverage = run.collector.coverage(&source_map);
+
+    let count_to = (coverage.sections.iter())
+        .find(|section| section.name == "0 CountTo ( n )")
+        .unwrap();
+    assert_eq!(count_to.hits, 2);
+    assert_eq!(count_to.statements.len(), 3);
+    assert!(count_to.statements.iter().all(|s| s.hits > 0));
+
+    let arms = (count_to.branches.iter())
+        .map(|branch| (branch.kind, branch.arms))
+        .collect::<Vec<_>>();
+    assert_eq!(arms, [(BranchKind::Loop, [6, 2]), (BranchKind::If, [1, 5])]);
+
+    // branches produced by rules are covered
+    for x in [-1, 1] {
+        let result = run.execute_fn_id(fn_ids[&tests.IsNegative.id], vec![Number(x)]);
+        assert!(matches!(result, Ok(Some(Boolean(b))) if b == (x < 0)));
+    }
+
+    let coverage = run.collector.coverage(&source_map);
+    let is_negative = (coverage.sections.iter())
+        .find(|section| section.name == "0 IsNegative ( x )")
+        .unwrap();
+    assert_eq!(is_negative.branches.len(), 1);
+    assert_eq!(is_negative.branches[0].arms, [1, 1]);
+
+    let add = (coverage.sections.iter())
+        .find(|section| section.name == "0 Add ( x, y )")
+        .unwrap();
+    assert_eq!(add.hits, 0);
+
+    let lcov = coverage.lcov("Tests.lisp");
+    assert!(lcov.starts_with("TN:\nSF:Tests.lisp\n"));
+    assert!(lcov.contains("FNDA:2,0 CountTo ( n )\n"));
+    assert!(lcov.contains("FNDA:0,0 Add ( x, y )\n"));
+    assert!(lcov.ends_with("end_of_record\n"));
+}
+
+#[test]
+fn heap_snapshots_restore_independent_copies() {
+    let lifted = lift_text(
+        r#"entrypoint @0;
+
+atom 1 = "Self";
+atom 2 = "Count";
+atom 3 = "Items";
+
+fn @0 "init"() {
+  @0.$0():
+    %0 = NewRecord;
+    RecordSet %0.[[1]] = %0
+    %1 = MakeInteger 0;
+    RecordSet %0.[[2]] = %1
+    %2 = NewList;
+    ListSet %2.[%1] = %0
+    %3 = NewTuple (%2, %1);
+    RecordSet %0.[[3]] = %3
+    Return %0;
+}
+
+fn @1 "increment"(%0, ) {
+  @1.$0():
+    %1 = RecordGet %0.[[2]];
+    %2 = MakeInteger 1;
+    %3 = BinOp Add %1, %2
+    RecordSet %0.[[2]] = %3
+    %4 = NewRecord;
+    Return %4;
+}
+"#,
+    );
+
+    let (init, increment) = (fn_id(&lifted, "init"), fn_id(&lifted, "increment"));
+
+    let builder = InterpreterBuilder::new(&lifted);
+    let atom = |n| RecordKey::Atom(isa::Atom(std::num::NonZeroU16::new(n).unwrap()));
+    let count = |realm: &Value| realm.try_into_record().unwrap()[&atom(2)].clone();
+
+    let mut run = builder.build();
+    let realm = run.execute_fn_id(init, vec![]).unwrap().unwrap();
+    let snapshot = run.snapshot_heap(std::slice::from_ref(&realm));
+    assert_eq!(snapshot.allocations(), 3);
+
+    let mut forked = builder.build();
+    let restored = forked.restore_heap(&snapshot).remove(0);
+
+    // cycles are restored as cycles, with the same allocation ids
+    let is_restored = |value: &Value| match (value, &restored) {
+        (Record(a), Record(b)) => Gc::ptr_eq(a, b),
+        _ => false,
+    };
+
+    {
+        let record = restored.try_into_record().unwrap();
+        assert_eq!(record.alloc_id, 0);
+        assert!(is_restored(&record[&atom(1)]));
+
+        let items = record[&atom(3)].try_into_tuple().unwrap();
+        assert!(is_restored(&items[0].try_into_list().unwrap()[0]));
+    }
+
+    // new allocations come after the ones in the snapshot
+    let allocated = forked.execute_fn_id(increment, vec![restored.clone()]);
+    let allocated = allocated.unwrap().unwrap();
+    assert_eq!(allocated.try_into_record().unwrap().alloc_id, 3);
+    assert!(matches!(count(&restored), Number(1)));
+
+    // neither the original nor other restores observe the mutation
+    let mut other = builder.build();
+    let other = other.restore_heap(&snapshot).remove(0);
+    assert!(matches!(count(&other), Number(0)));
+    assert!(matches!(count(&realm), Number(0)));
+}
+
+#[test]
+fn malformed_programs_are_errors() {
+    use jssat_ir::frontend::ir::InstructionData;
+
+    let mut lifted = lift_text(
+        r#"entrypoint @0;
+
+fn @0 "reassign"() {
+  @0.$0():
+    %0 = MakeInteger 1;
+    %1 = MakeInteger 2;
+    Return %1;
+}
+
+fn @1 "call"() {
+  @1.$0():
+    %0 = CallStatic @0()
+    Return %0;
+}
+"#,
+    );
+
+    // the verifier rejects malformed programs when lifting, so the programs
+    // are broken once lifted
+    let (reassign, call) = (fn_id(&lifted, "reassign"), fn_id(&lifted, "call"));
+
+    let instructions = &mut lifted.functions.get_mut(&reassign).unwrap().instructions;
+    let first = instructions[0].assigned_to().unwrap();
+    if let InstructionData::MakeInteger(make) = &mut instructions[1].data {
+        make.result = first;
+    }
+
+    let instructions = &mut lifted.functions.get_mut(&call).unwrap().instructions;
+    if let InstructionData::CallStatic(call) = &mut instructions[0].data {
+        call.args.push(RegisterId::new_with_value(100));
+    }
+
+    let builder = InterpreterBuilder::new(&lifted);
+    let mut run = builder.build();
+
+    let reassigned = run.execute_fn_id(reassign, vec![]);
+    assert!(matches!(reassigned, Err(SSAViolation(r, _)) if r == first));
+
+    let called = run.execute_fn_id(call, vec![]);
+    assert!(matches!(called, Err(SnapshotRegisterDNE(r, _)) if r.value() == 100));
+
+    let unknown = run.execute_fn_id(FunctionId::new_with_value(100), vec![]);
+    assert!(matches!(unknown, Err(FunctionDNE(f, _)) if f.value() == 100));
+}
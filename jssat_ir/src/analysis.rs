@@ -0,0 +1,629 @@
+//! Control flow and data flow analyses over [`LiftedProgram`]s.
+//!
+//! Every lifted function is a single block of the [`IR`] function it was
+//! lifted from, and jumps between blocks are jumps between lifted functions.
+//! The [`ControlFlow`] of a function is the graph of lifted functions reachable
+//! through jumps from the lifted function that is called, i.e. the entry block.
+//! Registers keep their ids across the blocks of a function, so analyses are
+//! performed over the whole [`ControlFlow`].
+//!
+//! Analyses return a [`MissingFunction`] if they come across a jump or call to
+//! a function that is not part of the program. Programs that pass
+//! [`crate::verify`] never do.
+//!
+//! [`ControlFlow`] and [`CallGraph`] only need to know which functions jump to
+//! and call one another, which programs other than [`LiftedProgram`]s can tell
+//! them through [`ProgramFlow`].
+//!
+//! [`IR`]: crate::frontend::ir::IR
+
+use std::fmt::{Debug, Display};
+
+use derive_more::Display;
+use petgraph::algo::dominators::{self, Dominators};
+use petgraph::graphmap::{DiGraphMap, NodeTrait};
+use petgraph::visit::{Dfs, Walker};
+use petgraph::Direction;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::frontend::ir::InstructionData;
+use crate::isa::Return;
+use crate::lifted::{EndInstruction, Function, FunctionId, LiftedProgram, RegisterId};
+
+/// A function that was jumped to or called, but is not part of the program.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+#[display(fmt = "@{} is not part of the program", _0)]
+pub struct MissingFunction<I = FunctionId>(pub I);
+
+impl<I: Debug + Display> std::error::Error for MissingFunction<I> {}
+
+fn find_function(program: &LiftedProgram, id: FunctionId) -> Result<&Function, MissingFunction> {
+    program.functions.get(&id).ok_or(MissingFunction(id))
+}
+
+/// The functions called by a function, and how they're called.
+pub type Calls<I> = Result<Vec<(I, CallKind)>, MissingFunction<I>>;
+
+/// A program of blocks that jump to one another, and functions that call one
+/// another, where a function is identified by its entry block.
+pub trait ProgramFlow {
+    type FunctionId: NodeTrait;
+
+    fn entrypoint(&self) -> Self::FunctionId;
+
+    /// The blocks that a block jumps to, or [`None`] if the block is not part
+    /// of the program.
+    fn jumps(&self, block: Self::FunctionId) -> Option<Vec<Self::FunctionId>>;
+
+    /// The functions called by the blocks of a function, and how they're
+    /// called.
+    fn calls(&self, control_flow: &ControlFlow<Self::FunctionId>) -> Calls<Self::FunctionId>;
+
+    /// The functions that the blocks of a function take pointers to.
+    fn fn_ptrs(
+        &self,
+        control_flow: &ControlFlow<Self::FunctionId>,
+    ) -> Result<Vec<Self::FunctionId>, MissingFunction<Self::FunctionId>>;
+}
+
+impl ProgramFlow for LiftedProgram {
+    type FunctionId = FunctionId;
+
+    fn entrypoint(&self) -> FunctionId {
+        self.entrypoint
+    }
+
+    fn jumps(&self, block: FunctionId) -> Option<Vec<FunctionId>> {
+        let function = self.functions.get(&block)?;
+        Some(
+            function
+                .end
+                .paths()
+                .into_iter()
+                .map(|path| path.0)
+                .collect(),
+        )
+    }
+
+    fn calls(&self, control_flow: &ControlFlow) -> Calls<FunctionId> {
+        let fn_ptrs = resolve_fn_ptrs(self, control_flow)?;
+        let mut calls = Vec::new();
+
+        for block in control_flow.blocks() {
+            let function = find_function(self, block)?;
+
+            for inst in function.instructions.iter() {
+                match &inst.data {
+                    InstructionData::CallStatic(call) => {
+                        calls.push((call.calling, CallKind::Static));
+                    }
+                    InstructionData::CallVirt(call) => {
+                        if let Some(callee) = fn_ptrs.get(&call.calling) {
+                            calls.push((*callee, CallKind::Virtual));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(calls)
+    }
+
+    fn fn_ptrs(&self, control_flow: &ControlFlow) -> Result<Vec<FunctionId>, MissingFunction> {
+        let mut fn_ptrs = Vec::new();
+
+        for block in control_flow.blocks() {
+            let function = find_function(self, block)?;
+
+            for inst in function.instructions.iter() {
+                if let InstructionData::GetFnPtr(make) = &inst.data {
+                    fn_ptrs.push(make.item);
+                }
+            }
+        }
+
+        Ok(fn_ptrs)
+    }
+}
+
+/// The graph of blocks of a function, where an edge is a jump from one block to
+/// another.
+#[derive(Debug, Clone)]
+pub struct ControlFlow<I: NodeTrait = FunctionId> {
+    pub entry: I,
+    pub graph: DiGraphMap<I, ()>,
+}
+
+impl<I: NodeTrait> ControlFlow<I> {
+    /// Builds the control flow of the function with the given entry block.
+    pub fn new<P>(program: &P, entry: I) -> Result<Self, MissingFunction<I>>
+    where
+        P: ProgramFlow<FunctionId = I>,
+    {
+        let mut graph = DiGraphMap::new();
+        graph.add_node(entry);
+
+        let mut queue = vec![entry];
+        while let Some(block) = queue.pop() {
+            let jumps = program.jumps(block).ok_or(MissingFunction(block))?;
+
+            for target in jumps {
+                if !graph.contains_node(target) {
+                    queue.push(target);
+                }
+
+                graph.add_edge(block, target, ());
+            }
+        }
+
+        Ok(Self { entry, graph })
+    }
+
+    /// The blocks of the function, in depth first order from the entry block.
+    pub fn blocks(&self) -> impl Iterator<Item = I> + '_ {
+        Dfs::new(&self.graph, self.entry).iter(&self.graph)
+    }
+
+    pub fn successors(&self, block: I) -> impl Iterator<Item = I> + '_ {
+        self.graph.neighbors_directed(block, Direction::Outgoing)
+    }
+
+    pub fn predecessors(&self, block: I) -> impl Iterator<Item = I> + '_ {
+        self.graph.neighbors_directed(block, Direction::Incoming)
+    }
+
+    pub fn dominators(&self) -> Dominators<I> {
+        dominators::simple_fast(&self.graph, self.entry)
+    }
+}
+
+/// Where in a function a register is defined or used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Location {
+    pub block: FunctionId,
+    pub site: Site,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Site {
+    /// The parameters of the block.
+    Parameter,
+    /// The instruction at the given index.
+    Instruction(usize),
+    /// The instruction at the end of the block.
+    End,
+}
+
+/// The definitions and uses of every register in a function.
+///
+/// As registers are passed along to blocks that need them, a register may be
+/// a parameter of many blocks and thus have many definitions.
+#[derive(Debug, Clone, Default)]
+pub struct DefUse {
+    pub definitions: FxHashMap<RegisterId, Vec<Location>>,
+    pub uses: FxHashMap<RegisterId, Vec<Location>>,
+}
+
+impl DefUse {
+    pub fn new(
+        program: &LiftedProgram,
+        control_flow: &ControlFlow,
+    ) -> Result<Self, MissingFunction> {
+        let mut def_use = DefUse::default();
+
+        for block in control_flow.blocks() {
+            let function = find_function(program, block)?;
+
+            let at = |site| Location { block, site };
+
+            for register in function.parameters.iter() {
+                def_use.define(*register, at(Site::Parameter));
+            }
+
+            for (idx, inst) in function.instructions.iter().enumerate() {
+                if let Some(register) = inst.assigned_to() {
+                    def_use.define(register, at(Site::Instruction(idx)));
+                }
+
+                for register in inst.used_registers() {
+                    def_use.used(register, at(Site::Instruction(idx)));
+                }
+            }
+
+            if let Some(register) = function.end.declared_register() {
+                def_use.define(register, at(Site::End));
+            }
+
+            for register in function.end.used_registers() {
+                def_use.used(register, at(Site::End));
+            }
+        }
+
+        Ok(def_use)
+    }
+
+    fn define(&mut self, register: RegisterId, location: Location) {
+        self.definitions.entry(register).or_default().push(location);
+    }
+
+    fn used(&mut self, register: RegisterId, location: Location) {
+        self.uses.entry(register).or_default().push(location);
+    }
+
+    pub fn definitions_of(&self, register: RegisterId) -> &[Location] {
+        self.definitions
+            .get(&register)
+            .map_or(&[], |d| d.as_slice())
+    }
+
+    pub fn uses_of(&self, register: RegisterId) -> &[Location] {
+        self.uses.get(&register).map_or(&[], |u| u.as_slice())
+    }
+
+    pub fn is_unused(&self, register: RegisterId) -> bool {
+        self.uses_of(register).is_empty()
+    }
+}
+
+/// The registers that are live when entering and leaving each block of a
+/// function. A register is live if its value may be used later on.
+///
+/// Blocks receive every register they use through their parameters, so the
+/// registers live when entering a block are the parameters it needs, and the
+/// registers live when leaving a block are those it passes along to parameters
+/// that its successors need. Parameters that are not live are unnecessary.
+#[derive(Debug, Clone, Default)]
+pub struct Liveness {
+    pub live_in: FxHashMap<FunctionId, FxHashSet<RegisterId>>,
+    pub live_out: FxHashMap<FunctionId, FxHashSet<RegisterId>>,
+}
+
+impl Liveness {
+    pub fn new(
+        program: &LiftedProgram,
+        control_flow: &ControlFlow,
+    ) -> Result<Self, MissingFunction> {
+        // registers used by a block itself, rather than passed along to its
+        // successors, before they're defined within the block
+        let mut used_first = FxHashMap::default();
+        let mut defined = FxHashMap::default();
+
+        for block in control_flow.blocks() {
+            let function = find_function(program, block)?;
+
+            let mut uses = FxHashSet::default();
+            let mut defs = FxHashSet::default();
+
+            for inst in function.instructions.iter() {
+                let used = inst.used_registers().into_iter();
+                uses.extend(used.filter(|r| !defs.contains(r)));
+                defs.extend(inst.assigned_to());
+            }
+
+            let used = match &function.end {
+                EndInstruction::Jump(_) => None,
+                EndInstruction::JumpIf(jump_if) => Some(jump_if.condition),
+                EndInstruction::Switch(switch) => Some(switch.value),
+                EndInstruction::Return(Return(value)) => *value,
+            };
+            uses.extend(used.filter(|r| !defs.contains(r)));
+
+            used_first.insert(block, uses);
+            defined.insert(block, defs);
+        }
+
+        let mut liveness = Liveness::default();
+        for block in control_flow.blocks() {
+            liveness.live_in.insert(block, FxHashSet::default());
+            liveness.live_out.insert(block, FxHashSet::default());
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for block in control_flow.blocks() {
+                let function = find_function(program, block)?;
+
+                let mut live_out = FxHashSet::default();
+                for path in function.end.paths() {
+                    let target = find_function(program, path.0)?;
+                    let needed = &liveness.live_in[&path.0];
+
+                    let passed = target.parameters.iter().zip(path.1.iter());
+                    live_out.extend(passed.filter(|(p, _)| needed.contains(p)).map(|(_, r)| *r));
+                }
+
+                let defs = &defined[&block];
+                let mut live_in = used_first[&block].clone();
+                live_in.extend(live_out.iter().filter(|r| !defs.contains(r)));
+
+                if live_in != liveness.live_in[&block] {
+                    changed = true;
+                    liveness.live_in.insert(block, live_in);
+                }
+
+                liveness.live_out.insert(block, live_out);
+            }
+        }
+
+        Ok(liveness)
+    }
+
+    pub fn is_live_in(&self, block: FunctionId, register: RegisterId) -> bool {
+        (self.live_in.get(&block)).map_or(false, |live| live.contains(&register))
+    }
+
+    pub fn is_live_out(&self, block: FunctionId, register: RegisterId) -> bool {
+        (self.live_out.get(&block)).map_or(false, |live| live.contains(&register))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CallKind {
+    /// A call with [`InstructionData::CallStatic`].
+    Static,
+    /// A call with [`InstructionData::CallVirt`], of which the function pointer
+    /// is known to always be the called function.
+    Virtual,
+}
+
+/// The graph of functions calling one another, reachable from the entrypoint.
+/// Functions are identified by their entry blocks.
+///
+/// Virtual calls are only present if the called function could be resolved.
+#[derive(Debug, Clone)]
+pub struct CallGraph<I: NodeTrait = FunctionId> {
+    pub graph: DiGraphMap<I, CallKind>,
+}
+
+impl<I: NodeTrait> CallGraph<I> {
+    pub fn new<P>(program: &P) -> Result<Self, MissingFunction<I>>
+    where
+        P: ProgramFlow<FunctionId = I>,
+    {
+        let mut graph = DiGraphMap::new();
+        graph.add_node(program.entrypoint());
+
+        let mut queue = vec![program.entrypoint()];
+        while let Some(caller) = queue.pop() {
+            let control_flow = ControlFlow::new(program, caller)?;
+
+            // functions that have their pointer taken may be called from
+            // anywhere, so they're still part of the graph
+            for function in program.fn_ptrs(&control_flow)? {
+                if !graph.contains_node(function) {
+                    graph.add_node(function);
+                    queue.push(function);
+                }
+            }
+
+            for (callee, kind) in program.calls(&control_flow)? {
+                if !graph.contains_node(callee) {
+                    queue.push(callee);
+                }
+
+                // a static call to a function is more certain than a virtual
+                // one, so that is what the edge records
+                if graph.edge_weight(caller, callee) != Some(&CallKind::Static) {
+                    graph.add_edge(caller, callee, kind);
+                }
+            }
+        }
+
+        Ok(Self { graph })
+    }
+
+    /// The functions in the graph, identified by their entry blocks.
+    pub fn functions(&self) -> impl Iterator<Item = I> + '_ {
+        self.graph.nodes()
+    }
+
+    pub fn callees(&self, function: I) -> impl Iterator<Item = I> + '_ {
+        self.graph.neighbors_directed(function, Direction::Outgoing)
+    }
+
+    pub fn callers(&self, function: I) -> impl Iterator<Item = I> + '_ {
+        self.graph.neighbors_directed(function, Direction::Incoming)
+    }
+
+    /// Whether the function may end up calling itself.
+    pub fn is_recursive(&self, function: I) -> bool {
+        let mut dfs = Dfs::empty(&self.graph);
+        dfs.stack.extend(self.callees(function));
+
+        dfs.iter(&self.graph).any(|callee| callee == function)
+    }
+}
+
+/// Finds the registers that always hold a pointer to the same function, either
+/// by having been made with [`InstructionData::GetFnPtr`] or by being passed
+/// such a register in every jump to the block.
+fn resolve_fn_ptrs(
+    program: &LiftedProgram,
+    control_flow: &ControlFlow,
+) -> Result<FxHashMap<RegisterId, FunctionId>, MissingFunction> {
+    let mut fn_ptrs = FxHashMap::default();
+
+    for block in control_flow.blocks() {
+        let function = find_function(program, block)?;
+
+        for inst in function.instructions.iter() {
+            if let InstructionData::GetFnPtr(make) = &inst.data {
+                fn_ptrs.insert(make.result, make.item);
+            }
+        }
+    }
+
+    // block parameters hold whatever was passed to them, so they are resolved
+    // once every register passed to them is
+    let mut incoming = FxHashMap::<RegisterId, Vec<RegisterId>>::default();
+    for block in control_flow.blocks() {
+        let function = find_function(program, block)?;
+
+        for path in function.end.paths() {
+            let target = find_function(program, path.0)?;
+
+            for (parameter, passed) in target.parameters.iter().zip(path.1.iter()) {
+                if parameter != passed {
+                    incoming.entry(*parameter).or_default().push(*passed);
+                }
+            }
+        }
+    }
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for (parameter, passed) in incoming.iter() {
+            if fn_ptrs.contains_key(parameter) {
+                continue;
+            }
+
+            let mut callees = passed.iter().map(|r| fn_ptrs.get(r).copied());
+            let first = callees.next().flatten();
+
+            if let Some(callee) = first {
+                if callees.all(|c| c == Some(callee)) {
+                    fn_ptrs.insert(*parameter, callee);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    Ok(fn_ptrs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::parse_jssatir::parse;
+    use crate::id::IdCompat;
+    use crate::lifted::lift;
+
+    const PROGRAM: &str = r#"entrypoint @0;
+
+fn @0() {
+  @0.$0():
+    %0 = MakeBoolean true;
+    %1 = MakeInteger 1;
+    %2 = GetFnPtr @2;
+    If %0:
+     $1();
+else $2(%2);
+  @0.$1():
+    %3 = CallStatic @1(%1)
+    Jump $3(%3)
+  @0.$2(%4, ):
+    %5 = CallVirt %4(%1)
+    Jump $3(%5)
+  @0.$3(%6, ):
+    Return %6;
+}
+
+fn @1(%0, ) {
+  @1.$0():
+    %1 = CallStatic @1(%0)
+    Return %1;
+}
+
+fn @2(%0, ) {
+  @2.$0():
+    Return %0;
+}
+
+"#;
+
+    fn block(program: &LiftedProgram, ir_blk_id: usize) -> FunctionId {
+        let (id, _) = (program.functions.iter())
+            .find(|(_, f)| f.ir_fn_id.value() == 0 && f.ir_blk_id.value() == ir_blk_id)
+            .unwrap();
+        *id
+    }
+
+    #[test]
+    fn control_flow_and_dominators() {
+        let program = lift(parse(PROGRAM).unwrap());
+        let control_flow = ControlFlow::new(&program, program.entrypoint).unwrap();
+        let [b0, b1, b2, b3] = [0, 1, 2, 3].map(|b| block(&program, b));
+
+        assert_eq!(b0, program.entrypoint);
+        assert_eq!(control_flow.blocks().count(), 4);
+        assert_eq!(control_flow.predecessors(b3).count(), 2);
+
+        let dominators = control_flow.dominators();
+        assert_eq!(dominators.immediate_dominator(b1), Some(b0));
+        assert_eq!(dominators.immediate_dominator(b2), Some(b0));
+        assert_eq!(dominators.immediate_dominator(b3), Some(b0));
+    }
+
+    #[test]
+    fn def_use_and_liveness() {
+        let program = lift(parse(PROGRAM).unwrap());
+        let control_flow = ControlFlow::new(&program, program.entrypoint).unwrap();
+        let [b0, b1, b2, b3] = [0, 1, 2, 3].map(|b| block(&program, b));
+
+        let entry = &program.functions[&b0];
+        let condition = entry.instructions[0].assigned_to().unwrap();
+        let one = entry.instructions[1].assigned_to().unwrap();
+        let fn_ptr = program.functions[&b2].parameters[0];
+
+        let def_use = DefUse::new(&program, &control_flow).unwrap();
+        assert_eq!(
+            def_use.definitions_of(one)[0],
+            Location {
+                block: b0,
+                site: Site::Instruction(1),
+            }
+        );
+        // used by both calls, and passed along to both blocks making them
+        assert_eq!(def_use.uses_of(one).len(), 4);
+        assert_eq!(def_use.uses_of(condition)[0].site, Site::End);
+        assert!(!def_use.is_unused(fn_ptr));
+
+        let liveness = Liveness::new(&program, &control_flow).unwrap();
+        assert!(liveness.is_live_out(b0, one));
+        assert!(!liveness.is_live_out(b0, condition));
+        assert!(liveness.is_live_in(b2, one));
+        assert!(liveness.is_live_in(b2, fn_ptr));
+        assert!(!liveness.is_live_out(b1, one));
+        assert!(liveness.live_out[&b3].is_empty());
+    }
+
+    #[test]
+    fn call_graph_resolves_virtual_calls() {
+        let program = lift(parse(PROGRAM).unwrap());
+        let call_graph = CallGraph::new(&program).unwrap();
+
+        let kinds = (call_graph.callees(program.entrypoint))
+            .map(|callee| {
+                *call_graph
+                    .graph
+                    .edge_weight(program.entrypoint, callee)
+                    .unwrap()
+            })
+            .collect::<FxHashSet<_>>();
+        assert_eq!(kinds.len(), 2);
+        assert!(kinds.contains(&CallKind::Virtual));
+
+        let recursive = (call_graph.callees(program.entrypoint))
+            .filter(|callee| call_graph.is_recursive(*callee))
+            .count();
+        assert_eq!(recursive, 1);
+        assert!(!call_graph.is_recursive(program.entrypoint));
+    }
+
+    #[test]
+    fn missing_functions_are_reported() {
+        let mut program = lift(parse(PROGRAM).unwrap());
+        let b3 = block(&program, 3);
+        program.functions.remove(&b3);
+
+        let error = ControlFlow::new(&program, program.entrypoint).unwrap_err();
+        assert_eq!(error, MissingFunction(b3));
+        assert_eq!(CallGraph::new(&program).unwrap_err(), MissingFunction(b3));
+    }
+}
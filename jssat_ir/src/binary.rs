@@ -0,0 +1,1363 @@
+//! A compact binary format for [`IR`] and [`LiftedProgram`]s, so that programs
+//! (e.g. the precompiled ECMA262 methods) can be cached on disk and shipped
+//! between tools without rebuilding them.
+//!
+//! Every file starts with a magic number, the version of the format, and
+//! whether it contains an [`IR`] or a [`LiftedProgram`]. Integers are encoded
+//! as LEB128 varints.
+//!
+//! Everything about a program is preserved except for the Rust source
+//! locations that instructions were constructed at, which are replaced with a
+//! location in this file.
+
+use std::fmt::Display;
+use std::io::{self, Read, Write};
+use std::num::NonZeroU16;
+use std::sync::Arc;
+
+use num_bigint::BigInt;
+use ordered_float::OrderedFloat;
+use rustc_hash::FxHashMap;
+
+use crate::frontend::ir::{self, *};
+use crate::frontend::source_map::SourceMapIdx;
+use crate::id::{IdCompat, Tag};
+use crate::isa::*;
+use crate::lifted::{self, EndInstruction, LiftedProgram};
+
+const MAGIC: &[u8; 6] = b"JSSAT\0";
+
+/// The version of the binary format. Files of a different version cannot be
+/// loaded, and this must be bumped whenever the format changes.
+pub const VERSION: u32 = 1;
+
+const KIND_IR: u8 = 0;
+const KIND_LIFTED: u8 = 1;
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    /// The data does not start with the magic number of the format.
+    NotJssat,
+    /// The data is of a version other than [`VERSION`].
+    UnsupportedVersion(u32),
+    /// The data contains a different kind of program than requested, e.g. a
+    /// [`LiftedProgram`] when an [`IR`] was requested.
+    WrongKind,
+    /// The data is not valid, e.g. because it was corrupted.
+    Malformed(&'static str),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io(error) => write!(f, "{}", error),
+            LoadError::NotJssat => write!(f, "not a JSSAT binary"),
+            LoadError::UnsupportedVersion(version) => {
+                write!(f, "unsupported version {}, expected {}", version, VERSION)
+            }
+            LoadError::WrongKind => write!(f, "contains a different kind of program"),
+            LoadError::Malformed(reason) => write!(f, "malformed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<io::Error> for LoadError {
+    fn from(error: io::Error) -> Self {
+        LoadError::Io(error)
+    }
+}
+
+type LoadResult<T> = Result<T, LoadError>;
+
+pub fn save_ir(ir: &IR, w: impl Write) -> io::Result<()> {
+    let mut w = Writer(w);
+    w.header(KIND_IR)?;
+    w.program(
+        &ir.dealer,
+        ir.entrypoint,
+        &ir.constants,
+        &ir.external_functions,
+    )?;
+
+    let functions = sorted(&ir.functions);
+    w.usize(functions.len())?;
+    for (id, function) in functions {
+        w.id(id)?;
+        w.function(function)?;
+    }
+
+    Ok(())
+}
+
+pub fn load_ir(r: impl Read) -> LoadResult<IR> {
+    let mut r = Reader(r);
+    r.header(KIND_IR)?;
+    let (dealer, entrypoint, constants, external_functions) = r.program()?;
+
+    let mut functions = FxHashMap::default();
+    for _ in 0..r.usize()? {
+        let id = r.id()?;
+        let function = r.function()?;
+        if functions.insert(id, function).is_some() {
+            return Err(LoadError::Malformed("duplicate function"));
+        }
+    }
+
+    Ok(IR {
+        dealer,
+        entrypoint,
+        constants,
+        external_functions,
+        functions,
+    })
+}
+
+pub fn save_lifted(program: &LiftedProgram, w: impl Write) -> io::Result<()> {
+    let mut w = Writer(w);
+    w.header(KIND_LIFTED)?;
+    w.program(
+        &program.dealer,
+        program.entrypoint,
+        &program.constants,
+        &program.external_functions,
+    )?;
+
+    let functions = sorted(&program.functions);
+    w.usize(functions.len())?;
+    for (id, function) in functions {
+        w.id(id)?;
+        w.lifted_function(function)?;
+    }
+
+    Ok(())
+}
+
+pub fn load_lifted(r: impl Read) -> LoadResult<LiftedProgram> {
+    let mut r = Reader(r);
+    r.header(KIND_LIFTED)?;
+    let (dealer, entrypoint, constants, external_functions) = r.program()?;
+
+    let mut functions = FxHashMap::default();
+    for _ in 0..r.usize()? {
+        let id = r.id()?;
+        let function = r.lifted_function()?;
+        if functions.insert(id, function).is_some() {
+            return Err(LoadError::Malformed("duplicate function"));
+        }
+    }
+
+    Ok(LiftedProgram {
+        dealer,
+        entrypoint,
+        constants,
+        external_functions,
+        functions,
+    })
+}
+
+fn sorted<K: IdCompat, V>(map: &FxHashMap<K, V>) -> Vec<(K, &V)> {
+    let mut entries = map.iter().map(|(k, v)| (*k, v)).collect::<Vec<_>>();
+    entries.sort_by_key(|(k, _)| k.value());
+    entries
+}
+
+/// The [`BinaryOperator`]s, in the order of their encoding.
+const BINARY_OPERATORS: [BinaryOperator; 17] = {
+    use BinaryOperator::*;
+    [
+        Add,
+        Subtract,
+        Multiply,
+        Divide,
+        Remainder,
+        Exponent,
+        And,
+        Or,
+        BitwiseAnd,
+        BitwiseOr,
+        BitwiseXor,
+        ShiftLeft,
+        ShiftRight,
+        UnsignedShiftRight,
+        Equals,
+        SameValue,
+        LessThan,
+    ]
+};
+
+/// The [`ValueType`]s, in the order of their encoding.
+const VALUE_TYPES: [ValueType; 10] = {
+    use ValueType::*;
+    [
+        Atom, Bytes, Number, Boolean, FnPtr, Record, BigNumber, List, Tuple, Runtime,
+    ]
+};
+
+/// The tags that the kinds of instructions are encoded with.
+mod tag {
+    pub const COMMENT: u8 = 0;
+    pub const NEW_RECORD: u8 = 1;
+    pub const RECORD_GET: u8 = 2;
+    pub const RECORD_SET: u8 = 3;
+    pub const RECORD_HAS_KEY: u8 = 4;
+    pub const NEW_LIST: u8 = 5;
+    pub const LIST_GET: u8 = 6;
+    pub const LIST_SET: u8 = 7;
+    pub const LIST_HAS_KEY: u8 = 8;
+    pub const LIST_LEN: u8 = 9;
+    pub const NEW_TUPLE: u8 = 10;
+    pub const TUPLE_GET: u8 = 11;
+    pub const TUPLE_LEN: u8 = 12;
+    pub const STRING_CONCAT: u8 = 13;
+    pub const STRING_LEN: u8 = 14;
+    pub const STRING_SLICE: u8 = 15;
+    pub const STRING_CODE_UNIT_AT: u8 = 16;
+    pub const NUMBER_TO_STRING: u8 = 17;
+    pub const STRING_TO_NUMBER: u8 = 18;
+    pub const GET_FN_PTR: u8 = 19;
+    pub const CALL_STATIC: u8 = 20;
+    pub const CALL_EXTERN: u8 = 21;
+    pub const CALL_VIRT: u8 = 22;
+    pub const MAKE_ATOM: u8 = 23;
+    pub const MAKE_BYTES: u8 = 24;
+    pub const MAKE_INTEGER: u8 = 25;
+    pub const MAKE_FLOAT: u8 = 26;
+    pub const MAKE_BIG_INT: u8 = 27;
+    pub const MAKE_BOOLEAN: u8 = 28;
+    pub const BIN_OP: u8 = 29;
+    pub const NEGATE: u8 = 30;
+    pub const NOT: u8 = 31;
+    pub const NUMBER_TO_BIG_INT: u8 = 32;
+    pub const GENERALIZE: u8 = 33;
+    pub const ASSERT: u8 = 34;
+    pub const IS_TYPE: u8 = 35;
+    pub const GET_RUNTIME: u8 = 36;
+    pub const UNREACHABLE: u8 = 37;
+}
+
+struct Writer<W>(W);
+
+impl<W: Write> Writer<W> {
+    fn header(&mut self, kind: u8) -> io::Result<()> {
+        self.0.write_all(MAGIC)?;
+        self.uint(VERSION as u64)?;
+        self.u8(kind)
+    }
+
+    fn program<F: Tag>(
+        &mut self,
+        dealer: &AtomDealer,
+        entrypoint: crate::id::FunctionId<F>,
+        constants: &FxHashMap<crate::id::ConstantId<F>, Constant>,
+        external_functions: &FxHashMap<crate::id::ExternalFunctionId<F>, ExternalFunction>,
+    ) -> io::Result<()> {
+        // atoms are dealt again in the same order when loading, which
+        // reproduces the same atoms
+        let atoms = dealer.iter().collect::<Vec<_>>();
+        self.usize(atoms.len())?;
+        for (atom, name) in atoms {
+            self.atom(atom)?;
+            self.str(name)?;
+        }
+
+        // as are generated atoms, which have no names
+        self.usize(dealer.generated().count())?;
+
+        self.id(entrypoint)?;
+
+        let constants = sorted(constants);
+        self.usize(constants.len())?;
+        for (id, constant) in constants {
+            self.id(id)?;
+            self.bytes(&constant.payload)?;
+        }
+
+        let external_functions = sorted(external_functions);
+        self.usize(external_functions.len())?;
+        for (id, ext_fn) in external_functions {
+            self.id(id)?;
+            self.str(&ext_fn.name)?;
+            self.usize(ext_fn.parameters.len())?;
+            for parameter in ext_fn.parameters.iter() {
+                self.ffi_value_type(parameter)?;
+            }
+
+            match &ext_fn.return_type {
+                Returns::Void => self.u8(0)?,
+                Returns::Value(value) => {
+                    self.u8(1)?;
+                    self.ffi_value_type(value)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn function(&mut self, function: &ir::Function) -> io::Result<()> {
+        self.option(&function.name, |w, name| w.str(name))?;
+        self.usize(function.parameters.len())?;
+        for parameter in function.parameters.iter() {
+            self.id(parameter.register)?;
+        }
+        self.id(function.entry_block)?;
+
+        let blocks = sorted(&function.blocks);
+        self.usize(blocks.len())?;
+        for (id, block) in blocks {
+            self.id(id)?;
+            self.ids(&block.parameters)?;
+            self.instructions(&block.instructions)?;
+
+            match &block.end {
+                ControlFlowInstruction::Jmp(Jump(path)) => {
+                    self.u8(0)?;
+                    self.block_jump(path)?;
+                }
+                ControlFlowInstruction::JmpIf(jump_if) => {
+                    self.u8(1)?;
+                    self.jump_if(jump_if)?;
+                }
+                ControlFlowInstruction::Ret(Return(value)) => {
+                    self.u8(2)?;
+                    self.option(value, |w, value| w.id(*value))?;
+                }
+                ControlFlowInstruction::Switch(switch) => {
+                    self.u8(3)?;
+                    self.switch(switch)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn lifted_function(&mut self, function: &lifted::Function) -> io::Result<()> {
+        self.option(&function.name, |w, name| w.str(name))?;
+        self.id(function.ir_fn_id)?;
+        self.id(function.ir_blk_id)?;
+        self.ids(&function.parameters)?;
+        self.instructions(&function.instructions)?;
+
+        match &function.end {
+            EndInstruction::Jump(Jump(path)) => {
+                self.u8(0)?;
+                self.block_jump(path)
+            }
+            EndInstruction::JumpIf(jump_if) => {
+                self.u8(1)?;
+                self.jump_if(jump_if)
+            }
+            EndInstruction::Return(Return(value)) => {
+                self.u8(2)?;
+                self.option(value, |w, value| w.id(*value))
+            }
+            EndInstruction::Switch(switch) => {
+                self.u8(3)?;
+                self.switch(switch)
+            }
+        }
+    }
+
+    fn block_jump<B: IdCompat, C: Tag>(&mut self, path: &BlockJump<B, C>) -> io::Result<()> {
+        self.id(path.0)?;
+        self.ids(&path.1)
+    }
+
+    fn jump_if<B: IdCompat, C: Tag>(&mut self, jump_if: &JumpIf<B, C>) -> io::Result<()> {
+        self.id(jump_if.condition)?;
+        self.block_jump(&jump_if.if_so)?;
+        self.block_jump(&jump_if.other)
+    }
+
+    fn switch<B: IdCompat, C: Tag>(&mut self, switch: &Switch<B, C>) -> io::Result<()> {
+        self.id(switch.value)?;
+        self.usize(switch.cases.len())?;
+        for (value, path) in switch.cases.iter() {
+            match *value {
+                SwitchValue::Atom(atom) => {
+                    self.u8(0)?;
+                    self.atom(atom)?;
+                }
+                SwitchValue::Integer(integer) => {
+                    self.u8(1)?;
+                    self.int(integer)?;
+                }
+                SwitchValue::Boolean(boolean) => {
+                    self.u8(2)?;
+                    self.u8(boolean as u8)?;
+                }
+            }
+            self.block_jump(path)?;
+        }
+        self.block_jump(&switch.default)
+    }
+
+    fn instructions<C: Tag, F: Tag>(
+        &mut self,
+        instructions: &[Instruction<C, F>],
+    ) -> io::Result<()> {
+        self.usize(instructions.len())?;
+        for instruction in instructions {
+            self.option(&instruction.source_map_idx, |w, idx| w.usize(idx.0))?;
+            self.instruction(&instruction.data)?;
+        }
+
+        Ok(())
+    }
+
+    fn instruction<C: Tag, F: Tag>(
+        &mut self,
+        instruction: &InstructionData<C, F>,
+    ) -> io::Result<()> {
+        match instruction {
+            InstructionData::Comment(inst) => {
+                self.u8(tag::COMMENT)?;
+                self.str(&inst.message)?;
+                self.str(&inst.location.file)?;
+                self.uint(inst.location.line as u64)?;
+                self.uint(inst.location.column as u64)
+            }
+            InstructionData::NewRecord(inst) => {
+                self.u8(tag::NEW_RECORD)?;
+                self.id(inst.result)
+            }
+            InstructionData::RecordGet(inst) => {
+                self.u8(tag::RECORD_GET)?;
+                self.id(inst.result)?;
+                self.id(inst.record)?;
+                self.record_key(inst.key)
+            }
+            InstructionData::RecordSet(inst) => {
+                self.u8(tag::RECORD_SET)?;
+                self.id(inst.record)?;
+                self.record_key(inst.key)?;
+                self.option(&inst.value, |w, value| w.id(*value))
+            }
+            InstructionData::RecordHasKey(inst) => {
+                self.u8(tag::RECORD_HAS_KEY)?;
+                self.id(inst.result)?;
+                self.id(inst.record)?;
+                self.record_key(inst.key)
+            }
+            InstructionData::NewList(inst) => {
+                self.u8(tag::NEW_LIST)?;
+                self.id(inst.result)
+            }
+            InstructionData::ListGet(inst) => {
+                self.u8(tag::LIST_GET)?;
+                self.id(inst.result)?;
+                self.id(inst.list)?;
+                self.list_key(inst.key)
+            }
+            InstructionData::ListSet(inst) => {
+                self.u8(tag::LIST_SET)?;
+                self.id(inst.list)?;
+                self.list_key(inst.key)?;
+                self.option(&inst.value, |w, value| w.id(*value))
+            }
+            InstructionData::ListHasKey(inst) => {
+                self.u8(tag::LIST_HAS_KEY)?;
+                self.id(inst.result)?;
+                self.id(inst.list)?;
+                self.list_key(inst.key)
+            }
+            InstructionData::ListLen(inst) => {
+                self.u8(tag::LIST_LEN)?;
+                self.id(inst.result)?;
+                self.id(inst.list)
+            }
+            InstructionData::NewTuple(inst) => {
+                self.u8(tag::NEW_TUPLE)?;
+                self.id(inst.result)?;
+                self.ids(&inst.elements)
+            }
+            InstructionData::TupleGet(inst) => {
+                self.u8(tag::TUPLE_GET)?;
+                self.id(inst.result)?;
+                self.id(inst.tuple)?;
+                self.usize(inst.index)
+            }
+            InstructionData::TupleLen(inst) => {
+                self.u8(tag::TUPLE_LEN)?;
+                self.id(inst.result)?;
+                self.id(inst.tuple)
+            }
+            InstructionData::StringConcat(inst) => {
+                self.u8(tag::STRING_CONCAT)?;
+                self.id(inst.result)?;
+                self.id(inst.lhs)?;
+                self.id(inst.rhs)
+            }
+            InstructionData::StringLen(inst) => {
+                self.u8(tag::STRING_LEN)?;
+                self.id(inst.result)?;
+                self.id(inst.string)
+            }
+            InstructionData::StringSlice(inst) => {
+                self.u8(tag::STRING_SLICE)?;
+                self.id(inst.result)?;
+                self.id(inst.string)?;
+                self.id(inst.start)?;
+                self.id(inst.end)
+            }
+            InstructionData::StringCodeUnitAt(inst) => {
+                self.u8(tag::STRING_CODE_UNIT_AT)?;
+                self.id(inst.result)?;
+                self.id(inst.string)?;
+                self.id(inst.index)
+            }
+            InstructionData::NumberToString(inst) => {
+                self.u8(tag::NUMBER_TO_STRING)?;
+                self.id(inst.result)?;
+                self.id(inst.number)
+            }
+            InstructionData::StringToNumber(inst) => {
+                self.u8(tag::STRING_TO_NUMBER)?;
+                self.id(inst.result)?;
+                self.id(inst.string)
+            }
+            InstructionData::GetFnPtr(inst) => {
+                self.u8(tag::GET_FN_PTR)?;
+                self.id(inst.result)?;
+                self.id(inst.item)
+            }
+            InstructionData::CallStatic(inst) => {
+                self.u8(tag::CALL_STATIC)?;
+                self.option(&inst.result, |w, result| w.id(*result))?;
+                self.id(inst.calling)?;
+                self.ids(&inst.args)
+            }
+            InstructionData::CallExtern(inst) => {
+                self.u8(tag::CALL_EXTERN)?;
+                self.option(&inst.result, |w, result| w.id(*result))?;
+                self.id(inst.calling)?;
+                self.ids(&inst.args)
+            }
+            InstructionData::CallVirt(inst) => {
+                self.u8(tag::CALL_VIRT)?;
+                self.option(&inst.result, |w, result| w.id(*result))?;
+                self.id(inst.calling)?;
+                self.ids(&inst.args)
+            }
+            InstructionData::MakeAtom(inst) => {
+                self.u8(tag::MAKE_ATOM)?;
+                self.id(inst.result)?;
+                self.atom(inst.item)
+            }
+            InstructionData::MakeBytes(inst) => {
+                self.u8(tag::MAKE_BYTES)?;
+                self.id(inst.result)?;
+                self.id(inst.item)
+            }
+            InstructionData::MakeInteger(inst) => {
+                self.u8(tag::MAKE_INTEGER)?;
+                self.id(inst.result)?;
+                self.int(inst.item)
+            }
+            InstructionData::MakeFloat(inst) => {
+                self.u8(tag::MAKE_FLOAT)?;
+                self.id(inst.result)?;
+                self.0.write_all(&inst.item.0.to_le_bytes())
+            }
+            InstructionData::MakeBigInt(inst) => {
+                self.u8(tag::MAKE_BIG_INT)?;
+                self.id(inst.result)?;
+                self.bytes(&inst.item.to_signed_bytes_le())
+            }
+            InstructionData::MakeBoolean(inst) => {
+                self.u8(tag::MAKE_BOOLEAN)?;
+                self.id(inst.result)?;
+                self.u8(inst.item as u8)
+            }
+            InstructionData::BinOp(inst) => {
+                self.u8(tag::BIN_OP)?;
+                self.id(inst.result)?;
+                self.index_of(&BINARY_OPERATORS, &inst.op)?;
+                self.id(inst.lhs)?;
+                self.id(inst.rhs)
+            }
+            InstructionData::Negate(inst) => {
+                self.u8(tag::NEGATE)?;
+                self.id(inst.result)?;
+                self.id(inst.operand)
+            }
+            InstructionData::Not(inst) => {
+                self.u8(tag::NOT)?;
+                self.id(inst.result)?;
+                self.id(inst.operand)
+            }
+            InstructionData::NumberToBigInt(inst) => {
+                self.u8(tag::NUMBER_TO_BIG_INT)?;
+                self.id(inst.result)?;
+                self.id(inst.operand)
+            }
+            InstructionData::Generalize(inst) => {
+                self.u8(tag::GENERALIZE)?;
+                self.id(inst.result)?;
+                self.id(inst.value)
+            }
+            InstructionData::Assert(inst) => {
+                self.u8(tag::ASSERT)?;
+                self.id(inst.condition)?;
+                self.str(&inst.message)
+            }
+            InstructionData::IsType(inst) => {
+                self.u8(tag::IS_TYPE)?;
+                self.id(inst.result)?;
+                self.id(inst.value)?;
+                match inst.kind {
+                    CompareType::Register(register) => {
+                        self.u8(0)?;
+                        self.id(register)
+                    }
+                    CompareType::Kind(kind) => {
+                        self.u8(1)?;
+                        self.index_of(&VALUE_TYPES, &kind)
+                    }
+                }
+            }
+            InstructionData::GetRuntime(inst) => {
+                self.u8(tag::GET_RUNTIME)?;
+                self.id(inst.result)
+            }
+            InstructionData::Unreachable(inst) => {
+                self.u8(tag::UNREACHABLE)?;
+                self.id(inst.result)
+            }
+        }
+    }
+
+    fn record_key<C: Tag>(&mut self, key: RecordKey<C>) -> io::Result<()> {
+        match key {
+            RecordKey::Prop(register) => {
+                self.u8(0)?;
+                self.id(register)
+            }
+            RecordKey::Atom(atom) => {
+                self.u8(1)?;
+                self.atom(atom)
+            }
+            RecordKey::DynAtom(register) => {
+                self.u8(2)?;
+                self.id(register)
+            }
+        }
+    }
+
+    fn list_key<C: Tag>(&mut self, key: ListKey<C>) -> io::Result<()> {
+        let ListKey::Index(register) = key;
+        self.id(register)
+    }
+
+    fn ffi_value_type(&mut self, value: &FFIValueType) -> io::Result<()> {
+        self.u8(match value {
+            FFIValueType::Any => 0,
+            FFIValueType::Runtime => 1,
+            FFIValueType::String => 2,
+            FFIValueType::I64 => 3,
+            FFIValueType::F64 => 4,
+            FFIValueType::Bool => 5,
+            FFIValueType::Bytes => 6,
+            FFIValueType::Opaque => 7,
+            FFIValueType::Record => 8,
+            FFIValueType::Nullable(inner) => {
+                self.u8(9)?;
+                return self.ffi_value_type(inner);
+            }
+        })
+    }
+
+    fn index_of<T: PartialEq>(&mut self, items: &[T], item: &T) -> io::Result<()> {
+        let idx = (items.iter().position(|x| x == item))
+            .expect("all items should be present in the list");
+        self.usize(idx)
+    }
+
+    fn option<T>(
+        &mut self,
+        value: &Option<T>,
+        write: impl FnOnce(&mut Self, &T) -> io::Result<()>,
+    ) -> io::Result<()> {
+        match value {
+            Some(value) => {
+                self.u8(1)?;
+                write(self, value)
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn u8(&mut self, value: u8) -> io::Result<()> {
+        self.0.write_all(&[value])
+    }
+
+    fn uint(&mut self, mut value: u64) -> io::Result<()> {
+        loop {
+            let byte = (value & 0x7F) as u8;
+            value >>= 7;
+
+            if value == 0 {
+                return self.u8(byte);
+            }
+
+            self.u8(byte | 0x80)?;
+        }
+    }
+
+    fn int(&mut self, value: i64) -> io::Result<()> {
+        // zigzag encoding, so that small negative numbers are small too
+        self.uint(((value << 1) ^ (value >> 63)) as u64)
+    }
+
+    fn usize(&mut self, value: usize) -> io::Result<()> {
+        self.uint(value as u64)
+    }
+
+    fn id<I: IdCompat>(&mut self, id: I) -> io::Result<()> {
+        self.usize(id.value())
+    }
+
+    fn ids<I: IdCompat>(&mut self, ids: &[I]) -> io::Result<()> {
+        self.usize(ids.len())?;
+        for id in ids {
+            self.id(*id)?;
+        }
+
+        Ok(())
+    }
+
+    fn atom(&mut self, atom: Atom) -> io::Result<()> {
+        self.uint(atom.0.get() as u64)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.usize(bytes.len())?;
+        self.0.write_all(bytes)
+    }
+
+    fn str(&mut self, string: &str) -> io::Result<()> {
+        self.bytes(string.as_bytes())
+    }
+}
+
+struct Reader<R>(R);
+
+type ProgramParts<F> = (
+    Arc<AtomDealer>,
+    crate::id::FunctionId<F>,
+    FxHashMap<crate::id::ConstantId<F>, Constant>,
+    FxHashMap<crate::id::ExternalFunctionId<F>, ExternalFunction>,
+);
+
+impl<R: Read> Reader<R> {
+    fn header(&mut self, kind: u8) -> LoadResult<()> {
+        let mut magic = [0; MAGIC.len()];
+        self.0.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(LoadError::NotJssat);
+        }
+
+        let version = self.uint()?;
+        if version != VERSION as u64 {
+            return Err(LoadError::UnsupportedVersion(version as u32));
+        }
+
+        match self.u8()? == kind {
+            true => Ok(()),
+            false => Err(LoadError::WrongKind),
+        }
+    }
+
+    fn program<F: Tag>(&mut self) -> LoadResult<ProgramParts<F>> {
+        let mut dealer = AtomDealer::new();
+        for _ in 0..self.usize()? {
+            let atom = self.atom()?;
+            if dealer.deal_dynamic(&self.string()?) != atom {
+                return Err(LoadError::Malformed("atoms are out of order"));
+            }
+        }
+
+        let generated = self.usize()?;
+        if generated > (u16::MAX - MAX_INTERNED_ATOM) as usize {
+            return Err(LoadError::Malformed("too many generated atoms"));
+        }
+
+        for _ in 0..generated {
+            dealer.gen();
+        }
+
+        let entrypoint = self.id()?;
+
+        let mut constants = FxHashMap::default();
+        for _ in 0..self.usize()? {
+            let id = self.id()?;
+            let payload = self.bytes()?;
+            if constants.insert(id, Constant { payload }).is_some() {
+                return Err(LoadError::Malformed("duplicate constant"));
+            }
+        }
+
+        let mut external_functions = FxHashMap::default();
+        for _ in 0..self.usize()? {
+            let id = self.id()?;
+            let name = self.string()?;
+
+            let mut parameters = Vec::new();
+            for _ in 0..self.usize()? {
+                parameters.push(self.ffi_value_type()?);
+            }
+
+            let return_type = match self.u8()? {
+                0 => Returns::Void,
+                1 => Returns::Value(self.ffi_value_type()?),
+                _ => return Err(LoadError::Malformed("invalid return type")),
+            };
+
+            let ext_fn = ExternalFunction {
+                name,
+                parameters,
+                return_type,
+            };
+
+            if external_functions.insert(id, ext_fn).is_some() {
+                return Err(LoadError::Malformed("duplicate external function"));
+            }
+        }
+
+        Ok((Arc::new(dealer), entrypoint, constants, external_functions))
+    }
+
+    fn function(&mut self) -> LoadResult<ir::Function> {
+        let name = self.option(Self::string)?;
+
+        let mut parameters = Vec::new();
+        for _ in 0..self.usize()? {
+            parameters.push(Parameter {
+                register: self.id()?,
+            });
+        }
+
+        let entry_block = self.id()?;
+
+        let mut blocks = FxHashMap::default();
+        for _ in 0..self.usize()? {
+            let id = self.id()?;
+            let parameters = self.ids()?;
+            let instructions = self.instructions()?;
+
+            let end = match self.u8()? {
+                0 => ControlFlowInstruction::Jmp(Jump(self.block_jump()?)),
+                1 => ControlFlowInstruction::JmpIf(self.jump_if()?),
+                2 => ControlFlowInstruction::Ret(Return(self.option(Self::id)?)),
+                3 => ControlFlowInstruction::Switch(self.switch()?),
+                _ => return Err(LoadError::Malformed("invalid control flow")),
+            };
+
+            let block = FunctionBlock {
+                parameters,
+                instructions,
+                end,
+            };
+
+            if blocks.insert(id, block).is_some() {
+                return Err(LoadError::Malformed("duplicate block"));
+            }
+        }
+
+        Ok(ir::Function {
+            name,
+            parameters,
+            entry_block,
+            blocks,
+        })
+    }
+
+    fn lifted_function(&mut self) -> LoadResult<lifted::Function> {
+        let name = self.option(Self::string)?;
+        let ir_fn_id = self.id()?;
+        let ir_blk_id = self.id()?;
+        let parameters = self.ids()?;
+        let instructions = self.instructions()?;
+
+        let end = match self.u8()? {
+            0 => EndInstruction::Jump(Jump(self.block_jump()?)),
+            1 => EndInstruction::JumpIf(self.jump_if()?),
+            2 => EndInstruction::Return(Return(self.option(Self::id)?)),
+            3 => EndInstruction::Switch(self.switch()?),
+            _ => return Err(LoadError::Malformed("invalid control flow")),
+        };
+
+        Ok(lifted::Function {
+            name,
+            ir_fn_id,
+            ir_blk_id,
+            parameters,
+            instructions,
+            end,
+        })
+    }
+
+    fn block_jump<B: IdCompat, C: Tag>(&mut self) -> LoadResult<BlockJump<B, C>> {
+        Ok(BlockJump(self.id()?, self.ids()?))
+    }
+
+    fn jump_if<B: IdCompat, C: Tag>(&mut self) -> LoadResult<JumpIf<B, C>> {
+        Ok(JumpIf {
+            condition: self.id()?,
+            if_so: self.block_jump()?,
+            other: self.block_jump()?,
+        })
+    }
+
+    fn switch<B: IdCompat, C: Tag>(&mut self) -> LoadResult<Switch<B, C>> {
+        let value = self.id()?;
+
+        let len = self.usize()?;
+        let mut cases = Vec::with_capacity(len.min(1024));
+        for _ in 0..len {
+            let case = match self.u8()? {
+                0 => SwitchValue::Atom(self.atom()?),
+                1 => SwitchValue::Integer(self.int()?),
+                2 => SwitchValue::Boolean(match self.u8()? {
+                    0 => false,
+                    1 => true,
+                    _ => return Err(LoadError::Malformed("invalid boolean")),
+                }),
+                _ => return Err(LoadError::Malformed("invalid switch value")),
+            };
+            cases.push((case, self.block_jump()?));
+        }
+
+        Ok(Switch {
+            value,
+            cases,
+            default: self.block_jump()?,
+        })
+    }
+
+    fn instructions<C: Tag, F: Tag>(&mut self) -> LoadResult<Vec<Instruction<C, F>>> {
+        let len = self.usize()?;
+        let mut instructions = Vec::with_capacity(len.min(1024));
+
+        for _ in 0..len {
+            let source_map_idx = self.option(|r| r.usize().map(SourceMapIdx))?;
+            let mut instruction = self.instruction()?;
+            instruction.source_map_idx = source_map_idx;
+            instructions.push(instruction);
+        }
+
+        Ok(instructions)
+    }
+
+    fn instruction<C: Tag, F: Tag>(&mut self) -> LoadResult<Instruction<C, F>> {
+        let instruction = match self.u8()? {
+            tag::COMMENT => Instruction::Comment(Comment {
+                message: self.string()?.into(),
+                location: CommentLocation {
+                    file: self.string()?.into(),
+                    line: self.u32()?,
+                    column: self.u32()?,
+                },
+            }),
+            tag::NEW_RECORD => Instruction::NewRecord(NewRecord { result: self.id()? }),
+            tag::RECORD_GET => Instruction::RecordGet(RecordGet {
+                result: self.id()?,
+                record: self.id()?,
+                key: self.record_key()?,
+            }),
+            tag::RECORD_SET => Instruction::RecordSet(RecordSet {
+                record: self.id()?,
+                key: self.record_key()?,
+                value: self.option(Self::id)?,
+            }),
+            tag::RECORD_HAS_KEY => Instruction::RecordHasKey(RecordHasKey {
+                result: self.id()?,
+                record: self.id()?,
+                key: self.record_key()?,
+            }),
+            tag::NEW_LIST => Instruction::NewList(NewList { result: self.id()? }),
+            tag::LIST_GET => Instruction::ListGet(ListGet {
+                result: self.id()?,
+                list: self.id()?,
+                key: ListKey::Index(self.id()?),
+            }),
+            tag::LIST_SET => Instruction::ListSet(ListSet {
+                list: self.id()?,
+                key: ListKey::Index(self.id()?),
+                value: self.option(Self::id)?,
+            }),
+            tag::LIST_HAS_KEY => Instruction::ListHasKey(ListHasKey {
+                result: self.id()?,
+                list: self.id()?,
+                key: ListKey::Index(self.id()?),
+            }),
+            tag::LIST_LEN => Instruction::ListLen(ListLen {
+                result: self.id()?,
+                list: self.id()?,
+            }),
+            tag::NEW_TUPLE => Instruction::NewTuple(NewTuple {
+                result: self.id()?,
+                elements: self.ids()?,
+            }),
+            tag::TUPLE_GET => Instruction::TupleGet(TupleGet {
+                result: self.id()?,
+                tuple: self.id()?,
+                index: self.usize()?,
+            }),
+            tag::TUPLE_LEN => Instruction::TupleLen(TupleLen {
+                result: self.id()?,
+                tuple: self.id()?,
+            }),
+            tag::STRING_CONCAT => Instruction::StringConcat(StringConcat {
+                result: self.id()?,
+                lhs: self.id()?,
+                rhs: self.id()?,
+            }),
+            tag::STRING_LEN => Instruction::StringLen(StringLen {
+                result: self.id()?,
+                string: self.id()?,
+            }),
+            tag::STRING_SLICE => Instruction::StringSlice(StringSlice {
+                result: self.id()?,
+                string: self.id()?,
+                start: self.id()?,
+                end: self.id()?,
+            }),
+            tag::STRING_CODE_UNIT_AT => Instruction::StringCodeUnitAt(StringCodeUnitAt {
+                result: self.id()?,
+                string: self.id()?,
+                index: self.id()?,
+            }),
+            tag::NUMBER_TO_STRING => Instruction::NumberToString(NumberToString {
+                result: self.id()?,
+                number: self.id()?,
+            }),
+            tag::STRING_TO_NUMBER => Instruction::StringToNumber(StringToNumber {
+                result: self.id()?,
+                string: self.id()?,
+            }),
+            tag::GET_FN_PTR => Instruction::GetFnPtr(Make {
+                result: self.id()?,
+                item: self.id()?,
+            }),
+            tag::CALL_STATIC => Instruction::CallStatic(Call {
+                result: self.option(Self::id)?,
+                calling: self.id()?,
+                args: self.ids()?,
+            }),
+            tag::CALL_EXTERN => Instruction::CallExtern(Call {
+                result: self.option(Self::id)?,
+                calling: self.id()?,
+                args: self.ids()?,
+            }),
+            tag::CALL_VIRT => Instruction::CallVirt(Call {
+                result: self.option(Self::id)?,
+                calling: self.id()?,
+                args: self.ids()?,
+            }),
+            tag::MAKE_ATOM => Instruction::MakeAtom(Make {
+                result: self.id()?,
+                item: self.atom()?,
+            }),
+            tag::MAKE_BYTES => Instruction::MakeBytes(Make {
+                result: self.id()?,
+                item: self.id()?,
+            }),
+            tag::MAKE_INTEGER => Instruction::MakeInteger(Make {
+                result: self.id()?,
+                item: self.int()?,
+            }),
+            tag::MAKE_FLOAT => {
+                let result = self.id()?;
+                let mut bytes = [0; 8];
+                self.0.read_exact(&mut bytes)?;
+                let item = OrderedFloat(f64::from_le_bytes(bytes));
+                Instruction::MakeFloat(Make { result, item })
+            }
+            tag::MAKE_BIG_INT => Instruction::MakeBigInt(Make {
+                result: self.id()?,
+                item: BigInt::from_signed_bytes_le(&self.bytes()?),
+            }),
+            tag::MAKE_BOOLEAN => {
+                let result = self.id()?;
+                let item = match self.u8()? {
+                    0 => false,
+                    1 => true,
+                    _ => return Err(LoadError::Malformed("invalid boolean")),
+                };
+                Instruction::MakeBoolean(Make { result, item })
+            }
+            tag::BIN_OP => Instruction::BinOp(BinOp {
+                result: self.id()?,
+                op: self.indexed(&BINARY_OPERATORS)?,
+                lhs: self.id()?,
+                rhs: self.id()?,
+            }),
+            tag::NEGATE => Instruction::Negate(Negate {
+                result: self.id()?,
+                operand: self.id()?,
+            }),
+            tag::NOT => Instruction::Not(Not {
+                result: self.id()?,
+                operand: self.id()?,
+            }),
+            tag::NUMBER_TO_BIG_INT => Instruction::NumberToBigInt(NumberToBigInt {
+                result: self.id()?,
+                operand: self.id()?,
+            }),
+            tag::GENERALIZE => Instruction::Generalize(Generalize {
+                result: self.id()?,
+                value: self.id()?,
+            }),
+            tag::ASSERT => Instruction::Assert(Assert {
+                condition: self.id()?,
+                message: self.string()?.into(),
+            }),
+            tag::IS_TYPE => {
+                let result = self.id()?;
+                let value = self.id()?;
+                let kind = match self.u8()? {
+                    0 => CompareType::Register(self.id()?),
+                    1 => CompareType::Kind(self.indexed(&VALUE_TYPES)?),
+                    _ => return Err(LoadError::Malformed("invalid type comparison")),
+                };
+                Instruction::IsType(IsType {
+                    result,
+                    value,
+                    kind,
+                })
+            }
+            tag::GET_RUNTIME => Instruction::GetRuntime(GetRuntime { result: self.id()? }),
+            tag::UNREACHABLE => Instruction::Unreachable(Unreachable { result: self.id()? }),
+            _ => return Err(LoadError::Malformed("invalid instruction")),
+        };
+
+        Ok(instruction)
+    }
+
+    fn record_key<C: Tag>(&mut self) -> LoadResult<RecordKey<C>> {
+        match self.u8()? {
+            0 => Ok(RecordKey::Prop(self.id()?)),
+            1 => Ok(RecordKey::Atom(self.atom()?)),
+            2 => Ok(RecordKey::DynAtom(self.id()?)),
+            _ => Err(LoadError::Malformed("invalid record key")),
+        }
+    }
+
+    fn ffi_value_type(&mut self) -> LoadResult<FFIValueType> {
+        match self.u8()? {
+            0 => Ok(FFIValueType::Any),
+            1 => Ok(FFIValueType::Runtime),
+            2 => Ok(FFIValueType::String),
+            3 => Ok(FFIValueType::I64),
+            4 => Ok(FFIValueType::F64),
+            5 => Ok(FFIValueType::Bool),
+            6 => Ok(FFIValueType::Bytes),
+            7 => Ok(FFIValueType::Opaque),
+            8 => Ok(FFIValueType::Record),
+            9 => Ok(FFIValueType::Nullable(Box::new(self.ffi_value_type()?))),
+            _ => Err(LoadError::Malformed("invalid FFI value type")),
+        }
+    }
+
+    fn indexed<T: Copy>(&mut self, items: &[T]) -> LoadResult<T> {
+        (items.get(self.usize()?).copied()).ok_or(LoadError::Malformed("invalid index"))
+    }
+
+    fn option<T>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> LoadResult<T>,
+    ) -> LoadResult<Option<T>> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(read(self)?)),
+            _ => Err(LoadError::Malformed("invalid option")),
+        }
+    }
+
+    fn u8(&mut self) -> LoadResult<u8> {
+        let mut byte = [0];
+        self.0.read_exact(&mut byte)?;
+        Ok(byte[0])
+    }
+
+    fn uint(&mut self) -> LoadResult<u64> {
+        let mut value = 0;
+
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7F) as u64) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(LoadError::Malformed("varint is too long"))
+    }
+
+    fn int(&mut self) -> LoadResult<i64> {
+        let value = self.uint()?;
+        Ok(((value >> 1) as i64) ^ -((value & 1) as i64))
+    }
+
+    fn u32(&mut self) -> LoadResult<u32> {
+        u32::try_from(self.uint()?).map_err(|_| LoadError::Malformed("integer is too large"))
+    }
+
+    fn usize(&mut self) -> LoadResult<usize> {
+        Ok(self.uint()? as usize)
+    }
+
+    fn id<I: IdCompat>(&mut self) -> LoadResult<I> {
+        match self.usize()? {
+            usize::MAX => Err(LoadError::Malformed("invalid id")),
+            value => Ok(I::new_with_value(value)),
+        }
+    }
+
+    fn ids<I: IdCompat>(&mut self) -> LoadResult<Vec<I>> {
+        let len = self.usize()?;
+        let mut ids = Vec::with_capacity(len.min(1024));
+
+        for _ in 0..len {
+            ids.push(self.id()?);
+        }
+
+        Ok(ids)
+    }
+
+    fn atom(&mut self) -> LoadResult<Atom> {
+        let value = u16::try_from(self.uint()?).ok().and_then(NonZeroU16::new);
+        value.map(Atom).ok_or(LoadError::Malformed("invalid atom"))
+    }
+
+    fn bytes(&mut self) -> LoadResult<Vec<u8>> {
+        let len = self.usize()?;
+        let mut bytes = Vec::with_capacity(len.min(1 << 16));
+        (&mut self.0).take(len as u64).read_to_end(&mut bytes)?;
+
+        match bytes.len() == len {
+            true => Ok(bytes),
+            false => Err(LoadError::Io(io::ErrorKind::UnexpectedEof.into())),
+        }
+    }
+
+    fn string(&mut self) -> LoadResult<String> {
+        String::from_utf8(self.bytes()?).map_err(|_| LoadError::Malformed("invalid string"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::{display_jssatir::display, parse_jssatir::parse};
+    use crate::lifted::lift;
+
+    const PROGRAM: &str = r#"entrypoint @0;
+
+atom 1 = "Key";
+atom 32768 = gen;
+const #0 = "hello";
+
+ext fn @@0 "print"(Runtime, String, ) -> Void;
+
+ext fn @@1 "ffi"(I64, F64, Bool, Bytes, Opaque, Record, ) -> Nullable(Bytes);
+
+fn @0 "main"() {
+  @0.$0():
+    %0 = GetRuntime;
+    %1 = MakeBytes #0;
+    CallExtern @@0(%0, %1)
+    %2 = MakeInteger -1234567;
+    %3 = CallStatic @1(%2)
+    Return %3;
+}
+
+fn @1(%0, ) {
+  @1.$0():
+    -- "comment", jssat_ir/src/binary.rs:1:1
+    %1 = NewRecord;
+    %2 = MakeAtom 32768;
+    RecordSet %1.[[1]] = %0
+    RecordSet %1.[[%2]] = <remove>
+    %3 = MakeFloat -0.5;
+    %4 = MakeBigInt -98765432109876543210;
+    %5 = BinOp LessThan %0, %3
+    %6 = IsType %4 = BigNumber;
+    Assert %6; -- "is a bigint"
+    %7 = NewTuple (%3, %4);
+    %8 = TupleGet %7.1;
+    If %5:
+     $1(%8);
+else $2();
+  @1.$1(%9, ):
+    Return %9;
+  @1.$2():
+    %10 = MakeBoolean true;
+    Switch %10:
+     case atom 1 => $1(%10);
+     case 3 => $1(%2);
+     case false => $2();
+else $1(%10);
+}
+
+"#;
+
+    fn display_lifted(program: &LiftedProgram) -> String {
+        let functions = sorted(&program.functions).into_iter();
+        let functions = functions.map(|(id, function)| lifted::display(id, function));
+        functions.collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn roundtrips_ir() {
+        let ir = parse(PROGRAM).unwrap();
+
+        let mut bytes = Vec::new();
+        save_ir(&ir, &mut bytes).unwrap();
+        let loaded = load_ir(bytes.as_slice()).unwrap();
+
+        assert_eq!(display(&loaded), display(&ir));
+    }
+
+    #[test]
+    fn roundtrips_lifted_programs() {
+        let program = lift(parse(PROGRAM).unwrap());
+
+        let mut bytes = Vec::new();
+        save_lifted(&program, &mut bytes).unwrap();
+        let loaded = load_lifted(bytes.as_slice()).unwrap();
+
+        assert_eq!(loaded.entrypoint, program.entrypoint);
+        assert_eq!(display_lifted(&loaded), display_lifted(&program));
+    }
+
+    #[test]
+    fn rejects_other_data() {
+        let ir = parse(PROGRAM).unwrap();
+        let mut bytes = Vec::new();
+        save_ir(&ir, &mut bytes).unwrap();
+
+        assert!(matches!(
+            load_lifted(bytes.as_slice()),
+            Err(LoadError::WrongKind)
+        ));
+        assert!(matches!(
+            load_ir(&bytes[..bytes.len() - 1]),
+            Err(LoadError::Io(_))
+        ));
+        assert!(matches!(
+            load_ir(&b"JSSAT\0\x7f"[..]),
+            Err(LoadError::UnsupportedVersion(127))
+        ));
+        assert!(matches!(
+            load_ir(&b"not jssat"[..]),
+            Err(LoadError::NotJssat)
+        ));
+    }
+}
@@ -0,0 +1,676 @@
+//! Structural diffs between two programs, e.g. the ECMA262 methods before and
+//! after changing the LISP they were generated from.
+//!
+//! The ids of functions, registers, constants and atoms are handed out in the
+//! order a program is built, so a small change shifts the ids of everything
+//! built after it and a textual diff of two programs is mostly noise. Instead,
+//! functions are matched by their name, and instructions are compared after
+//! being normalized:
+//!
+//! - the blocks of a function are numbered in depth first order, and
+//! - functions, external functions, constants and atoms are referred to by
+//!   their names or contents.
+//!
+//! The normalized instructions of two functions are aligned with a longest
+//! common subsequence while ignoring registers, which finds the added, removed
+//! and changed instructions. Aligned instructions are only the same if their
+//! registers agree with how the registers of the instructions before them were
+//! renamed.
+
+use std::{
+    convert::TryFrom,
+    fmt::{Display, Write},
+    num::NonZeroU16,
+};
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    analysis::{ControlFlow, MissingFunction},
+    frontend::{
+        display_jssatir::display_instruction,
+        ir::{InstructionData, IR},
+    },
+    id::IdCompat,
+    isa::{decode_utf16, Atom, AtomDealer},
+    lifted::{lift, FunctionId, LiftedProgram},
+};
+
+/// The differences between two programs. Functions that are the same in both
+/// programs are not present.
+#[derive(Debug, Clone, Default)]
+pub struct ProgramDiff {
+    pub functions: Vec<FunctionDiff>,
+}
+
+impl ProgramDiff {
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionDiff {
+    /// The name of the function. Unnamed functions are matched in the order
+    /// they were created, and are named `<anonymous N>`.
+    pub name: String,
+    pub change: FunctionChange,
+}
+
+#[derive(Debug, Clone)]
+pub enum FunctionChange {
+    Added,
+    Removed,
+    Changed(Vec<InstructionChange>),
+}
+
+/// A change to an instruction of a function. Instructions are referred to by
+/// their index in the normalized listing of the function, which has a line
+/// for the parameters of every block followed by the instructions of the block.
+/// Registers keep the ids they have in their own program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstructionChange {
+    Added {
+        new: usize,
+        instruction: String,
+    },
+    Removed {
+        old: usize,
+        instruction: String,
+    },
+    Changed {
+        old: usize,
+        new: usize,
+        before: String,
+        after: String,
+    },
+}
+
+/// Computes the differences between two [`IR`]s, by lifting them.
+pub fn diff_ir(old: &IR, new: &IR) -> Result<ProgramDiff, MissingFunction> {
+    diff(&lift(old.clone()), &lift(new.clone()))
+}
+
+/// Computes the differences between two [`LiftedProgram`]s.
+pub fn diff(old: &LiftedProgram, new: &LiftedProgram) -> Result<ProgramDiff, MissingFunction> {
+    let old_fns = listings(old)?;
+    let mut new_fns = listings(new)?;
+
+    let mut functions = vec![];
+
+    for (name, old_listing) in old_fns {
+        let change = match new_fns.iter().position(|(n, _)| *n == name) {
+            Some(idx) => {
+                let (_, new_listing) = new_fns.remove(idx);
+                let changes = align(&old_listing, &new_listing);
+
+                if changes.is_empty() {
+                    continue;
+                }
+
+                FunctionChange::Changed(changes)
+            }
+            None => FunctionChange::Removed,
+        };
+
+        functions.push(FunctionDiff { name, change });
+    }
+
+    for (name, _) in new_fns {
+        functions.push(FunctionDiff {
+            name,
+            change: FunctionChange::Added,
+        });
+    }
+
+    Ok(ProgramDiff { functions })
+}
+
+/// The names and normalized listings of every function in a program, in the
+/// order the functions were created.
+fn listings(program: &LiftedProgram) -> Result<Vec<(String, Vec<Line>)>, MissingFunction> {
+    // every block of a function is lifted into its own function. the entry
+    // block keeps the id the function was given before the ids of the other
+    // blocks were generated, so it has the lowest id of all the blocks
+    let mut entries = FxHashMap::default();
+    for (id, function) in program.functions.iter() {
+        let entry = entries.entry(function.ir_fn_id).or_insert(*id);
+        if id.value() < entry.value() {
+            *entry = *id;
+        }
+    }
+
+    let mut entries = entries.into_iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(ir_fn_id, _)| ir_fn_id.value());
+
+    let mut names = FxHashMap::default();
+    let mut taken = FxHashMap::default();
+    let mut anonymous = 0;
+    for (_, entry) in entries.iter() {
+        let name = match &program.functions.get(entry).unwrap().name {
+            Some(name) => {
+                // names aren't guaranteed to be unique, so duplicates are
+                // matched in the order they were created
+                let count = taken.entry(name.clone()).or_insert(0);
+                *count += 1;
+                match *count {
+                    1 => name.clone(),
+                    n => format!("{} ({})", name, n),
+                }
+            }
+            None => {
+                anonymous += 1;
+                format!("<anonymous {}>", anonymous)
+            }
+        };
+
+        names.insert(*entry, name);
+    }
+
+    (entries.into_iter())
+        .map(|(_, entry)| {
+            let listing = Normalizer::new(program, &names, entry)?.listing();
+            Ok((names[&entry].clone(), listing))
+        })
+        .collect()
+}
+
+struct Normalizer<'p> {
+    program: &'p LiftedProgram,
+    dealer: &'p AtomDealer,
+    names: &'p FxHashMap<FunctionId, String>,
+    blocks: Vec<FunctionId>,
+}
+
+/// An instruction in the listing of a function.
+struct Line {
+    text: String,
+    /// The text with every register replaced by `%_`.
+    shape: String,
+    /// The registers in the text, in order.
+    registers: Vec<usize>,
+}
+
+impl<'p> Normalizer<'p> {
+    fn new(
+        program: &'p LiftedProgram,
+        names: &'p FxHashMap<FunctionId, String>,
+        entry: FunctionId,
+    ) -> Result<Self, MissingFunction> {
+        Ok(Self {
+            program,
+            dealer: &program.dealer,
+            names,
+            blocks: ControlFlow::new(program, entry)?.blocks().collect(),
+        })
+    }
+
+    fn listing(self) -> Vec<Line> {
+        let mut listing = vec![];
+
+        for (idx, block) in self.blocks.iter().enumerate() {
+            let function = self.program.functions.get(block).unwrap();
+
+            let mut header = format!("${}(", idx);
+            for register in function.parameters.iter() {
+                write!(header, "%{}, ", register).unwrap();
+            }
+            header.push_str("):");
+            listing.push(self.normalize(&header));
+
+            for inst in function.instructions.iter() {
+                let text = match &inst.data {
+                    // comments hold the rust source location they were made at,
+                    // which changes whenever the code generating them changes
+                    InstructionData::Comment(comment) => format!("-- {:?}", comment.message),
+                    data => {
+                        let mut text = String::new();
+                        display_instruction(&mut text, data).unwrap();
+                        text
+                    }
+                };
+
+                listing.push(self.normalize(&text));
+            }
+
+            let mut text = String::new();
+            function.end.display(&mut text).unwrap();
+            listing.push(self.normalize(&text));
+        }
+
+        listing
+    }
+
+    fn normalize(&self, text: &str) -> Line {
+        let mut normalized = String::new();
+        let mut shape = String::new();
+        let mut registers = vec![];
+        let mut rest = text;
+
+        while !rest.is_empty() {
+            // strings are kept as they are, as they may look like ids
+            if rest.starts_with('"') {
+                let len = quoted_len(rest);
+                normalized.push_str(&rest[..len]);
+                shape.push_str(&rest[..len]);
+                rest = &rest[len..];
+                continue;
+            }
+
+            let (prefix, sigil) = match ["%", "@@", "@", "#", "[[", "MakeAtom ", "atom "]
+                .into_iter()
+                .find(|sigil| rest.starts_with(sigil))
+            {
+                Some(sigil) => (sigil, &rest[sigil.len()..]),
+                None => {
+                    let c = rest.chars().next().unwrap();
+                    normalized.push(c);
+                    shape.push(c);
+                    rest = &rest[c.len_utf8()..];
+                    continue;
+                }
+            };
+
+            let digits = sigil.bytes().take_while(u8::is_ascii_digit).count();
+            if digits == 0 {
+                normalized.push_str(prefix);
+                shape.push_str(prefix);
+                rest = sigil;
+                continue;
+            }
+
+            let id = sigil[..digits].parse::<usize>().unwrap();
+            rest = &sigil[digits..];
+
+            if prefix == "%" {
+                write!(normalized, "%{}", id).unwrap();
+                shape.push_str("%_");
+                registers.push(id);
+            } else {
+                let start = normalized.len();
+                self.name(&mut normalized, prefix, id);
+                shape.push_str(&normalized[start..]);
+            }
+        }
+
+        Line {
+            text: normalized,
+            shape,
+            registers,
+        }
+    }
+
+    fn name(&self, w: &mut String, prefix: &str, id: usize) {
+        match prefix {
+            "@@" => {
+                let id = crate::lifted::ExternalFunctionId::new_with_value(id);
+                match self.program.external_functions.get(&id) {
+                    Some(ext_fn) => write!(w, "@@{:?}", ext_fn.name),
+                    None => write!(w, "@@{}", id),
+                }
+            }
+            "@" => {
+                let id = FunctionId::new_with_value(id);
+                match self.blocks.iter().position(|b| *b == id) {
+                    Some(block) => write!(w, "${}", block),
+                    None => match self.names.get(&id) {
+                        Some(name) => write!(w, "@{:?}", name),
+                        None => write!(w, "@{}", id),
+                    },
+                }
+            }
+            "#" => {
+                let id = crate::lifted::ConstantId::new_with_value(id);
+                match self.program.constants.get(&id) {
+                    Some(constant) => match decode_utf16(&constant.payload) {
+                        Some(string) => write!(w, "#{:?}", string),
+                        None => write!(w, "#{:?}", constant.payload),
+                    },
+                    None => write!(w, "#{}", id),
+                }
+            }
+            // `[[`, `MakeAtom ` and `atom `
+            _ => {
+                let name = (u16::try_from(id).ok())
+                    .and_then(NonZeroU16::new)
+                    .and_then(|atom| self.dealer.try_resolve_name(Atom(atom)));
+
+                match name {
+                    Some(name) => write!(w, "{}{:?}", prefix, name),
+                    None => write!(w, "{}{}", prefix, id),
+                }
+            }
+        }
+        .unwrap();
+    }
+}
+
+/// Aligns the listings of two functions with a longest common subsequence.
+/// Removals followed by additions are paired up as changes.
+fn align(old: &[Line], new: &[Line]) -> Vec<InstructionChange> {
+    // lcs[i][j] is the length of the longest common subsequence of old[i..]
+    // and new[j..]
+    let mut lcs = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = match old[i].shape == new[j].shape {
+                true => lcs[i + 1][j + 1] + 1,
+                false => lcs[i + 1][j].max(lcs[i][j + 1]),
+            };
+        }
+    }
+
+    let mut changes = vec![];
+    let mut removed = vec![];
+    let mut added = vec![];
+    let mut renames = Renames::default();
+
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i].shape == new[j].shape {
+            match renames.agree(&old[i].registers, &new[j].registers) {
+                true => flush(&mut changes, &mut removed, &mut added, old, new),
+                false => {
+                    removed.push(i);
+                    added.push(j);
+                }
+            }
+
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            added.push(j);
+            j += 1;
+        } else {
+            removed.push(i);
+            i += 1;
+        }
+    }
+
+    flush(&mut changes, &mut removed, &mut added, old, new);
+    return changes;
+
+    fn flush(
+        changes: &mut Vec<InstructionChange>,
+        removed: &mut Vec<usize>,
+        added: &mut Vec<usize>,
+        old: &[Line],
+        new: &[Line],
+    ) {
+        let paired = removed.len().min(added.len());
+
+        for (old_idx, new_idx) in removed.iter().zip(added.iter()) {
+            changes.push(InstructionChange::Changed {
+                old: *old_idx,
+                new: *new_idx,
+                before: old[*old_idx].text.clone(),
+                after: new[*new_idx].text.clone(),
+            });
+        }
+
+        for old_idx in removed.drain(..).skip(paired) {
+            changes.push(InstructionChange::Removed {
+                old: old_idx,
+                instruction: old[old_idx].text.clone(),
+            });
+        }
+
+        for new_idx in added.drain(..).skip(paired) {
+            changes.push(InstructionChange::Added {
+                new: new_idx,
+                instruction: new[new_idx].text.clone(),
+            });
+        }
+    }
+}
+
+/// How the registers of one function were renamed in the other.
+#[derive(Default)]
+struct Renames {
+    old_to_new: FxHashMap<usize, usize>,
+    new_to_old: FxHashMap<usize, usize>,
+}
+
+impl Renames {
+    /// Checks that the registers of two aligned instructions agree with the
+    /// renames so far, recording the renames of registers not seen before.
+    fn agree(&mut self, old: &[usize], new: &[usize]) -> bool {
+        let agrees = old.iter().zip(new.iter()).all(|(o, n)| {
+            !matches!(self.old_to_new.get(o), Some(x) if x != n)
+                && !matches!(self.new_to_old.get(n), Some(x) if x != o)
+        });
+
+        if agrees {
+            for (o, n) in old.iter().zip(new.iter()) {
+                self.old_to_new.insert(*o, *n);
+                self.new_to_old.insert(*n, *o);
+            }
+        }
+
+        agrees
+    }
+}
+
+impl Display for ProgramDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for function in self.functions.iter() {
+            let changes = match &function.change {
+                FunctionChange::Added => {
+                    writeln!(f, "+ fn {}", function.name)?;
+                    continue;
+                }
+                FunctionChange::Removed => {
+                    writeln!(f, "- fn {}", function.name)?;
+                    continue;
+                }
+                FunctionChange::Changed(changes) => changes,
+            };
+
+            writeln!(f, "~ fn {}", function.name)?;
+
+            for change in changes {
+                match change {
+                    InstructionChange::Added { new, instruction } => {
+                        writeln!(f, "    + [{}] {}", new, indent(instruction))?
+                    }
+                    InstructionChange::Removed { old, instruction } => {
+                        writeln!(f, "    - [{}] {}", old, indent(instruction))?
+                    }
+                    InstructionChange::Changed {
+                        old,
+                        new,
+                        before,
+                        after,
+                    } => {
+                        writeln!(f, "    - [{}] {}", old, indent(before))?;
+                        writeln!(f, "    + [{}] {}", new, indent(after))?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The length of the string literal that `text` starts with, including its
+/// quotes. Unterminated strings last until the end of `text`.
+fn quoted_len(text: &str) -> usize {
+    let mut escaped = false;
+
+    for (idx, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return idx + 1,
+            _ => {}
+        }
+    }
+
+    text.len()
+}
+
+// `If` and `Switch` instructions span multiple lines
+fn indent(instruction: &str) -> String {
+    instruction.replace('\n', "\n          ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::parse_jssatir::parse;
+
+    const OLD: &str = r#"entrypoint @0;
+
+atom 1 = "Foo";
+const #0 = "hi";
+
+fn @0 "Main"() {
+  @0.$0():
+    %0 = MakeAtom 1;
+    %1 = CallStatic @1(%0)
+    Return %1;
+}
+
+fn @1 "Identity"(%0, ) {
+  @1.$0():
+    Return %0;
+}
+
+fn @2 "Unused"() {
+  @2.$0():
+    Return;
+}
+
+fn @3 "Pick"(%0, %1, ) {
+  @3.$0():
+    Return %0;
+}
+
+"#;
+
+    // every id is shifted, `Main` computes a string before its call, `Pick`
+    // returns its other parameter and `Unused` was replaced by `Greeting`
+    const NEW: &str = r#"entrypoint @1;
+
+atom 1 = "Bar";
+atom 2 = "Foo";
+const #3 = "hi";
+
+fn @0 "Greeting"() {
+  @0.$0():
+    %4 = MakeBytes #3;
+    Return %4;
+}
+
+fn @1 "Main"() {
+  @1.$0():
+    %7 = MakeBytes #3;
+    %5 = MakeAtom 2;
+    %8 = CallStatic @2(%5)
+    Return %8;
+}
+
+fn @2 "Identity"(%3, ) {
+  @2.$0():
+    Return %3;
+}
+
+fn @3 "Pick"(%0, %1, ) {
+  @3.$0():
+    Return %1;
+}
+
+"#;
+
+    #[test]
+    fn same_program_has_no_differences() {
+        let program = parse(OLD).unwrap();
+        assert!(diff_ir(&program, &program).unwrap().is_empty());
+    }
+
+    #[test]
+    fn matches_functions_by_name_modulo_ids() {
+        let diff = diff_ir(&parse(OLD).unwrap(), &parse(NEW).unwrap()).unwrap();
+
+        let change = |name: &str| {
+            (diff.functions.iter())
+                .find(|f| f.name == name)
+                .map(|f| f.change.clone())
+        };
+
+        assert!(change("Identity").is_none());
+        assert!(matches!(change("Unused"), Some(FunctionChange::Removed)));
+        assert!(matches!(change("Greeting"), Some(FunctionChange::Added)));
+
+        let changes = match change("Main") {
+            Some(FunctionChange::Changed(changes)) => changes,
+            other => panic!("expected `Main` to change, got {:?}", other),
+        };
+
+        assert_eq!(
+            changes,
+            vec![InstructionChange::Added {
+                new: 1,
+                instruction: "%7 = MakeBytes #\"hi\";".to_string(),
+            }]
+        );
+
+        let changes = match change("Pick") {
+            Some(FunctionChange::Changed(changes)) => changes,
+            other => panic!("expected `Pick` to change, got {:?}", other),
+        };
+
+        assert_eq!(
+            changes,
+            vec![InstructionChange::Changed {
+                old: 1,
+                new: 1,
+                before: "Return %0;".to_string(),
+                after: "Return %1;".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn strings_are_not_normalized() {
+        let program = |register: usize| {
+            let source = format!(
+                r#"entrypoint @0;
+
+fn @0 "Main"() {{
+  @0.$0():
+    -- "50%1 of @0 and #0", src/main.rs:1:1
+    %{0} = MakeBoolean true;
+    Assert %{0}; -- "%{0} is \"true\""
+    Return;
+}}
+"#,
+                register
+            );
+            parse(&source).unwrap()
+        };
+
+        assert!(diff_ir(&program(0), &program(0)).unwrap().is_empty());
+
+        // the register is renamed, but the same register in the message isn't
+        let diff = diff_ir(&program(0), &program(1)).unwrap();
+        let changes = match &diff.functions[..] {
+            [FunctionDiff {
+                change: FunctionChange::Changed(changes),
+                ..
+            }] => changes,
+            other => panic!("expected `Main` to change, got {:?}", other),
+        };
+
+        assert_eq!(
+            changes,
+            &vec![InstructionChange::Changed {
+                old: 3,
+                new: 3,
+                before: "Assert %0; -- \"%0 is \\\"true\\\"\"".to_string(),
+                after: "Assert %1; -- \"%1 is \\\"true\\\"\"".to_string(),
+            }]
+        );
+    }
+}
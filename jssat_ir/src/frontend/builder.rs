@@ -1,15 +1,19 @@
 use std::ops::{Deref, DerefMut};
-use std::sync::Arc;
+use std::panic::Location;
+use std::sync::{Arc, RwLock};
 
+use num_bigint::BigInt;
+use ordered_float::OrderedFloat;
 use rustc_hash::FxHashMap;
 
 use crate::frontend::ir::*;
 use crate::id::{Counter, IdCompat};
+use crate::verify::{self, Violation};
 use crate::UnwrapNone;
 
 use crate::isa::*;
 
-use derive_more::{Deref, DerefMut};
+use derive_more::{Deref, DerefMut, Display};
 
 use super::source_map::SourceMapIdx;
 
@@ -31,6 +35,155 @@ pub fn panics_on_drop_with_function_start_without_function_end() {
     // builder.end_function(my_fn);
 }
 
+#[test]
+pub fn checked_reports_function_start_without_function_end() {
+    let mut builder = ProgramBuilder::new_checked();
+    builder.create_blank_entrypoint();
+    #[allow(unused_variables)]
+    let (my_fn, []) = builder.start_function();
+
+    let error = builder.try_finish().expect_err("expected an error");
+    assert!(matches!(error.kind, BuildErrorKind::UnfinishedFunction(_)));
+}
+
+#[test]
+pub fn checked_reports_arity_mismatches() {
+    let mut builder = ProgramBuilder::new_checked();
+    builder.create_blank_entrypoint();
+
+    let (mut my_fn, [x]) = builder.start_function();
+    let (target, []) = my_fn.start_block();
+    let mut block = my_fn.start_block_main();
+    let error = block.try_call_dynargs(my_fn.id, vec![]).err();
+    assert!(matches!(
+        error.map(|e| e.kind),
+        Some(BuildErrorKind::ArityMismatch {
+            expected: 1,
+            passed: 0,
+            ..
+        })
+    ));
+
+    let target_signature = target.signature();
+    my_fn.end_block(target.ret(None));
+    let error = my_fn
+        .try_end_block_dyn(
+            block
+                .into_dynamic()
+                .jmp_dynargs(target_signature.id, vec![x]),
+        )
+        .err();
+    assert!(matches!(
+        error.map(|e| e.kind),
+        Some(BuildErrorKind::ArityMismatch {
+            expected: 0,
+            passed: 1,
+            ..
+        })
+    ));
+}
+
+/// A mistake made while building a program, as reported by the checked builder
+/// methods such as [`ProgramBuilder::try_finish`].
+#[derive(Clone, Debug)]
+pub struct BuildError {
+    pub kind: BuildErrorKind,
+    /// Where the mistake was made, e.g. the call to `try_end_block` that
+    /// finished a block jumping with the wrong amount of arguments.
+    pub location: &'static Location<'static>,
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.location, self.kind)
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+#[derive(Clone, Debug, Display)]
+pub enum BuildErrorKind {
+    #[display(
+        fmt = "expected an entrypoint function! generate an entrypoint function using ProgramBuilder::start_function_main()"
+    )]
+    MissingEntrypoint,
+    #[display(
+        fmt = "function @{} was started, but `end_function` was never called",
+        _0
+    )]
+    UnfinishedFunction(FunctionId),
+    #[display(fmt = "function @{} was ended more than once", _0)]
+    DuplicateFunction(FunctionId),
+    #[display(fmt = "function @{} does not exist", _0)]
+    MissingFunction(FunctionId),
+    #[display(fmt = "external function @@{} does not exist", _0)]
+    MissingExternalFunction(ExternalFunctionId),
+    #[display(fmt = "function @{} has no entry block", _0)]
+    MissingEntryBlock(FunctionId),
+    #[display(fmt = "block ${} was started, but `end_block` was never called", _0)]
+    UnfinishedBlock(BlockId),
+    #[display(fmt = "block ${} was ended more than once", _0)]
+    DuplicateBlock(BlockId),
+    #[display(fmt = "block ${} does not exist", _0)]
+    MissingBlock(BlockId),
+    #[display(
+        fmt = "{} is passed {} arguments, but has {} parameters",
+        target,
+        passed,
+        expected
+    )]
+    ArityMismatch {
+        /// The jump target or called function, e.g. `$1` or `@@2`.
+        target: String,
+        expected: usize,
+        passed: usize,
+    },
+    /// The program was built without mistakes the builder could catch, but
+    /// failed verification.
+    #[display(fmt = "malformed IR:\n{}", "verify::display_violations(_0)")]
+    Malformed(Vec<Violation>),
+}
+
+/// What the builders of a program know about the functions in it, so that the
+/// checked builder methods can validate calls while functions are still being
+/// built.
+struct Shared {
+    /// In checked mode, forgetting to end a function or block is reported by
+    /// the checked methods instead of panicking when the builder is dropped.
+    checked: bool,
+    /// The functions and the arity of the external functions of the program.
+    /// These are only recorded in checked mode, so that unchecked builders
+    /// don't contend over the locks.
+    functions: RwLock<FxHashMap<FunctionId, Started>>,
+    external_functions: RwLock<Vec<usize>>,
+}
+
+#[derive(Clone, Copy)]
+struct Started {
+    parameters: usize,
+    location: &'static Location<'static>,
+}
+
+fn check_arity(
+    target: impl FnOnce() -> String,
+    expected: usize,
+    passed: usize,
+    location: &'static Location<'static>,
+) -> Result<(), BuildError> {
+    if expected == passed {
+        return Ok(());
+    }
+
+    Err(BuildError {
+        kind: BuildErrorKind::ArityMismatch {
+            target: target(),
+            expected,
+            passed,
+        },
+        location,
+    })
+}
+
 #[derive(Deref, DerefMut)]
 pub struct ProgramBuilder {
     #[deref]
@@ -41,6 +194,7 @@ pub struct ProgramBuilder {
     external_functions: Vec<ExternalFunction>,
     functions: FxHashMap<FunctionId, Function>,
     gen_function_id: Counter<FunctionId>,
+    shared: Arc<Shared>,
 }
 
 #[derive(Clone, Copy, Hash, PartialEq, Eq)]
@@ -48,6 +202,19 @@ pub struct ExtFnIdTyped<const PARAMETERS: usize>(pub ExternalFunctionId);
 
 impl ProgramBuilder {
     pub fn new() -> Self {
+        Self::new_with_mode(false)
+    }
+
+    /// Creates a builder in checked mode. Mistakes are reported as
+    /// [`BuildError`]s by the `try_` methods, and dropping a function or block
+    /// builder without ending it does not panic, but is reported by
+    /// [`ProgramBuilder::try_finish`] or [`FunctionBuilder::try_end_function`]
+    /// instead.
+    pub fn new_checked() -> Self {
+        Self::new_with_mode(true)
+    }
+
+    fn new_with_mode(checked: bool) -> Self {
         ProgramBuilder {
             dealer: AtomDealer::new(),
             entrypoint: None,
@@ -55,6 +222,11 @@ impl ProgramBuilder {
             external_functions: vec![],
             functions: FxHashMap::default(),
             gen_function_id: Counter::new(),
+            shared: Arc::new(Shared {
+                checked,
+                functions: RwLock::default(),
+                external_functions: RwLock::default(),
+            }),
         }
     }
 
@@ -73,6 +245,55 @@ impl ProgramBuilder {
     pub fn finish(self) -> IR {
         let entrypoint = self.entrypoint.expect("expected an entrypoint function! generate an entrypoint function using ProgramBuilder::start_function_main()");
 
+        let ir = self.build(entrypoint);
+        crate::verify::debug_verify(&ir);
+        ir
+    }
+
+    /// Analogous to [`ProgramBuilder::finish`], except that mistakes are
+    /// returned rather than panicked on. The built program is always verified,
+    /// even in release builds.
+    #[track_caller]
+    pub fn try_finish(self) -> Result<IR, BuildError> {
+        let location = Location::caller();
+
+        let entrypoint = (self.entrypoint).ok_or(BuildError {
+            kind: BuildErrorKind::MissingEntrypoint,
+            location,
+        })?;
+
+        let unfinished = {
+            let started = self.shared.functions.read().unwrap();
+            (started.iter())
+                .filter(|(id, _)| !self.functions.contains_key(id))
+                .map(|(id, started)| (*id, started.location))
+                .min_by_key(|(id, _)| id.value())
+        };
+
+        if let Some((id, location)) = unfinished {
+            return Err(BuildError {
+                kind: BuildErrorKind::UnfinishedFunction(id),
+                location,
+            });
+        }
+
+        let ir = self.build(entrypoint);
+
+        if let Err(violations) = verify::verify(&ir) {
+            let location = (violations.iter())
+                .find_map(|v| v.constructed_at)
+                .unwrap_or(location);
+
+            return Err(BuildError {
+                kind: BuildErrorKind::Malformed(violations),
+                location,
+            });
+        }
+
+        Ok(ir)
+    }
+
+    fn build(self, entrypoint: FunctionId) -> IR {
         fn into_map<I: IdCompat, E>(collection: impl IntoIterator<Item = E>) -> FxHashMap<I, E> {
             collection
                 .into_iter()
@@ -102,17 +323,7 @@ impl ProgramBuilder {
     }
 
     pub fn constant_str_utf16<S: Into<String>>(&mut self, message: S) -> ConstantId {
-        let message = message.into();
-        let mut payload = Vec::with_capacity(message.len() * 2);
-
-        let utf16_payload = message
-            .encode_utf16()
-            .into_iter()
-            .flat_map(|x| std::array::IntoIter::new(x.to_ne_bytes()));
-
-        payload.extend(utf16_payload);
-
-        self.constant(payload)
+        self.constant(encode_utf16(&message.into()))
     }
 
     pub fn external_function<N: ToString, const PARAMETERS: usize>(
@@ -131,6 +342,10 @@ impl ProgramBuilder {
         parameters: Vec<FFIValueType>,
         return_type: FFIReturnType,
     ) -> ExternalFunctionId {
+        if self.shared.checked {
+            (self.shared.external_functions.write().unwrap()).push(parameters.len());
+        }
+
         self.external_functions.push(ExternalFunction {
             name: name.to_string(),
             parameters: parameters.to_vec(),
@@ -141,6 +356,7 @@ impl ProgramBuilder {
         ExternalFunctionId::new_with_value(id)
     }
 
+    #[track_caller]
     pub fn start_function_main(&mut self) -> FunctionBuilder<0> {
         assert!(
             matches!(self.entrypoint, None),
@@ -152,11 +368,20 @@ impl ProgramBuilder {
         builder
     }
 
+    #[track_caller]
     pub fn start_function<const PARAMETERS: usize>(
         &self,
     ) -> (FunctionBuilder<PARAMETERS>, [RegisterId; PARAMETERS]) {
         let id = self.gen_function_id.next();
 
+        if self.shared.checked {
+            let started = Started {
+                parameters: PARAMETERS,
+                location: Location::caller(),
+            };
+            (self.shared.functions.write().unwrap()).insert(id, started);
+        }
+
         // TODO: is there a better way to do this?
         // PARAMETERS = 3, parameters = [RegisterId::new_with_value_const(0), ...(1),
         // ...(2)]
@@ -165,7 +390,7 @@ impl ProgramBuilder {
             *parameter = RegisterId::new_with_value_const(idx);
         }
 
-        (FunctionBuilder::new(id), parameters)
+        (FunctionBuilder::new(id, self.shared.clone()), parameters)
     }
 
     pub fn end_function<const PARAMETERS: usize>(
@@ -181,6 +406,30 @@ impl ProgramBuilder {
 
         signature
     }
+
+    /// Analogous to [`ProgramBuilder::end_function`], except that mistakes are
+    /// returned rather than panicked on.
+    #[track_caller]
+    pub fn try_end_function<const PARAMETERS: usize>(
+        &mut self,
+        mut builder: FunctionBuilder<PARAMETERS>,
+    ) -> Result<FnSignature<PARAMETERS>, BuildError> {
+        let location = Location::caller();
+        let signature = builder.signature();
+        builder.is_ok_to_drop = true;
+
+        if self.functions.contains_key(&signature.id) {
+            return Err(BuildError {
+                kind: BuildErrorKind::DuplicateFunction(signature.id),
+                location,
+            });
+        }
+
+        let function = builder.try_finish(location)?;
+        self.functions.insert(signature.id, function);
+
+        Ok(signature)
+    }
 }
 
 impl Default for ProgramBuilder {
@@ -196,6 +445,10 @@ pub struct FunctionBuilder<const PARAMETERS: usize> {
     gen_register_id: Arc<Counter<RegisterId>>,
     entrypoint: Option<BlockId>,
     blocks: FxHashMap<BlockId, FunctionBlock>,
+    /// The amount of parameters of every started block, and where it was
+    /// started.
+    started_blocks: FxHashMap<BlockId, Started>,
+    shared: Arc<Shared>,
     /// To prevent mistakes, if the user does not call `end_function` after
     /// having declared a builder with `start_function`, then this field will
     /// remain `false`. If, when being dropped, this field is `false`, a panic
@@ -203,12 +456,13 @@ pub struct FunctionBuilder<const PARAMETERS: usize> {
     /// `end_function`. Ideally, this would be done at compile time with
     /// ownership principles, but any design (that I can come up with) that
     /// uses mutability would prevent the builder from being used in a
-    /// multithreaded environment.
+    /// multithreaded environment. In checked mode, the mistake is reported by
+    /// [`ProgramBuilder::try_finish`] instead.
     pub(crate) is_ok_to_drop: bool,
 }
 
 impl<const P: usize> FunctionBuilder<P> {
-    pub(crate) fn new(id: FunctionId) -> Self {
+    fn new(id: FunctionId, shared: Arc<Shared>) -> Self {
         Self {
             id,
             name: None,
@@ -216,6 +470,8 @@ impl<const P: usize> FunctionBuilder<P> {
             gen_register_id: Arc::new(Counter::new_with_value(P)),
             entrypoint: None,
             blocks: FxHashMap::default(),
+            started_blocks: FxHashMap::default(),
+            shared,
             is_ok_to_drop: false,
         }
     }
@@ -239,6 +495,28 @@ impl<const P: usize> FunctionBuilder<P> {
         }
     }
 
+    fn try_finish(self, location: &'static Location<'static>) -> Result<Function, BuildError> {
+        if self.entrypoint.is_none() {
+            return Err(BuildError {
+                kind: BuildErrorKind::MissingEntryBlock(self.id),
+                location,
+            });
+        }
+
+        let unfinished = (self.started_blocks.iter())
+            .filter(|(id, _)| !self.blocks.contains_key(id))
+            .min_by_key(|(id, _)| id.value());
+
+        if let Some((id, started)) = unfinished {
+            return Err(BuildError {
+                kind: BuildErrorKind::UnfinishedBlock(*id),
+                location: started.location,
+            });
+        }
+
+        Ok(self.finish())
+    }
+
     pub const fn parameter_const<const PARAMETER: usize>(&self) -> RegisterId {
         // TODO: make this a constant assertion
         // this is currently not possible because use of generic parameters from
@@ -265,6 +543,7 @@ impl<const P: usize> FunctionBuilder<P> {
         FnSignature { id: self.id }
     }
 
+    #[track_caller]
     pub fn start_block_main(&mut self) -> BlockBuilder<0> {
         assert!(
             matches!(self.entrypoint, None),
@@ -277,10 +556,11 @@ impl<const P: usize> FunctionBuilder<P> {
         builder
     }
 
+    #[track_caller]
     pub fn start_block<const PARAMETERS: usize>(
         &mut self,
     ) -> (BlockBuilder<PARAMETERS>, [RegisterId; PARAMETERS]) {
-        let id = self.gen_block_id.next();
+        let id = self.start_block_id(PARAMETERS);
 
         // TODO: is there a better way to do this?
         // PARAMETERS = 3, parameters = [RegisterId::new_with_value_const(0), ...(1),
@@ -290,24 +570,48 @@ impl<const P: usize> FunctionBuilder<P> {
             *parameter = self.gen_register_id.next();
         }
 
-        let builder = BlockBuilder::new(id, self.gen_register_id.clone(), parameters);
+        let builder = BlockBuilder::new(
+            id,
+            self.gen_register_id.clone(),
+            self.shared.clone(),
+            parameters,
+        );
 
         (builder, parameters)
     }
 
+    #[track_caller]
     pub fn start_block_dynargs(&mut self, arg_len: usize) -> (DynBlockBuilder, Vec<RegisterId>) {
-        let id = self.gen_block_id.next();
+        let id = self.start_block_id(arg_len);
 
         let mut parameters = Vec::with_capacity(arg_len);
         for _ in 0..arg_len {
             parameters.push(self.gen_register_id.next());
         }
 
-        let builder = DynBlockBuilder::new(id, self.gen_register_id.clone(), parameters.clone());
+        let builder = DynBlockBuilder::new(
+            id,
+            self.gen_register_id.clone(),
+            self.shared.clone(),
+            parameters.clone(),
+        );
 
         (builder, parameters)
     }
 
+    #[track_caller]
+    fn start_block_id(&mut self, parameters: usize) -> BlockId {
+        let id = self.gen_block_id.next();
+
+        let started = Started {
+            parameters,
+            location: Location::caller(),
+        };
+        self.started_blocks.insert(id, started);
+
+        id
+    }
+
     pub fn end_block<const PARAMETERS: usize>(
         &mut self,
         builder: FinalizedBlockBuilder<PARAMETERS>,
@@ -325,11 +629,57 @@ impl<const P: usize> FunctionBuilder<P> {
 
         signature
     }
+
+    /// Analogous to [`FunctionBuilder::end_block`], except that mistakes are
+    /// returned rather than panicked on.
+    #[track_caller]
+    pub fn try_end_block<const PARAMETERS: usize>(
+        &mut self,
+        builder: FinalizedBlockBuilder<PARAMETERS>,
+    ) -> Result<BlkSignature<PARAMETERS>, BuildError> {
+        self.try_end_block_dyn(builder.0).map(BlkSignature)
+    }
+
+    /// Analogous to [`FunctionBuilder::end_block_dyn`], except that mistakes
+    /// are returned rather than panicked on. This includes jumping to a block
+    /// with an amount of arguments other than the amount of parameters it has,
+    /// which is otherwise only caught when verifying the finished program.
+    #[track_caller]
+    pub fn try_end_block_dyn(
+        &mut self,
+        mut builder: DynFinalizedBlockBuilder,
+    ) -> Result<DynBlkSignature, BuildError> {
+        let location = Location::caller();
+        let signature = builder.builder.signature();
+        builder.is_ok_to_drop = true;
+
+        if self.blocks.contains_key(&signature.id) {
+            return Err(BuildError {
+                kind: BuildErrorKind::DuplicateBlock(signature.id),
+                location,
+            });
+        }
+
+        for BlockJump(target, values) in builder.end_control_flow.children() {
+            let started = self.started_blocks.get(target).ok_or(BuildError {
+                kind: BuildErrorKind::MissingBlock(*target),
+                location,
+            })?;
+
+            let target = || format!("${}", target);
+            check_arity(target, started.parameters, values.len(), location)?;
+        }
+
+        let block = builder.finish();
+        self.blocks.insert(signature.id, block);
+
+        Ok(signature)
+    }
 }
 
 impl<const P: usize> Drop for FunctionBuilder<P> {
     fn drop(&mut self) {
-        if !self.is_ok_to_drop {
+        if !self.is_ok_to_drop && !self.shared.checked {
             panic!("A `FunctionBuilder` (created with `start_function`) was dropped without `end_function` being called.");
         }
     }
@@ -377,11 +727,13 @@ impl<const P: usize> BlockBuilder<P> {
     fn new(
         id: BlockId,
         gen_register_id: Arc<Counter<RegisterId>>,
+        shared: Arc<Shared>,
         parameters: [RegisterId; P],
     ) -> Self {
         Self(DynBlockBuilder::new(
             id,
             gen_register_id,
+            shared,
             parameters.to_vec(),
         ))
     }
@@ -440,6 +792,19 @@ impl<const P: usize> BlockBuilder<P> {
         ))
     }
 
+    pub fn switch_dynargs(
+        self,
+        value: RegisterId,
+        cases: Vec<(SwitchValue, BlockId, Vec<RegisterId>)>,
+        block_default: BlockId,
+        values_default: Vec<RegisterId>,
+    ) -> FinalizedBlockBuilder<P> {
+        FinalizedBlockBuilder(
+            self.0
+                .switch_dynargs(value, cases, block_default, values_default),
+        )
+    }
+
     pub fn signature(&self) -> BlkSignature<P> {
         BlkSignature(self.0.signature())
     }
@@ -449,6 +814,7 @@ impl<const P: usize> BlockBuilder<P> {
 pub struct DynBlockBuilder {
     pub id: BlockId,
     gen_register_id: Arc<Counter<RegisterId>>,
+    shared: Arc<Shared>,
     parameters: Vec<RegisterId>,
     instructions: Vec<Instruction>,
     pub(crate) is_ok_to_drop: bool,
@@ -458,11 +824,13 @@ impl DynBlockBuilder {
     fn new(
         id: BlockId,
         gen_register_id: Arc<Counter<RegisterId>>,
+        shared: Arc<Shared>,
         parameters: Vec<RegisterId>,
     ) -> Self {
         Self {
             id,
             gen_register_id,
+            shared,
             parameters: parameters.to_vec(),
             instructions: vec![],
             is_ok_to_drop: false,
@@ -511,15 +879,17 @@ impl DynBlockBuilder {
     #[track_caller]
     pub fn comment(&mut self, message: &'static str) {
         self.instructions.push(Instruction::Comment(Comment {
-            message,
-            location: std::panic::Location::caller(),
+            message: message.into(),
+            location: std::panic::Location::caller().into(),
         }));
     }
 
     #[track_caller]
     pub fn assert(&mut self, condition: RegisterId, message: &'static str) {
-        self.instructions
-            .push(Instruction::Assert(Assert { condition, message }))
+        self.instructions.push(Instruction::Assert(Assert {
+            condition,
+            message: message.into(),
+        }))
     }
 
     #[track_caller]
@@ -574,6 +944,26 @@ impl DynBlockBuilder {
         result
     }
 
+    #[track_caller]
+    pub fn make_number_float(&mut self, value: f64) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions.push(Instruction::MakeFloat(Make {
+            result,
+            item: OrderedFloat(value),
+        }));
+        result
+    }
+
+    #[track_caller]
+    pub fn make_bigint(&mut self, value: BigInt) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions.push(Instruction::MakeBigInt(Make {
+            result,
+            item: value,
+        }));
+        result
+    }
+
     #[track_caller]
     pub fn make_bool(&mut self, value: bool) -> RegisterId {
         let result = self.gen_register_id.next();
@@ -763,6 +1153,101 @@ impl DynBlockBuilder {
         result
     }
 
+    #[track_caller]
+    pub fn tuple_new(&mut self, elements: Vec<RegisterId>) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Instruction::NewTuple(NewTuple { result, elements }));
+        result
+    }
+
+    #[track_caller]
+    pub fn tuple_get(&mut self, tuple: RegisterId, index: usize) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions.push(Instruction::TupleGet(TupleGet {
+            result,
+            tuple,
+            index,
+        }));
+        result
+    }
+
+    #[track_caller]
+    pub fn tuple_len(&mut self, tuple: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Instruction::TupleLen(TupleLen { result, tuple }));
+        result
+    }
+
+    #[track_caller]
+    pub fn string_concat(&mut self, lhs: RegisterId, rhs: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Instruction::StringConcat(StringConcat { result, lhs, rhs }));
+        result
+    }
+
+    #[track_caller]
+    pub fn string_len(&mut self, string: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Instruction::StringLen(StringLen { result, string }));
+        result
+    }
+
+    #[track_caller]
+    pub fn string_slice(
+        &mut self,
+        string: RegisterId,
+        start: RegisterId,
+        end: RegisterId,
+    ) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Instruction::StringSlice(StringSlice {
+                result,
+                string,
+                start,
+                end,
+            }));
+        result
+    }
+
+    #[track_caller]
+    pub fn string_code_unit_at(&mut self, string: RegisterId, index: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Instruction::StringCodeUnitAt(StringCodeUnitAt {
+                result,
+                string,
+                index,
+            }));
+        result
+    }
+
+    #[track_caller]
+    pub fn number_to_string(&mut self, number: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Instruction::NumberToString(NumberToString {
+                result,
+                number,
+            }));
+        result
+    }
+
+    #[track_caller]
+    pub fn string_to_number(&mut self, string: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Instruction::StringToNumber(StringToNumber {
+                result,
+                string,
+            }));
+        result
+    }
+
     #[track_caller]
     fn binop(
         result: RegisterId,
@@ -786,6 +1271,98 @@ impl DynBlockBuilder {
         result
     }
 
+    #[track_caller]
+    pub fn subtract(&mut self, lhs: RegisterId, rhs: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Self::binop(result, lhs, rhs, BinaryOperator::Subtract));
+        result
+    }
+
+    #[track_caller]
+    pub fn multiply(&mut self, lhs: RegisterId, rhs: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Self::binop(result, lhs, rhs, BinaryOperator::Multiply));
+        result
+    }
+
+    #[track_caller]
+    pub fn divide(&mut self, lhs: RegisterId, rhs: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Self::binop(result, lhs, rhs, BinaryOperator::Divide));
+        result
+    }
+
+    #[track_caller]
+    pub fn remainder(&mut self, lhs: RegisterId, rhs: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Self::binop(result, lhs, rhs, BinaryOperator::Remainder));
+        result
+    }
+
+    #[track_caller]
+    pub fn exponent(&mut self, lhs: RegisterId, rhs: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Self::binop(result, lhs, rhs, BinaryOperator::Exponent));
+        result
+    }
+
+    #[track_caller]
+    pub fn bitwise_and(&mut self, lhs: RegisterId, rhs: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Self::binop(result, lhs, rhs, BinaryOperator::BitwiseAnd));
+        result
+    }
+
+    #[track_caller]
+    pub fn bitwise_or(&mut self, lhs: RegisterId, rhs: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Self::binop(result, lhs, rhs, BinaryOperator::BitwiseOr));
+        result
+    }
+
+    #[track_caller]
+    pub fn bitwise_xor(&mut self, lhs: RegisterId, rhs: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Self::binop(result, lhs, rhs, BinaryOperator::BitwiseXor));
+        result
+    }
+
+    #[track_caller]
+    pub fn shift_left(&mut self, lhs: RegisterId, rhs: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Self::binop(result, lhs, rhs, BinaryOperator::ShiftLeft));
+        result
+    }
+
+    #[track_caller]
+    pub fn shift_right(&mut self, lhs: RegisterId, rhs: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Self::binop(result, lhs, rhs, BinaryOperator::ShiftRight));
+        result
+    }
+
+    #[track_caller]
+    pub fn unsigned_shift_right(&mut self, lhs: RegisterId, rhs: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions.push(Self::binop(
+            result,
+            lhs,
+            rhs,
+            BinaryOperator::UnsignedShiftRight,
+        ));
+        result
+    }
+
     #[track_caller]
     pub fn compare_equal(&mut self, lhs: RegisterId, rhs: RegisterId) -> RegisterId {
         let result = self.gen_register_id.next();
@@ -794,6 +1371,14 @@ impl DynBlockBuilder {
         result
     }
 
+    #[track_caller]
+    pub fn compare_same_value(&mut self, lhs: RegisterId, rhs: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Self::binop(result, lhs, rhs, BinaryOperator::SameValue));
+        result
+    }
+
     #[track_caller]
     pub fn compare_less_than(&mut self, lhs: RegisterId, rhs: RegisterId) -> RegisterId {
         let result = self.gen_register_id.next();
@@ -826,6 +1411,25 @@ impl DynBlockBuilder {
         result
     }
 
+    #[track_caller]
+    pub fn not(&mut self, operand: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Instruction::Not(Not { result, operand }));
+        result
+    }
+
+    #[track_caller]
+    pub fn number_to_bigint(&mut self, operand: RegisterId) -> RegisterId {
+        let result = self.gen_register_id.next();
+        self.instructions
+            .push(Instruction::NumberToBigInt(NumberToBigInt {
+                result,
+                operand,
+            }));
+        result
+    }
+
     #[track_caller]
     pub fn call<const PARAMETERS: usize>(
         &mut self,
@@ -960,6 +1564,101 @@ impl DynBlockBuilder {
         result
     }
 
+    /// Checks that a function exists and is passed as many arguments as it has
+    /// parameters. Unchecked builders don't know which functions exist, so
+    /// their calls are left to verification instead.
+    #[track_caller]
+    fn check_call(&self, fn_id: FunctionId, passed: usize) -> Result<(), BuildError> {
+        let location = Location::caller();
+
+        if !self.shared.checked {
+            return Ok(());
+        }
+
+        let functions = self.shared.functions.read().unwrap();
+        let started = functions.get(&fn_id).ok_or(BuildError {
+            kind: BuildErrorKind::MissingFunction(fn_id),
+            location,
+        })?;
+
+        check_arity(
+            || format!("@{}", fn_id),
+            started.parameters,
+            passed,
+            location,
+        )
+    }
+
+    #[track_caller]
+    fn check_call_external(
+        &self,
+        fn_id: ExternalFunctionId,
+        passed: usize,
+    ) -> Result<(), BuildError> {
+        let location = Location::caller();
+
+        if !self.shared.checked {
+            return Ok(());
+        }
+
+        let external_functions = self.shared.external_functions.read().unwrap();
+        let parameters = external_functions.get(fn_id.value()).ok_or(BuildError {
+            kind: BuildErrorKind::MissingExternalFunction(fn_id),
+            location,
+        })?;
+
+        check_arity(|| format!("@@{}", fn_id), *parameters, passed, location)
+    }
+
+    /// Analogous to [`DynBlockBuilder::call_dynargs`], except that calling a
+    /// function that does not exist or with the wrong amount of arguments is
+    /// returned as an error rather than caught when verifying the finished
+    /// program.
+    #[track_caller]
+    pub fn try_call_dynargs(
+        &mut self,
+        fn_id: FunctionId,
+        args: Vec<RegisterId>,
+    ) -> Result<(), BuildError> {
+        self.check_call(fn_id, args.len())?;
+        self.call_dynargs(fn_id, args);
+        Ok(())
+    }
+
+    /// Analogous to [`DynBlockBuilder::try_call_dynargs`].
+    #[track_caller]
+    pub fn try_call_dynargs_with_result(
+        &mut self,
+        fn_id: FunctionId,
+        args: Vec<RegisterId>,
+    ) -> Result<RegisterId, BuildError> {
+        self.check_call(fn_id, args.len())?;
+        Ok(self.call_dynargs_with_result(fn_id, args))
+    }
+
+    /// Analogous to [`DynBlockBuilder::try_call_dynargs`].
+    #[track_caller]
+    pub fn try_call_external_function_dynargs(
+        &mut self,
+        fn_id: ExternalFunctionId,
+        args: Vec<RegisterId>,
+    ) -> Result<(), BuildError> {
+        self.check_call_external(fn_id, args.len())?;
+        self.call_external_function_dynargs(fn_id, args);
+        Ok(())
+    }
+
+    /// Analogous to [`DynBlockBuilder::try_call_dynargs`].
+    #[track_caller]
+    pub fn try_call_external_function_dynargs_with_result(
+        &mut self,
+        fn_id: ExternalFunctionId,
+        args: Vec<RegisterId>,
+    ) -> Result<RegisterId, BuildError> {
+        self.check_call_external(fn_id, args.len())?;
+        Ok(self.call_external_function_dynargs_with_result(fn_id, args))
+    }
+
     pub fn jmp_dynargs(
         mut self,
         block: BlockId,
@@ -1002,6 +1701,27 @@ impl DynBlockBuilder {
         }
     }
 
+    pub fn switch_dynargs(
+        mut self,
+        value: RegisterId,
+        cases: Vec<(SwitchValue, BlockId, Vec<RegisterId>)>,
+        block_default: BlockId,
+        values_default: Vec<RegisterId>,
+    ) -> DynFinalizedBlockBuilder {
+        self.is_ok_to_drop = true;
+        DynFinalizedBlockBuilder {
+            builder: self,
+            is_ok_to_drop: false,
+            end_control_flow: ControlFlowInstruction::Switch(Switch {
+                value,
+                cases: (cases.into_iter())
+                    .map(|(case, block, values)| (case, BlockJump(block, values)))
+                    .collect(),
+                default: BlockJump(block_default, values_default),
+            }),
+        }
+    }
+
     pub fn signature(&self) -> DynBlkSignature {
         DynBlkSignature { id: self.id }
     }
@@ -1047,7 +1767,7 @@ impl DynFinalizedBlockBuilder {
 
 impl Drop for DynFinalizedBlockBuilder {
     fn drop(&mut self) {
-        if !self.is_ok_to_drop {
+        if !self.is_ok_to_drop && !self.builder.shared.checked {
             panic!("A `DynFinalizedBlockBuilder` (created with a finalizing method) was dropped without `end_block` being called.");
         }
     }
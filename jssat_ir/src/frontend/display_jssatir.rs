@@ -1,6 +1,10 @@
-use crate::{frontend::ir::ControlFlowInstruction, isa::ISAInstruction};
+use crate::{
+    frontend::ir::{ControlFlowInstruction, InstructionData},
+    id::Tag,
+    isa::{decode_utf16, ISAInstruction, ListKey},
+};
 
-use super::ir::{FFIValueType, IR};
+use super::ir::{FFIValueType, Returns, IR};
 use std::fmt::Write;
 
 /// Infallible write
@@ -16,24 +20,68 @@ macro_rules! iwl {
     };
 }
 
+/// Displays a program in the textual JSSAT IR format. The text produced can be
+/// read back with [`crate::frontend::parse_jssatir::parse`].
 pub fn display(program: &IR) -> String {
     let mut text = String::new();
 
-    for (id, ext_fn) in program.external_functions.iter() {
-        iw!(text, "ext fn @@{}(", id);
+    iw!(text, "entrypoint @{};\n\n", program.entrypoint);
+
+    let mut printed_header = false;
+    for (atom, name) in program.dealer.iter() {
+        iwl!(text, "atom {} = {:?};", atom, name);
+        printed_header = true;
+    }
+
+    for atom in program.dealer.generated() {
+        iwl!(text, "atom {} = gen;", atom);
+        printed_header = true;
+    }
+
+    let mut constants = program.constants.iter().collect::<Vec<_>>();
+    constants.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    for (id, constant) in constants {
+        // constants are usually UTF-16 strings, so we try to display them as
+        // such to keep the output readable
+        match decode_utf16(&constant.payload) {
+            Some(string) => iwl!(text, "const #{} = {:?};", id, string),
+            None => iwl!(text, "const #{} = {:?};", id, constant.payload),
+        }
+        printed_header = true;
+    }
+
+    if printed_header {
+        iwl!(text);
+    }
+
+    let mut ext_fns = program.external_functions.iter().collect::<Vec<_>>();
+    ext_fns.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    for (id, ext_fn) in ext_fns {
+        iw!(text, "ext fn @@{} {:?}(", id, ext_fn.name);
 
         for arg in ext_fn.parameters.iter() {
             iw!(text, "{}, ", display_norecord(arg));
         }
 
-        iw!(text, ");\n\n");
+        match &ext_fn.return_type {
+            Returns::Value(value) => iw!(text, ") -> {};\n\n", display_norecord(value)),
+            Returns::Void => iw!(text, ") -> Void;\n\n"),
+        }
     }
 
     let mut fns = program.functions.iter().collect::<Vec<_>>();
     fns.sort_by(|(a, _), (b, _)| a.cmp(b));
 
     for (fn_id, f) in fns {
-        iw!(text, "fn @{}(", fn_id);
+        iw!(text, "fn @{}", fn_id);
+
+        if let Some(name) = &f.name {
+            iw!(text, " {:?}", name);
+        }
+
+        iw!(text, "(");
 
         for p in f.parameters.iter() {
             iw!(text, "%{}, ", p.register);
@@ -41,10 +89,14 @@ pub fn display(program: &IR) -> String {
 
         iw!(text, ") {{\n");
 
-        let mut blocks = std::iter::once((&f.entry_block, f.blocks.get(&f.entry_block).unwrap()))
-            .chain(f.blocks.iter().filter(|(id, _)| **id != f.entry_block))
+        // the entry block is always displayed first, so that it can be
+        // identified when reading the text back in
+        let mut blocks = (f.blocks.iter())
+            .filter(|(id, _)| **id != f.entry_block)
             .collect::<Vec<_>>();
         blocks.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let blocks =
+            std::iter::once((&f.entry_block, f.blocks.get(&f.entry_block).unwrap())).chain(blocks);
 
         for (id, block) in blocks {
             iw!(text, "  @{}.${}(", fn_id, id);
@@ -54,13 +106,16 @@ pub fn display(program: &IR) -> String {
             iw!(text, "):\n");
 
             for inst in block.instructions.iter() {
-                iwl!(text, "    {}", inst.as_display());
+                iw!(text, "    ");
+                display_instruction(&mut text, &inst.data).unwrap();
+                iwl!(text);
             }
 
             iw!(text, "    ");
             match &block.end {
                 ControlFlowInstruction::Jmp(inst) => inst.display(&mut text),
                 ControlFlowInstruction::JmpIf(inst) => inst.display(&mut text),
+                ControlFlowInstruction::Switch(inst) => inst.display(&mut text),
                 ControlFlowInstruction::Ret(inst) => inst.display(&mut text),
             }
             .unwrap();
@@ -73,6 +128,43 @@ pub fn display(program: &IR) -> String {
     text
 }
 
+/// Displays an instruction the way [`InstructionData::display`] does, except
+/// for the instructions whose display can't be read back: every kind of item a
+/// [`crate::isa::Make`] creates is named, the keys of lists are displayed as
+/// the registers they are, and messages are quoted.
+pub fn display_instruction<C: Tag, F: Tag>(
+    w: &mut impl Write,
+    data: &InstructionData<C, F>,
+) -> std::fmt::Result {
+    match data {
+        InstructionData::Comment(i) => write!(w, "-- {:?}, {}", i.message, i.location),
+        InstructionData::Assert(i) => write!(w, "Assert %{}; -- {:?}", i.condition, i.message),
+        InstructionData::GetFnPtr(i) => write!(w, "%{} = GetFnPtr @{};", i.result, i.item),
+        InstructionData::MakeAtom(i) => write!(w, "%{} = MakeAtom {};", i.result, i.item),
+        InstructionData::MakeBytes(i) => write!(w, "%{} = MakeBytes #{};", i.result, i.item),
+        InstructionData::MakeInteger(i) => write!(w, "%{} = MakeInteger {};", i.result, i.item),
+        InstructionData::MakeFloat(i) => write!(w, "%{} = MakeFloat {};", i.result, i.item),
+        InstructionData::MakeBigInt(i) => write!(w, "%{} = MakeBigInt {};", i.result, i.item),
+        InstructionData::MakeBoolean(i) => write!(w, "%{} = MakeBoolean {};", i.result, i.item),
+        InstructionData::ListHasKey(i) => {
+            let ListKey::Index(key) = i.key;
+            write!(w, "%{} = ListHasKey %{}.[%{}];", i.result, i.list, key)
+        }
+        InstructionData::ListGet(i) => {
+            let ListKey::Index(key) = i.key;
+            write!(w, "%{} = ListGet %{}.[%{}];", i.result, i.list, key)
+        }
+        InstructionData::ListSet(i) => {
+            let ListKey::Index(key) = i.key;
+            match i.value {
+                Some(value) => write!(w, "ListSet %{}.[%{}] = %{}", i.list, key, value),
+                None => write!(w, "ListSet %{}.[%{}] = <remove>", i.list, key),
+            }
+        }
+        data => data.display(w),
+    }
+}
+
 fn display_norecord(t: &FFIValueType) -> String {
     match t {
         // ValueType::Bool(b) => format!("{}", b),
@@ -161,7 +161,7 @@ impl<'b, const P: usize> Emitter<'b, P> {
 
         // jump out of the loop if the condition is false
         self.if_then(
-            |e| e.negate(condition),
+            |e| e.not(condition),
             |_| ControlFlow::Jump(final_block_id, vec![]),
         );
 
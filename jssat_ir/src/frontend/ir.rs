@@ -3,6 +3,8 @@ use std::hash::Hash;
 use std::panic::Location;
 use std::sync::Arc;
 
+use num_bigint::BigInt;
+use ordered_float::OrderedFloat;
 use rustc_hash::FxHashMap;
 use tinyvec::TinyVec;
 
@@ -34,19 +36,19 @@ impl IR {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Constant {
     pub payload: Vec<u8>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ExternalFunction {
     pub name: String,
     pub parameters: Vec<FFIValueType>,
     pub return_type: FFIReturnType,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum FFIValueType {
     /// Useful to box a value into the largest possible idea of what it may be.
     /// Primarily used during prototyping, and is only really useful if our
@@ -57,6 +59,24 @@ pub enum FFIValueType {
     Runtime,
     /// A parameter of the [`jssatrt::string::String`] type.
     String,
+    /// An integer, passed as an `i64`.
+    I64,
+    /// A float, passed as an `f64`.
+    F64,
+    /// A boolean, passed as a `bool`.
+    Bool,
+    /// Bytes, passed as a pointer to a buffer of bytes owned by the runtime.
+    Bytes,
+    /// A pointer that JSSAT never looks into, such as a handle to something
+    /// owned by the runtime. It is passed as a pointer-sized integer, and JSSAT
+    /// code carries it around as a number.
+    Opaque,
+    /// A record, passed as a handle to the record in the runtime's heap.
+    Record,
+    /// Either a value of the inner type, or null. Only the return type of an
+    /// external function may be nullable, and the value is given to JSSAT code
+    /// as a tuple which is empty if it is null, or otherwise holds the value.
+    Nullable(Box<FFIValueType>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -78,7 +98,7 @@ pub struct Function {
      * pub register_flow: ValueFlowGraph, */
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Parameter {
     pub register: PlainRegisterId,
 }
@@ -127,6 +147,15 @@ impl<C: Tag, F: Tag> Instruction<C, F> {
     enum_bridge!(ListSet, ListSet<C>);
     enum_bridge!(ListHasKey, ListHasKey<C>);
     enum_bridge!(ListLen, ListLen<C>);
+    enum_bridge!(NewTuple, NewTuple<C>);
+    enum_bridge!(TupleGet, TupleGet<C>);
+    enum_bridge!(TupleLen, TupleLen<C>);
+    enum_bridge!(StringConcat, StringConcat<C>);
+    enum_bridge!(StringLen, StringLen<C>);
+    enum_bridge!(StringSlice, StringSlice<C>);
+    enum_bridge!(StringCodeUnitAt, StringCodeUnitAt<C>);
+    enum_bridge!(NumberToString, NumberToString<C>);
+    enum_bridge!(StringToNumber, StringToNumber<C>);
     enum_bridge!(GetFnPtr, Make<C, crate::id::FunctionId<F>>);
     enum_bridge!(CallStatic, Call<C, crate::id::FunctionId<F>>);
     enum_bridge!(CallExtern, Call<C, crate::id::ExternalFunctionId<F>>);
@@ -134,9 +163,13 @@ impl<C: Tag, F: Tag> Instruction<C, F> {
     enum_bridge!(MakeAtom, Make<C, Atom>);
     enum_bridge!(MakeBytes, Make<C, crate::id::ConstantId<F>>);
     enum_bridge!(MakeInteger, Make<C, i64>);
+    enum_bridge!(MakeFloat, Make<C, OrderedFloat<f64>>);
+    enum_bridge!(MakeBigInt, Make<C, BigInt>);
     enum_bridge!(MakeBoolean, Make<C, bool>);
     enum_bridge!(BinOp, BinOp<C>);
     enum_bridge!(Negate, Negate<C>);
+    enum_bridge!(Not, Not<C>);
+    enum_bridge!(NumberToBigInt, NumberToBigInt<C>);
     enum_bridge!(Generalize, Generalize<C>);
     enum_bridge!(Assert, Assert<C>);
     enum_bridge!(IsType, IsType<C>);
@@ -144,7 +177,7 @@ impl<C: Tag, F: Tag> Instruction<C, F> {
     enum_bridge!(Unreachable, Unreachable<C>);
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum InstructionData<C: Tag = crate::id::IrCtx, F: Tag = crate::id::IrCtx> {
     Comment(Comment),
     NewRecord(NewRecord<C>),
@@ -156,6 +189,15 @@ pub enum InstructionData<C: Tag = crate::id::IrCtx, F: Tag = crate::id::IrCtx> {
     ListSet(ListSet<C>),
     ListHasKey(ListHasKey<C>),
     ListLen(ListLen<C>),
+    NewTuple(NewTuple<C>),
+    TupleGet(TupleGet<C>),
+    TupleLen(TupleLen<C>),
+    StringConcat(StringConcat<C>),
+    StringLen(StringLen<C>),
+    StringSlice(StringSlice<C>),
+    StringCodeUnitAt(StringCodeUnitAt<C>),
+    NumberToString(NumberToString<C>),
+    StringToNumber(StringToNumber<C>),
     GetFnPtr(Make<C, crate::id::FunctionId<F>>),
     CallStatic(Call<C, crate::id::FunctionId<F>>),
     CallExtern(Call<C, crate::id::ExternalFunctionId<F>>),
@@ -163,9 +205,13 @@ pub enum InstructionData<C: Tag = crate::id::IrCtx, F: Tag = crate::id::IrCtx> {
     MakeAtom(Make<C, Atom>),
     MakeBytes(Make<C, crate::id::ConstantId<F>>),
     MakeInteger(Make<C, i64>),
+    MakeFloat(Make<C, OrderedFloat<f64>>),
+    MakeBigInt(Make<C, BigInt>),
     MakeBoolean(Make<C, bool>),
     BinOp(BinOp<C>),
     Negate(Negate<C>),
+    Not(Not<C>),
+    NumberToBigInt(NumberToBigInt<C>),
     Generalize(Generalize<C>),
     Assert(Assert<C>),
     IsType(IsType<C>),
@@ -200,10 +246,11 @@ impl<'i, C: Tag, F: Tag> Display for DisplayInst<'i, C, F> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ControlFlowInstruction<Ctx: Tag = IrCtx, Path: Tag = IrCtx> {
     Jmp(Jump<crate::id::BlockId<Path>, Ctx>),
     JmpIf(JumpIf<crate::id::BlockId<Path>, Ctx>),
+    Switch(Switch<crate::id::BlockId<Path>, Ctx>),
     Ret(Return<Ctx>),
 }
 
@@ -274,6 +321,25 @@ impl<C: Tag, F: Tag> InstructionData<C, F> {
             InstructionData::ListSet(inst) => InstructionData::ListSet(inst.retag(retagger)),
             InstructionData::ListHasKey(inst) => InstructionData::ListHasKey(inst.retag(retagger)),
             InstructionData::ListLen(inst) => InstructionData::ListLen(inst.retag(retagger)),
+            InstructionData::NewTuple(inst) => InstructionData::NewTuple(inst.retag(retagger)),
+            InstructionData::TupleGet(inst) => InstructionData::TupleGet(inst.retag(retagger)),
+            InstructionData::TupleLen(inst) => InstructionData::TupleLen(inst.retag(retagger)),
+            InstructionData::StringConcat(inst) => {
+                InstructionData::StringConcat(inst.retag(retagger))
+            }
+            InstructionData::StringLen(inst) => InstructionData::StringLen(inst.retag(retagger)),
+            InstructionData::StringSlice(inst) => {
+                InstructionData::StringSlice(inst.retag(retagger))
+            }
+            InstructionData::StringCodeUnitAt(inst) => {
+                InstructionData::StringCodeUnitAt(inst.retag(retagger))
+            }
+            InstructionData::NumberToString(inst) => {
+                InstructionData::NumberToString(inst.retag(retagger))
+            }
+            InstructionData::StringToNumber(inst) => {
+                InstructionData::StringToNumber(inst.retag(retagger))
+            }
             InstructionData::GetFnPtr(inst) => {
                 InstructionData::GetFnPtr(inst.retag(retagger, fn_retagger))
             }
@@ -288,10 +354,16 @@ impl<C: Tag, F: Tag> InstructionData<C, F> {
             InstructionData::MakeInteger(inst) => {
                 InstructionData::MakeInteger(inst.retag(retagger))
             }
+            InstructionData::MakeFloat(inst) => InstructionData::MakeFloat(inst.retag(retagger)),
+            InstructionData::MakeBigInt(inst) => InstructionData::MakeBigInt(inst.retag(retagger)),
             InstructionData::MakeBoolean(inst) => {
                 InstructionData::MakeBoolean(inst.retag(retagger))
             }
             InstructionData::Negate(inst) => InstructionData::Negate(inst.retag(retagger)),
+            InstructionData::Not(inst) => InstructionData::Not(inst.retag(retagger)),
+            InstructionData::NumberToBigInt(inst) => {
+                InstructionData::NumberToBigInt(inst.retag(retagger))
+            }
             InstructionData::Generalize(inst) => InstructionData::Generalize(inst.retag(retagger)),
             InstructionData::Assert(inst) => InstructionData::Assert(inst.retag(retagger)),
             InstructionData::IsType(inst) => InstructionData::IsType(inst.retag(retagger)),
@@ -318,6 +390,9 @@ impl<CO: Tag, PO: Tag> ControlFlowInstruction<CO, PO> {
             ControlFlowInstruction::JmpIf(inst) => {
                 ControlFlowInstruction::JmpIf(inst.retag(retagger, blk_retagger))
             }
+            ControlFlowInstruction::Switch(inst) => {
+                ControlFlowInstruction::Switch(inst.retag(retagger, blk_retagger))
+            }
             ControlFlowInstruction::Ret(inst) => ControlFlowInstruction::Ret(inst.retag(retagger)),
         }
     }
@@ -339,8 +414,12 @@ impl<C: Tag, F: Tag> InstructionData<C, F> {
             InstructionData::RecordSet(inst) => inst.declared_register(),
             InstructionData::RecordHasKey(inst) => inst.declared_register(),
             InstructionData::MakeInteger(inst) => inst.declared_register(),
+            InstructionData::MakeFloat(inst) => inst.declared_register(),
+            InstructionData::MakeBigInt(inst) => inst.declared_register(),
             InstructionData::MakeBoolean(inst) => inst.declared_register(),
             InstructionData::Negate(inst) => inst.declared_register(),
+            InstructionData::Not(inst) => inst.declared_register(),
+            InstructionData::NumberToBigInt(inst) => inst.declared_register(),
             InstructionData::Generalize(inst) => inst.declared_register(),
             InstructionData::Assert(inst) => inst.declared_register(),
             InstructionData::IsType(inst) => inst.declared_register(),
@@ -349,6 +428,15 @@ impl<C: Tag, F: Tag> InstructionData<C, F> {
             InstructionData::ListSet(inst) => inst.declared_register(),
             InstructionData::ListHasKey(inst) => inst.declared_register(),
             InstructionData::ListLen(inst) => inst.declared_register(),
+            InstructionData::NewTuple(inst) => inst.declared_register(),
+            InstructionData::TupleGet(inst) => inst.declared_register(),
+            InstructionData::TupleLen(inst) => inst.declared_register(),
+            InstructionData::StringConcat(inst) => inst.declared_register(),
+            InstructionData::StringLen(inst) => inst.declared_register(),
+            InstructionData::StringSlice(inst) => inst.declared_register(),
+            InstructionData::StringCodeUnitAt(inst) => inst.declared_register(),
+            InstructionData::NumberToString(inst) => inst.declared_register(),
+            InstructionData::StringToNumber(inst) => inst.declared_register(),
             InstructionData::GetRuntime(inst) => inst.declared_register(),
             InstructionData::Unreachable(inst) => inst.declared_register(),
         }
@@ -369,8 +457,12 @@ impl<C: Tag, F: Tag> InstructionData<C, F> {
             InstructionData::RecordSet(inst) => inst.used_registers(),
             InstructionData::RecordHasKey(inst) => inst.used_registers(),
             InstructionData::MakeInteger(inst) => inst.used_registers(),
+            InstructionData::MakeFloat(inst) => inst.used_registers(),
+            InstructionData::MakeBigInt(inst) => inst.used_registers(),
             InstructionData::MakeBoolean(inst) => inst.used_registers(),
             InstructionData::Negate(inst) => inst.used_registers(),
+            InstructionData::Not(inst) => inst.used_registers(),
+            InstructionData::NumberToBigInt(inst) => inst.used_registers(),
             InstructionData::Generalize(inst) => inst.used_registers(),
             InstructionData::Assert(inst) => inst.used_registers(),
             InstructionData::IsType(inst) => inst.used_registers(),
@@ -379,6 +471,15 @@ impl<C: Tag, F: Tag> InstructionData<C, F> {
             InstructionData::ListSet(inst) => inst.used_registers(),
             InstructionData::ListHasKey(inst) => inst.used_registers(),
             InstructionData::ListLen(inst) => inst.used_registers(),
+            InstructionData::NewTuple(inst) => inst.used_registers(),
+            InstructionData::TupleGet(inst) => inst.used_registers(),
+            InstructionData::TupleLen(inst) => inst.used_registers(),
+            InstructionData::StringConcat(inst) => inst.used_registers(),
+            InstructionData::StringLen(inst) => inst.used_registers(),
+            InstructionData::StringSlice(inst) => inst.used_registers(),
+            InstructionData::StringCodeUnitAt(inst) => inst.used_registers(),
+            InstructionData::NumberToString(inst) => inst.used_registers(),
+            InstructionData::StringToNumber(inst) => inst.used_registers(),
             InstructionData::GetRuntime(inst) => inst.used_registers(),
             InstructionData::Unreachable(inst) => inst.used_registers(),
         }
@@ -399,8 +500,12 @@ impl<C: Tag, F: Tag> InstructionData<C, F> {
             InstructionData::RecordSet(inst) => inst.used_registers_mut(),
             InstructionData::RecordHasKey(inst) => inst.used_registers_mut(),
             InstructionData::MakeInteger(inst) => inst.used_registers_mut(),
+            InstructionData::MakeFloat(inst) => inst.used_registers_mut(),
+            InstructionData::MakeBigInt(inst) => inst.used_registers_mut(),
             InstructionData::MakeBoolean(inst) => inst.used_registers_mut(),
             InstructionData::Negate(inst) => inst.used_registers_mut(),
+            InstructionData::Not(inst) => inst.used_registers_mut(),
+            InstructionData::NumberToBigInt(inst) => inst.used_registers_mut(),
             InstructionData::Generalize(inst) => inst.used_registers_mut(),
             InstructionData::Assert(inst) => inst.used_registers_mut(),
             InstructionData::IsType(inst) => inst.used_registers_mut(),
@@ -409,6 +514,15 @@ impl<C: Tag, F: Tag> InstructionData<C, F> {
             InstructionData::ListSet(inst) => inst.used_registers_mut(),
             InstructionData::ListHasKey(inst) => inst.used_registers_mut(),
             InstructionData::ListLen(inst) => inst.used_registers_mut(),
+            InstructionData::NewTuple(inst) => inst.used_registers_mut(),
+            InstructionData::TupleGet(inst) => inst.used_registers_mut(),
+            InstructionData::TupleLen(inst) => inst.used_registers_mut(),
+            InstructionData::StringConcat(inst) => inst.used_registers_mut(),
+            InstructionData::StringLen(inst) => inst.used_registers_mut(),
+            InstructionData::StringSlice(inst) => inst.used_registers_mut(),
+            InstructionData::StringCodeUnitAt(inst) => inst.used_registers_mut(),
+            InstructionData::NumberToString(inst) => inst.used_registers_mut(),
+            InstructionData::StringToNumber(inst) => inst.used_registers_mut(),
             InstructionData::GetRuntime(inst) => inst.used_registers_mut(),
             InstructionData::Unreachable(inst) => inst.used_registers_mut(),
         }
@@ -435,8 +549,12 @@ impl<C: Tag, F: Tag> InstructionData<C, F> {
             InstructionData::RecordSet(inst) => inst.display(w),
             InstructionData::RecordHasKey(inst) => inst.display(w),
             InstructionData::MakeInteger(inst) => inst.display(w),
+            InstructionData::MakeFloat(inst) => inst.display(w),
+            InstructionData::MakeBigInt(inst) => inst.display(w),
             InstructionData::MakeBoolean(inst) => inst.display(w),
             InstructionData::Negate(inst) => inst.display(w),
+            InstructionData::Not(inst) => inst.display(w),
+            InstructionData::NumberToBigInt(inst) => inst.display(w),
             InstructionData::Generalize(inst) => inst.display(w),
             InstructionData::Assert(inst) => inst.display(w),
             InstructionData::IsType(inst) => inst.display(w),
@@ -445,6 +563,15 @@ impl<C: Tag, F: Tag> InstructionData<C, F> {
             InstructionData::ListSet(inst) => inst.display(w),
             InstructionData::ListHasKey(inst) => inst.display(w),
             InstructionData::ListLen(inst) => inst.display(w),
+            InstructionData::NewTuple(inst) => inst.display(w),
+            InstructionData::TupleGet(inst) => inst.display(w),
+            InstructionData::TupleLen(inst) => inst.display(w),
+            InstructionData::StringConcat(inst) => inst.display(w),
+            InstructionData::StringLen(inst) => inst.display(w),
+            InstructionData::StringSlice(inst) => inst.display(w),
+            InstructionData::StringCodeUnitAt(inst) => inst.display(w),
+            InstructionData::NumberToString(inst) => inst.display(w),
+            InstructionData::StringToNumber(inst) => inst.display(w),
             InstructionData::GetRuntime(inst) => inst.display(w),
             InstructionData::Unreachable(inst) => inst.display(w),
         }
@@ -456,6 +583,7 @@ impl<C: Tag, P: Tag> ControlFlowInstruction<C, P> {
         match self {
             ControlFlowInstruction::Jmp(inst) => inst.used_registers().to_vec(),
             ControlFlowInstruction::JmpIf(inst) => inst.used_registers().to_vec(),
+            ControlFlowInstruction::Switch(inst) => inst.used_registers().to_vec(),
             ControlFlowInstruction::Ret(inst) => inst.used_registers().to_vec(),
         }
     }
@@ -464,6 +592,7 @@ impl<C: Tag, P: Tag> ControlFlowInstruction<C, P> {
         match self {
             ControlFlowInstruction::Jmp(inst) => inst.used_registers_mut(),
             ControlFlowInstruction::JmpIf(inst) => inst.used_registers_mut(),
+            ControlFlowInstruction::Switch(inst) => inst.used_registers_mut(),
             ControlFlowInstruction::Ret(inst) => inst.used_registers_mut(),
         }
     }
@@ -472,6 +601,7 @@ impl<C: Tag, P: Tag> ControlFlowInstruction<C, P> {
         match self {
             ControlFlowInstruction::Jmp(inst) => inst.paths(),
             ControlFlowInstruction::JmpIf(inst) => inst.paths(),
+            ControlFlowInstruction::Switch(inst) => inst.paths(),
             ControlFlowInstruction::Ret(_) => Vec::new(),
         }
     }
@@ -480,6 +610,7 @@ impl<C: Tag, P: Tag> ControlFlowInstruction<C, P> {
         match self {
             ControlFlowInstruction::Jmp(inst) => inst.paths_mut(),
             ControlFlowInstruction::JmpIf(inst) => inst.paths_mut(),
+            ControlFlowInstruction::Switch(inst) => inst.paths_mut(),
             ControlFlowInstruction::Ret(_) => Vec::new(),
         }
     }
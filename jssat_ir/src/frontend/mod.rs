@@ -2,4 +2,5 @@ pub mod builder;
 pub mod display_jssatir;
 pub mod emitter;
 pub mod ir;
+pub mod parse_jssatir;
 pub mod source_map;
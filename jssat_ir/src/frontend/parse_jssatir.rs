@@ -0,0 +1,1129 @@
+//! Parses the textual JSSAT IR format produced by
+//! [`crate::frontend::display_jssatir::display`] back into an [`IR`]. This
+//! makes it possible to write programs (e.g. regression tests) as small
+//! `.jssatir` files rather than with the [`super::builder::ProgramBuilder`].
+//!
+//! Parsing the display of a program produces a program that displays exactly
+//! the same, including the messages and locations of [`Comment`]s and the
+//! [`Atom`]s generated with [`AtomDealer::gen`]. The only information not part
+//! of the display is the [`Instruction::source_map_idx`] and
+//! [`Instruction::constructed_at`] of every instruction, which are not
+//! preserved.
+
+use std::fmt::Display;
+use std::num::NonZeroU16;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use num_bigint::BigInt;
+use ordered_float::OrderedFloat;
+use rustc_hash::FxHashMap;
+
+use super::ir::*;
+use crate::id::{IdCompat, IrCtx};
+use crate::isa::*;
+
+type BlockId = crate::id::BlockId<IrCtx>;
+type ConstantId = crate::id::ConstantId<IrCtx>;
+type ExternalFunctionId = crate::id::ExternalFunctionId<IrCtx>;
+type FunctionId = crate::id::FunctionId<IrCtx>;
+type RegisterId = crate::id::RegisterId<IrCtx>;
+
+/// An error encountered while parsing, along with the position in the text it
+/// occurred at. Lines and columns both start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+type ParseResult<T> = Result<T, ParseError>;
+
+pub fn parse(text: &str) -> Result<IR, ParseError> {
+    let mut parser = Parser { text, position: 0 };
+
+    parser.expect("entrypoint")?;
+    let entrypoint = parser.id("@")?;
+    parser.expect(";")?;
+
+    let mut dealer = AtomDealer::new();
+    let mut constants = FxHashMap::default();
+    let mut external_functions = FxHashMap::default();
+    let mut functions = FxHashMap::default();
+
+    while !parser.is_at_end() {
+        let start = parser.position;
+        let duplicate = match parser.peek_word() {
+            "atom" => {
+                parser.atom(&mut dealer)?;
+                false
+            }
+            "const" => {
+                let (id, constant) = parser.constant()?;
+                constants.insert(id, constant).is_some()
+            }
+            "ext" => {
+                let (id, ext_fn) = parser.external_function()?;
+                external_functions.insert(id, ext_fn).is_some()
+            }
+            "fn" => {
+                let (id, function) = parser.function()?;
+                functions.insert(id, function).is_some()
+            }
+            word => return parser.error(format!("unexpected `{}`", word)),
+        };
+
+        if duplicate {
+            parser.position = start;
+            return parser.error("duplicate definition");
+        }
+    }
+
+    if !functions.contains_key(&entrypoint) {
+        return parser.error(format!("entrypoint @{} is not defined", entrypoint));
+    }
+
+    Ok(IR {
+        dealer: Arc::new(dealer),
+        entrypoint,
+        constants,
+        external_functions,
+        functions,
+    })
+}
+
+struct Parser<'text> {
+    text: &'text str,
+    position: usize,
+}
+
+impl<'text> Parser<'text> {
+    fn rest(&self) -> &'text str {
+        &self.text[self.position..]
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> ParseResult<T> {
+        let parsed = &self.text[..self.position];
+        let line_start = parsed.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+
+        Err(ParseError {
+            line: parsed.matches('\n').count() + 1,
+            column: parsed[line_start..].chars().count() + 1,
+            message: message.into(),
+        })
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn is_at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        self.rest().is_empty()
+    }
+
+    fn peek(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        self.rest().starts_with(token)
+    }
+
+    /// Consumes `token` if the text continues with it.
+    fn eat(&mut self, token: &str) -> bool {
+        let matches = self.peek(token);
+        if matches {
+            self.position += token.len();
+        }
+        matches
+    }
+
+    fn expect(&mut self, token: &str) -> ParseResult<()> {
+        match self.eat(token) {
+            true => Ok(()),
+            false => self.error(format!("expected `{}`", token)),
+        }
+    }
+
+    /// Consumes a keyword or literal, which continues until whitespace or
+    /// punctuation.
+    fn word(&mut self) -> &'text str {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '+' | '.')))
+            .unwrap_or_else(|| rest.len());
+        self.position += len;
+        &rest[..len]
+    }
+
+    fn peek_word(&mut self) -> &'text str {
+        let start = self.position;
+        let word = self.word();
+        self.position = start;
+        word
+    }
+
+    fn literal<T: FromStr>(&mut self, kind: &str) -> ParseResult<T> {
+        let start = self.position;
+        match self.word().parse() {
+            Ok(value) => Ok(value),
+            Err(_) => {
+                self.position = start;
+                self.skip_whitespace();
+                self.error(format!("expected {}", kind))
+            }
+        }
+    }
+
+    /// Parses an id, which is a number immediately preceded by a sigil.
+    fn id<I: IdCompat>(&mut self, sigil: &str) -> ParseResult<I> {
+        self.expect(sigil)?;
+
+        let rest = self.rest();
+        let len = (rest.find(|c: char| !c.is_ascii_digit())).unwrap_or_else(|| rest.len());
+
+        match rest[..len].parse() {
+            Ok(value) => {
+                self.position += len;
+                Ok(I::new_with_value(value))
+            }
+            Err(_) => self.error(format!("expected an id after `{}`", sigil)),
+        }
+    }
+
+    fn register(&mut self) -> ParseResult<RegisterId> {
+        self.id("%")
+    }
+
+    fn atom_id(&mut self) -> ParseResult<Atom> {
+        match NonZeroU16::new(self.literal("an atom")?) {
+            Some(value) => Ok(Atom(value)),
+            None => self.error("atoms cannot be 0"),
+        }
+    }
+
+    /// Parses a comma separated list of items between `open` and `close`. A
+    /// trailing comma is allowed.
+    fn list<T>(
+        &mut self,
+        open: &str,
+        close: &str,
+        mut item: impl FnMut(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<Vec<T>> {
+        self.expect(open)?;
+
+        let mut items = Vec::new();
+        while !self.eat(close) {
+            items.push(item(self)?);
+
+            if !self.eat(",") {
+                self.expect(close)?;
+                break;
+            }
+        }
+
+        Ok(items)
+    }
+
+    fn registers(&mut self) -> ParseResult<Vec<RegisterId>> {
+        self.list("(", ")", Self::register)
+    }
+
+    /// Parses a string literal, as displayed by the [`std::fmt::Debug`]
+    /// implementation of [`str`].
+    fn string(&mut self) -> ParseResult<String> {
+        self.expect("\"")?;
+
+        let mut string = String::new();
+        let mut chars = self.rest().char_indices();
+
+        while let Some((idx, c)) = chars.next() {
+            let c = match c {
+                '"' => {
+                    self.position += idx + 1;
+                    return Ok(string);
+                }
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some('0') => '\0',
+                    Some(c @ ('\\' | '"' | '\'')) => c,
+                    Some('u') => {
+                        let escape = chars.as_str();
+                        let code_point = (escape.strip_prefix('{'))
+                            .and_then(|escape| escape.split_once('}'))
+                            .and_then(|(hex, _)| u32::from_str_radix(hex, 16).ok())
+                            .and_then(char::from_u32);
+
+                        match code_point {
+                            Some(c) => {
+                                chars.find(|(_, c)| *c == '}');
+                                c
+                            }
+                            None => {
+                                self.position += idx;
+                                return self.error("invalid unicode escape");
+                            }
+                        }
+                    }
+                    _ => {
+                        self.position += idx;
+                        return self.error("invalid escape");
+                    }
+                },
+                c => c,
+            };
+
+            string.push(c);
+        }
+
+        self.error("unterminated string")
+    }
+
+    fn rest_of_line(&mut self) -> &'text str {
+        let rest = self.rest();
+        let len = rest.find('\n').unwrap_or_else(|| rest.len());
+        self.position += len;
+        &rest[..len]
+    }
+
+    /// `atom 1 = "name";` or `atom 32768 = gen;`
+    ///
+    /// Atoms must be declared in the order they were dealt, so that dealing
+    /// them again produces the same atoms.
+    fn atom(&mut self, dealer: &mut AtomDealer) -> ParseResult<()> {
+        self.expect("atom")?;
+        let atom = self.atom_id()?;
+        self.expect("=")?;
+
+        let dealt = match self.eat("gen") {
+            true => dealer.gen(),
+            false => dealer.deal_dynamic(&self.string()?),
+        };
+        self.expect(";")?;
+
+        match dealt == atom {
+            true => Ok(()),
+            false => self.error(format!("atom {} was declared out of order", atom)),
+        }
+    }
+
+    /// `path/to/file.rs:1:2`, the location of a [`Comment`], which lasts until
+    /// the end of the line.
+    fn comment_location(&mut self) -> ParseResult<CommentLocation> {
+        self.skip_whitespace();
+        let location = self.rest_of_line().trim_end();
+
+        let mut parts = location.rsplitn(3, ':');
+        let column = parts.next().and_then(|column| column.parse().ok());
+        let line = parts.next().and_then(|line| line.parse().ok());
+
+        match (parts.next(), line, column) {
+            (Some(file), Some(line), Some(column)) => Ok(CommentLocation {
+                file: file.to_owned().into(),
+                line,
+                column,
+            }),
+            _ => self.error("expected a location"),
+        }
+    }
+
+    /// `const #1 = "utf-16 text";` or `const #1 = [1, 2, 3];`
+    fn constant(&mut self) -> ParseResult<(ConstantId, Constant)> {
+        self.expect("const")?;
+        let id = self.id("#")?;
+        self.expect("=")?;
+
+        let payload = match self.peek("\"") {
+            true => encode_utf16(&self.string()?),
+            false => self.list("[", "]", |me| me.literal("a byte"))?,
+        };
+
+        self.expect(";")?;
+        Ok((id, Constant { payload }))
+    }
+
+    /// `ext fn @@1 "name"(Runtime, Any, ) -> Void;`
+    fn external_function(&mut self) -> ParseResult<(ExternalFunctionId, ExternalFunction)> {
+        self.expect("ext")?;
+        self.expect("fn")?;
+        let id = self.id("@@")?;
+        let name = self.string()?;
+        let parameters = self.list("(", ")", Self::ffi_value_type)?;
+        self.expect("->")?;
+
+        let return_type = match self.eat("Void") {
+            true => Returns::Void,
+            false => Returns::Value(self.ffi_value_type()?),
+        };
+
+        self.expect(";")?;
+
+        let ext_fn = ExternalFunction {
+            name,
+            parameters,
+            return_type,
+        };
+
+        Ok((id, ext_fn))
+    }
+
+    fn ffi_value_type(&mut self) -> ParseResult<FFIValueType> {
+        match self.word() {
+            "Any" => Ok(FFIValueType::Any),
+            "Runtime" => Ok(FFIValueType::Runtime),
+            "String" => Ok(FFIValueType::String),
+            "I64" => Ok(FFIValueType::I64),
+            "F64" => Ok(FFIValueType::F64),
+            "Bool" => Ok(FFIValueType::Bool),
+            "Bytes" => Ok(FFIValueType::Bytes),
+            "Opaque" => Ok(FFIValueType::Opaque),
+            "Record" => Ok(FFIValueType::Record),
+            "Nullable" => {
+                self.expect("(")?;
+                let inner = self.ffi_value_type()?;
+                self.expect(")")?;
+                Ok(FFIValueType::Nullable(Box::new(inner)))
+            }
+            word => self.error(format!("unknown FFI value type `{}`", word)),
+        }
+    }
+
+    /// ```text
+    /// fn @1 "name"(%0, ) {
+    ///   @1.$0():
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// The first block of a function is its entry block.
+    fn function(&mut self) -> ParseResult<(FunctionId, Function)> {
+        self.expect("fn")?;
+        let id = self.id("@")?;
+
+        let name = match self.peek("\"") {
+            true => Some(self.string()?),
+            false => None,
+        };
+
+        let parameters = (self.registers()?.into_iter())
+            .map(|register| Parameter { register })
+            .collect();
+
+        self.expect("{")?;
+
+        let mut entry_block = None;
+        let mut blocks = FxHashMap::default();
+
+        while !self.eat("}") {
+            let start = self.position;
+            let (block_id, block) = self.block(id)?;
+            entry_block.get_or_insert(block_id);
+
+            if blocks.insert(block_id, block).is_some() {
+                self.position = start;
+                return self.error("duplicate definition");
+            }
+        }
+
+        let entry_block = match entry_block {
+            Some(entry_block) => entry_block,
+            None => return self.error(format!("function @{} has no blocks", id)),
+        };
+
+        let function = Function {
+            name,
+            parameters,
+            entry_block,
+            blocks,
+        };
+
+        Ok((id, function))
+    }
+
+    fn block(&mut self, function: FunctionId) -> ParseResult<(BlockId, FunctionBlock)> {
+        if self.id::<FunctionId>("@")? != function {
+            return self.error(format!("expected block of function @{}", function));
+        }
+
+        self.expect(".")?;
+        let id = self.id("$")?;
+        let parameters = self.registers()?;
+        self.expect(":")?;
+
+        let mut instructions = Vec::new();
+        let end = loop {
+            match self.peek_word() {
+                "Jump" | "If" | "Switch" | "Return" => break self.control_flow()?,
+                _ => instructions.push(self.instruction()?),
+            }
+        };
+
+        let block = FunctionBlock {
+            parameters,
+            instructions,
+            end,
+        };
+
+        Ok((id, block))
+    }
+
+    fn control_flow(&mut self) -> ParseResult<ControlFlowInstruction> {
+        let end = match self.word() {
+            "Jump" => ControlFlowInstruction::Jmp(Jump(self.block_jump()?)),
+            "If" => {
+                let condition = self.register()?;
+                self.expect(":")?;
+                let if_so = self.block_jump()?;
+                self.expect(";")?;
+                self.expect("else")?;
+                let other = self.block_jump()?;
+
+                ControlFlowInstruction::JmpIf(JumpIf {
+                    condition,
+                    if_so,
+                    other,
+                })
+            }
+            "Switch" => {
+                let value = self.register()?;
+                self.expect(":")?;
+
+                let mut cases = Vec::new();
+                while self.eat("case") {
+                    let case = self.switch_value()?;
+                    self.expect("=>")?;
+                    cases.push((case, self.block_jump()?));
+                    self.expect(";")?;
+                }
+
+                self.expect("else")?;
+                let default = self.block_jump()?;
+
+                ControlFlowInstruction::Switch(Switch {
+                    value,
+                    cases,
+                    default,
+                })
+            }
+            "Return" => match self.peek("%") {
+                true => ControlFlowInstruction::Ret(Return(Some(self.register()?))),
+                false => ControlFlowInstruction::Ret(Return(None)),
+            },
+            word => return self.error(format!("unknown control flow `{}`", word)),
+        };
+
+        self.eat(";");
+        Ok(end)
+    }
+
+    fn switch_value(&mut self) -> ParseResult<SwitchValue> {
+        if self.eat("atom") {
+            return Ok(SwitchValue::Atom(self.atom_id()?));
+        }
+
+        match self.peek_word() {
+            "true" | "false" => Ok(SwitchValue::Boolean(self.literal("a boolean")?)),
+            _ => Ok(SwitchValue::Integer(self.literal("an integer")?)),
+        }
+    }
+
+    fn block_jump(&mut self) -> ParseResult<BlockJump<BlockId, IrCtx>> {
+        Ok(BlockJump(self.id("$")?, self.registers()?))
+    }
+
+    fn instruction(&mut self) -> ParseResult<Instruction> {
+        if self.eat("--") {
+            let message = self.string()?.into();
+            self.expect(",")?;
+            let location = self.comment_location()?;
+
+            return Ok(Instruction::Comment(Comment { message, location }));
+        }
+
+        let result = match self.peek("%") {
+            true => {
+                let result = self.register()?;
+                self.expect("=")?;
+                Some(result)
+            }
+            false => None,
+        };
+
+        let start = self.position;
+        let operation = self.word();
+
+        let instruction = match (operation, result) {
+            ("CallStatic", result) => Instruction::CallStatic(Call {
+                result,
+                calling: self.id("@")?,
+                args: self.registers()?,
+            }),
+            ("CallExtern", result) => Instruction::CallExtern(Call {
+                result,
+                calling: self.id("@@")?,
+                args: self.registers()?,
+            }),
+            ("CallVirt", result) => Instruction::CallVirt(Call {
+                result,
+                calling: self.register()?,
+                args: self.registers()?,
+            }),
+            ("RecordSet", None) => {
+                let record = self.register()?;
+                self.expect(".")?;
+                let key = self.record_key()?;
+                self.expect("=")?;
+                let value = self.set_value()?;
+                Instruction::RecordSet(RecordSet { record, key, value })
+            }
+            ("ListSet", None) => {
+                let list = self.register()?;
+                self.expect(".")?;
+                let key = self.list_key()?;
+                self.expect("=")?;
+                let value = self.set_value()?;
+                Instruction::ListSet(ListSet { list, key, value })
+            }
+            ("Assert", None) => {
+                let condition = self.register()?;
+                self.expect(";")?;
+                self.expect("--")?;
+                let message = self.string()?.into();
+                Instruction::Assert(Assert { condition, message })
+            }
+            (operation, Some(result)) => match self.assigning_instruction(operation, result)? {
+                Some(instruction) => instruction,
+                None => return self.unknown_instruction(start, operation),
+            },
+            (operation, None) => return self.unknown_instruction(start, operation),
+        };
+
+        self.eat(";");
+        Ok(instruction)
+    }
+
+    fn unknown_instruction<T>(&mut self, start: usize, operation: &str) -> ParseResult<T> {
+        self.position = start;
+        self.skip_whitespace();
+        self.error(format!("unknown instruction `{}`", operation))
+    }
+
+    /// Parses the operands of an instruction that assigns to `result`. Returns
+    /// [`None`] if `operation` is not such an instruction.
+    fn assigning_instruction(
+        &mut self,
+        operation: &str,
+        result: RegisterId,
+    ) -> ParseResult<Option<Instruction>> {
+        let instruction = match operation {
+            "NewRecord" => Instruction::NewRecord(NewRecord { result }),
+            "RecordGet" | "RecordHasKey" => {
+                let record = self.register()?;
+                self.expect(".")?;
+                let key = self.record_key()?;
+
+                match operation {
+                    "RecordGet" => Instruction::RecordGet(RecordGet {
+                        result,
+                        record,
+                        key,
+                    }),
+                    _ => Instruction::RecordHasKey(RecordHasKey {
+                        result,
+                        record,
+                        key,
+                    }),
+                }
+            }
+            "NewList" => Instruction::NewList(NewList { result }),
+            "ListGet" | "ListHasKey" => {
+                let list = self.register()?;
+                self.expect(".")?;
+                let key = self.list_key()?;
+
+                match operation {
+                    "ListGet" => Instruction::ListGet(ListGet { result, list, key }),
+                    _ => Instruction::ListHasKey(ListHasKey { result, list, key }),
+                }
+            }
+            "ListLen" => Instruction::ListLen(ListLen {
+                result,
+                list: self.register()?,
+            }),
+            "NewTuple" => Instruction::NewTuple(NewTuple {
+                result,
+                elements: self.registers()?,
+            }),
+            "TupleGet" => {
+                let tuple = self.register()?;
+                self.expect(".")?;
+                let index = self.literal("a tuple index")?;
+                Instruction::TupleGet(TupleGet {
+                    result,
+                    tuple,
+                    index,
+                })
+            }
+            "TupleLen" => Instruction::TupleLen(TupleLen {
+                result,
+                tuple: self.register()?,
+            }),
+            "StringConcat" => {
+                let lhs = self.register()?;
+                self.expect(",")?;
+                let rhs = self.register()?;
+                Instruction::StringConcat(StringConcat { result, lhs, rhs })
+            }
+            "StringLen" => Instruction::StringLen(StringLen {
+                result,
+                string: self.register()?,
+            }),
+            "StringSlice" => {
+                let string = self.register()?;
+                self.expect("[")?;
+                let start = self.register()?;
+                self.expect("..")?;
+                let end = self.register()?;
+                self.expect("]")?;
+                Instruction::StringSlice(StringSlice {
+                    result,
+                    string,
+                    start,
+                    end,
+                })
+            }
+            "StringCodeUnitAt" => {
+                let string = self.register()?;
+                self.expect("[")?;
+                let index = self.register()?;
+                self.expect("]")?;
+                Instruction::StringCodeUnitAt(StringCodeUnitAt {
+                    result,
+                    string,
+                    index,
+                })
+            }
+            "NumberToString" => Instruction::NumberToString(NumberToString {
+                result,
+                number: self.register()?,
+            }),
+            "StringToNumber" => Instruction::StringToNumber(StringToNumber {
+                result,
+                string: self.register()?,
+            }),
+            "GetFnPtr" => Instruction::GetFnPtr(Make {
+                result,
+                item: self.id("@")?,
+            }),
+            "MakeAtom" => Instruction::MakeAtom(Make {
+                result,
+                item: self.atom_id()?,
+            }),
+            "MakeBytes" => Instruction::MakeBytes(Make {
+                result,
+                item: self.id("#")?,
+            }),
+            "MakeInteger" => Instruction::MakeInteger(Make {
+                result,
+                item: self.literal("an integer")?,
+            }),
+            "MakeFloat" => Instruction::MakeFloat(Make {
+                result,
+                item: OrderedFloat(self.literal("a float")?),
+            }),
+            "MakeBigInt" => Instruction::MakeBigInt(Make {
+                result,
+                item: self.literal::<BigInt>("a bigint")?,
+            }),
+            "MakeBoolean" => Instruction::MakeBoolean(Make {
+                result,
+                item: self.literal("a boolean")?,
+            }),
+            "BinOp" => {
+                let op = self.binary_operator()?;
+                let lhs = self.register()?;
+                self.expect(",")?;
+                let rhs = self.register()?;
+                Instruction::BinOp(BinOp {
+                    result,
+                    op,
+                    lhs,
+                    rhs,
+                })
+            }
+            "Negate" => Instruction::Negate(Negate {
+                result,
+                operand: self.register()?,
+            }),
+            "Not" => Instruction::Not(Not {
+                result,
+                operand: self.register()?,
+            }),
+            "NumberToBigInt" => Instruction::NumberToBigInt(NumberToBigInt {
+                result,
+                operand: self.register()?,
+            }),
+            "Generalize" => Instruction::Generalize(Generalize {
+                result,
+                value: self.register()?,
+            }),
+            "IsType" => {
+                let value = self.register()?;
+                self.expect("=")?;
+
+                let kind = match self.peek("%") {
+                    true => CompareType::Register(self.register()?),
+                    false => CompareType::Kind(self.value_type()?),
+                };
+
+                Instruction::IsType(IsType {
+                    result,
+                    value,
+                    kind,
+                })
+            }
+            "GetRuntime" => Instruction::GetRuntime(GetRuntime { result }),
+            "Unreachable" => Instruction::Unreachable(Unreachable { result }),
+            _ => return Ok(None),
+        };
+
+        Ok(Some(instruction))
+    }
+
+    /// `%1`, `[[1]]` or `[[%1]]`
+    fn record_key(&mut self) -> ParseResult<RecordKey<IrCtx>> {
+        if !self.eat("[[") {
+            return Ok(RecordKey::Prop(self.register()?));
+        }
+
+        let key = match self.peek("%") {
+            true => RecordKey::DynAtom(self.register()?),
+            false => RecordKey::Atom(self.atom_id()?),
+        };
+
+        self.expect("]]")?;
+        Ok(key)
+    }
+
+    /// `[%1]`
+    fn list_key(&mut self) -> ParseResult<ListKey<IrCtx>> {
+        self.expect("[")?;
+        let key = ListKey::Index(self.register()?);
+        self.expect("]")?;
+        Ok(key)
+    }
+
+    /// `%1` or `<remove>`
+    fn set_value(&mut self) -> ParseResult<Option<RegisterId>> {
+        match self.eat("<remove>") {
+            true => Ok(None),
+            false => Ok(Some(self.register()?)),
+        }
+    }
+
+    fn binary_operator(&mut self) -> ParseResult<BinaryOperator> {
+        use BinaryOperator::*;
+
+        let op = match self.word() {
+            "Add" => Add,
+            "Subtract" => Subtract,
+            "Multiply" => Multiply,
+            "Divide" => Divide,
+            "Remainder" => Remainder,
+            "Exponent" => Exponent,
+            "And" => And,
+            "Or" => Or,
+            "BitwiseAnd" => BitwiseAnd,
+            "BitwiseOr" => BitwiseOr,
+            "BitwiseXor" => BitwiseXor,
+            "ShiftLeft" => ShiftLeft,
+            "ShiftRight" => ShiftRight,
+            "UnsignedShiftRight" => UnsignedShiftRight,
+            "Equals" => Equals,
+            "SameValue" => SameValue,
+            "LessThan" => LessThan,
+            word => return self.error(format!("unknown binary operator `{}`", word)),
+        };
+
+        Ok(op)
+    }
+
+    fn value_type(&mut self) -> ParseResult<ValueType> {
+        use ValueType::*;
+
+        let kind = match self.word() {
+            "Atom" => Atom,
+            "Bytes" => Bytes,
+            "Number" => Number,
+            "Boolean" => Boolean,
+            "FnPtr" => FnPtr,
+            "Record" => Record,
+            "BigNumber" => BigNumber,
+            "List" => List,
+            "Tuple" => Tuple,
+            "Runtime" => Runtime,
+            word => return self.error(format!("unknown value type `{}`", word)),
+        };
+
+        Ok(kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::{builder::ProgramBuilder, display_jssatir::display};
+
+    const EVERY_INSTRUCTION: &str = r#"entrypoint @0;
+
+atom 1 = "Key";
+const #0 = "hello";
+const #1 = [0, 216];
+
+ext fn @@0 "print"(Runtime, String, ) -> Void;
+
+ext fn @@1 "id"(Any, ) -> Any;
+
+ext fn @@2 "ffi"(I64, F64, Bool, Bytes, Opaque, Record, ) -> Nullable(Record);
+
+fn @0 "main"() {
+  @0.$0():
+    %0 = GetRuntime;
+    %1 = MakeBytes #0;
+    CallExtern @@0(%0, %1)
+    %2 = CallExtern @@1(%1)
+    %3 = GetFnPtr @1;
+    %4 = CallVirt %3(%2)
+    %5 = CallStatic @1(%4)
+    Return;
+}
+
+fn @1(%0, ) {
+  @1.$2(%1, ):
+    %2 = NewRecord;
+    %3 = MakeAtom 1;
+    RecordSet %2.[[1]] = %1
+    RecordSet %2.%3 = %1
+    RecordSet %2.[[%3]] = <remove>
+    %4 = RecordGet %2.[[1]];
+    %5 = RecordHasKey %2.%3;
+    %6 = NewList;
+    %7 = MakeInteger -12;
+    ListSet %6.[%7] = %5
+    ListSet %6.[%7] = <remove>
+    %8 = ListGet %6.[%7];
+    %9 = ListHasKey %6.[%7];
+    %10 = ListLen %6;
+    %11 = NewTuple (%8, %9);
+    %12 = TupleGet %11.1;
+    %13 = TupleLen %11;
+    %14 = NewTuple ();
+    %15 = MakeFloat 0.1;
+    %16 = MakeFloat -inf;
+    %17 = MakeBigInt -123456789012345678901234567890;
+    %18 = MakeBoolean false;
+    %19 = BinOp UnsignedShiftRight %15, %16
+    %20 = Negate %19;
+    %21 = Not %18;
+    %22 = NumberToBigInt %7;
+    %23 = Generalize %22;
+    Assert %21; -- "must be true"
+    %24 = IsType %23 = BigNumber;
+    %25 = IsType %23 = %17;
+    %26 = StringConcat %1, %1;
+    %27 = StringLen %26;
+    %28 = StringSlice %26[%7..%27];
+    %29 = StringCodeUnitAt %26[%7];
+    %30 = NumberToString %15;
+    %31 = StringToNumber %30;
+    %32 = Unreachable;
+    Jump $1(%32)
+  @1.$1(%0, ):
+    If %0:
+     $2(%0);
+else $3();
+  @1.$3():
+    Switch %0:
+     case atom 1 => $4();
+     case -12 => $4();
+     case true => $1(%0);
+else $4();
+  @1.$4():
+    Return %0;
+}
+
+"#;
+
+    /// Asserts that two programs are the same, apart from where their
+    /// instructions were constructed, which isn't part of the text format.
+    fn assert_same_ir(a: &IR, b: &IR) {
+        let atoms = |ir: &IR| {
+            let mut atoms = ir.dealer.iter().collect::<Vec<_>>();
+            atoms.sort_unstable();
+            let mut generated = ir.dealer.generated().collect::<Vec<_>>();
+            generated.sort_unstable();
+            let atoms = (atoms.into_iter())
+                .map(|(atom, name)| (atom, name.to_string()))
+                .collect::<Vec<_>>();
+            (atoms, generated)
+        };
+
+        assert_eq!(atoms(a), atoms(b));
+        assert_eq!(a.entrypoint, b.entrypoint);
+        assert_eq!(a.constants, b.constants);
+        assert_eq!(a.external_functions, b.external_functions);
+        assert_eq!(a.functions.len(), b.functions.len());
+
+        for (id, a) in a.functions.iter() {
+            let b = &b.functions[id];
+            assert_eq!(a.name, b.name);
+            assert_eq!(a.parameters, b.parameters);
+            assert_eq!(a.entry_block, b.entry_block);
+            assert_eq!(a.blocks.len(), b.blocks.len());
+
+            for (id, a) in a.blocks.iter() {
+                let b = &b.blocks[id];
+                assert_eq!(a.parameters, b.parameters);
+                assert_eq!(a.end, b.end);
+
+                let data = |block: &FunctionBlock| {
+                    (block.instructions.iter())
+                        .map(|inst| inst.data.clone())
+                        .collect::<Vec<_>>()
+                };
+                assert_eq!(data(a), data(b));
+            }
+        }
+    }
+
+    #[test]
+    fn roundtrips_every_instruction() {
+        let ir = parse(EVERY_INSTRUCTION).unwrap();
+        assert_eq!(display(&ir), EVERY_INSTRUCTION);
+        assert_same_ir(&ir, &parse(&display(&ir)).unwrap());
+
+        let main = &ir.functions[&ir.entrypoint];
+        assert_eq!(main.name.as_deref(), Some("main"));
+        assert_eq!(ir.functions[&FunctionId::new_with_value(1)].entry_block, {
+            BlockId::new_with_value(2)
+        });
+        assert_eq!(
+            ir.dealer.resolve_name(Atom(NonZeroU16::new(1).unwrap())),
+            "Key"
+        );
+        assert_eq!(
+            ir.constants[&ConstantId::new_with_value(1)].payload,
+            [0, 216]
+        );
+    }
+
+    #[test]
+    fn parses_comments() {
+        let ir = parse(
+            r#"entrypoint @0;
+
+fn @0() {
+  @0.$0():
+    -- "a \"comment\"\n", jssat_ir/src/frontend/builder.rs:1:1
+    Return;
+}
+"#,
+        )
+        .unwrap();
+
+        let main = &ir.functions[&ir.entrypoint];
+        let comment = match &main.blocks[&main.entry_block].instructions[0].data {
+            InstructionData::Comment(comment) => comment,
+            data => panic!("expected a comment, got {}", data.as_display()),
+        };
+
+        assert_eq!(comment.message, "a \"comment\"\n");
+        assert_eq!(
+            comment.location,
+            CommentLocation {
+                file: "jssat_ir/src/frontend/builder.rs".into(),
+                line: 1,
+                column: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn roundtrips_generated_atoms() {
+        let text = r#"entrypoint @0;
+
+atom 1 = "Key";
+atom 32768 = gen;
+atom 32769 = gen;
+
+fn @0() {
+  @0.$0():
+    %0 = MakeAtom 32769;
+    Return %0;
+}
+
+"#;
+
+        let ir = parse(text).unwrap();
+        assert_eq!(display(&ir), text);
+        assert_same_ir(&ir, &parse(&display(&ir)).unwrap());
+
+        let generated = ir.dealer.generated().collect::<Vec<_>>();
+        assert_eq!(generated.len(), 2);
+        assert!(generated
+            .iter()
+            .all(|atom| ir.dealer.try_resolve_name(*atom).is_none()));
+    }
+
+    #[test]
+    fn roundtrips_programs_from_builder() {
+        let mut builder = ProgramBuilder::new();
+        let key = builder.dealer.deal("key");
+        let generated = builder.dealer.gen();
+        let print = builder.external_function(
+            "print",
+            [FFIValueType::Runtime, FFIValueType::String],
+            Returns::Void,
+        );
+        let message = builder.constant_str_utf16("hello, world!");
+
+        let mut main = builder.start_function_main();
+        let mut block = main.start_block_main();
+        block.comment("made by the builder, at a location in this file");
+        let runtime = block.get_runtime();
+        let string = block.make_string(message);
+        block.call_external_function(print, [runtime, string]);
+        let record = block.record_new();
+        block.record_set_atom(record, key, string);
+        let generated = block.make_atom(generated);
+        block.record_set_atom(record, key, generated);
+        main.end_block(block.ret(Some(record)));
+        builder.end_function(main);
+
+        let ir = builder.finish();
+        let text = display(&ir);
+        let parsed = parse(&text).unwrap();
+        assert_eq!(display(&parsed), text);
+        assert_same_ir(&ir, &parsed);
+    }
+
+    #[test]
+    fn reports_position_of_errors() {
+        let error =
+            parse("entrypoint @0;\n\nfn @0() {\n  @0.$0():\n    %0 = Bogus;\n").unwrap_err();
+
+        assert_eq!((error.line, error.column), (5, 10));
+        assert_eq!(error.message, "unknown instruction `Bogus`");
+    }
+}
@@ -1,18 +1,18 @@
 //! Contains utilities for building source maps.
 
-use crate::pyramid_api::PyramidApi;
+use crate::{isa::Atom, pyramid_api::PyramidApi};
 use std::sync::Mutex;
 
 #[derive(Clone, Copy, Debug)]
 pub struct SourceMapIdx(pub usize);
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct SourceSpan {
     pub start: SourcePos,
     pub end: SourcePos,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct SourcePos {
     pub line: usize,
     pub column: usize,
@@ -42,11 +42,22 @@ impl SourceMap {
         me.end();
     }
 
+    pub fn span(&self, idx: SourceMapIdx) -> Option<SourceSpan> {
+        let me = self.0.lock().unwrap();
+        me.span(idx)
+    }
+
     pub fn try_into(self) -> SourceMapImpl {
         self.0.into_inner().unwrap()
     }
 }
 
+impl From<SourceMapImpl> for SourceMap {
+    fn from(source_map: SourceMapImpl) -> Self {
+        Self(Mutex::new(source_map))
+    }
+}
+
 pub struct SourceMapImpl {
     pub source: String,
     pub pyramid: PyramidApi<SourceSpan>,
@@ -73,4 +84,94 @@ impl SourceMapImpl {
     pub fn end(&mut self) {
         self.pyramid.end();
     }
+
+    /// The innermost span sampled at the index.
+    pub fn span(&self, idx: SourceMapIdx) -> Option<SourceSpan> {
+        let sample = self.pyramid.snapshots.get(idx.0)?.0.as_ref()?;
+        Some(sample.info)
+    }
+}
+
+/// A second layer of source maps, mapping the parse nodes that the JavaScript
+/// frontend emits back to the JavaScript source text they were parsed from.
+///
+/// Unlike [`SourceMap`], instructions aren't connected to these spans: the
+/// same ECMAScript method is executed for every parse node. Instead, every
+/// parse node record stores its [`SourceMapIdx`] under [`JsSourceMap::key`],
+/// and the interpreter records which parse node each frame is called with.
+pub struct JsSourceMap {
+    pub sources: SourceMapImpl,
+    /// The record key a parse node stores its [`SourceMapIdx`] under.
+    pub key: Atom,
+    /// The byte offset every line of the source text starts at.
+    line_starts: Vec<usize>,
+}
+
+impl JsSourceMap {
+    pub fn new(source: String, key: Atom) -> Self {
+        let newlines = source.match_indices('\n').map(|(idx, _)| idx + 1);
+        let line_starts = std::iter::once(0).chain(newlines).collect();
+
+        Self {
+            sources: SourceMapImpl::new(source),
+            key,
+            line_starts,
+        }
+    }
+
+    /// Converts byte offsets into the source text into a [`SourceSpan`], or
+    /// [`None`] if either offset is not at a character of the source text.
+    pub fn span(&self, lo: usize, hi: usize) -> Option<SourceSpan> {
+        Some(SourceSpan {
+            start: self.position(lo)?,
+            end: self.position(hi)?,
+        })
+    }
+
+    fn position(&self, offset: usize) -> Option<SourcePos> {
+        // the first line always starts at 0, so there is always a line before
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let line_start = self.line_starts[line];
+
+        let column = self.sources.source.get(line_start..offset)?.chars().count();
+
+        Some(SourcePos {
+            line: line + 1,
+            column,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::isa::AtomDealer;
+
+    #[test]
+    fn spans_are_converted_to_lines_and_columns() {
+        let source = "f(1);\nprint(\"hi\");\n";
+        let map = JsSourceMap::new(source.to_string(), AtomDealer::new().deal("key"));
+
+        let span = map.span(6, 17).unwrap();
+        assert_eq!((span.start.line, span.start.column), (2, 0));
+        assert_eq!((span.end.line, span.end.column), (2, 11));
+
+        let span = map.span(0, 19).unwrap();
+        assert_eq!((span.start.line, span.start.column), (1, 0));
+        assert_eq!((span.end.line, span.end.column), (3, 0));
+
+        assert!(map.span(0, 20).is_none());
+    }
+
+    #[test]
+    fn offsets_within_characters_have_no_position() {
+        let source = "print(\"é\");";
+        let map = JsSourceMap::new(source.to_string(), AtomDealer::new().deal("key"));
+
+        let span = map.span(7, 9).unwrap();
+        assert_eq!((span.start.line, span.start.column), (1, 7));
+        assert_eq!((span.end.line, span.end.column), (1, 8));
+
+        assert!(map.span(7, 8).is_none());
+    }
 }
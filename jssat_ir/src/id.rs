@@ -189,6 +189,7 @@ gen_id!(RecordId);
 gen_id!(UniqueRecordId);
 gen_id!(UnionId);
 gen_id!(UniqueListId);
+gen_id!(UniqueTupleId);
 
 #[derive(Debug)]
 pub struct Counter<I> {
@@ -1,13 +1,13 @@
-use std::fmt::Write;
+use std::{borrow::Cow, fmt::Write};
 use tinyvec::{tiny_vec, TinyVec};
 
 use super::ISAInstruction;
 use crate::{id::*, retag::RegRetagger};
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct Assert<C: Tag> {
     pub condition: RegisterId<C>,
-    pub message: &'static str,
+    pub message: Cow<'static, str>,
 }
 
 impl<C: Tag> ISAInstruction<C> for Assert<C> {
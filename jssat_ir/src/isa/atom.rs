@@ -19,7 +19,7 @@ pub struct Atom(pub NonZeroU16);
 /// To support [`AtomDealer::gen`] method, we set a limit to the number of
 /// interned atoms that exist. This is so that we can begin generating [`Atom`]s
 /// after this value, and prevent interning before this value.
-const MAX_INTERNED_ATOM: u16 = 0b1000_0000_0000_0000;
+pub(crate) const MAX_INTERNED_ATOM: u16 = 0b1000_0000_0000_0000;
 
 unsafe impl Key for Atom {
     fn into_usize(self) -> usize {
@@ -76,6 +76,18 @@ impl AtomDealer {
         self.rodeo.get_or_intern_static(name)
     }
 
+    /// Analogous to [`AtomDealer::deal`], except for names that are only known
+    /// at runtime (e.g. names read from a file).
+    pub fn deal_dynamic(&mut self, name: &str) -> Atom {
+        self.rodeo.get_or_intern(name)
+    }
+
+    /// Iterates over every [`Atom`] dealt with [`AtomDealer::deal`], in the
+    /// order they were dealt.
+    pub fn iter(&self) -> impl Iterator<Item = (Atom, &str)> {
+        self.rodeo.iter()
+    }
+
     /// Generates a unique [`Atom`] that has never been generated before.
     pub fn gen(&mut self) -> Atom {
         let return_result = self.gen_unused;
@@ -85,10 +97,17 @@ impl AtomDealer {
             panic!("Reached maximum number of Atoms generatable.")
         }
 
-        NonZeroU16::new(value + 1).expect("`value + 1` should succeed");
+        self.gen_unused = Atom(NonZeroU16::new(value + 1).expect("`value + 1` should succeed"));
 
         return_result
     }
+
+    /// Iterates over every [`Atom`] generated with [`AtomDealer::gen`], in the
+    /// order they were generated.
+    pub fn generated(&self) -> impl Iterator<Item = Atom> {
+        (MAX_INTERNED_ATOM..self.gen_unused.0.get())
+            .map(|value| Atom(NonZeroU16::new(value).expect("generated atoms are not 0")))
+    }
 }
 
 impl Default for AtomDealer {
@@ -1,4 +1,7 @@
 use derive_more::Display;
+use num_bigint::BigInt;
+use num_traits::{Pow, Signed, ToPrimitive, Zero};
+use std::convert::TryInto;
 use std::fmt::Write;
 use tinyvec::{tiny_vec, TinyVec};
 
@@ -8,12 +11,177 @@ use crate::{id::*, retag::RegRetagger};
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Display)]
 pub enum BinaryOperator {
     Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Exponent,
+    /// Logical and of two booleans.
     And,
+    /// Logical or of two booleans.
     Or,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    ShiftLeft,
+    /// Sign-propagating right shift (`>>`).
+    ShiftRight,
+    /// Zero-filling right shift (`>>>`).
+    UnsignedShiftRight,
+    /// Compares two values for equality. For numbers, this follows IEEE-754
+    /// semantics: `NaN` is not equal to itself, and `+0` is equal to `-0`.
     Equals,
+    /// Compares two values like [`BinaryOperator::Equals`], except that `NaN`
+    /// is the same value as `NaN`, and `+0` is not the same value as `-0`.
+    SameValue,
     LessThan,
 }
 
+/// The numeric result of folding an arithmetic [`BinaryOperator`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NumericValue {
+    Integer(i64),
+    Float(f64),
+}
+
+impl BinaryOperator {
+    /// Performs an arithmetic or bitwise operator on two integers, following
+    /// the semantics of JavaScript numbers. Integer results that are not
+    /// representable (overflow, inexact division, `-0`) are promoted to floats.
+    ///
+    /// Returns [`None`] if the operator is not arithmetic or bitwise.
+    pub fn fold_integers(self, lhs: i64, rhs: i64) -> Option<NumericValue> {
+        use BinaryOperator::*;
+        use NumericValue::*;
+
+        let promote = || self.fold_floats(lhs as f64, rhs as f64);
+
+        let value = match self {
+            Add => lhs.checked_add(rhs).map(Integer).or_else(promote)?,
+            Subtract => lhs.checked_sub(rhs).map(Integer).or_else(promote)?,
+            // zero is `-0` when either operand is negative, e.g. `0 * -5`
+            Multiply => match lhs.checked_mul(rhs) {
+                Some(0) if lhs < 0 || rhs < 0 => promote()?,
+                value => value.map(Integer).or_else(promote)?,
+            },
+            Divide => match lhs.checked_rem(rhs) {
+                Some(0) if lhs == 0 && rhs < 0 => promote()?,
+                Some(0) => Integer(lhs / rhs),
+                _ => promote()?,
+            },
+            // the remainder takes the sign of the dividend, e.g. `-5 % 5` is `-0`
+            Remainder => match lhs.checked_rem(rhs) {
+                Some(0) if lhs < 0 => promote()?,
+                value => value.map(Integer).or_else(promote)?,
+            },
+            Exponent => (rhs.try_into().ok())
+                .and_then(|rhs| lhs.checked_pow(rhs))
+                .map(Integer)
+                .or_else(promote)?,
+            BitwiseAnd | BitwiseOr | BitwiseXor | ShiftLeft | ShiftRight | UnsignedShiftRight => {
+                self.fold_int32(lhs as i32, rhs as i32)?
+            }
+            And | Or | Equals | SameValue | LessThan => return None,
+        };
+
+        Some(value)
+    }
+
+    /// Performs an arithmetic or bitwise operator on two IEEE-754 doubles,
+    /// following the semantics of JavaScript numbers.
+    ///
+    /// Returns [`None`] if the operator is not arithmetic or bitwise.
+    pub fn fold_floats(self, lhs: f64, rhs: f64) -> Option<NumericValue> {
+        use BinaryOperator::*;
+        use NumericValue::*;
+
+        let value = match self {
+            Add => Float(lhs + rhs),
+            Subtract => Float(lhs - rhs),
+            Multiply => Float(lhs * rhs),
+            Divide => Float(lhs / rhs),
+            Remainder => Float(lhs % rhs),
+            // Number::exponentiate differs from `powf` when the exponent is
+            // `NaN`, or when `|base| = 1` and the exponent is infinite
+            Exponent if rhs.is_nan() || (lhs.abs() == 1.0 && rhs.is_infinite()) => Float(f64::NAN),
+            Exponent => Float(lhs.powf(rhs)),
+            BitwiseAnd | BitwiseOr | BitwiseXor | ShiftLeft | ShiftRight | UnsignedShiftRight => {
+                self.fold_int32(to_int32(lhs), to_int32(rhs))?
+            }
+            And | Or | Equals | SameValue | LessThan => return None,
+        };
+
+        Some(value)
+    }
+
+    /// Performs an arithmetic or bitwise operator on two BigInts, following
+    /// the semantics of JavaScript BigInts.
+    ///
+    /// Returns [`None`] if the operator is not arithmetic or bitwise, or if the
+    /// operation would throw: division by zero, a negative exponent, and
+    /// unsigned right shifts all throw in JavaScript.
+    pub fn fold_bigints(self, lhs: &BigInt, rhs: &BigInt) -> Option<BigInt> {
+        use BinaryOperator::*;
+
+        let value = match self {
+            Add => lhs + rhs,
+            Subtract => lhs - rhs,
+            Multiply => lhs * rhs,
+            // `/` and `%` truncate towards zero, matching BigInt::divide and
+            // BigInt::remainder
+            Divide | Remainder if rhs.is_zero() => return None,
+            Divide => lhs / rhs,
+            Remainder => lhs % rhs,
+            Exponent => lhs.pow(rhs.to_u32()?),
+            BitwiseAnd => lhs & rhs,
+            BitwiseOr => lhs | rhs,
+            BitwiseXor => lhs ^ rhs,
+            ShiftLeft | ShiftRight => {
+                // a negative shift amount shifts in the opposite direction
+                let left = matches!(self, ShiftLeft) != rhs.is_negative();
+                let shift = rhs.abs().to_usize()?;
+
+                match left {
+                    true => lhs << shift,
+                    false => lhs >> shift,
+                }
+            }
+            UnsignedShiftRight | And | Or | Equals | SameValue | LessThan => return None,
+        };
+
+        Some(value)
+    }
+
+    fn fold_int32(self, lhs: i32, rhs: i32) -> Option<NumericValue> {
+        use BinaryOperator::*;
+
+        // shift counts only use the lower 5 bits
+        let shift = rhs as u32 & 0x1F;
+
+        let value = match self {
+            BitwiseAnd => (lhs & rhs) as i64,
+            BitwiseOr => (lhs | rhs) as i64,
+            BitwiseXor => (lhs ^ rhs) as i64,
+            ShiftLeft => lhs.wrapping_shl(shift) as i64,
+            ShiftRight => (lhs >> shift) as i64,
+            UnsignedShiftRight => (lhs as u32 >> shift) as i64,
+            _ => return None,
+        };
+
+        Some(NumericValue::Integer(value))
+    }
+}
+
+/// The `ToInt32` abstract operation.
+fn to_int32(value: f64) -> i32 {
+    if !value.is_finite() {
+        return 0;
+    }
+
+    // truncating to the range of a `u32` first allows the cast to wrap
+    (value.trunc() % 4294967296.0) as i64 as i32
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct BinOp<T: Tag> {
     pub result: RegisterId<T>,
@@ -1,13 +1,43 @@
-use std::{fmt::Write, panic::Location};
+use std::{
+    borrow::Cow,
+    fmt::{Display, Write},
+    panic::Location,
+};
 use tinyvec::TinyVec;
 
 use super::ISAInstruction;
 use crate::id::*;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Comment {
-    pub message: &'static str,
-    pub location: &'static Location<'static>,
+    pub message: Cow<'static, str>,
+    pub location: CommentLocation,
+}
+
+/// The place a [`Comment`] was made at. Comments made by the builder point to
+/// the Rust source that made them, while comments read back from text keep
+/// whichever location the text had.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CommentLocation {
+    pub file: Cow<'static, str>,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl From<&'static Location<'static>> for CommentLocation {
+    fn from(location: &'static Location<'static>) -> Self {
+        Self {
+            file: Cow::Borrowed(location.file()),
+            line: location.line(),
+            column: location.column(),
+        }
+    }
+}
+
+impl Display for CommentLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
 }
 
 impl<T: Tag> ISAInstruction<T> for Comment {
@@ -4,7 +4,7 @@ use tinyvec::TinyVec;
 use super::ISAInstruction;
 use crate::{
     id::*,
-    isa::Registers,
+    isa::{Atom, Registers},
     retag::{BlkRetagger, BlkToFn, FnRetagger, RegRetagger},
 };
 
@@ -359,3 +359,185 @@ impl<B: IdCompat, T: Tag> JumpIf<B, T> {
         }
     }
 }
+
+/// A value that a [`Switch`] can jump on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SwitchValue {
+    Atom(Atom),
+    Integer(i64),
+    Boolean(bool),
+}
+
+impl Display for SwitchValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SwitchValue::Atom(atom) => write!(f, "atom {}", atom),
+            SwitchValue::Integer(integer) => write!(f, "{}", integer),
+            SwitchValue::Boolean(boolean) => write!(f, "{}", boolean),
+        }
+    }
+}
+
+/// Jumps to the path of the first case that is equal to the value, or to the
+/// default path if no case is.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Switch<B: IdCompat, C: Tag> {
+    pub value: RegisterId<C>,
+    pub cases: Vec<(SwitchValue, BlockJump<B, C>)>,
+    pub default: BlockJump<B, C>,
+}
+
+impl<B: Tag, C: Tag> ISAInstruction<C> for Switch<BlockId<B>, C> {
+    fn is_pure() -> bool {
+        // purity is only useful in regards to eliminating work,
+        // LLVM will optimize control flow
+        false
+    }
+
+    fn declared_register(&self) -> Option<RegisterId<C>> {
+        None
+    }
+
+    fn used_registers(&self) -> TinyVec<[RegisterId<C>; 3]> {
+        self.used_registers_impl()
+    }
+
+    fn used_registers_mut(&mut self) -> Vec<&mut RegisterId<C>> {
+        self.used_registers_mut_impl()
+    }
+
+    fn display(&self, w: &mut impl Write) -> std::fmt::Result {
+        self.display_impl(w)
+    }
+}
+
+impl<F: Tag, C: Tag> ISAInstruction<C> for Switch<FunctionId<F>, C> {
+    fn is_pure() -> bool {
+        // purity is only useful in regards to eliminating work,
+        // LLVM will optimize control flow
+        false
+    }
+
+    fn declared_register(&self) -> Option<RegisterId<C>> {
+        None
+    }
+
+    fn used_registers(&self) -> TinyVec<[RegisterId<C>; 3]> {
+        self.used_registers_impl()
+    }
+
+    fn used_registers_mut(&mut self) -> Vec<&mut RegisterId<C>> {
+        self.used_registers_mut_impl()
+    }
+
+    fn display(&self, w: &mut impl Write) -> std::fmt::Result {
+        self.display_impl(w)
+    }
+}
+
+impl<B: Tag, C: Tag> Switch<BlockId<B>, C> {
+    #[track_caller]
+    pub fn retag<B2: Tag, C2: Tag>(
+        self,
+        retagger: &impl RegRetagger<C, C2>,
+        blk_retagger: &impl BlkRetagger<B, B2>,
+    ) -> Switch<BlockId<B2>, C2> {
+        let mut cases = Vec::with_capacity(self.cases.len());
+        for (value, path) in self.cases {
+            cases.push((value, path.retag(retagger, blk_retagger)));
+        }
+
+        Switch {
+            value: retagger.retag_old(self.value),
+            cases,
+            default: self.default.retag(retagger, blk_retagger),
+        }
+    }
+
+    #[track_caller]
+    pub fn retag_to_blk<F: Tag, C2: Tag>(
+        self,
+        retagger: &impl RegRetagger<C, C2>,
+        blk_to_fn: &impl BlkToFn<B, F>,
+    ) -> Switch<FunctionId<F>, C2> {
+        let mut cases = Vec::with_capacity(self.cases.len());
+        for (value, path) in self.cases {
+            cases.push((value, path.retag_to_blk(retagger, blk_to_fn)));
+        }
+
+        Switch {
+            value: retagger.retag_old(self.value),
+            cases,
+            default: self.default.retag_to_blk(retagger, blk_to_fn),
+        }
+    }
+}
+
+impl<F: Tag, R: Tag> Switch<FunctionId<F>, R> {
+    #[track_caller]
+    pub fn retag<F2: Tag, R2: Tag>(
+        self,
+        retagger: &impl RegRetagger<R, R2>,
+        fn_retagger: &impl FnRetagger<F, F2>,
+    ) -> Switch<FunctionId<F2>, R2> {
+        let mut cases = Vec::with_capacity(self.cases.len());
+        for (value, path) in self.cases {
+            cases.push((value, path.retag(retagger, fn_retagger)));
+        }
+
+        Switch {
+            value: retagger.retag_old(self.value),
+            cases,
+            default: self.default.retag(retagger, fn_retagger),
+        }
+    }
+}
+
+impl<B: IdCompat, C: Tag> Switch<B, C> {
+    pub fn paths(&self) -> Vec<&BlockJump<B, C>> {
+        let cases = self.cases.iter().map(|(_, path)| path);
+        cases.chain(std::iter::once(&self.default)).collect()
+    }
+
+    pub fn paths_mut(&mut self) -> Vec<&mut BlockJump<B, C>> {
+        let cases = self.cases.iter_mut().map(|(_, path)| path);
+        cases.chain(std::iter::once(&mut self.default)).collect()
+    }
+
+    pub fn take_path(&self, value: SwitchValue) -> &BlockJump<B, C> {
+        (self.cases.iter())
+            .find(|(case, _)| *case == value)
+            .map_or(&self.default, |(_, path)| path)
+    }
+
+    fn used_registers_impl(&self) -> TinyVec<[RegisterId<C>; 3]> {
+        let mut used_registers = TinyVec::new();
+        used_registers.push(self.value);
+        for path in self.paths() {
+            used_registers.extend_from_slice(&path.1);
+        }
+        used_registers
+    }
+
+    fn used_registers_mut_impl(&mut self) -> Vec<&mut RegisterId<C>> {
+        let mut used_registers = vec![&mut self.value];
+        for (_, path) in self.cases.iter_mut() {
+            used_registers.extend(path.1.iter_mut());
+        }
+        used_registers.extend(self.default.1.iter_mut());
+        used_registers
+    }
+
+    fn display_impl(&self, w: &mut impl Write) -> std::fmt::Result
+    where
+        BlockJump<B, C>: Display,
+    {
+        write!(w, "Switch %{}:", self.value)?;
+        for (value, path) in self.cases.iter() {
+            w.write_str("\n")?;
+            write!(w, "     case {} => {};", value, path)?;
+        }
+        w.write_str("\n")?;
+        write!(w, "else {};", self.default)
+    }
+}
@@ -15,6 +15,7 @@ pub enum ValueType {
     Record,
     BigNumber,
     List,
+    Tuple,
     Runtime,
 }
 
@@ -24,9 +25,9 @@ pub enum ValueType {
 /// specific type.
 #[derive(Clone, Copy, Debug, Display, PartialEq, Eq, Hash)]
 pub enum CompareType<C: Tag> {
-    #[display("%{}", .0)]
+    #[display(fmt = "%{}", _0)]
     Register(RegisterId<C>),
-    #[display("{}", .0)]
+    #[display(fmt = "{}", _0)]
     Kind(ValueType),
 }
 
@@ -1,3 +1,5 @@
+use num_bigint::BigInt;
+use ordered_float::OrderedFloat;
 use std::fmt::{Display, Write};
 use tinyvec::TinyVec;
 
@@ -76,6 +78,29 @@ impl<T: Tag> Make<T, i64> {
     }
 }
 
+impl<T: Tag> Make<T, OrderedFloat<f64>> {
+    #[track_caller]
+    pub fn retag<T2: Tag>(
+        self,
+        retagger: &mut impl RegRetagger<T, T2>,
+    ) -> Make<T2, OrderedFloat<f64>> {
+        Make {
+            result: retagger.retag_new(self.result),
+            item: self.item,
+        }
+    }
+}
+
+impl<T: Tag> Make<T, BigInt> {
+    #[track_caller]
+    pub fn retag<T2: Tag>(self, retagger: &mut impl RegRetagger<T, T2>) -> Make<T2, BigInt> {
+        Make {
+            result: retagger.retag_new(self.result),
+            item: self.item,
+        }
+    }
+}
+
 impl<T: Tag, F: Tag> Make<T, FunctionId<F>> {
     #[track_caller]
     pub fn retag<T2: Tag, F2: Tag>(
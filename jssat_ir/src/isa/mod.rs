@@ -51,6 +51,7 @@ pub trait ISAInstruction<C: Tag> {
 }
 
 mod atom;
+pub(crate) use atom::MAX_INTERNED_ATOM;
 pub use atom::{Atom, AtomDealer};
 
 mod noop;
@@ -60,10 +61,10 @@ mod unreachable;
 pub use unreachable::Unreachable;
 
 mod comment;
-pub use comment::Comment;
+pub use comment::{Comment, CommentLocation};
 
 mod control_flow;
-pub use control_flow::{BlockJump, Jump, JumpIf, Return};
+pub use control_flow::{BlockJump, Jump, JumpIf, Return, Switch, SwitchValue};
 
 mod make;
 pub use make::Make;
@@ -71,8 +72,14 @@ pub use make::Make;
 mod negate;
 pub use negate::Negate;
 
+mod not;
+pub use not::Not;
+
+mod number_to_bigint;
+pub use number_to_bigint::NumberToBigInt;
+
 mod binop;
-pub use binop::{BinOp, BinaryOperator};
+pub use binop::{BinOp, BinaryOperator, NumericValue};
 
 mod records;
 pub use records::{NewRecord, RecordGet, RecordHasKey, RecordKey, RecordSet};
@@ -80,6 +87,16 @@ pub use records::{NewRecord, RecordGet, RecordHasKey, RecordKey, RecordSet};
 mod lists;
 pub use lists::{ListGet, ListHasKey, ListKey, ListLen, ListSet, NewList};
 
+mod tuples;
+pub use tuples::{NewTuple, TupleGet, TupleLen};
+
+mod strings;
+pub use strings::{
+    decode_utf16, encode_utf16, number_to_string, string_code_unit_at, string_len, string_slice,
+    string_to_number, NumberToString, StringCodeUnitAt, StringConcat, StringLen, StringSlice,
+    StringToNumber,
+};
+
 mod call;
 pub use call::Call;
 
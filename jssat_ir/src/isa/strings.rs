@@ -0,0 +1,433 @@
+//! Instructions operating on strings. A string is a sequence of UTF-16 code
+//! units stored in native byte order, which is how JavaScript represents
+//! strings. All lengths and indices are counted in code units, not bytes.
+
+use std::fmt::Write;
+use tinyvec::{tiny_vec, TinyVec};
+
+use super::{ISAInstruction, NumericValue};
+use crate::{id::*, retag::RegRetagger};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StringConcat<C: Tag> {
+    pub result: RegisterId<C>,
+    pub lhs: RegisterId<C>,
+    pub rhs: RegisterId<C>,
+}
+
+impl<C: Tag> ISAInstruction<C> for StringConcat<C> {
+    fn declared_register(&self) -> Option<RegisterId<C>> {
+        Some(self.result)
+    }
+
+    fn used_registers(&self) -> TinyVec<[RegisterId<C>; 3]> {
+        tiny_vec![self.lhs, self.rhs]
+    }
+
+    fn used_registers_mut(&mut self) -> Vec<&mut RegisterId<C>> {
+        vec![&mut self.lhs, &mut self.rhs]
+    }
+
+    fn display(&self, w: &mut impl Write) -> std::fmt::Result {
+        write!(
+            w,
+            "%{} = StringConcat %{}, %{};",
+            self.result, self.lhs, self.rhs
+        )
+    }
+}
+
+impl<C: Tag> StringConcat<C> {
+    #[track_caller]
+    pub fn retag<C2: Tag>(self, retagger: &mut impl RegRetagger<C, C2>) -> StringConcat<C2> {
+        StringConcat {
+            result: retagger.retag_new(self.result),
+            lhs: retagger.retag_old(self.lhs),
+            rhs: retagger.retag_old(self.rhs),
+        }
+    }
+}
+
+/// [`StringLen`] produces the amount of code units in a string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StringLen<C: Tag> {
+    pub result: RegisterId<C>,
+    pub string: RegisterId<C>,
+}
+
+impl<C: Tag> ISAInstruction<C> for StringLen<C> {
+    fn declared_register(&self) -> Option<RegisterId<C>> {
+        Some(self.result)
+    }
+
+    fn used_registers(&self) -> TinyVec<[RegisterId<C>; 3]> {
+        tiny_vec![self.string]
+    }
+
+    fn used_registers_mut(&mut self) -> Vec<&mut RegisterId<C>> {
+        vec![&mut self.string]
+    }
+
+    fn display(&self, w: &mut impl Write) -> std::fmt::Result {
+        write!(w, "%{} = StringLen %{};", self.result, self.string)
+    }
+}
+
+impl<C: Tag> StringLen<C> {
+    #[track_caller]
+    pub fn retag<C2: Tag>(self, retagger: &mut impl RegRetagger<C, C2>) -> StringLen<C2> {
+        StringLen {
+            result: retagger.retag_new(self.result),
+            string: retagger.retag_old(self.string),
+        }
+    }
+}
+
+/// [`StringSlice`] produces the code units of a string from `start`
+/// (inclusive) to `end` (exclusive). It is an error for the range to be out of
+/// bounds of the string, or for `start` to be greater than `end`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StringSlice<C: Tag> {
+    pub result: RegisterId<C>,
+    pub string: RegisterId<C>,
+    pub start: RegisterId<C>,
+    pub end: RegisterId<C>,
+}
+
+impl<C: Tag> ISAInstruction<C> for StringSlice<C> {
+    fn declared_register(&self) -> Option<RegisterId<C>> {
+        Some(self.result)
+    }
+
+    fn used_registers(&self) -> TinyVec<[RegisterId<C>; 3]> {
+        tiny_vec![self.string, self.start, self.end]
+    }
+
+    fn used_registers_mut(&mut self) -> Vec<&mut RegisterId<C>> {
+        vec![&mut self.string, &mut self.start, &mut self.end]
+    }
+
+    fn display(&self, w: &mut impl Write) -> std::fmt::Result {
+        write!(
+            w,
+            "%{} = StringSlice %{}[%{}..%{}];",
+            self.result, self.string, self.start, self.end
+        )
+    }
+}
+
+impl<C: Tag> StringSlice<C> {
+    #[track_caller]
+    pub fn retag<C2: Tag>(self, retagger: &mut impl RegRetagger<C, C2>) -> StringSlice<C2> {
+        StringSlice {
+            result: retagger.retag_new(self.result),
+            string: retagger.retag_old(self.string),
+            start: retagger.retag_old(self.start),
+            end: retagger.retag_old(self.end),
+        }
+    }
+}
+
+/// [`StringCodeUnitAt`] produces the numeric value of the code unit at an
+/// index of a string. It is an error for the index to be out of bounds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StringCodeUnitAt<C: Tag> {
+    pub result: RegisterId<C>,
+    pub string: RegisterId<C>,
+    pub index: RegisterId<C>,
+}
+
+impl<C: Tag> ISAInstruction<C> for StringCodeUnitAt<C> {
+    fn declared_register(&self) -> Option<RegisterId<C>> {
+        Some(self.result)
+    }
+
+    fn used_registers(&self) -> TinyVec<[RegisterId<C>; 3]> {
+        tiny_vec![self.string, self.index]
+    }
+
+    fn used_registers_mut(&mut self) -> Vec<&mut RegisterId<C>> {
+        vec![&mut self.string, &mut self.index]
+    }
+
+    fn display(&self, w: &mut impl Write) -> std::fmt::Result {
+        write!(
+            w,
+            "%{} = StringCodeUnitAt %{}[%{}];",
+            self.result, self.string, self.index
+        )
+    }
+}
+
+impl<C: Tag> StringCodeUnitAt<C> {
+    #[track_caller]
+    pub fn retag<C2: Tag>(self, retagger: &mut impl RegRetagger<C, C2>) -> StringCodeUnitAt<C2> {
+        StringCodeUnitAt {
+            result: retagger.retag_new(self.result),
+            string: retagger.retag_old(self.string),
+            index: retagger.retag_old(self.index),
+        }
+    }
+}
+
+/// [`NumberToString`] converts a number into its string representation, as
+/// specified by `Number::toString` with a radix of 10.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NumberToString<C: Tag> {
+    pub result: RegisterId<C>,
+    pub number: RegisterId<C>,
+}
+
+impl<C: Tag> ISAInstruction<C> for NumberToString<C> {
+    fn declared_register(&self) -> Option<RegisterId<C>> {
+        Some(self.result)
+    }
+
+    fn used_registers(&self) -> TinyVec<[RegisterId<C>; 3]> {
+        tiny_vec![self.number]
+    }
+
+    fn used_registers_mut(&mut self) -> Vec<&mut RegisterId<C>> {
+        vec![&mut self.number]
+    }
+
+    fn display(&self, w: &mut impl Write) -> std::fmt::Result {
+        write!(w, "%{} = NumberToString %{};", self.result, self.number)
+    }
+}
+
+impl<C: Tag> NumberToString<C> {
+    #[track_caller]
+    pub fn retag<C2: Tag>(self, retagger: &mut impl RegRetagger<C, C2>) -> NumberToString<C2> {
+        NumberToString {
+            result: retagger.retag_new(self.result),
+            number: retagger.retag_old(self.number),
+        }
+    }
+}
+
+/// [`StringToNumber`] parses a string into a number, as specified by the
+/// `StringToNumber` abstract operation. Strings which are not numeric literals
+/// produce `NaN`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StringToNumber<C: Tag> {
+    pub result: RegisterId<C>,
+    pub string: RegisterId<C>,
+}
+
+impl<C: Tag> ISAInstruction<C> for StringToNumber<C> {
+    fn declared_register(&self) -> Option<RegisterId<C>> {
+        Some(self.result)
+    }
+
+    fn used_registers(&self) -> TinyVec<[RegisterId<C>; 3]> {
+        tiny_vec![self.string]
+    }
+
+    fn used_registers_mut(&mut self) -> Vec<&mut RegisterId<C>> {
+        vec![&mut self.string]
+    }
+
+    fn display(&self, w: &mut impl Write) -> std::fmt::Result {
+        write!(w, "%{} = StringToNumber %{};", self.result, self.string)
+    }
+}
+
+impl<C: Tag> StringToNumber<C> {
+    #[track_caller]
+    pub fn retag<C2: Tag>(self, retagger: &mut impl RegRetagger<C, C2>) -> StringToNumber<C2> {
+        StringToNumber {
+            result: retagger.retag_new(self.result),
+            string: retagger.retag_old(self.string),
+        }
+    }
+}
+
+/// Encodes a string into UTF-16 code units in native byte order.
+pub fn encode_utf16(string: &str) -> Vec<u8> {
+    string
+        .encode_utf16()
+        .flat_map(|x| x.to_ne_bytes())
+        .collect()
+}
+
+/// Decodes UTF-16 code units in native byte order into a string. Returns
+/// [`None`] if the bytes are not valid UTF-16.
+pub fn decode_utf16(bytes: &[u8]) -> Option<String> {
+    if bytes.len() % 2 != 0 {
+        return None;
+    }
+
+    let code_units = bytes
+        .chunks_exact(2)
+        .map(|x| u16::from_ne_bytes([x[0], x[1]]))
+        .collect::<Vec<u16>>();
+
+    String::from_utf16(&code_units).ok()
+}
+
+/// The amount of code units in a string.
+pub fn string_len(bytes: &[u8]) -> usize {
+    bytes.len() / 2
+}
+
+/// The code units of a string from `start` (inclusive) to `end` (exclusive).
+/// Returns [`None`] if the range is out of bounds.
+pub fn string_slice(bytes: &[u8], start: i64, end: i64) -> Option<&[u8]> {
+    let start: usize = start.try_into().ok()?;
+    let end: usize = end.try_into().ok()?;
+
+    if start > end || end > string_len(bytes) {
+        return None;
+    }
+
+    Some(&bytes[start * 2..end * 2])
+}
+
+/// The code unit at an index of a string. Returns [`None`] if the index is out
+/// of bounds.
+pub fn string_code_unit_at(bytes: &[u8], index: i64) -> Option<u16> {
+    let index: usize = index.try_into().ok()?;
+
+    if index >= string_len(bytes) {
+        return None;
+    }
+
+    Some(u16::from_ne_bytes([bytes[index * 2], bytes[index * 2 + 1]]))
+}
+
+/// Converts a number into a string, following `Number::toString`.
+pub fn number_to_string(value: NumericValue) -> String {
+    let value = match value {
+        NumericValue::Integer(value) => return value.to_string(),
+        NumericValue::Float(value) => value,
+    };
+
+    if value.is_nan() {
+        return "NaN".to_owned();
+    } else if value == 0.0 {
+        return "0".to_owned();
+    } else if value < 0.0 {
+        return format!("-{}", number_to_string(NumericValue::Float(-value)));
+    } else if value.is_infinite() {
+        return "Infinity".to_owned();
+    }
+
+    // the exponential formatting of a float produces the shortest digits which
+    // roundtrip, which are the `s` and `k` of the specification
+    let formatted = format!("{:e}", value);
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().unwrap() + 1;
+
+    if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        let (int, fract) = digits.split_at(n as usize);
+        format!("{}.{}", int, fract)
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        let sign = if n - 1 < 0 { '-' } else { '+' };
+        let exponent = (n - 1).abs();
+
+        match digits.split_at(1) {
+            (first, "") => format!("{}e{}{}", first, sign, exponent),
+            (first, rest) => format!("{}.{}e{}{}", first, rest, sign, exponent),
+        }
+    }
+}
+
+/// Parses a string into a number, following the `StringToNumber` abstract
+/// operation. Integral results are produced as integers.
+pub fn string_to_number(string: &str) -> NumericValue {
+    let value = parse_string_numeric_literal(string).unwrap_or(f64::NAN);
+
+    let is_integer = value.fract() == 0.0
+        && value.abs() <= (1u64 << 53) as f64
+        && !(value == 0.0 && value.is_sign_negative());
+
+    if is_integer {
+        NumericValue::Integer(value as i64)
+    } else {
+        NumericValue::Float(value)
+    }
+}
+
+fn parse_string_numeric_literal(string: &str) -> Option<f64> {
+    // `char::is_whitespace` matches all `WhiteSpace` and `LineTerminator`s,
+    // except for <ZWNBSP>, and additionally matches U+0085
+    let string =
+        string.trim_matches(|c: char| c == '\u{FEFF}' || (c.is_whitespace() && c != '\u{85}'));
+
+    if string.is_empty() {
+        return Some(0.0);
+    }
+
+    let radix = match string.get(..2) {
+        Some("0x" | "0X") => Some(16),
+        Some("0o" | "0O") => Some(8),
+        Some("0b" | "0B") => Some(2),
+        _ => None,
+    };
+
+    if let Some(radix) = radix {
+        let digits = &string[2..];
+
+        if digits.is_empty() {
+            return None;
+        }
+
+        return digits.chars().try_fold(0.0, |value, c| {
+            c.to_digit(radix)
+                .map(|digit| value * radix as f64 + digit as f64)
+        });
+    }
+
+    let unsigned = string
+        .strip_prefix(|c| c == '+' || c == '-')
+        .unwrap_or(string);
+
+    if unsigned == "Infinity" {
+        return Some(if string.starts_with('-') {
+            f64::NEG_INFINITY
+        } else {
+            f64::INFINITY
+        });
+    }
+
+    // only `StrDecimalLiteral`s are accepted, whereas `f64::from_str` would
+    // also accept things like `inf` and `NaN`
+    let (mantissa, exponent) = match unsigned.find(|c| c == 'e' || c == 'E') {
+        Some(idx) => (&unsigned[..idx], Some(&unsigned[idx + 1..])),
+        None => (unsigned, None),
+    };
+
+    let is_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+
+    let mantissa_valid = match mantissa.split_once('.') {
+        Some((int, fract)) => {
+            (is_digits(int) || int.is_empty())
+                && (is_digits(fract) || fract.is_empty())
+                && !(int.is_empty() && fract.is_empty())
+        }
+        None => is_digits(mantissa),
+    };
+
+    let exponent_valid = match exponent {
+        Some(exponent) => {
+            let exponent = exponent
+                .strip_prefix(|c| c == '+' || c == '-')
+                .unwrap_or(exponent);
+            is_digits(exponent)
+        }
+        None => true,
+    };
+
+    if !(mantissa_valid && exponent_valid) {
+        return None;
+    }
+
+    string.parse().ok()
+}
@@ -0,0 +1,123 @@
+use std::fmt::Write;
+use tinyvec::{tiny_vec, TinyVec};
+
+use super::{ISAInstruction, Registers};
+use crate::{id::*, retag::RegRetagger};
+
+/// [`NewTuple`] creates a tuple out of some elements. Unlike records and lists,
+/// tuples are immutable: the amount of elements in a tuple and the elements
+/// themselves are fixed once the tuple is created.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NewTuple<C: Tag> {
+    pub result: RegisterId<C>,
+    pub elements: Vec<RegisterId<C>>,
+}
+
+impl<C: Tag> ISAInstruction<C> for NewTuple<C> {
+    fn declared_register(&self) -> Option<RegisterId<C>> {
+        Some(self.result)
+    }
+
+    fn used_registers(&self) -> TinyVec<[RegisterId<C>; 3]> {
+        TinyVec::from(self.elements.as_slice())
+    }
+
+    fn used_registers_mut(&mut self) -> Vec<&mut RegisterId<C>> {
+        self.elements.iter_mut().collect()
+    }
+
+    fn display(&self, w: &mut impl Write) -> std::fmt::Result {
+        write!(
+            w,
+            "%{} = NewTuple ({});",
+            self.result,
+            Registers(&self.elements)
+        )
+    }
+}
+
+impl<C: Tag> NewTuple<C> {
+    #[track_caller]
+    pub fn retag<C2: Tag>(self, retagger: &mut impl RegRetagger<C, C2>) -> NewTuple<C2> {
+        NewTuple {
+            result: retagger.retag_new(self.result),
+            elements: retagger.retag_olds(self.elements),
+        }
+    }
+}
+
+/// [`TupleGet`] gets an element of a tuple. As tuples have a fixed size, the
+/// index of the element is known ahead of time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TupleGet<C: Tag> {
+    pub result: RegisterId<C>,
+    pub tuple: RegisterId<C>,
+    pub index: usize,
+}
+
+impl<C: Tag> ISAInstruction<C> for TupleGet<C> {
+    fn declared_register(&self) -> Option<RegisterId<C>> {
+        Some(self.result)
+    }
+
+    fn used_registers(&self) -> TinyVec<[RegisterId<C>; 3]> {
+        tiny_vec![self.tuple]
+    }
+
+    fn used_registers_mut(&mut self) -> Vec<&mut RegisterId<C>> {
+        vec![&mut self.tuple]
+    }
+
+    fn display(&self, w: &mut impl Write) -> std::fmt::Result {
+        write!(
+            w,
+            "%{} = TupleGet %{}.{};",
+            self.result, self.tuple, self.index
+        )
+    }
+}
+
+impl<C: Tag> TupleGet<C> {
+    #[track_caller]
+    pub fn retag<C2: Tag>(self, retagger: &mut impl RegRetagger<C, C2>) -> TupleGet<C2> {
+        TupleGet {
+            result: retagger.retag_new(self.result),
+            tuple: retagger.retag_old(self.tuple),
+            index: self.index,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TupleLen<C: Tag> {
+    pub result: RegisterId<C>,
+    pub tuple: RegisterId<C>,
+}
+
+impl<C: Tag> ISAInstruction<C> for TupleLen<C> {
+    fn declared_register(&self) -> Option<RegisterId<C>> {
+        Some(self.result)
+    }
+
+    fn used_registers(&self) -> TinyVec<[RegisterId<C>; 3]> {
+        tiny_vec![self.tuple]
+    }
+
+    fn used_registers_mut(&mut self) -> Vec<&mut RegisterId<C>> {
+        vec![&mut self.tuple]
+    }
+
+    fn display(&self, w: &mut impl Write) -> std::fmt::Result {
+        write!(w, "%{} = TupleLen %{};", self.result, self.tuple)
+    }
+}
+
+impl<C: Tag> TupleLen<C> {
+    #[track_caller]
+    pub fn retag<C2: Tag>(self, retagger: &mut impl RegRetagger<C, C2>) -> TupleLen<C2> {
+        TupleLen {
+            result: retagger.retag_new(self.result),
+            tuple: retagger.retag_old(self.tuple),
+        }
+    }
+}
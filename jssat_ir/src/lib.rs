@@ -3,7 +3,10 @@
 #![feature(const_option)]
 #![feature(associated_type_bounds)]
 
+pub mod analysis;
+pub mod binary;
 pub mod collections;
+pub mod diff;
 pub mod frontend;
 pub mod id;
 pub mod isa;
@@ -11,6 +14,7 @@ pub mod lifted;
 pub mod pyramid_api;
 pub mod retag;
 pub mod value_snapshot;
+pub mod verify;
 
 /// can't have nice things :'( https://github.com/rust-lang/rust/issues/62633
 pub trait UnwrapNone {
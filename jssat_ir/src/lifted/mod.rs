@@ -6,7 +6,7 @@ use tinyvec::TinyVec;
 use crate::{
     frontend::ir::{self, ControlFlowInstruction, Instruction, IR},
     id::{IrCtx, LiftedCtx},
-    isa::{AtomDealer, BlockJump, ISAInstruction, Jump, JumpIf, Return},
+    isa::{AtomDealer, BlockJump, ISAInstruction, Jump, JumpIf, Return, Switch},
     retag::{
         BlkToFn, CnstPassRetagger, CnstRetagger, ExtFnPassRetagger, ExtFnRetagger, FnGenRetagger,
         FnMapRetagger, FnRetagger, RegPassRetagger, RegRetagger,
@@ -46,6 +46,7 @@ pub struct Function {
 pub enum EndInstruction {
     Jump(Jump<FunctionId, LiftedCtx>),
     JumpIf(JumpIf<FunctionId, LiftedCtx>),
+    Switch(Switch<FunctionId, LiftedCtx>),
     Return(Return<LiftedCtx>),
 }
 
@@ -61,6 +62,7 @@ impl EndInstruction {
         match self {
             EndInstruction::Jump(inst) => inst.declared_register(),
             EndInstruction::JumpIf(inst) => inst.declared_register(),
+            EndInstruction::Switch(inst) => inst.declared_register(),
             EndInstruction::Return(inst) => inst.declared_register(),
         }
     }
@@ -69,6 +71,7 @@ impl EndInstruction {
         match self {
             EndInstruction::Jump(inst) => inst.used_registers(),
             EndInstruction::JumpIf(inst) => inst.used_registers(),
+            EndInstruction::Switch(inst) => inst.used_registers(),
             EndInstruction::Return(inst) => inst.used_registers(),
         }
     }
@@ -77,6 +80,7 @@ impl EndInstruction {
         match self {
             EndInstruction::Jump(inst) => inst.used_registers_mut(),
             EndInstruction::JumpIf(inst) => inst.used_registers_mut(),
+            EndInstruction::Switch(inst) => inst.used_registers_mut(),
             EndInstruction::Return(inst) => inst.used_registers_mut(),
         }
     }
@@ -85,6 +89,7 @@ impl EndInstruction {
         match self {
             EndInstruction::Jump(inst) => inst.paths(),
             EndInstruction::JumpIf(inst) => inst.paths(),
+            EndInstruction::Switch(inst) => inst.paths(),
             EndInstruction::Return(_) => Vec::new(),
         }
     }
@@ -93,6 +98,7 @@ impl EndInstruction {
         match self {
             EndInstruction::Jump(inst) => inst.paths_mut(),
             EndInstruction::JumpIf(inst) => inst.paths_mut(),
+            EndInstruction::Switch(inst) => inst.paths_mut(),
             EndInstruction::Return(_) => Vec::new(),
         }
     }
@@ -105,6 +111,7 @@ impl EndInstruction {
         match self {
             EndInstruction::Jump(inst) => inst.display(w),
             EndInstruction::JumpIf(inst) => inst.display(w),
+            EndInstruction::Switch(inst) => inst.display(w),
             EndInstruction::Return(inst) => inst.display(w),
         }
     }
@@ -164,13 +171,16 @@ pub fn lift(ir: IR) -> LiftedProgram {
 
     let dealer = ir.dealer;
 
-    LiftedProgram {
+    let program = LiftedProgram {
         dealer,
         entrypoint,
         constants,
         external_functions,
         functions,
-    }
+    };
+
+    crate::verify::debug_verify_lifted(&program);
+    program
 }
 
 fn lift_function(
@@ -273,6 +283,9 @@ fn fn_block_to_fn(
         ControlFlowInstruction::JmpIf(inst) => {
             EndInstruction::JumpIf(inst.retag_to_blk(retagger, blk_to_fn))
         }
+        ControlFlowInstruction::Switch(inst) => {
+            EndInstruction::Switch(inst.retag_to_blk(retagger, blk_to_fn))
+        }
         ControlFlowInstruction::Ret(inst) => EndInstruction::Return(inst.retag(retagger)),
     };
 
@@ -1,5 +1,7 @@
 //! just a hacky thing to bridge values from interpreter to domino
 
+use num_bigint::BigInt;
+use ordered_float::OrderedFloat;
 use rustc_hash::FxHashMap;
 
 use crate::{
@@ -13,10 +15,16 @@ pub enum SnapshotValue {
     // TODO: intern value of bytes
     Bytes(Vec<u8>),
     Number(i64),
+    Float(OrderedFloat<f64>),
+    BigInt(BigInt),
+    /// A BigInt of an unknown value. The abstract interpreter does not track
+    /// the values of BigInts, so this only appears in its snapshots.
+    BigNumber,
     Boolean(bool),
     FnPtr(FunctionId),
     Record(usize),
     List(usize),
+    Tuple(usize),
     Runtime,
 }
 
@@ -26,12 +34,16 @@ pub struct SnapshotRecord(pub FxHashMap<SnapshotValue, SnapshotValue>);
 #[derive(Clone, Debug)]
 pub struct SnapshotList(pub Vec<SnapshotValue>);
 
+#[derive(Clone, Debug)]
+pub struct SnapshotTuple(pub Vec<SnapshotValue>);
+
 #[derive(Clone, Default, Debug)]
 pub struct ValueSnapshotArena {
     /// register id |-> index in value map
     pub registers: FxHashMap<RegisterId, SnapshotValue>,
     pub records: FxHashMap<usize, SnapshotRecord>,
     pub lists: FxHashMap<usize, SnapshotList>,
+    pub tuples: FxHashMap<usize, SnapshotTuple>,
 }
 
 impl ValueSnapshotArena {
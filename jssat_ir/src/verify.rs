@@ -0,0 +1,797 @@
+//! Verifies that a program is well formed. Malformed programs otherwise tend to
+//! be found through panics deep within lifting, the retaggers, or the
+//! interpreter, far away from the code that constructed them.
+//!
+//! Both [`IR`] and [`LiftedProgram`]s can be verified. For a program to be well
+//! formed:
+//!
+//! - the entrypoint must exist
+//! - registers must be in SSA form, i.e. every register is defined only once
+//!   per function
+//! - every used register must be defined, and its definition must dominate its
+//!   use
+//! - jumps must target blocks that exist, passing as many registers as the
+//!   block has parameters
+//! - a switch may only have one case for each value
+//! - statically called functions must exist, and are called with as many
+//!   arguments as they have parameters
+//! - referenced external functions, functions and constants must exist
+//! - only the return types of external functions may be nullable, and nullable
+//!   types may not be nested
+//!
+//! [`LiftedProgram`]s have no notion of blocks, so every register used by a
+//! lifted function must be defined within that function.
+//!
+//! [`ProgramBuilder::finish`](crate::frontend::builder::ProgramBuilder::finish)
+//! and [`lift`](crate::lifted::lift) verify every program in debug builds.
+
+use std::fmt::Display;
+use std::panic::Location;
+
+use derive_more::Display;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::frontend::ir::{
+    self, Constant, ExternalFunction, FFIValueType, Instruction, InstructionData, Returns, IR,
+};
+use crate::id::{ConstantId, ExternalFunctionId, FunctionId, IdCompat, IrCtx, Tag};
+use crate::isa::{Switch, SwitchValue};
+use crate::lifted::{self, LiftedProgram};
+
+type BlockId = crate::id::BlockId<IrCtx>;
+type RegisterId = crate::id::RegisterId<IrCtx>;
+
+/// A single way in which a program is not well formed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    pub kind: ViolationKind,
+    /// The function the violation is in. For [`LiftedProgram`]s, this is the
+    /// lifted function.
+    pub function: Option<usize>,
+    /// The block the violation is in. This is always [`None`] for
+    /// [`LiftedProgram`]s.
+    pub block: Option<usize>,
+    pub site: Site,
+    /// Where the offending instruction was constructed, if the violation was
+    /// caused by an instruction.
+    pub constructed_at: Option<&'static Location<'static>>,
+}
+
+/// Where in a function or block a [`Violation`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Site {
+    /// The declaration of the function or block, such as its parameters.
+    Declaration,
+    /// The instruction at the given index.
+    Instruction(usize),
+    /// The control flow instruction at the end of the block.
+    End,
+}
+
+#[derive(Clone, Debug, Display, PartialEq, Eq)]
+pub enum ViolationKind {
+    #[display(fmt = "entrypoint @{} does not exist", _0)]
+    MissingEntrypoint(usize),
+    #[display(fmt = "entry block ${} does not exist", _0)]
+    MissingEntryBlock(usize),
+    #[display(fmt = "entry block has {} parameters, expected none", _0)]
+    EntryBlockParameters(usize),
+    #[display(fmt = "register %{} is used but never defined", _0)]
+    UndefinedRegister(usize),
+    #[display(fmt = "definition of register %{} does not dominate its use", _0)]
+    UndominatedUse(usize),
+    #[display(fmt = "register %{} is defined more than once", _0)]
+    RedefinedRegister(usize),
+    #[display(fmt = "block ${} does not exist", _0)]
+    MissingBlock(usize),
+    #[display(fmt = "function @{} does not exist", _0)]
+    MissingFunction(usize),
+    #[display(fmt = "external function @@{} does not exist", _0)]
+    MissingExternalFunction(usize),
+    #[display(fmt = "constant #{} does not exist", _0)]
+    MissingConstant(usize),
+    #[display(
+        fmt = "external function @@{} has a nullable type other than its return type",
+        _0
+    )]
+    MisplacedNullable(usize),
+    #[display(
+        fmt = "{} is passed {} arguments, but has {} parameters",
+        target,
+        passed,
+        expected
+    )]
+    ArityMismatch {
+        /// The jump target or called function, e.g. `$1` or `@@2`.
+        target: String,
+        expected: usize,
+        passed: usize,
+    },
+    #[display(fmt = "switch has more than one case for {}", _0)]
+    DuplicateSwitchCase(SwitchValue),
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(function) = self.function {
+            write!(f, "@{}", function)?;
+        }
+
+        if let Some(block) = self.block {
+            write!(f, ".${}", block)?;
+        }
+
+        match self.site {
+            Site::Declaration => {}
+            Site::Instruction(idx) => write!(f, " #{}", idx)?,
+            Site::End => write!(f, " end")?,
+        };
+
+        write!(f, ": {}", self.kind)?;
+
+        if let Some(location) = self.constructed_at {
+            write!(f, " (constructed at {})", location)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Verifies that `ir` is well formed, returning every [`Violation`] found if
+/// it is not.
+pub fn verify(ir: &IR) -> Result<(), Vec<Violation>> {
+    let mut violations = Vec::new();
+
+    if !ir.functions.contains_key(&ir.entrypoint) {
+        violations.push(Violation {
+            kind: ViolationKind::MissingEntrypoint(ir.entrypoint.value()),
+            function: None,
+            block: None,
+            site: Site::Declaration,
+            constructed_at: None,
+        });
+    }
+
+    verify_external_functions(&ir.external_functions, &mut violations);
+
+    let references = References {
+        function_arity: |id| ir.functions.get(&id).map(|f| f.parameters.len()),
+        external_functions: &ir.external_functions,
+        constants: &ir.constants,
+    };
+
+    for (id, function) in sorted(&ir.functions) {
+        verify_function(id, function, &references, &mut violations);
+    }
+
+    into_result(violations)
+}
+
+/// Verifies that `program` is well formed, returning every [`Violation`] found
+/// if it is not.
+pub fn verify_lifted(program: &LiftedProgram) -> Result<(), Vec<Violation>> {
+    let mut violations = Vec::new();
+
+    if !program.functions.contains_key(&program.entrypoint) {
+        violations.push(Violation {
+            kind: ViolationKind::MissingEntrypoint(program.entrypoint.value()),
+            function: None,
+            block: None,
+            site: Site::Declaration,
+            constructed_at: None,
+        });
+    }
+
+    verify_external_functions(&program.external_functions, &mut violations);
+
+    let references = References {
+        function_arity: |id| program.functions.get(&id).map(|f| f.parameters.len()),
+        external_functions: &program.external_functions,
+        constants: &program.constants,
+    };
+
+    for (id, function) in sorted(&program.functions) {
+        verify_lifted_function(id, function, program, &references, &mut violations);
+    }
+
+    into_result(violations)
+}
+
+/// Panics with every [`Violation`] found in `ir`, if it is not well formed.
+/// Does nothing in release builds.
+#[track_caller]
+pub fn debug_verify(ir: &IR) {
+    if cfg!(debug_assertions) {
+        if let Err(violations) = verify(ir) {
+            panic!("malformed IR:\n{}", display_violations(&violations));
+        }
+    }
+}
+
+/// Analogous to [`debug_verify`], except for a [`LiftedProgram`].
+#[track_caller]
+pub fn debug_verify_lifted(program: &LiftedProgram) {
+    if cfg!(debug_assertions) {
+        if let Err(violations) = verify_lifted(program) {
+            panic!(
+                "malformed lifted program:\n{}",
+                display_violations(&violations)
+            );
+        }
+    }
+}
+
+pub fn display_violations(violations: &[Violation]) -> String {
+    let lines = violations.iter().map(|v| v.to_string());
+    lines.collect::<Vec<_>>().join("\n")
+}
+
+fn into_result(violations: Vec<Violation>) -> Result<(), Vec<Violation>> {
+    match violations.is_empty() {
+        true => Ok(()),
+        false => Err(violations),
+    }
+}
+
+fn verify_external_functions<F: Tag + Ord>(
+    external_functions: &FxHashMap<ExternalFunctionId<F>, ExternalFunction>,
+    violations: &mut Vec<Violation>,
+) {
+    let is_nullable = |typ: &FFIValueType| matches!(typ, FFIValueType::Nullable(_));
+
+    for (id, external_function) in sorted(external_functions) {
+        let misplaced = match &external_function.return_type {
+            Returns::Value(FFIValueType::Nullable(inner)) => is_nullable(inner),
+            _ => false,
+        } || external_function.parameters.iter().any(is_nullable);
+
+        if misplaced {
+            violations.push(Violation {
+                kind: ViolationKind::MisplacedNullable(id.value()),
+                function: None,
+                block: None,
+                site: Site::Declaration,
+                constructed_at: None,
+            });
+        }
+    }
+}
+
+fn sorted<K: Ord + Copy, V>(map: &FxHashMap<K, V>) -> Vec<(K, &V)> {
+    let mut entries = map.iter().map(|(k, v)| (*k, v)).collect::<Vec<_>>();
+    entries.sort_by_key(|(k, _)| *k);
+    entries
+}
+
+/// The parts of a program that instructions may reference.
+struct References<'program, F: Tag, A: Fn(FunctionId<F>) -> Option<usize>> {
+    function_arity: A,
+    external_functions: &'program FxHashMap<ExternalFunctionId<F>, ExternalFunction>,
+    constants: &'program FxHashMap<ConstantId<F>, Constant>,
+}
+
+impl<F: Tag, A: Fn(FunctionId<F>) -> Option<usize>> References<'_, F, A> {
+    /// Checks that everything referenced by an instruction exists.
+    fn check<C: Tag>(&self, instruction: &InstructionData<C, F>) -> Option<ViolationKind> {
+        let arity = |target: String, expected: usize, passed: usize| {
+            (expected != passed).then(|| ViolationKind::ArityMismatch {
+                target,
+                expected,
+                passed,
+            })
+        };
+
+        match instruction {
+            InstructionData::CallStatic(call) => match (self.function_arity)(call.calling) {
+                Some(expected) => arity(format!("@{}", call.calling), expected, call.args.len()),
+                None => Some(ViolationKind::MissingFunction(call.calling.value())),
+            },
+            InstructionData::CallExtern(call) => match self.external_functions.get(&call.calling) {
+                Some(ext_fn) => arity(
+                    format!("@@{}", call.calling),
+                    ext_fn.parameters.len(),
+                    call.args.len(),
+                ),
+                None => Some(ViolationKind::MissingExternalFunction(call.calling.value())),
+            },
+            InstructionData::GetFnPtr(make) => (self.function_arity)(make.item)
+                .is_none()
+                .then(|| ViolationKind::MissingFunction(make.item.value())),
+            InstructionData::MakeBytes(make) => (!self.constants.contains_key(&make.item))
+                .then(|| ViolationKind::MissingConstant(make.item.value())),
+            _ => None,
+        }
+    }
+}
+
+/// Where a register is defined within a function of the [`IR`]. Positions are
+/// ordered such that the parameters of a block come before its instructions,
+/// which come before its end.
+#[derive(Clone, Copy)]
+struct Definition {
+    block: BlockId,
+    position: usize,
+}
+
+fn verify_function<A: Fn(FunctionId<IrCtx>) -> Option<usize>>(
+    id: FunctionId<IrCtx>,
+    function: &ir::Function,
+    references: &References<IrCtx, A>,
+    violations: &mut Vec<Violation>,
+) {
+    let violation = |block: Option<BlockId>,
+                     site: Site,
+                     instruction: Option<&Instruction>,
+                     kind: ViolationKind| Violation {
+        kind,
+        function: Some(id.value()),
+        block: block.map(|b| b.value()),
+        site,
+        constructed_at: instruction.map(|i| i.constructed_at),
+    };
+
+    let entry = match function.blocks.get(&function.entry_block) {
+        Some(entry) => entry,
+        None => {
+            violations.push(violation(
+                None,
+                Site::Declaration,
+                None,
+                ViolationKind::MissingEntryBlock(function.entry_block.value()),
+            ));
+            return;
+        }
+    };
+
+    if !entry.parameters.is_empty() {
+        violations.push(violation(
+            Some(function.entry_block),
+            Site::Declaration,
+            None,
+            ViolationKind::EntryBlockParameters(entry.parameters.len()),
+        ));
+    }
+
+    let blocks = sorted(&function.blocks);
+
+    // find where every register is defined, so that uses can be checked
+    // against their definition
+    let mut definitions = FxHashMap::default();
+    let mut define = |register: RegisterId,
+                      definition: Definition,
+                      site: Site,
+                      instruction: Option<&Instruction>| {
+        if definitions.insert(register, definition).is_some() {
+            violations.push(violation(
+                Some(definition.block),
+                site,
+                instruction,
+                ViolationKind::RedefinedRegister(register.value()),
+            ));
+        }
+    };
+
+    for parameter in function.parameters.iter() {
+        let definition = Definition {
+            block: function.entry_block,
+            position: 0,
+        };
+        define(parameter.register, definition, Site::Declaration, None);
+    }
+
+    for (block_id, block) in blocks.iter().copied() {
+        for register in block.parameters.iter().copied() {
+            let definition = Definition {
+                block: block_id,
+                position: 0,
+            };
+            define(register, definition, Site::Declaration, None);
+        }
+
+        for (idx, instruction) in block.instructions.iter().enumerate() {
+            if let Some(register) = instruction.assigned_to() {
+                let definition = Definition {
+                    block: block_id,
+                    position: idx + 1,
+                };
+                define(
+                    register,
+                    definition,
+                    Site::Instruction(idx),
+                    Some(instruction),
+                );
+            }
+        }
+    }
+
+    let dominators = dominators(function);
+
+    let check_use = |register: RegisterId, block: BlockId, position: usize| {
+        let definition = match definitions.get(&register) {
+            Some(definition) => definition,
+            None => return Some(ViolationKind::UndefinedRegister(register.value())),
+        };
+
+        let dominates = match dominators.get(&block) {
+            Some(_) if definition.block == block => definition.position < position,
+            Some(dominators) => dominators.contains(&definition.block),
+            // uses in unreachable blocks only need to be defined
+            None => true,
+        };
+
+        (!dominates).then(|| ViolationKind::UndominatedUse(register.value()))
+    };
+
+    for (block_id, block) in blocks.iter().copied() {
+        for (idx, instruction) in block.instructions.iter().enumerate() {
+            let site = Site::Instruction(idx);
+
+            for register in instruction.used_registers() {
+                if let Some(kind) = check_use(register, block_id, idx + 1) {
+                    violations.push(violation(Some(block_id), site, Some(instruction), kind));
+                }
+            }
+
+            if let Some(kind) = references.check(&instruction.data) {
+                violations.push(violation(Some(block_id), site, Some(instruction), kind));
+            }
+        }
+
+        let end_position = block.instructions.len() + 1;
+        for register in block.end.used_registers() {
+            if let Some(kind) = check_use(register, block_id, end_position) {
+                violations.push(violation(Some(block_id), Site::End, None, kind));
+            }
+        }
+
+        for path in block.end.children() {
+            let kind = match function.blocks.get(&path.0) {
+                Some(target) if target.parameters.len() != path.1.len() => {
+                    ViolationKind::ArityMismatch {
+                        target: format!("${}", path.0),
+                        expected: target.parameters.len(),
+                        passed: path.1.len(),
+                    }
+                }
+                Some(_) => continue,
+                None => ViolationKind::MissingBlock(path.0.value()),
+            };
+
+            violations.push(violation(Some(block_id), Site::End, None, kind));
+        }
+
+        if let ir::ControlFlowInstruction::Switch(switch) = &block.end {
+            for value in duplicate_cases(switch) {
+                let kind = ViolationKind::DuplicateSwitchCase(value);
+                violations.push(violation(Some(block_id), Site::End, None, kind));
+            }
+        }
+    }
+}
+
+/// Computes the blocks that dominate each block reachable from the entry block
+/// of a function. A block is considered to dominate itself.
+fn dominators(function: &ir::Function) -> FxHashMap<BlockId, FxHashSet<BlockId>> {
+    let successors = |block: BlockId| -> Vec<BlockId> {
+        match function.blocks.get(&block) {
+            Some(block) => (block.end.children().into_iter())
+                .map(|path| path.0)
+                .filter(|id| function.blocks.contains_key(id))
+                .collect(),
+            None => Vec::new(),
+        }
+    };
+
+    // find all reachable blocks, and their predecessors
+    let mut reachable = vec![function.entry_block];
+    let mut predecessors = FxHashMap::<BlockId, Vec<BlockId>>::default();
+    let mut visited = FxHashSet::default();
+    visited.insert(function.entry_block);
+
+    let mut idx = 0;
+    while let Some(block) = reachable.get(idx).copied() {
+        idx += 1;
+
+        for successor in successors(block) {
+            predecessors.entry(successor).or_default().push(block);
+
+            if visited.insert(successor) {
+                reachable.push(successor);
+            }
+        }
+    }
+
+    let mut dominators = FxHashMap::default();
+    for block in reachable.iter().copied() {
+        let initial = match block == function.entry_block {
+            true => std::iter::once(block).collect(),
+            false => visited.clone(),
+        };
+
+        dominators.insert(block, initial);
+    }
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for block in reachable.iter().copied().skip(1) {
+            let mut new = (predecessors[&block].iter())
+                .map(|pred| &dominators[pred])
+                .fold(None, |acc: Option<FxHashSet<_>>, doms| match acc {
+                    None => Some(doms.clone()),
+                    Some(acc) => Some(acc.intersection(doms).copied().collect()),
+                })
+                .unwrap_or_default();
+            new.insert(block);
+
+            if new != dominators[&block] {
+                dominators.insert(block, new);
+                changed = true;
+            }
+        }
+    }
+
+    dominators
+}
+
+fn verify_lifted_function<A: Fn(lifted::FunctionId) -> Option<usize>>(
+    id: lifted::FunctionId,
+    function: &lifted::Function,
+    program: &LiftedProgram,
+    references: &References<crate::id::LiftedCtx, A>,
+    violations: &mut Vec<Violation>,
+) {
+    let violation =
+        |site: Site, instruction: Option<&Instruction<_, _>>, kind: ViolationKind| Violation {
+            kind,
+            function: Some(id.value()),
+            block: None,
+            site,
+            constructed_at: instruction.map(|i| i.constructed_at),
+        };
+
+    let mut defined = FxHashSet::default();
+
+    for register in function.parameters.iter().copied() {
+        if !defined.insert(register) {
+            let kind = ViolationKind::RedefinedRegister(register.value());
+            violations.push(violation(Site::Declaration, None, kind));
+        }
+    }
+
+    for (idx, instruction) in function.instructions.iter().enumerate() {
+        let site = Site::Instruction(idx);
+
+        for register in instruction.used_registers() {
+            if !defined.contains(&register) {
+                let kind = ViolationKind::UndefinedRegister(register.value());
+                violations.push(violation(site, Some(instruction), kind));
+            }
+        }
+
+        if let Some(register) = instruction.assigned_to() {
+            if !defined.insert(register) {
+                let kind = ViolationKind::RedefinedRegister(register.value());
+                violations.push(violation(site, Some(instruction), kind));
+            }
+        }
+
+        if let Some(kind) = references.check(&instruction.data) {
+            violations.push(violation(site, Some(instruction), kind));
+        }
+    }
+
+    for register in function.end.used_registers() {
+        if !defined.contains(&register) {
+            let kind = ViolationKind::UndefinedRegister(register.value());
+            violations.push(violation(Site::End, None, kind));
+        }
+    }
+
+    for path in function.end.paths() {
+        let kind = match program.functions.get(&path.0) {
+            Some(target) if target.parameters.len() != path.1.len() => {
+                ViolationKind::ArityMismatch {
+                    target: format!("@{}", path.0),
+                    expected: target.parameters.len(),
+                    passed: path.1.len(),
+                }
+            }
+            Some(_) => continue,
+            None => ViolationKind::MissingFunction(path.0.value()),
+        };
+
+        violations.push(violation(Site::End, None, kind));
+    }
+
+    if let lifted::EndInstruction::Switch(switch) = &function.end {
+        for value in duplicate_cases(switch) {
+            let kind = ViolationKind::DuplicateSwitchCase(value);
+            violations.push(violation(Site::End, None, kind));
+        }
+    }
+}
+
+/// The values that a switch has more than one case for. Only the first of them
+/// would ever be taken.
+fn duplicate_cases<B: IdCompat, C: Tag>(switch: &Switch<B, C>) -> Vec<SwitchValue> {
+    let mut seen = FxHashSet::default();
+    let mut duplicates = Vec::new();
+
+    for (value, _) in switch.cases.iter() {
+        if !seen.insert(*value) && !duplicates.contains(value) {
+            duplicates.push(*value);
+        }
+    }
+
+    duplicates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::parse_jssatir::parse;
+    use crate::lifted::lift;
+
+    fn kinds(text: &str) -> Vec<ViolationKind> {
+        let ir = parse(text).unwrap();
+        match verify(&ir) {
+            Ok(()) => Vec::new(),
+            Err(violations) => violations.into_iter().map(|v| v.kind).collect(),
+        }
+    }
+
+    #[test]
+    fn accepts_well_formed_programs() {
+        let ir = parse(
+            r#"entrypoint @0;
+
+fn @0(%0, ) {
+  @0.$0():
+    %1 = CallStatic @0(%0)
+    Jump $1(%1)
+  @0.$1(%2, ):
+    If %2:
+     $2();
+else $1(%0);
+  @0.$2():
+    Return %1;
+}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(verify(&ir), Ok(()));
+        assert_eq!(verify_lifted(&lift(ir)), Ok(()));
+    }
+
+    #[test]
+    fn finds_every_violation() {
+        let kinds = kinds(
+            r#"entrypoint @0;
+
+ext fn @@1 "nested"() -> Nullable(Nullable(Bool));
+
+ext fn @@2 "parameter"(Nullable(I64), ) -> Void;
+
+ext fn @@3 "returned"() -> Nullable(I64);
+
+fn @0(%0, ) {
+  @0.$0():
+    %1 = MakeBytes #0;
+    %1 = CallStatic @0()
+    %2 = CallExtern @@0()
+    If %0:
+     $1(%0);
+else $2();
+  @0.$1():
+    %3 = GetFnPtr @1;
+    Jump $3()
+  @0.$2():
+    Return %3;
+}
+"#,
+        );
+
+        assert_eq!(
+            kinds,
+            vec![
+                ViolationKind::MisplacedNullable(1),
+                ViolationKind::MisplacedNullable(2),
+                ViolationKind::RedefinedRegister(1),
+                ViolationKind::MissingConstant(0),
+                ViolationKind::ArityMismatch {
+                    target: "@0".to_string(),
+                    expected: 1,
+                    passed: 0
+                },
+                ViolationKind::MissingExternalFunction(0),
+                ViolationKind::ArityMismatch {
+                    target: "$1".to_string(),
+                    expected: 0,
+                    passed: 1
+                },
+                ViolationKind::MissingFunction(1),
+                ViolationKind::MissingBlock(3),
+                ViolationKind::UndominatedUse(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn uses_must_follow_definitions() {
+        let kinds = kinds(
+            r#"entrypoint @0;
+
+fn @0() {
+  @0.$0():
+    %1 = Negate %0;
+    %0 = MakeInteger 1;
+    Return %2;
+}
+"#,
+        );
+
+        assert_eq!(
+            kinds,
+            vec![
+                ViolationKind::UndominatedUse(0),
+                ViolationKind::UndefinedRegister(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_duplicate_switch_cases() {
+        let kinds = kinds(
+            r#"entrypoint @0;
+
+fn @0() {
+  @0.$0():
+    %0 = MakeInteger 1;
+    Switch %0:
+     case 1 => $1();
+     case true => $1();
+     case 1 => $1();
+     case 1 => $1();
+else $1();
+  @0.$1():
+    Return;
+}
+"#,
+        );
+
+        assert_eq!(
+            kinds,
+            vec![ViolationKind::DuplicateSwitchCase(SwitchValue::Integer(1))]
+        );
+    }
+
+    #[test]
+    fn reports_locations() {
+        let ir = parse(
+            r#"entrypoint @0;
+
+fn @0() {
+  @0.$0():
+    Return;
+  @0.$1():
+    %0 = MakeBytes #0;
+    Return;
+}
+"#,
+        )
+        .unwrap();
+
+        let violations = verify(&ir).unwrap_err();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].function, Some(0));
+        assert_eq!(violations[0].block, Some(1));
+        assert_eq!(violations[0].site, Site::Instruction(0));
+        assert!(violations[0].constructed_at.is_some());
+        assert!(violations[0]
+            .to_string()
+            .starts_with("@0.$1 #0: constant #0 does not exist (constructed at "));
+    }
+}
@@ -0,0 +1,68 @@
+use std::fmt::Display;
+
+use crate::runtime::Runtime;
+
+/// A buffer of bytes owned by the runtime.
+pub struct Bytes(pub(crate) Vec<u8>);
+
+/// Copies a buffer of bytes into a new buffer, associating it with the
+/// specified runtime.
+///
+/// The `length` argument is the number of bytes. The buffer is owned by the
+/// caller, and is freed with [`jssatrt_bytes_drop`].
+///
+/// # Safety
+///
+/// Valid only if a valid `runtime` and a `payload` of `length` bytes are passed
+/// to it.
+#[no_mangle]
+pub unsafe extern "C" fn jssatrt_bytes_new(
+    runtime: *const Runtime,
+    payload: *const u8,
+    length: usize,
+) -> *const Bytes {
+    notnull!(runtime);
+
+    let bytes = if length == 0 {
+        Bytes(Vec::new())
+    } else {
+        notnull!(payload);
+        let payload = std::slice::from_raw_parts(payload, length);
+        Bytes(payload.to_vec())
+    };
+
+    Box::into_raw(Box::new(bytes))
+}
+
+/// Frees a buffer of bytes made by [`jssatrt_bytes_new`].
+///
+/// # Safety
+///
+/// Valid only if a valid `runtime` and `bytes` that haven't been freed yet are
+/// passed to it.
+#[no_mangle]
+pub unsafe extern "C" fn jssatrt_bytes_drop(runtime: *const Runtime, bytes: *mut Bytes) {
+    notnull!(runtime);
+    notnull!(bytes);
+
+    let bytes = Box::from_raw(bytes);
+    drop(bytes);
+}
+
+/// Gets the number of bytes in a buffer.
+///
+/// # Safety
+///
+/// Valid only if valid `bytes` are passed to it.
+#[no_mangle]
+pub unsafe extern "C" fn jssatrt_bytes_len(bytes: *const Bytes) -> i64 {
+    notnull!(bytes);
+
+    (*bytes).0.len() as i64
+}
+
+impl Display for Bytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", std::string::String::from_utf8_lossy(&self.0))
+    }
+}
@@ -3,6 +3,7 @@
 //! This crate
 
 use any::Any;
+use bytes::Bytes;
 use mimalloc::MiMalloc;
 use runtime::Runtime;
 
@@ -28,6 +29,7 @@ macro_rules! not0 {
 }
 
 pub mod any;
+pub mod bytes;
 pub mod runtime;
 pub mod string;
 
@@ -55,3 +57,45 @@ pub unsafe extern "C" fn jssatrt_print_any(_runtime: *const Runtime, arguments:
     let any = &*arguments;
     println!("{}", any);
 }
+
+/// # Safety
+///
+/// Valid only if a valid `_runtime` is passed to it.
+#[no_mangle]
+pub unsafe extern "C" fn jssatrt_print_i64(_runtime: *const Runtime, value: i64) {
+    notnull!(_runtime);
+
+    println!("{}", value);
+}
+
+/// # Safety
+///
+/// Valid only if a valid `_runtime` is passed to it.
+#[no_mangle]
+pub unsafe extern "C" fn jssatrt_print_f64(_runtime: *const Runtime, value: f64) {
+    notnull!(_runtime);
+
+    println!("{}", value);
+}
+
+/// # Safety
+///
+/// Valid only if a valid `_runtime` is passed to it.
+#[no_mangle]
+pub unsafe extern "C" fn jssatrt_print_bool(_runtime: *const Runtime, value: bool) {
+    notnull!(_runtime);
+
+    println!("{}", value);
+}
+
+/// # Safety
+///
+/// Valid only if a valid `_runtime` and `bytes` are passed to it.
+#[no_mangle]
+pub unsafe extern "C" fn jssatrt_print_bytes(_runtime: *const Runtime, bytes: *const Bytes) {
+    notnull!(_runtime);
+    notnull!(bytes);
+
+    let bytes = &*bytes;
+    println!("{}", bytes);
+}